@@ -87,7 +87,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
     }));
 
     vtable.insert(1, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
 
             (Rc::clone(datum), *i)
         } else { unreachable!(1) };
@@ -101,7 +101,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     //Frame size x
     vtable.insert(2, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
 
             (Rc::clone(datum), *i)
         } else { unreachable!(2) };
@@ -115,7 +115,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     //Frame size y
     vtable.insert(3, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
 
             (Rc::clone(datum), *i)
         } else { unreachable!(3) };
@@ -129,7 +129,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     //Frame index
     vtable.insert(4, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
 
             (Rc::clone(datum), *i)
         } else { unreachable!(4) };
@@ -162,7 +162,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     //Text content
     vtable.insert(6, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
             (Rc::clone(datum), *i)
         } else { unreachable!(6) };
 
@@ -172,7 +172,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
     }));
 
     vtable.insert(7, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
             (Rc::clone(datum), *i)
         } else { unreachable!(7) };
 
@@ -182,7 +182,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
     }));
 
     vtable.insert(8, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
             (Rc::clone(datum), *i)
         } else { unreachable!(8) };
         return TypesCoproduct::Transform2D(
@@ -193,7 +193,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     // {Color::rgba(100%, (100 - (i * 12.5))%, (i * 12.5)%, 100%)}
     vtable.insert(9, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
             (Rc::clone(datum), *i)
         } else { unreachable!(9) };
 
@@ -204,7 +204,7 @@ pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<u64
 
     // {(20 + (i * 5))px}
     vtable.insert(10, Box::new(|ec: ExpressionContext<R>| -> TypesCoproduct {
-        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
+        let (datum, i) = if let PropertiesCoproduct::RepeatItem(datum, i, _) = &*(*(*ec.stack_frame).borrow().get_properties()).borrow() {
             (Rc::clone(datum), *i)
         } else { unreachable!(10) };
 
@@ -248,7 +248,11 @@ pub fn instantiate_component_stacker<R: 'static + RenderContext>(instance_regist
                                 scroller_args: None,
                                 slot_index: Some(Box::new(PropertyExpression::new(4))),
                                 repeat_source_expression: None,
+                                repeat_key_expression: None,
                                 conditional_boolean_expression: None,
+                                conditional_false_branch_children: None,
+                                match_expression: None,
+                                match_arms: None,
                                 compute_properties_fn: None
                             }),
                         ]))),
@@ -256,13 +260,21 @@ pub fn instantiate_component_stacker<R: 'static + RenderContext>(instance_regist
                         scroller_args: None,
                         slot_index: None,
                         repeat_source_expression: None,
+                        repeat_key_expression: None,
                         conditional_boolean_expression: None,
+                        conditional_false_branch_children: None,
+                        match_expression: None,
+                        match_arms: None,
                         compute_properties_fn: None
                     }),
                 ]))),
                 slot_index: None,
                 repeat_source_expression: Some(Box::new(PropertyExpression::new(0))),
+                repeat_key_expression: None,
                 conditional_boolean_expression: None,
+                conditional_false_branch_children: None,
+                match_expression: None,
+                match_arms: None,
                 compute_properties_fn: None,
                 scroller_args: None
             }),
@@ -408,7 +420,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                             scroller_args: None,
                                                             slot_index: None,
                                                             repeat_source_expression: None,
+                                                            repeat_key_expression: None,
                                                             conditional_boolean_expression: None,
+                                                            conditional_false_branch_children: None,
+                                                            match_expression: None,
+                                                            match_arms: None,
                                                             compute_properties_fn: None
                                                         }),
                                                         RectangleInstance::instantiate(InstantiationArgs{
@@ -428,7 +444,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                             scroller_args: None,
                                                             slot_index: None,
                                                             repeat_source_expression: None,
+                                                            repeat_key_expression: None,
                                                             conditional_boolean_expression: None,
+                                                            conditional_false_branch_children: None,
+                                                            match_expression: None,
+                                                            match_arms: None,
                                                             compute_properties_fn: None
                                                         }),
                                                     ]))),
@@ -436,7 +456,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                     scroller_args: None,
                                                     slot_index: None,
                                                     repeat_source_expression: None,
+                                                    repeat_key_expression: None,
                                                     conditional_boolean_expression: None,
+                                                    conditional_false_branch_children: None,
+                                                    match_expression: None,
+                                                    match_arms: None,
                                                     compute_properties_fn: None
                                                 })
                                             ]))),
@@ -447,6 +471,9 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                 Rc::new(PropertiesCoproduct::isize(i))
                                             }).collect()))),
                                             conditional_boolean_expression: None,
+                                            conditional_false_branch_children: None,
+                                            match_expression: None,
+                                            match_arms: None,
                                             compute_properties_fn: None
                                         }),
 
@@ -455,7 +482,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                     scroller_args: None,
                                     slot_index: None,
                                     repeat_source_expression: None,
+                                    repeat_key_expression: None,
                                     conditional_boolean_expression: None,
+                                    conditional_false_branch_children: None,
+                                    match_expression: None,
+                                    match_arms: None,
                                     compute_properties_fn: None,
                                 }
                             ),
@@ -492,7 +523,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                 scroller_args: None,
                                                 slot_index: None,
                                                 repeat_source_expression: None,
+                                                repeat_key_expression: None,
                                                 conditional_boolean_expression: None,
+                                                conditional_false_branch_children: None,
+                                                match_expression: None,
+                                                match_arms: None,
                                                 compute_properties_fn: None
                                             }),
                                             RectangleInstance::instantiate(InstantiationArgs{
@@ -512,7 +547,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                                 scroller_args: None,
                                                 slot_index: None,
                                                 repeat_source_expression: None,
+                                                repeat_key_expression: None,
                                                 conditional_boolean_expression: None,
+                                                conditional_false_branch_children: None,
+                                                match_expression: None,
+                                                match_arms: None,
                                                 compute_properties_fn: None
                                             }),
                                         ]))),
@@ -520,7 +559,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                         scroller_args: None,
                                         slot_index: None,
                                         repeat_source_expression: None,
+                                        repeat_key_expression: None,
                                         conditional_boolean_expression: None,
+                                        conditional_false_branch_children: None,
+                                        match_expression: None,
+                                        match_arms: None,
                                         compute_properties_fn: None
                                     })
                                 ]))),
@@ -531,6 +574,9 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                     Rc::new(PropertiesCoproduct::isize(i))
                                 }).collect()))),
                                 conditional_boolean_expression: None,
+                                conditional_false_branch_children: None,
+                                match_expression: None,
+                                match_arms: None,
                                 compute_properties_fn: None
                             }),
                             GroupInstance::instantiate(InstantiationArgs {
@@ -570,7 +616,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                         scroller_args: None,
                                         slot_index: None,
                                         repeat_source_expression: None,
+                                        repeat_key_expression: None,
                                         conditional_boolean_expression: None,
+                                        conditional_false_branch_children: None,
+                                        match_expression: None,
+                                        match_arms: None,
                                         compute_properties_fn: None
                                     }),
                                     RectangleInstance::instantiate(InstantiationArgs{
@@ -590,7 +640,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                         scroller_args: None,
                                         slot_index: None,
                                         repeat_source_expression: None,
+                                        repeat_key_expression: None,
                                         conditional_boolean_expression: None,
+                                        conditional_false_branch_children: None,
+                                        match_expression: None,
+                                        match_arms: None,
                                         compute_properties_fn: None
                                     }),
                                 ]))),
@@ -598,7 +652,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                                 scroller_args: None,
                                 slot_index: None,
                                 repeat_source_expression: None,
+                                repeat_key_expression: None,
                                 conditional_boolean_expression: None,
+                                conditional_false_branch_children: None,
+                                match_expression: None,
+                                match_arms: None,
                                 compute_properties_fn: None,
                             }),
                         ]))),
@@ -606,7 +664,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
                         scroller_args: None,
                         slot_index: None,
                         repeat_source_expression: None,
+                        repeat_key_expression: None,
                         conditional_boolean_expression: None,
+                        conditional_false_branch_children: None,
+                        match_expression: None,
+                        match_arms: None,
                         compute_properties_fn: None,
                     }
                 ),
@@ -614,7 +676,11 @@ pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry:
             scroller_args: None,
             slot_index: None,
             repeat_source_expression: None,
+            repeat_key_expression: None,
             conditional_boolean_expression: None,
+            conditional_false_branch_children: None,
+            match_expression: None,
+            match_arms: None,
             compute_properties_fn: Some(Box::new(|properties, rtc|{
                 let properties = &mut *properties.as_ref().borrow_mut();
                 let properties = if let PropertiesCoproduct::HelloWorld(p) = properties {p} else {unreachable!()};
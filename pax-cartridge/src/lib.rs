@@ -3,13 +3,13 @@ use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::ops::Deref;
 use std::rc::Rc;
-use pax_core::{ComponentInstance, RenderNodePtr, PropertyExpression, RenderNodePtrList, RenderTreeContext, ExpressionContext, PaxEngine, RenderNode, InstanceRegistry, HandlerRegistry, InstantiationArgs, ConditionalInstance, SlotInstance, StackFrame};
+use pax_core::{HotReloadCartridge, ComponentInstance, RenderNodePtr, PropertyExpression, RenderNodePtrList, RenderTreeContext, ExpressionContext, ExpressionVTable, PaxEngine, RenderNode, InstanceRegistry, HandlerRegistry, InstantiationArgs, ConditionalInstance, SlotInstance, StackFrame};
 use pax_core::pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 use piet_common::RenderContext;
 
 const PLACEHOLDER_ERROR : &str = "Fatal: the development placeholder cartridge is still attached -- a defined cartridge must be attached during compilation.  This means that Pax compilation failed -- please try again with `pax build` or `pax run`.";
 
-pub fn instantiate_expression_table<R: 'static + RenderContext>() -> HashMap<usize, Box<dyn Fn(ExpressionContext<R>) -> TypesCoproduct>> {
+pub fn instantiate_expression_table<R: 'static + RenderContext>() -> ExpressionVTable<R> {
     unreachable!("{}", PLACEHOLDER_ERROR)
 }
 
@@ -19,4 +19,8 @@ pub fn instantiate_component_stacker<R: 'static + RenderContext>(instance_regist
 
 pub fn instantiate_main_component<R: 'static + RenderContext>(instance_registry: Rc<RefCell<InstanceRegistry<R>>>) -> Rc<RefCell<ComponentInstance<R>>> {
     unreachable!("{}", PLACEHOLDER_ERROR)
-}
\ No newline at end of file
+}
+
+pub fn instantiate_hot_reload_cartridge<R: 'static + RenderContext>() -> HotReloadCartridge<R> {
+    unreachable!("{}", PLACEHOLDER_ERROR)
+}
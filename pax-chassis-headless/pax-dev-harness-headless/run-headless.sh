@@ -0,0 +1,22 @@
+#!/bin/sh
+
+# Expects args:
+# 1: SHOULD_ALSO_RUN ∈ {"true" , "false"}
+# 2: OUTPUT_PATH : output directory for build
+# Any further args are passed through to pax-chassis-headless, e.g. `--frames 60 --all-frames`
+SHOULD_ALSO_RUN=$1
+OUTPUT_PATH=$2
+shift 2
+
+# Clear old build and move to output directory
+rm -rf "$OUTPUT_PATH"
+mkdir -p "$OUTPUT_PATH"
+cp ../target/debug/pax-chassis-headless "$OUTPUT_PATH"
+
+if [ "$SHOULD_ALSO_RUN" = "true" ]; then
+  # Run from the project root, so that image paths resolve as they do for other targets
+  set -e
+  cd ../../../..
+  "$OUTPUT_PATH/pax-chassis-headless" --output "$OUTPUT_PATH/frames" "$@"
+  echo "Wrote frames to $OUTPUT_PATH/frames"
+fi
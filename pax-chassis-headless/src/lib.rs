@@ -0,0 +1,181 @@
+//! Runs a Pax cartridge without a display: frames are rendered to an in-memory bitmap with piet's cairo backend,
+//! interrupts are scripted by the caller, and native messages are collected rather than rendered.
+
+pub mod snapshot;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::path::Path;
+use std::rc::Rc;
+
+use cairo::{Format, ImageSurface};
+use serde::Deserialize;
+use piet::{Color, NullRenderContext, RenderContext};
+use piet_cairo::CairoRenderContext;
+
+use pax_core::{InstanceRegistry, PaxEngine, read_png_as_rgba, surface_to_rgba};
+use pax_core::testing::TestDriver;
+use pax_message::{ImageLoadInterruptArgs, NativeInterrupt, NativeMessage};
+
+pub type HeadlessRenderContext = CairoRenderContext<'static>;
+
+pub fn log_wrapper(msg: &str) {
+    println!("{}", msg);
+}
+
+/// An interrupt to dispatch just before rendering `frame`, as read from a script of interrupts, e.g.
+/// `[{"frame": 2, "interrupt": {"Click": {"x": 10.0, "y": 10.0, "button": "Left", "modifiers": []}}}]`
+#[derive(Deserialize)]
+pub struct ScriptedInterrupt {
+    pub frame: usize,
+    pub interrupt: NativeInterrupt,
+}
+
+pub struct PaxChassisHeadless {
+    engine: PaxEngine<HeadlessRenderContext>,
+    surface: ImageSurface,
+    /// Boxed so that the `'static` references lent to each frame's render context stay valid as the chassis moves
+    context: Box<cairo::Context>,
+    /// Native messages emitted by each tick so far, indexed by frame
+    native_messages: Vec<Vec<NativeMessage>>,
+}
+
+/// Instantiates the attached cartridge's main component with a viewport of `width` x `height` pixels, to be driven by
+/// tests without rendering; see `pax_core::testing`
+pub fn new_test_driver(width: usize, height: usize) -> TestDriver<NullRenderContext> {
+    let instance_registry : Rc<RefCell<InstanceRegistry<NullRenderContext>>> = Rc::new(RefCell::new(InstanceRegistry::new()));
+    let main_component_instance = pax_cartridge::instantiate_main_component(Rc::clone(&instance_registry));
+    let expression_table = pax_cartridge::instantiate_expression_table();
+
+    let engine = PaxEngine::new(main_component_instance, expression_table, pax_runtime_api::PlatformSpecificLogger::Native(log_wrapper), (width as f64, height as f64), instance_registry);
+    TestDriver::new(engine, vec![NullRenderContext::new()])
+}
+
+impl PaxChassisHeadless {
+    /// Instantiates the attached cartridge's main component with a viewport of `width` x `height` pixels
+    pub fn new(width: usize, height: usize) -> Result<Self, String> {
+        let surface = ImageSurface::create(Format::ARgb32, width as i32, height as i32).map_err(|error| format!("Failed to create bitmap: {}", error))?;
+        let context = Box::new(cairo::Context::new(&surface).map_err(|error| format!("Failed to create drawing context: {}", error))?);
+
+        let instance_registry : Rc<RefCell<InstanceRegistry<HeadlessRenderContext>>> = Rc::new(RefCell::new(InstanceRegistry::new()));
+        let main_component_instance = pax_cartridge::instantiate_main_component(Rc::clone(&instance_registry));
+        let expression_table = pax_cartridge::instantiate_expression_table();
+
+        let engine = PaxEngine::new(main_component_instance, expression_table, pax_runtime_api::PlatformSpecificLogger::Native(log_wrapper), (width as f64, height as f64), instance_registry);
+
+        Ok(PaxChassisHeadless {
+            engine,
+            surface,
+            context,
+            native_messages: vec![],
+        })
+    }
+
+    pub fn engine(&self) -> &PaxEngine<HeadlessRenderContext> {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut PaxEngine<HeadlessRenderContext> {
+        &mut self.engine
+    }
+
+    /// Number of ticks so far
+    pub fn frames_elapsed(&self) -> usize {
+        self.native_messages.len()
+    }
+
+    /// Native messages emitted by the tick that rendered `frame`
+    pub fn get_native_messages(&self, frame: usize) -> Option<&Vec<NativeMessage>> {
+        self.native_messages.get(frame)
+    }
+
+    /// Clears the bitmap and ticks the engine, returning the native messages emitted by the tick.
+    /// Images requested by the tick are read from disk, relative to the working directory, and loaded for the next.
+    pub fn tick(&mut self) -> &Vec<NativeMessage> {
+        //SAFETY: the render context is dropped at the end of this tick, while `self.context` lives as long as `self`
+        let context : &'static cairo::Context = unsafe { &*(self.context.as_ref() as *const cairo::Context) };
+        let mut render_context = CairoRenderContext::new(context);
+        render_context.clear(None, Color::rgba8(0, 0, 0, 0));
+
+        //all layers are composited onto a single bitmap, so `LayerAdd` patches are ignored
+        let mut render_contexts = vec![render_context];
+        let messages = self.engine.tick(&mut render_contexts);
+        for mut render_context in render_contexts {
+            let _ = render_context.finish();
+        }
+        self.surface.flush();
+
+        let image_loads : Vec<(Vec<u64>, String)> = messages.iter().filter_map(|message| match message {
+            NativeMessage::ImageLoad(patch) => patch.path.clone().map(|path| (patch.id_chain.clone(), path)),
+            _ => None,
+        }).collect();
+        for (id_chain, path) in image_loads {
+            match read_png_as_rgba(Path::new(&path)) {
+                Ok((data, width, height)) => self.engine.loadImage(id_chain, data, width, height),
+                Err(message) => log_wrapper(&format!("Failed to load image {}: {}", &path, message)),
+            }
+        }
+
+        self.native_messages.push(messages);
+        self.native_messages.last().unwrap()
+    }
+
+    /// Ticks until `frames_elapsed` reaches `frame`, i.e. until `frame` is the next frame to be rendered
+    pub fn advance_to(&mut self, frame: usize) {
+        while self.frames_elapsed() < frame {
+            self.tick();
+        }
+    }
+
+    /// Writes the most recently rendered frame to `path` as a PNG
+    pub fn write_png(&self, path: &Path) -> Result<(), String> {
+        let mut file = File::create(path).map_err(|error| format!("Failed to create {}: {}", path.display(), error))?;
+        self.surface.write_to_png(&mut file).map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+    }
+
+    /// The most recently rendered frame as non-premultiplied RGBA, row by row, along with its width and height
+    pub fn get_rgba(&self) -> (Vec<u8>, usize, usize) {
+        surface_to_rgba(&self.surface).expect("Failed to read bitmap")
+    }
+
+    /// Renders frames `0..frame_count`, dispatching each of `interrupts` before its frame, and calls `on_frame` after each
+    pub fn run(&mut self, frame_count: usize, interrupts: Vec<ScriptedInterrupt>, mut on_frame: impl FnMut(&mut Self, usize)) {
+        let mut interrupts = interrupts;
+        interrupts.sort_by_key(|scripted| scripted.frame);
+        let mut interrupts = interrupts.into_iter().peekable();
+        for frame in 0..frame_count {
+            while let Some(scripted) = interrupts.next_if(|scripted| scripted.frame <= frame) {
+                self.interrupt(scripted.interrupt);
+            }
+            self.tick();
+            on_frame(self, frame);
+        }
+    }
+
+    /// Dispatches a scripted interrupt, as a platform chassis would for user input.  Returns whether a handler called
+    /// `prevent_default` on the resulting event.
+    pub fn interrupt(&mut self, native_interrupt: NativeInterrupt) -> bool {
+        self.engine.request_render();
+        let mut default_prevented = false;
+        match native_interrupt {
+            NativeInterrupt::Image(args) => {
+                match args {
+                    ImageLoadInterruptArgs::Reference(_) => {},
+                    ImageLoadInterruptArgs::Data(_) => {
+                        log_wrapper("Image data interrupts carry no payload in headless; images are loaded from the paths requested by `ImageLoad` messages");
+                    },
+                }
+            },
+            NativeInterrupt::AddedLayer(_) => {},
+            NativeInterrupt::HotReload(patch) => {
+                if let Err(message) = self.engine.apply_hot_reload_patch(patch, &pax_cartridge::instantiate_hot_reload_cartridge()) {
+                    log_wrapper(&format!("Failed to hot reload: {}", message));
+                }
+            },
+            input => {
+                default_prevented = self.engine.dispatch_interrupt(input);
+            },
+        }
+        default_prevented
+    }
+}
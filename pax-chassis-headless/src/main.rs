@@ -0,0 +1,115 @@
+//! Renders the attached cartridge without a display, writing frames to PNG and native messages to JSON.
+//!
+//! Usage: `pax-chassis-headless [--width W] [--height H] [--frames N] [--frame F | --all-frames] [--interrupts script.json] [--output DIR]`
+//!
+//! By default renders a single frame at 1280x720 and writes it to `./frame-0.png`.  `--frame` selects which frame to write
+//! (the last by default), while `--all-frames` writes every frame.  The native messages of every frame are written
+//! to `messages.json` alongside.  See `ScriptedInterrupt` for the format of `--interrupts`.
+//!
+//! With `--snapshots DIR [--update-snapshots]`, instead runs the golden-image snapshot tests in `DIR` (see `snapshot`),
+//! exiting with a failure status if any fail, including any without a golden image unless `--update-snapshots` is given.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::exit;
+
+use paxchassisheadless::{snapshot, PaxChassisHeadless, ScriptedInterrupt};
+
+struct HeadlessArgs {
+    width: usize,
+    height: usize,
+    frame_count: usize,
+    selected_frame: Option<usize>,
+    should_write_all_frames: bool,
+    interrupts_path: Option<PathBuf>,
+    output_dir: PathBuf,
+    snapshots_dir: Option<PathBuf>,
+    should_update_snapshots: bool,
+}
+
+fn parse_args() -> Result<HeadlessArgs, String> {
+    let mut args = HeadlessArgs {
+        width: 1280,
+        height: 720,
+        frame_count: 1,
+        selected_frame: None,
+        should_write_all_frames: false,
+        interrupts_path: None,
+        output_dir: PathBuf::from("."),
+        snapshots_dir: None,
+        should_update_snapshots: false,
+    };
+
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(flag) = raw_args.next() {
+        if flag == "--all-frames" {
+            args.should_write_all_frames = true;
+            continue;
+        }
+        if flag == "--update-snapshots" {
+            args.should_update_snapshots = true;
+            continue;
+        }
+        let value = raw_args.next().ok_or_else(|| format!("Missing value for {}", &flag))?;
+        let parse_number = |value: &str| value.parse::<usize>().map_err(|_| format!("Expected a number for {}, found `{}`", &flag, value));
+        match flag.as_str() {
+            "--width" => args.width = parse_number(&value)?,
+            "--height" => args.height = parse_number(&value)?,
+            "--frames" => args.frame_count = parse_number(&value)?,
+            "--frame" => args.selected_frame = Some(parse_number(&value)?),
+            "--interrupts" => args.interrupts_path = Some(PathBuf::from(value)),
+            "--output" => args.output_dir = PathBuf::from(value),
+            "--snapshots" => args.snapshots_dir = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unknown argument {}", &flag)),
+        }
+    }
+
+    if let Some(selected_frame) = args.selected_frame {
+        args.frame_count = args.frame_count.max(selected_frame + 1);
+    }
+    if args.frame_count == 0 {
+        return Err("--frames must be at least 1".to_string());
+    }
+    Ok(args)
+}
+
+fn run(args: HeadlessArgs) -> Result<(), String> {
+    if let Some(snapshots_dir) = &args.snapshots_dir {
+        return if snapshot::run_snapshots(snapshots_dir, args.should_update_snapshots)? {
+            Ok(())
+        } else {
+            Err("Some snapshots differ from their golden images".to_string())
+        };
+    }
+
+    let interrupts : Vec<ScriptedInterrupt> = match &args.interrupts_path {
+        Some(path) => {
+            let script = fs::read_to_string(path).map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+            serde_json::from_str(&script).map_err(|error| format!("Invalid interrupt script {}: {}", path.display(), error))?
+        },
+        None => vec![],
+    };
+    fs::create_dir_all(&args.output_dir).map_err(|error| format!("Failed to create {}: {}", args.output_dir.display(), error))?;
+
+    let selected_frame = args.selected_frame.unwrap_or(args.frame_count - 1);
+    let mut chassis = PaxChassisHeadless::new(args.width, args.height)?;
+    let mut write_result = Ok(());
+    chassis.run(args.frame_count, interrupts, |chassis, frame| {
+        if write_result.is_ok() && (args.should_write_all_frames || frame == selected_frame) {
+            write_result = chassis.write_png(&args.output_dir.join(format!("frame-{}.png", frame)));
+        }
+    });
+    write_result?;
+
+    let messages : Vec<_> = (0..chassis.frames_elapsed()).map(|frame| chassis.get_native_messages(frame).unwrap()).collect();
+    let messages_path = args.output_dir.join("messages.json");
+    fs::write(&messages_path, serde_json::to_string_pretty(&messages).unwrap()).map_err(|error| format!("Failed to write {}: {}", messages_path.display(), error))
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        exit(1);
+    }
+}
@@ -0,0 +1,263 @@
+//! Golden-image snapshot tests, run by `pax test`.
+//!
+//! Each `<name>.json` in a snapshots directory describes a `SnapshotSpec`: the main component is rendered at the given
+//! viewport for the given number of frames, and the last frame is compared against the golden image `<name>.png`
+//! alongside it.  Goldens are written only when updating snapshots, so that a missing golden fails rather than passing
+//! unchecked, e.g. in CI; on failure, the actual frame and an image highlighting the differing pixels are written to
+//! `failures/`.
+
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use cairo::{Format, ImageSurface};
+use serde::Deserialize;
+
+use pax_core::read_png_as_rgba;
+
+use crate::{PaxChassisHeadless, ScriptedInterrupt};
+
+pub const SNAPSHOT_FAILURES_DIR: &str = "failures";
+
+fn default_width() -> usize { 1280 }
+fn default_height() -> usize { 720 }
+fn default_frames() -> usize { 1 }
+fn default_threshold() -> f64 { 0.1 }
+
+#[derive(Deserialize)]
+pub struct SnapshotSpec {
+    #[serde(default = "default_width")]
+    pub width: usize,
+    #[serde(default = "default_height")]
+    pub height: usize,
+    /// Number of ticks to render; the last is compared against the golden image
+    #[serde(default = "default_frames")]
+    pub frames: usize,
+    #[serde(default)]
+    pub interrupts: Vec<ScriptedInterrupt>,
+    /// Perceptual difference, from 0 to 1, below which two pixels are considered equal.  See `pixel_delta`
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    /// Fraction of pixels that may differ before the snapshot fails, e.g. to tolerate antialiasing differences across platforms
+    #[serde(default)]
+    pub max_diff_ratio: f64,
+}
+
+/// Non-premultiplied RGBA pixels, row by row
+pub struct Bitmap {
+    pub rgba: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+}
+
+pub struct BitmapDiff {
+    pub differing_pixels: usize,
+    /// `actual`, faded, with differing pixels in red
+    pub diff: Bitmap,
+}
+
+impl BitmapDiff {
+    pub fn get_diff_ratio(&self) -> f64 {
+        let total_pixels = self.diff.width * self.diff.height;
+        if total_pixels == 0 { 0.0 } else { self.differing_pixels as f64 / total_pixels as f64 }
+    }
+}
+
+pub enum SnapshotOutcome {
+    Passed,
+    /// No golden image existed while updating snapshots, so one was written
+    Created,
+    Updated,
+    Failed(String),
+}
+
+/// Maximum value of `pixel_delta`, between black and white
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+/// Perceptual difference between two pixels, as the squared distance between their colors in YIQ space,
+/// after blending each onto white.  See "Measuring perceived color difference using YIQ NTSC transmission color space
+/// in mobile applications" (Kotsarenko & Ramos, 2010), as used by e.g. pixelmatch.
+fn pixel_delta(a: &[u8], b: &[u8]) -> f64 {
+    let blend = |pixel: &[u8], channel: usize| -> f64 {
+        let alpha = pixel[3] as f64 / 255.0;
+        255.0 + (pixel[channel] as f64 - 255.0) * alpha
+    };
+    let (r1, g1, b1) = (blend(a, 0), blend(a, 1), blend(a, 2));
+    let (r2, g2, b2) = (blend(b, 0), blend(b, 1), blend(b, 2));
+
+    let y = |r: f64, g: f64, b: f64| r * 0.29889531 + g * 0.58662247 + b * 0.11448223;
+    let i = |r: f64, g: f64, b: f64| r * 0.59597799 - g * 0.27417610 - b * 0.32180189;
+    let q = |r: f64, g: f64, b: f64| r * 0.21147017 - g * 0.52261711 + b * 0.31114694;
+
+    let dy = y(r1, g1, b1) - y(r2, g2, b2);
+    let di = i(r1, g1, b1) - i(r2, g2, b2);
+    let dq = q(r1, g1, b1) - q(r2, g2, b2);
+    0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq
+}
+
+/// Compares two bitmaps pixel by pixel, counting those whose perceptual difference exceeds `threshold` (from 0 to 1)
+pub fn diff_bitmaps(actual: &Bitmap, expected: &Bitmap, threshold: f64) -> Result<BitmapDiff, String> {
+    if actual.width != expected.width || actual.height != expected.height {
+        return Err(format!("expected a {}x{} image, but rendered {}x{}", expected.width, expected.height, actual.width, actual.height));
+    }
+
+    let max_delta = MAX_YIQ_DELTA * threshold * threshold;
+    let mut differing_pixels = 0;
+    let mut diff_rgba = Vec::with_capacity(actual.rgba.len());
+    for (actual_pixel, expected_pixel) in actual.rgba.chunks(4).zip(expected.rgba.chunks(4)) {
+        if pixel_delta(actual_pixel, expected_pixel) > max_delta {
+            differing_pixels += 1;
+            diff_rgba.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let faded = |channel: u8| 255 - (255 - channel) / 4;
+            diff_rgba.extend_from_slice(&[faded(actual_pixel[0]), faded(actual_pixel[1]), faded(actual_pixel[2]), 255]);
+        }
+    }
+
+    Ok(BitmapDiff {
+        differing_pixels,
+        diff: Bitmap { rgba: diff_rgba, width: actual.width, height: actual.height },
+    })
+}
+
+pub fn read_png(path: &Path) -> Result<Bitmap, String> {
+    let (rgba, width, height) = read_png_as_rgba(path)?;
+    Ok(Bitmap { rgba, width, height })
+}
+
+pub fn write_png(path: &Path, bitmap: &Bitmap) -> Result<(), String> {
+    let mut surface = ImageSurface::create(Format::ARgb32, bitmap.width as i32, bitmap.height as i32).map_err(|error| error.to_string())?;
+    let stride = surface.stride() as usize;
+    {
+        let mut data = surface.data().map_err(|error| error.to_string())?;
+        for (index, pixel) in bitmap.rgba.chunks(4).enumerate() {
+            let offset = (index / bitmap.width) * stride + (index % bitmap.width) * 4;
+            let alpha = pixel[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+            let argb = (alpha << 24) | (premultiply(pixel[0]) << 16) | (premultiply(pixel[1]) << 8) | premultiply(pixel[2]);
+            data[offset..offset + 4].copy_from_slice(&argb.to_ne_bytes());
+        }
+    }
+    let mut file = File::create(path).map_err(|error| format!("Failed to create {}: {}", path.display(), error))?;
+    surface.write_to_png(&mut file).map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
+
+/// Renders the snapshot described by `spec_path` and compares it against (or writes) its golden image
+pub fn run_snapshot(spec_path: &Path, should_update: bool) -> SnapshotOutcome {
+    match try_run_snapshot(spec_path, should_update) {
+        Ok(outcome) => outcome,
+        Err(message) => SnapshotOutcome::Failed(message),
+    }
+}
+
+fn try_run_snapshot(spec_path: &Path, should_update: bool) -> Result<SnapshotOutcome, String> {
+    let spec : SnapshotSpec = serde_json::from_str(&fs::read_to_string(spec_path).map_err(|error| error.to_string())?)
+        .map_err(|error| format!("invalid snapshot: {}", error))?;
+
+    let mut chassis = PaxChassisHeadless::new(spec.width, spec.height)?;
+    chassis.run(spec.frames.max(1), spec.interrupts, |_, _| {});
+    let (rgba, width, height) = chassis.get_rgba();
+    let actual = Bitmap { rgba, width, height };
+    check_against_golden(spec_path, &actual, spec.threshold, spec.max_diff_ratio, should_update)
+}
+
+/// Compares `actual` against the golden image alongside `spec_path`, or writes it as the golden image if `should_update`
+fn check_against_golden(spec_path: &Path, actual: &Bitmap, threshold: f64, max_diff_ratio: f64, should_update: bool) -> Result<SnapshotOutcome, String> {
+    let golden_path = spec_path.with_extension("png");
+    if should_update {
+        let outcome = if golden_path.exists() { SnapshotOutcome::Updated } else { SnapshotOutcome::Created };
+        write_png(&golden_path, actual)?;
+        return Ok(outcome);
+    }
+    if !golden_path.exists() {
+        return Ok(SnapshotOutcome::Failed(format!("no golden image at {}; run with `--update-snapshots` to create it", golden_path.display())));
+    }
+
+    let expected = read_png(&golden_path)?;
+    let diff = diff_bitmaps(actual, &expected, threshold)?;
+    if diff.get_diff_ratio() <= max_diff_ratio {
+        return Ok(SnapshotOutcome::Passed);
+    }
+
+    let name = spec_path.file_stem().unwrap().to_string_lossy().to_string();
+    let failures_dir = spec_path.parent().unwrap().join(SNAPSHOT_FAILURES_DIR);
+    fs::create_dir_all(&failures_dir).map_err(|error| error.to_string())?;
+    let actual_path = failures_dir.join(format!("{}.actual.png", &name));
+    let diff_path = failures_dir.join(format!("{}.diff.png", &name));
+    write_png(&actual_path, actual)?;
+    write_png(&diff_path, &diff.diff)?;
+    Ok(SnapshotOutcome::Failed(format!(
+        "{} of {} pixels ({:.3}%) differ from {}, above the allowed {:.3}%; see {} and {}",
+        diff.differing_pixels, actual.width * actual.height, diff.get_diff_ratio() * 100.0, golden_path.display(), max_diff_ratio * 100.0, actual_path.display(), diff_path.display(),
+    )))
+}
+
+/// Runs every snapshot in `snapshots_dir`, printing the outcome of each.  Returns whether all passed.
+pub fn run_snapshots(snapshots_dir: &Path, should_update: bool) -> Result<bool, String> {
+    let mut spec_paths : Vec<PathBuf> = fs::read_dir(snapshots_dir)
+        .map_err(|error| format!("Failed to read snapshots from {}: {}", snapshots_dir.display(), error))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map_or(false, |extension| extension == "json"))
+        .collect();
+    spec_paths.sort();
+
+    let mut failure_count = 0;
+    for spec_path in spec_paths.iter() {
+        let name = spec_path.file_stem().unwrap().to_string_lossy().to_string();
+        match run_snapshot(spec_path, should_update) {
+            SnapshotOutcome::Passed => println!("snapshot {} ... ok", &name),
+            SnapshotOutcome::Created => println!("snapshot {} ... created", &name),
+            SnapshotOutcome::Updated => println!("snapshot {} ... updated", &name),
+            SnapshotOutcome::Failed(message) => {
+                failure_count += 1;
+                println!("snapshot {} ... FAILED: {}", &name, message);
+            },
+        }
+    }
+
+    println!("\nsnapshot result: {}. {} passed; {} failed", if failure_count == 0 { "ok" } else { "FAILED" }, spec_paths.len() - failure_count, failure_count);
+    Ok(failure_count == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: usize, height: usize, pixel: [u8; 4]) -> Bitmap {
+        Bitmap { rgba: pixel.iter().cycle().take(width * height * 4).cloned().collect(), width, height }
+    }
+
+    #[test]
+    fn test_diff_bitmaps() {
+        let white = solid(4, 4, [255, 255, 255, 255]);
+        let black = solid(4, 4, [0, 0, 0, 255]);
+        let near_white = solid(4, 4, [250, 252, 255, 255]);
+        let transparent = solid(4, 4, [0, 0, 0, 0]);
+
+        assert_eq!(diff_bitmaps(&white, &white, 0.1).unwrap().differing_pixels, 0);
+        assert_eq!(diff_bitmaps(&white, &black, 0.1).unwrap().differing_pixels, 16);
+        assert_eq!(diff_bitmaps(&white, &near_white, 0.1).unwrap().differing_pixels, 0);
+        assert_eq!(diff_bitmaps(&white, &near_white, 0.0).unwrap().differing_pixels, 16);
+        //transparent pixels are compared as if blended onto white
+        assert_eq!(diff_bitmaps(&white, &transparent, 0.1).unwrap().differing_pixels, 0);
+        assert!(diff_bitmaps(&white, &solid(2, 2, [255, 255, 255, 255]), 0.1).is_err());
+    }
+
+    #[test]
+    fn test_missing_golden_fails_unless_updating() {
+        let snapshots_dir = std::env::temp_dir().join(format!("pax-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&snapshots_dir).unwrap();
+        let spec_path = snapshots_dir.join("missing.json");
+        let golden_path = spec_path.with_extension("png");
+        let white = solid(4, 4, [255, 255, 255, 255]);
+
+        assert!(matches!(check_against_golden(&spec_path, &white, 0.1, 0.0, false), Ok(SnapshotOutcome::Failed(_))));
+        assert!(!golden_path.exists());
+
+        assert!(matches!(check_against_golden(&spec_path, &white, 0.1, 0.0, true), Ok(SnapshotOutcome::Created)));
+        assert!(matches!(check_against_golden(&spec_path, &white, 0.1, 0.0, false), Ok(SnapshotOutcome::Passed)));
+        assert!(matches!(check_against_golden(&spec_path, &white, 0.1, 0.0, true), Ok(SnapshotOutcome::Updated)));
+
+        fs::remove_dir_all(&snapshots_dir).unwrap();
+    }
+}
@@ -0,0 +1,21 @@
+#!/bin/sh
+
+# Expects args:
+# 1: SHOULD_ALSO_RUN ∈ {"true" , "false"}
+# 2: OUTPUT_PATH : output directory for build
+# Any further args are passed through to pax-chassis-linux, e.g. `--width 800 --height 600`
+SHOULD_ALSO_RUN=$1
+OUTPUT_PATH=$2
+shift 2
+
+# Clear old build and move to output directory
+rm -rf "$OUTPUT_PATH"
+mkdir -p "$OUTPUT_PATH"
+cp ../target/debug/pax-chassis-linux "$OUTPUT_PATH"
+
+if [ "$SHOULD_ALSO_RUN" = "true" ]; then
+  # Run from the project root, so that image paths resolve as they do for other targets
+  set -e
+  cd ../../../..
+  "$OUTPUT_PATH/pax-chassis-linux" "$@"
+fi
@@ -0,0 +1,275 @@
+//! Runs a Pax cartridge as a native Linux desktop app.  Each canvas layer is rendered to its own bitmap with piet's
+//! cairo backend; the layers are then composited, with native elements (`Text`, `Frame` and `Scroller`) drawn over the
+//! layer at their depth (see `native`.)  Windowing and input are left to the binary, which presents composited frames.
+
+pub mod native;
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
+
+use cairo::{Format, ImageSurface};
+use piet::{Color, RenderContext};
+use piet_cairo::CairoRenderContext;
+
+use pax_core::{InstanceRegistry, PaxEngine, read_png_as_rgba};
+use pax_message::{ImageLoadInterruptArgs, NativeInterrupt, NativeMessage};
+use pax_message::hot_reload::HotReloadPatch;
+
+use crate::native::NativeElements;
+
+pub type LinuxRenderContext = CairoRenderContext<'static>;
+
+pub fn log_wrapper(msg: &str) {
+    eprintln!("{}", msg);
+}
+
+/// The bitmap of a single canvas layer
+struct Layer {
+    surface: ImageSurface,
+    /// Boxed so that the `'static` references lent to each frame's render context stay valid as the chassis moves
+    context: Box<cairo::Context>,
+}
+
+impl Layer {
+    fn new(width: usize, height: usize) -> Result<Self, String> {
+        let surface = ImageSurface::create(Format::ARgb32, width as i32, height as i32).map_err(|error| format!("Failed to create bitmap: {}", error))?;
+        let context = Box::new(cairo::Context::new(&surface).map_err(|error| format!("Failed to create drawing context: {}", error))?);
+        Ok(Layer { surface, context })
+    }
+}
+
+pub struct PaxChassisLinux {
+    engine: PaxEngine<LinuxRenderContext>,
+    layers: Vec<Layer>,
+    /// The composited layers and native elements, as presented
+    frame: ImageSurface,
+    native_elements: NativeElements,
+    /// Size of the bitmaps, in physical pixels
+    width: usize,
+    height: usize,
+    /// Physical pixels per logical pixel; the engine's viewport is sized in logical pixels
+    scale_factor: f64,
+    /// Patches received from `pax run --watch`, applied between ticks
+    hot_reload_patches: Option<Receiver<HotReloadPatch>>,
+}
+
+impl PaxChassisLinux {
+    /// Instantiates the attached cartridge's main component for a window of `width` x `height` physical pixels
+    pub fn new(width: usize, height: usize, scale_factor: f64) -> Result<Self, String> {
+        let instance_registry : Rc<RefCell<InstanceRegistry<LinuxRenderContext>>> = Rc::new(RefCell::new(InstanceRegistry::new()));
+        let main_component_instance = pax_cartridge::instantiate_main_component(Rc::clone(&instance_registry));
+        let expression_table = pax_cartridge::instantiate_expression_table();
+
+        let viewport_size = (width as f64 / scale_factor, height as f64 / scale_factor);
+        let engine = PaxEngine::new(main_component_instance, expression_table, pax_runtime_api::PlatformSpecificLogger::Native(log_wrapper), viewport_size, instance_registry);
+
+        Ok(PaxChassisLinux {
+            engine,
+            layers: vec![Layer::new(width, height)?],
+            frame: ImageSurface::create(Format::Rgb24, width as i32, height as i32).map_err(|error| format!("Failed to create bitmap: {}", error))?,
+            native_elements: NativeElements::default(),
+            width,
+            height,
+            scale_factor,
+            hot_reload_patches: None,
+        })
+    }
+
+    pub fn engine(&self) -> &PaxEngine<LinuxRenderContext> {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut PaxEngine<LinuxRenderContext> {
+        &mut self.engine
+    }
+
+    pub fn get_scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Called upon window resizes, including changes of scale factor, e.g. when moving between displays
+    pub fn resize(&mut self, width: usize, height: usize, scale_factor: f64) -> Result<(), String> {
+        if (width, height, scale_factor) == (self.width, self.height, self.scale_factor) {
+            return Ok(());
+        }
+        self.layers = (0..self.layers.len()).map(|_| Layer::new(width, height)).collect::<Result<_, _>>()?;
+        self.frame = ImageSurface::create(Format::Rgb24, width as i32, height as i32).map_err(|error| format!("Failed to create bitmap: {}", error))?;
+        self.width = width;
+        self.height = height;
+        self.scale_factor = scale_factor;
+        self.engine.set_viewport_size((width as f64 / scale_factor, height as f64 / scale_factor));
+        self.engine.request_render();
+        Ok(())
+    }
+
+    /// If this chassis was built by `pax run --watch`, listens on a background thread for hot-reloaded templates,
+    /// which are sent as Server-Sent Events with patches as JSON data
+    pub fn listen_for_hot_reload_patches(&mut self) {
+        let port : u16 = match option_env!("PAX_HOT_RELOAD_PORT").and_then(|port| port.parse().ok()) {
+            Some(port) => port,
+            None => return,
+        };
+        let (sender, receiver) = channel();
+        self.hot_reload_patches = Some(receiver);
+
+        std::thread::spawn(move || {
+            let mut stream = match TcpStream::connect(("127.0.0.1", port)) {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            if stream.write_all(b"GET / HTTP/1.1\r\nAccept: text/event-stream\r\n\r\n").is_err() {
+                return;
+            }
+            for line in BufReader::new(stream).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if let Some(data) = line.strip_prefix("data: ") {
+                    if let Ok(patch) = serde_json::from_str::<HotReloadPatch>(data) {
+                        if sender.send(patch).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Whether the next `tick` may render anything new, polled on each display refresh so that idle frames can be
+    /// skipped without redrawing.  Also applies any hot-reloaded templates received since the last call.
+    pub fn needs_render(&mut self) -> bool {
+        if let Some(receiver) = &self.hot_reload_patches {
+            for patch in receiver.try_iter() {
+                if let Err(message) = self.engine.apply_hot_reload_patch(patch, &pax_cartridge::instantiate_hot_reload_cartridge()) {
+                    log_wrapper(&format!("Failed to hot reload: {}", message));
+                }
+            }
+        }
+        self.engine.needs_render()
+    }
+
+    /// Ticks the engine, rendering each canvas layer, then composites the layers and native elements into the frame
+    pub fn tick(&mut self) {
+        let mut render_contexts : Vec<LinuxRenderContext> = self.layers.iter().map(|layer| {
+            //SAFETY: the render contexts are dropped at the end of this tick, while each `layer.context` lives until
+            //the layer is replaced, which only happens between ticks
+            let context : &'static cairo::Context = unsafe { &*(layer.context.as_ref() as *const cairo::Context) };
+            context.identity_matrix();
+            context.scale(self.scale_factor, self.scale_factor);
+            let mut render_context = CairoRenderContext::new(context);
+            render_context.clear(None, Color::rgba8(0, 0, 0, 0));
+            render_context
+        }).collect();
+
+        let messages = self.engine.tick(&mut render_contexts);
+        for mut render_context in render_contexts {
+            let _ = render_context.finish();
+        }
+        for layer in self.layers.iter() {
+            layer.surface.flush();
+        }
+
+        for message in messages.iter() {
+            match message {
+                NativeMessage::ImageLoad(patch) => {
+                    if let Some(path) = &patch.path {
+                        match read_png_as_rgba(Path::new(path)) {
+                            Ok((data, width, height)) => self.engine.loadImage(patch.id_chain.clone(), data, width, height),
+                            Err(message) => log_wrapper(&format!("Failed to load image {}: {}", path, message)),
+                        }
+                    }
+                },
+                NativeMessage::LayerAdd(patch) => {
+                    for _ in 0..patch.num_layers_to_add {
+                        match Layer::new(self.width, self.height) {
+                            Ok(layer) => self.layers.push(layer),
+                            Err(message) => log_wrapper(&message),
+                        }
+                    }
+                    //the added layers are rendered to from the next tick
+                    self.engine.request_render();
+                },
+                _ => self.native_elements.handle_message(message),
+            }
+        }
+
+        self.composite();
+    }
+
+    /// Paints the layers bottom to top over a white background, drawing the native elements at each depth above its layer
+    fn composite(&mut self) {
+        let context = match cairo::Context::new(&self.frame) {
+            Ok(context) => context,
+            Err(error) => return log_wrapper(&format!("Failed to create drawing context: {}", error)),
+        };
+        context.set_source_rgb(1.0, 1.0, 1.0);
+        let _ = context.paint();
+
+        self.native_elements.begin_frame();
+        let layer_count = self.layers.len();
+        for (depth, layer) in self.layers.iter().enumerate() {
+            context.identity_matrix();
+            if context.set_source_surface(&layer.surface, 0.0, 0.0).is_ok() {
+                let _ = context.paint();
+            }
+
+            context.scale(self.scale_factor, self.scale_factor);
+            let mut render_context = CairoRenderContext::new(&context);
+            self.native_elements.draw(&mut render_context, depth, layer_count);
+            let _ = render_context.finish();
+        }
+        drop(context);
+        self.frame.flush();
+    }
+
+    /// Copies the last composited frame into `buffer`, row by row, as `0RGB` pixels of `width` x `height`
+    pub fn copy_frame_to(&self, buffer: &mut [u32]) {
+        let stride = self.frame.stride() as usize;
+        let _ = self.frame.with_data(|data| {
+            for (row, pixels) in buffer.chunks_mut(self.width).take(self.height).enumerate() {
+                for (column, pixel) in pixels.iter_mut().enumerate() {
+                    let offset = row * stride + column * 4;
+                    *pixel = u32::from_ne_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) & 0x00ffffff;
+                }
+            }
+        });
+    }
+
+    /// The URL of the topmost link in a `Text` at `(x, y)`, in logical pixels, as of the last frame
+    pub fn get_link_beneath_point(&self, x: f64, y: f64) -> Option<&str> {
+        self.native_elements.get_link_beneath_point((x, y))
+    }
+
+    /// Dispatches an interrupt translated from OS input, with coordinates in logical pixels.  Returns whether a handler
+    /// called `prevent_default` on the resulting event, in which case the chassis skips its own handling, e.g. opening links.
+    pub fn interrupt(&mut self, native_interrupt: NativeInterrupt) -> bool {
+        self.engine.request_render();
+        let mut default_prevented = false;
+        match native_interrupt {
+            NativeInterrupt::Image(args) => {
+                match args {
+                    ImageLoadInterruptArgs::Reference(_) => {},
+                    ImageLoadInterruptArgs::Data(_) => {
+                        log_wrapper("Image data interrupts carry no payload on Linux; images are loaded from the paths requested by `ImageLoad` messages");
+                    },
+                }
+            },
+            //layers are added upon `LayerAdd` messages, as they're emitted
+            NativeInterrupt::AddedLayer(_) => {},
+            NativeInterrupt::HotReload(patch) => {
+                if let Err(message) = self.engine.apply_hot_reload_patch(patch, &pax_cartridge::instantiate_hot_reload_cartridge()) {
+                    log_wrapper(&format!("Failed to hot reload: {}", message));
+                }
+            },
+            input => {
+                default_prevented = self.engine.dispatch_interrupt(input);
+            },
+        }
+        default_prevented
+    }
+}
@@ -0,0 +1,296 @@
+//! Opens the attached cartridge in a native window, translating window-system input into `NativeInterrupt`s.
+//!
+//! Usage: `pax-chassis-linux [--width W] [--height H]`, where the initial size of the window is in logical pixels
+//! (1280x720 by default.)  Frames are rendered only when the engine needs them, polled on each display refresh.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::process::{exit, Command};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use winit::dpi::{LogicalSize, PhysicalPosition};
+use winit::event::{ElementState, Event, KeyEvent, MouseScrollDelta, TouchPhase, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::WindowBuilder;
+
+use pax_message::{ClickInterruptArgs, ContextMenuInterruptArgs, DoubleClickInterruptArgs, JabInterruptArgs, KeyDownInterruptArgs, KeyPressInterruptArgs, KeyUpInterruptArgs, ModifierKeyMessage, MouseButtonMessage, MouseDownInterruptArgs, MouseMoveInterruptArgs, MouseOutInterruptArgs, MouseOverInterruptArgs, MouseUpInterruptArgs, NativeInterrupt, ScrollInterruptArgs, TouchEndInterruptArgs, TouchMessage, TouchMoveInterruptArgs, TouchStartInterruptArgs, WheelInterruptArgs};
+use paxchassislinux::PaxChassisLinux;
+
+/// Interval between polls of `needs_render`, i.e. the maximum frame rate
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+/// Pixels scrolled per line, for wheels that scroll by lines rather than pixels
+const WHEEL_LINE_HEIGHT: f64 = 40.0;
+/// Maximum interval and distance between two clicks for the second to also be a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const DOUBLE_CLICK_DISTANCE: f64 = 4.0;
+
+struct LinuxArgs {
+    width: f64,
+    height: f64,
+}
+
+fn parse_args() -> Result<LinuxArgs, String> {
+    let mut args = LinuxArgs { width: 1280.0, height: 720.0 };
+
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(flag) = raw_args.next() {
+        let value = raw_args.next().ok_or_else(|| format!("Missing value for {}", &flag))?;
+        let parse_number = |value: &str| value.parse::<f64>().map_err(|_| format!("Expected a number for {}, found `{}`", &flag, value));
+        match flag.as_str() {
+            "--width" => args.width = parse_number(&value)?,
+            "--height" => args.height = parse_number(&value)?,
+            _ => return Err(format!("Unknown argument {}", &flag)),
+        }
+    }
+    Ok(args)
+}
+
+/// Input state accumulated across window events, for translating them into interrupts
+#[derive(Default)]
+struct InputState {
+    /// Cursor position, in logical pixels
+    cursor: (f64, f64),
+    modifiers: ModifiersState,
+    /// Button pressed within the window and not yet released, with where it was pressed
+    pressed: Option<(MouseButtonMessage, (f64, f64))>,
+    last_click: Option<(Instant, (f64, f64))>,
+    /// Active touches by id, with their last positions in logical pixels
+    touches: HashMap<u64, (f64, f64)>,
+}
+
+impl InputState {
+    fn get_modifiers(&self) -> Vec<ModifierKeyMessage> {
+        let mut modifiers = vec![];
+        if self.modifiers.shift_key() { modifiers.push(ModifierKeyMessage::Shift); }
+        if self.modifiers.control_key() { modifiers.push(ModifierKeyMessage::Control); }
+        if self.modifiers.alt_key() { modifiers.push(ModifierKeyMessage::Alt); }
+        if self.modifiers.super_key() { modifiers.push(ModifierKeyMessage::Command); }
+        modifiers
+    }
+
+    /// The pressed button, for events such as `MouseMove` that report one regardless; `Left` when none is pressed, as on Web
+    fn get_button(&self) -> MouseButtonMessage {
+        match &self.pressed {
+            Some((button, _)) => clone_button(button),
+            None => MouseButtonMessage::Left,
+        }
+    }
+
+    /// Touch messages for every active touch, with deltas from `previous` positions
+    fn get_touch_messages(&self, previous: &HashMap<u64, (f64, f64)>) -> Vec<TouchMessage> {
+        self.touches.iter().map(|(id, (x, y))| {
+            let (last_x, last_y) = previous.get(id).cloned().unwrap_or((*x, *y));
+            TouchMessage { x: *x, y: *y, identifier: *id as i64, delta_x: last_x - x, delta_y: last_y - y }
+        }).collect()
+    }
+}
+
+fn clone_button(button: &MouseButtonMessage) -> MouseButtonMessage {
+    match button {
+        MouseButtonMessage::Left => MouseButtonMessage::Left,
+        MouseButtonMessage::Right => MouseButtonMessage::Right,
+        MouseButtonMessage::Middle => MouseButtonMessage::Middle,
+        MouseButtonMessage::Unknown => MouseButtonMessage::Unknown,
+    }
+}
+
+fn get_button_message(button: winit::event::MouseButton) -> MouseButtonMessage {
+    match button {
+        winit::event::MouseButton::Left => MouseButtonMessage::Left,
+        winit::event::MouseButton::Right => MouseButtonMessage::Right,
+        winit::event::MouseButton::Middle => MouseButtonMessage::Middle,
+        _ => MouseButtonMessage::Unknown,
+    }
+}
+
+/// Names keys as the `key` of a DOM `KeyboardEvent` would, e.g. `ArrowDown` or `a`, for parity with the web chassis
+fn get_key_name(key: &Key) -> Option<String> {
+    match key {
+        Key::Character(text) => Some(text.to_string()),
+        Key::Named(NamedKey::Space) => Some(" ".to_string()),
+        Key::Named(NamedKey::Super) => Some("Meta".to_string()),
+        Key::Named(named) => Some(format!("{:?}", named)),
+        _ => None,
+    }
+}
+
+/// Translates a window event into interrupts for the chassis, updating `input` along the way
+fn handle_window_event(chassis: &mut PaxChassisLinux, input: &mut InputState, event: &WindowEvent) {
+    let scale_factor = chassis.get_scale_factor();
+    let to_logical = |position: &PhysicalPosition<f64>| (position.x / scale_factor, position.y / scale_factor);
+
+    match event {
+        WindowEvent::ModifiersChanged(modifiers) => {
+            input.modifiers = modifiers.state();
+        },
+        WindowEvent::CursorMoved { position, .. } => {
+            input.cursor = to_logical(position);
+            let (x, y) = input.cursor;
+            chassis.interrupt(NativeInterrupt::MouseMove(MouseMoveInterruptArgs { x, y, button: input.get_button(), modifiers: input.get_modifiers() }));
+        },
+        WindowEvent::CursorEntered { .. } => {
+            let (x, y) = input.cursor;
+            chassis.interrupt(NativeInterrupt::MouseOver(MouseOverInterruptArgs { x, y, button: input.get_button(), modifiers: input.get_modifiers() }));
+        },
+        WindowEvent::CursorLeft { .. } => {
+            let (x, y) = input.cursor;
+            chassis.interrupt(NativeInterrupt::MouseOut(MouseOutInterruptArgs { x, y, button: input.get_button(), modifiers: input.get_modifiers() }));
+        },
+        WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+            let (x, y) = input.cursor;
+            let button = get_button_message(*button);
+            if let MouseButtonMessage::Right = button {
+                chassis.interrupt(NativeInterrupt::ContextMenu(ContextMenuInterruptArgs { x, y, button: MouseButtonMessage::Right, modifiers: input.get_modifiers() }));
+            }
+            chassis.interrupt(NativeInterrupt::MouseDown(MouseDownInterruptArgs { x, y, button: clone_button(&button), modifiers: input.get_modifiers() }));
+            input.pressed = Some((button, (x, y)));
+        },
+        WindowEvent::MouseInput { state: ElementState::Released, button, .. } => {
+            let (x, y) = input.cursor;
+            let button = get_button_message(*button);
+            chassis.interrupt(NativeInterrupt::MouseUp(MouseUpInterruptArgs { x, y, button: clone_button(&button), modifiers: input.get_modifiers() }));
+
+            //as on Web, a click is a press and release of the primary button within the window
+            let was_pressed_within_window = matches!(input.pressed.take(), Some((MouseButtonMessage::Left, _)));
+            if !matches!(button, MouseButtonMessage::Left) || !was_pressed_within_window {
+                return;
+            }
+            let is_click_prevented = chassis.interrupt(NativeInterrupt::Click(ClickInterruptArgs { x, y, button: MouseButtonMessage::Left, modifiers: input.get_modifiers() }));
+            chassis.interrupt(NativeInterrupt::Jab(JabInterruptArgs { x, y }));
+
+            let now = Instant::now();
+            let is_double_click = input.last_click.is_some_and(|(time, (last_x, last_y))| {
+                now - time <= DOUBLE_CLICK_INTERVAL && (x - last_x).hypot(y - last_y) <= DOUBLE_CLICK_DISTANCE
+            });
+            if is_double_click {
+                chassis.interrupt(NativeInterrupt::DoubleClick(DoubleClickInterruptArgs { x, y, button: MouseButtonMessage::Left, modifiers: input.get_modifiers() }));
+                input.last_click = None;
+            } else {
+                input.last_click = Some((now, (x, y)));
+            }
+
+            if is_click_prevented {
+                return;
+            }
+            if let Some(url) = chassis.get_link_beneath_point(x, y) {
+                if let Err(error) = Command::new("xdg-open").arg(url).spawn() {
+                    eprintln!("Failed to open {}: {}", url, error);
+                }
+            }
+        },
+        WindowEvent::MouseWheel { delta, .. } => {
+            //positive deltas scroll down and right, as with DOM `WheelEvent`s
+            let (delta_x, delta_y) = match delta {
+                MouseScrollDelta::LineDelta(x, y) => (-*x as f64 * WHEEL_LINE_HEIGHT, -*y as f64 * WHEEL_LINE_HEIGHT),
+                MouseScrollDelta::PixelDelta(position) => (-position.x / scale_factor, -position.y / scale_factor),
+            };
+            let (x, y) = input.cursor;
+            chassis.interrupt(NativeInterrupt::Wheel(WheelInterruptArgs { x, y, delta_x, delta_y, modifiers: input.get_modifiers() }));
+            chassis.interrupt(NativeInterrupt::Scroll(ScrollInterruptArgs { delta_x, delta_y }));
+        },
+        WindowEvent::KeyboardInput { event: KeyEvent { logical_key, text, state, repeat, .. }, .. } => {
+            let key = match get_key_name(logical_key) {
+                Some(key) => key,
+                None => return,
+            };
+            if let ElementState::Released = state {
+                chassis.interrupt(NativeInterrupt::KeyUp(KeyUpInterruptArgs { key, modifiers: input.get_modifiers(), is_repeat: *repeat }));
+                return;
+            }
+            chassis.interrupt(NativeInterrupt::KeyDown(KeyDownInterruptArgs { key: key.clone(), modifiers: input.get_modifiers(), is_repeat: *repeat }));
+            //as on Web, key presses are only for keys that produce characters, e.g. not for arrow keys
+            if text.is_some() {
+                chassis.interrupt(NativeInterrupt::KeyPress(KeyPressInterruptArgs { key, modifiers: input.get_modifiers(), is_repeat: *repeat }));
+            }
+        },
+        WindowEvent::Touch(touch) => {
+            let position = to_logical(&touch.location);
+            let previous = input.touches.clone();
+            match touch.phase {
+                TouchPhase::Started => {
+                    input.touches.insert(touch.id, position);
+                    chassis.interrupt(NativeInterrupt::TouchStart(TouchStartInterruptArgs { touches: input.get_touch_messages(&previous) }));
+                    chassis.interrupt(NativeInterrupt::Jab(JabInterruptArgs { x: position.0, y: position.1 }));
+                },
+                TouchPhase::Moved => {
+                    input.touches.insert(touch.id, position);
+                    let touches = input.get_touch_messages(&previous);
+                    let (delta_x, delta_y) = touches.iter().find(|message| message.identifier == touch.id as i64).map_or((0.0, 0.0), |message| (message.delta_x, message.delta_y));
+                    chassis.interrupt(NativeInterrupt::TouchMove(TouchMoveInterruptArgs { touches }));
+                    chassis.interrupt(NativeInterrupt::Scroll(ScrollInterruptArgs { delta_x, delta_y }));
+                },
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    let (last_x, last_y) = previous.get(&touch.id).cloned().unwrap_or(position);
+                    input.touches.remove(&touch.id);
+                    let touches = vec![TouchMessage { x: position.0, y: position.1, identifier: touch.id as i64, delta_x: last_x - position.0, delta_y: last_y - position.1 }];
+                    chassis.interrupt(NativeInterrupt::TouchEnd(TouchEndInterruptArgs { touches }));
+                },
+            }
+        },
+        _ => {},
+    }
+}
+
+fn run(args: LinuxArgs) -> Result<(), String> {
+    let event_loop = EventLoop::new().map_err(|error| format!("Failed to connect to the display: {}", error))?;
+    let window = Rc::new(WindowBuilder::new()
+        .with_title("Pax")
+        .with_inner_size(LogicalSize::new(args.width, args.height))
+        .build(&event_loop)
+        .map_err(|error| format!("Failed to open a window: {}", error))?);
+    let context = softbuffer::Context::new(Rc::clone(&window)).map_err(|error| format!("Failed to create a drawing surface: {}", error))?;
+    let mut surface = softbuffer::Surface::new(&context, Rc::clone(&window)).map_err(|error| format!("Failed to create a drawing surface: {}", error))?;
+
+    let size = window.inner_size();
+    let mut chassis = PaxChassisLinux::new(size.width.max(1) as usize, size.height.max(1) as usize, window.scale_factor())?;
+    chassis.listen_for_hot_reload_patches();
+    let mut input = InputState::default();
+
+    event_loop.run(move |event, event_loop| {
+        match event {
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => event_loop.exit(),
+            Event::WindowEvent { event: WindowEvent::Resized(size), .. } if size.width > 0 && size.height > 0 => {
+                if let Err(message) = chassis.resize(size.width as usize, size.height as usize, window.scale_factor()) {
+                    eprintln!("{}", message);
+                }
+            },
+            Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => {
+                let size = window.inner_size();
+                let (width, height) = match (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+                    (Some(width), Some(height)) => (width, height),
+                    _ => return,
+                };
+                //the bitmaps may lag the window between a resize and its `Resized` event
+                if let Err(message) = chassis.resize(size.width as usize, size.height as usize, window.scale_factor()) {
+                    return eprintln!("{}", message);
+                }
+                chassis.tick();
+                if surface.resize(width, height).is_err() {
+                    return;
+                }
+                if let Ok(mut buffer) = surface.buffer_mut() {
+                    chassis.copy_frame_to(&mut buffer);
+                    let _ = buffer.present();
+                }
+            },
+            Event::WindowEvent { event, .. } => handle_window_event(&mut chassis, &mut input, &event),
+            Event::AboutToWait => {
+                if chassis.needs_render() {
+                    window.request_redraw();
+                }
+                event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + FRAME_INTERVAL));
+            },
+            _ => {},
+        }
+    }).map_err(|error| error.to_string())
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        exit(1);
+    }
+}
@@ -0,0 +1,460 @@
+//! Native elements, i.e. `Text`, `Frame` and `Scroller`, which other chassis render with platform views (e.g. DOM
+//! elements on Web) above each canvas layer.  Here they are tracked from their patches and drawn with piet over the
+//! canvas layer at their depth, clipped by the frames and scrollers that contain them.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use piet::{Color, FontFamily, FontStyle, FontWeight, RenderContext, Text, TextAlignment, TextAttribute, TextLayout, TextLayoutBuilder};
+use piet::kurbo::{Affine, Point, Rect, Shape};
+
+use pax_message::{AnyCreatePatch, ColorVariantMessage, FontPatch, FontStyleMessage, FontWeightMessage, FramePatch, NativeMessage, ScrollerPatch, TextAlignHorizontalMessage, TextAlignVerticalMessage, TextPatch, TextStyleMessage};
+
+/// The rectangle of a `Frame` or `Scroller`, outside of which its contents are clipped
+#[derive(Default)]
+struct Clip {
+    transform: Affine,
+    size: (f64, f64),
+}
+
+impl Clip {
+    fn get_shape(&self) -> impl Shape {
+        self.transform * Rect::new(0.0, 0.0, self.size.0, self.size.1).to_path(0.1)
+    }
+}
+
+/// A text style with every field resolved, accumulated from the partial styles of successive `TextPatch`es
+struct TextStyle {
+    font_family: Option<String>,
+    font_weight: FontWeight,
+    font_style: FontStyle,
+    font_size: f64,
+    fill: Color,
+    underline: bool,
+    align_multiline: Option<TextAlignment>,
+    align_horizontal: Option<TextAlignment>,
+    /// Fraction of the free vertical space above the text: 0 for top, 0.5 for center and 1 for bottom
+    align_vertical: f64,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            font_family: None,
+            font_weight: FontWeight::REGULAR,
+            font_style: FontStyle::Regular,
+            font_size: 16.0,
+            fill: Color::BLACK,
+            underline: false,
+            align_multiline: None,
+            align_horizontal: None,
+            align_vertical: 0.0,
+        }
+    }
+}
+
+/// Overrides applied to the links within a text, as with `style_link`
+#[derive(Default)]
+struct LinkStyle {
+    font_family: Option<String>,
+    fill: Option<Color>,
+    underline: Option<bool>,
+}
+
+struct Link {
+    range: Range<usize>,
+    url: String,
+}
+
+struct TextElement {
+    /// Order of creation, by which texts at the same depth are drawn
+    ordinal: usize,
+    clipping_ids: Vec<Vec<u64>>,
+    transform: Affine,
+    size: (f64, f64),
+    depth: usize,
+    /// `content` with its links' markup removed
+    text: String,
+    links: Vec<Link>,
+    style: TextStyle,
+    link_style: LinkStyle,
+}
+
+/// A link as last drawn, for hit testing clicks
+struct LinkRegion {
+    transform: Affine,
+    rect: Rect,
+    url: String,
+}
+
+#[derive(Default)]
+pub struct NativeElements {
+    texts: HashMap<Vec<u64>, TextElement>,
+    clips: HashMap<Vec<u64>, Clip>,
+    created_count: usize,
+    link_regions: Vec<LinkRegion>,
+}
+
+impl NativeElements {
+    /// Applies a native message emitted by a tick; `ImageLoad` and `LayerAdd` are handled by the chassis
+    pub fn handle_message(&mut self, message: &NativeMessage) {
+        match message {
+            NativeMessage::TextCreate(patch) => self.create_text(patch),
+            NativeMessage::TextUpdate(patch) => self.update_text(patch),
+            NativeMessage::TextDelete(id_chain) => {
+                self.texts.remove(id_chain);
+            },
+            NativeMessage::FrameCreate(patch) | NativeMessage::ScrollerCreate(patch) => {
+                self.clips.insert(patch.id_chain.clone(), Clip::default());
+            },
+            NativeMessage::FrameUpdate(patch) => self.update_frame(patch),
+            NativeMessage::ScrollerUpdate(patch) => self.update_scroller(patch),
+            NativeMessage::FrameDelete(id_chain) | NativeMessage::ScrollerDelete(id_chain) => {
+                self.clips.remove(id_chain);
+            },
+            NativeMessage::ImageLoad(_) | NativeMessage::LayerAdd(_) => {},
+        }
+    }
+
+    fn create_text(&mut self, patch: &AnyCreatePatch) {
+        self.created_count += 1;
+        self.texts.insert(patch.id_chain.clone(), TextElement {
+            ordinal: self.created_count,
+            clipping_ids: patch.clipping_ids.clone(),
+            transform: Affine::IDENTITY,
+            size: (0.0, 0.0),
+            depth: 0,
+            text: String::new(),
+            links: vec![],
+            style: TextStyle::default(),
+            link_style: LinkStyle::default(),
+        });
+    }
+
+    fn update_text(&mut self, patch: &TextPatch) {
+        let element = match self.texts.get_mut(&patch.id_chain) {
+            Some(element) => element,
+            None => return,
+        };
+        if let Some(content) = &patch.content {
+            let (text, links) = parse_links(content);
+            element.text = text;
+            element.links = links;
+        }
+        if let Some(transform) = &patch.transform {
+            element.transform = affine_from_coeffs(transform);
+        }
+        if let Some(size_x) = patch.size_x {
+            element.size.0 = size_x;
+        }
+        if let Some(size_y) = patch.size_y {
+            element.size.1 = size_y;
+        }
+        if let Some(depth) = patch.depth {
+            element.depth = depth;
+        }
+        if let Some(style) = &patch.style {
+            apply_text_style(&mut element.style, style);
+        }
+        if let Some(style_link) = &patch.style_link {
+            let link_style = &mut element.link_style;
+            if let Some(font) = &style_link.font {
+                link_style.font_family = get_font_family(font);
+            }
+            if let Some(fill) = &style_link.fill {
+                link_style.fill = Some(color_from_message(fill));
+            }
+            if let Some(underline) = style_link.underline {
+                link_style.underline = Some(underline);
+            }
+        }
+    }
+
+    fn update_frame(&mut self, patch: &FramePatch) {
+        if let Some(clip) = self.clips.get_mut(&patch.id_chain) {
+            if let Some(transform) = &patch.transform {
+                clip.transform = affine_from_coeffs(transform);
+            }
+            if let Some(size_x) = patch.size_x {
+                clip.size.0 = size_x;
+            }
+            if let Some(size_y) = patch.size_y {
+                clip.size.1 = size_y;
+            }
+        }
+    }
+
+    fn update_scroller(&mut self, patch: &ScrollerPatch) {
+        if let Some(clip) = self.clips.get_mut(&patch.id_chain) {
+            if let Some(transform) = &patch.transform {
+                clip.transform = affine_from_coeffs(transform);
+            }
+            if let Some(size_frame_x) = patch.size_frame_x {
+                clip.size.0 = size_frame_x;
+            }
+            if let Some(size_frame_y) = patch.size_frame_y {
+                clip.size.1 = size_frame_y;
+            }
+        }
+    }
+
+    /// Forgets the link regions of the last frame, before drawing the next
+    pub fn begin_frame(&mut self) {
+        self.link_regions.clear();
+    }
+
+    /// Draws the texts at `depth`, or at any deeper depth if this is the last of `layer_count` layers, as the
+    /// engine renders their canvas counterparts
+    pub fn draw<R: RenderContext>(&mut self, rc: &mut R, depth: usize, layer_count: usize) {
+        let is_last_layer = depth + 1 >= layer_count;
+        let mut texts : Vec<&TextElement> = self.texts.values()
+            .filter(|element| element.depth == depth || (is_last_layer && element.depth > depth))
+            .collect();
+        texts.sort_by_key(|element| element.ordinal);
+
+        for element in texts {
+            let _ = rc.with_save(|rc| {
+                for clipping_id in element.clipping_ids.iter() {
+                    if let Some(clip) = self.clips.get(clipping_id) {
+                        rc.clip(clip.get_shape());
+                    }
+                }
+                rc.transform(element.transform);
+
+                let layout = build_text_layout(rc.text(), element)?;
+                let free_height = (element.size.1 - layout.size().height).max(0.0);
+                let origin = Point::new(0.0, free_height * element.style.align_vertical);
+                rc.draw_text(&layout, origin);
+
+                for link in element.links.iter() {
+                    for rect in layout.rects_for_range(link.range.clone()) {
+                        self.link_regions.push(LinkRegion {
+                            transform: element.transform,
+                            rect: rect + origin.to_vec2(),
+                            url: link.url.clone(),
+                        });
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    /// The URL of the topmost link drawn at `point`, if any
+    pub fn get_link_beneath_point(&self, point: (f64, f64)) -> Option<&str> {
+        let point = Point::new(point.0, point.1);
+        self.link_regions.iter().rev()
+            .find(|region| region.rect.contains(region.transform.inverse() * point))
+            .map(|region| region.url.as_str())
+    }
+}
+
+fn build_text_layout<T: Text>(text: &mut T, element: &TextElement) -> Result<T::TextLayout, piet::Error> {
+    let style = &element.style;
+    let font_family = style.font_family.as_ref().map_or(FontFamily::SYSTEM_UI, |family| FontFamily::new_unchecked(family.as_str()));
+    let alignment = style.align_multiline.or(style.align_horizontal).unwrap_or(TextAlignment::Start);
+
+    let mut builder = text.new_text_layout(element.text.clone())
+        .font(font_family, style.font_size)
+        .default_attribute(TextAttribute::Weight(style.font_weight))
+        .default_attribute(TextAttribute::Style(style.font_style))
+        .default_attribute(TextAttribute::Underline(style.underline))
+        .text_color(style.fill)
+        .alignment(alignment)
+        .max_width(element.size.0);
+
+    let link_style = &element.link_style;
+    for link in element.links.iter() {
+        if let Some(family) = &link_style.font_family {
+            builder = builder.range_attribute(link.range.clone(), TextAttribute::FontFamily(FontFamily::new_unchecked(family.as_str())));
+        }
+        if let Some(fill) = &link_style.fill {
+            builder = builder.range_attribute(link.range.clone(), TextAttribute::TextColor(*fill));
+        }
+        if let Some(underline) = link_style.underline {
+            builder = builder.range_attribute(link.range.clone(), TextAttribute::Underline(underline));
+        }
+    }
+    builder.build()
+}
+
+fn apply_text_style(style: &mut TextStyle, patch: &TextStyleMessage) {
+    if let Some(font) = &patch.font {
+        style.font_family = get_font_family(font);
+        let (font_style, font_weight) = match font {
+            FontPatch::System(font) => (&font.style, &font.weight),
+            FontPatch::Web(font) => (&font.style, &font.weight),
+            FontPatch::Local(font) => (&font.style, &font.weight),
+        };
+        if let Some(font_style) = font_style {
+            style.font_style = match font_style {
+                FontStyleMessage::Normal => FontStyle::Regular,
+                FontStyleMessage::Italic | FontStyleMessage::Oblique => FontStyle::Italic,
+            };
+        }
+        if let Some(font_weight) = font_weight {
+            style.font_weight = font_weight_from_message(font_weight);
+        }
+    }
+    if let Some(font_size) = patch.font_size {
+        style.font_size = font_size;
+    }
+    if let Some(fill) = &patch.fill {
+        style.fill = color_from_message(fill);
+    }
+    if let Some(underline) = patch.underline {
+        style.underline = underline;
+    }
+    if let Some(align_multiline) = &patch.align_multiline {
+        style.align_multiline = Some(alignment_from_message(align_multiline));
+    }
+    if let Some(align_horizontal) = &patch.align_horizontal {
+        style.align_horizontal = Some(alignment_from_message(align_horizontal));
+    }
+    if let Some(align_vertical) = &patch.align_vertical {
+        style.align_vertical = match align_vertical {
+            TextAlignVerticalMessage::Top => 0.0,
+            TextAlignVerticalMessage::Center => 0.5,
+            TextAlignVerticalMessage::Bottom => 1.0,
+        };
+    }
+}
+
+/// Web fonts can't be fetched and Pango can't load fonts from data, so fonts are resolved by family name alone,
+/// falling back to the system UI font where the family isn't installed
+fn get_font_family(font: &FontPatch) -> Option<String> {
+    match font {
+        FontPatch::System(font) => font.family.clone(),
+        FontPatch::Web(font) => font.family.clone(),
+        FontPatch::Local(font) => font.family.clone(),
+    }
+}
+
+fn font_weight_from_message(weight: &FontWeightMessage) -> FontWeight {
+    match weight {
+        FontWeightMessage::Thin => FontWeight::THIN,
+        FontWeightMessage::ExtraLight => FontWeight::EXTRA_LIGHT,
+        FontWeightMessage::Light => FontWeight::LIGHT,
+        FontWeightMessage::Normal => FontWeight::REGULAR,
+        FontWeightMessage::Medium => FontWeight::MEDIUM,
+        FontWeightMessage::SemiBold => FontWeight::SEMI_BOLD,
+        FontWeightMessage::Bold => FontWeight::BOLD,
+        FontWeightMessage::ExtraBold => FontWeight::EXTRA_BOLD,
+        FontWeightMessage::Black => FontWeight::BLACK,
+    }
+}
+
+fn alignment_from_message(alignment: &TextAlignHorizontalMessage) -> TextAlignment {
+    match alignment {
+        TextAlignHorizontalMessage::Left => TextAlignment::Start,
+        TextAlignHorizontalMessage::Center => TextAlignment::Center,
+        TextAlignHorizontalMessage::Right => TextAlignment::End,
+    }
+}
+
+fn color_from_message(color: &ColorVariantMessage) -> Color {
+    match color {
+        ColorVariantMessage::Hlca(channels) => Color::hlca(channels[0], channels[1], channels[2], channels[3]),
+        ColorVariantMessage::Hlc(channels) => Color::hlc(channels[0], channels[1], channels[2]),
+        ColorVariantMessage::Rgba(channels) => Color::rgba(channels[0], channels[1], channels[2], channels[3]),
+        ColorVariantMessage::Rgb(channels) => Color::rgb(channels[0], channels[1], channels[2]),
+    }
+}
+
+fn affine_from_coeffs(coeffs: &[f64]) -> Affine {
+    Affine::new([coeffs[0], coeffs[1], coeffs[2], coeffs[3], coeffs[4], coeffs[5]])
+}
+
+/// Separates the links in a text's content from the text to display, for the subset of markup rendered by the web
+/// chassis that's used for links: Markdown links, e.g. `[Docs](https://docs.pax.rs)`, and HTML anchors, e.g.
+/// `<a href='https://docs.pax.rs'>Docs</a>`.  Other HTML tags are removed, apart from `<br>`, which breaks the line.
+fn parse_links(content: &str) -> (String, Vec<Link>) {
+    let mut text = String::with_capacity(content.len());
+    let mut links = vec![];
+    let mut rest = content;
+
+    while let Some(character) = rest.chars().next() {
+        if character == '[' {
+            if let Some((label, url, remainder)) = parse_markdown_link(rest) {
+                let start = text.len();
+                text.push_str(label);
+                links.push(Link { range: start..text.len(), url: url.to_string() });
+                rest = remainder;
+                continue;
+            }
+        }
+        if character == '<' {
+            if let Some((label, url, remainder)) = parse_anchor(rest) {
+                let start = text.len();
+                text.push_str(label);
+                links.push(Link { range: start..text.len(), url: url.to_string() });
+                rest = remainder;
+                continue;
+            }
+            let is_tag_start = rest[1..].starts_with(|next: char| next.is_ascii_alphabetic() || next == '/');
+            if let Some(tag_end) = rest.find('>').filter(|tag_end| is_tag_start && !rest[1..*tag_end].contains('<')) {
+                let tag = rest[1..tag_end].trim().trim_end_matches('/').trim();
+                if tag.eq_ignore_ascii_case("br") {
+                    text.push('\n');
+                }
+                rest = &rest[tag_end + 1..];
+                continue;
+            }
+        }
+        text.push(character);
+        rest = &rest[character.len_utf8()..];
+    }
+    (text, links)
+}
+
+/// Parses `[label](url)` at the start of `input`, returning the label, the URL and the remaining input
+fn parse_markdown_link(input: &str) -> Option<(&str, &str, &str)> {
+    let label_end = input.find("](")?;
+    let label = &input[1..label_end];
+    if label.contains(']') {
+        return None;
+    }
+    let url_start = label_end + 2;
+    let url_end = url_start + input[url_start..].find(')')?;
+    Some((label, &input[url_start..url_end], &input[url_end + 1..]))
+}
+
+/// Parses `<a href='url' ...>label</a>` at the start of `input`, returning the label, the URL and the remaining input
+fn parse_anchor(input: &str) -> Option<(&str, &str, &str)> {
+    if !input.starts_with("<a ") && !input.starts_with("<a\t") {
+        return None;
+    }
+    let tag_end = input.find('>')?;
+    let tag = &input[..tag_end];
+    let href_start = tag.find("href=")? + "href=".len();
+    let quote = tag[href_start..].chars().next().filter(|quote| *quote == '\'' || *quote == '"')?;
+    let url_start = href_start + 1;
+    let url_end = url_start + tag[url_start..].find(quote)?;
+
+    let label_start = tag_end + 1;
+    let label_end = label_start + input[label_start..].find("</a>")?;
+    Some((&input[label_start..label_end], &input[url_start..url_end], &input[label_end + "</a>".len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_links() {
+        let (text, links) = parse_links("[Docs](https://docs.pax.rs) | [GitHub](https://www.github.com/pax-lang/pax)");
+        assert_eq!(text, "Docs | GitHub");
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text[links[1].range.clone()], "GitHub");
+        assert_eq!(links[1].url, "https://www.github.com/pax-lang/pax");
+
+        let (text, links) = parse_links("<a href='https://docs.pax.rs' target='_blank'>Docs</a>");
+        assert_eq!(text, "Docs");
+        assert_eq!(links[0].range, 0..4);
+        assert_eq!(links[0].url, "https://docs.pax.rs");
+
+        let (text, links) = parse_links("Footprints are <b>tiny</b>: <100KB<br>[unclosed link");
+        assert_eq!(text, "Footprints are tiny: <100KB\n[unclosed link");
+        assert!(links.is_empty());
+    }
+}
@@ -236,7 +236,10 @@ struct PaxView: View {
             CVDisplayLinkCreateWithActiveCGDisplays(&displayLink)
             CVDisplayLinkSetOutputHandler(displayLink!) { [weak self] (_, _, _, _, _) -> CVReturn in
                 DispatchQueue.main.async {
-                    self?.setNeedsDisplay(self?.bounds ?? NSRect.zero)
+                    //skip idle frames, keeping the last render on screen
+                    if PaxEngineContainer.paxEngineContainer == nil || pax_needs_render(PaxEngineContainer.paxEngineContainer!) {
+                        self?.setNeedsDisplay(self?.bounds ?? NSRect.zero)
+                    }
                     self?.processRequestAnimationFrameQueue()
                 }
                 return kCVReturnSuccess
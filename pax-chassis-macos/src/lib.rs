@@ -7,6 +7,9 @@ use std::cell::RefCell;
 use std::ffi::c_void;
 use std::mem::{ManuallyDrop, transmute};
 use std::os::raw::{c_char};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Receiver};
 
 use core_graphics::context::CGContext;
 use piet_coregraphics::{CoreGraphicsContext};
@@ -17,12 +20,13 @@ use flexbuffers::{Buffer, DeserializationError, Reader};
 
 use pax_core::{InstanceRegistry, PaxEngine};
 use pax_cartridge;
+use pax_message::hot_reload::HotReloadPatch;
 
 //Re-export all native message types; used by Swift via FFI.
 //Note that any types exposed by pax_message must ALSO be added to `paxchassismacos.h`
 //in order to be visible to Swift
 pub use pax_message::*;
-use pax_runtime_api::{ArgsClick, ArgsScroll, ModifierKey, MouseButton, MouseEventArgs};
+use pax_runtime_api::{ArgsClick, ArgsScroll, EventControl, ModifierKey, MouseButton, MouseEventArgs};
 
 /// Container data structure for PaxEngine, aggregated to support passing across C bridge
 #[repr(C)] //Exposed to Swift via paxchassismacos.h
@@ -31,6 +35,45 @@ pub struct PaxEngineContainer {
     //NOTE: since that has become a single field, this data structure may be be retired and `*mut PaxEngine` could be passed directly.
 }
 
+thread_local! {
+    /// Patches received from `pax run --watch`, applied between ticks on the main thread
+    static HOT_RELOAD_PATCHES: RefCell<Option<Receiver<HotReloadPatch>>> = RefCell::new(None);
+}
+
+/// If this chassis was built by `pax run --watch`, listens on a background thread for hot-reloaded templates,
+/// which are sent as Server-Sent Events with patches as JSON data
+fn listen_for_hot_reload_patches() {
+    let port : u16 = match option_env!("PAX_HOT_RELOAD_PORT").and_then(|port| port.parse().ok()) {
+        Some(port) => port,
+        None => return,
+    };
+    let (sender, receiver) = channel();
+    HOT_RELOAD_PATCHES.with(|patches| *patches.borrow_mut() = Some(receiver));
+
+    std::thread::spawn(move || {
+        let mut stream = match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => stream,
+            Err(_) => return,
+        };
+        if stream.write_all(b"GET / HTTP/1.1\r\nAccept: text/event-stream\r\n\r\n").is_err() {
+            return;
+        }
+        for line in BufReader::new(stream).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if let Some(data) = line.strip_prefix("data: ") {
+                if let Ok(patch) = serde_json::from_str::<HotReloadPatch>(data) {
+                    if sender.send(patch).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
 /// Allocate an instance of the Pax engine, with a specified root/main component from the loaded `pax_cartridge`.
 #[no_mangle] //Exposed to Swift via paxchassismacos.h
 pub extern "C" fn pax_init(logger: extern "C" fn(*const c_char)) -> *mut PaxEngineContainer {
@@ -58,6 +101,8 @@ pub extern "C" fn pax_init(logger: extern "C" fn(*const c_char)) -> *mut PaxEngi
         _engine: Box::into_raw(ManuallyDrop::into_inner(engine)),
     }));
 
+    listen_for_hot_reload_patches();
+
     Box::into_raw(ManuallyDrop::into_inner(container))
 }
 
@@ -94,6 +139,7 @@ pub extern "C" fn pax_interrupt(engine_container: *mut PaxEngineContainer, buffe
 
     let interrupt_wrapped: Result<NativeInterrupt, DeserializationError> = flexbuffers::from_slice(slice);
     let interrupt = interrupt_wrapped.unwrap();
+    engine.request_render();
     match interrupt {
         NativeInterrupt::Click(args) => {
             let prospective_hit = engine.get_topmost_element_beneath_ray((args.x, args.y));
@@ -105,7 +151,7 @@ pub extern "C" fn pax_interrupt(engine_container: *mut PaxEngineContainer, buffe
                         y: args.y,
                         button: MouseButton::from(args.button),
                         modifiers,
-                    }};
+                    }, control: EventControl::default() };
                     topmost_node.dispatch_click(args_click);
                 },
                 _ => {},
@@ -116,7 +162,7 @@ pub extern "C" fn pax_interrupt(engine_container: *mut PaxEngineContainer, buffe
             let prospective_hit = engine.get_focused_element();
             match prospective_hit {
                 Some(topmost_node) => {
-                    let args_scroll = ArgsScroll {delta_x: args.delta_x , delta_y: args.delta_y};
+                    let args_scroll = ArgsScroll {delta_x: args.delta_x , delta_y: args.delta_y, control: EventControl::default()};
                     topmost_node.dispatch_scroll(args_scroll);
                 },
                 _ => {},
@@ -139,6 +185,27 @@ pub extern "C" fn pax_interrupt(engine_container: *mut PaxEngineContainer, buffe
     unsafe {(*engine_container)._engine=  Box::into_raw(engine)};
 }
 
+/// Whether the next `pax_tick` may render anything new, polled by the chassis on each display refresh so that idle frames
+/// can be skipped without redrawing.  Also applies any hot-reloaded templates received since the last call.
+#[no_mangle] //Exposed to Swift via paxchassismacos.h
+pub extern "C" fn pax_needs_render(engine_container: *mut PaxEngineContainer) -> bool {
+    let mut engine = unsafe { Box::from_raw((*engine_container)._engine) };
+
+    HOT_RELOAD_PATCHES.with(|patches| {
+        if let Some(receiver) = patches.borrow().as_ref() {
+            for patch in receiver.try_iter() {
+                if let Err(message) = (*engine).apply_hot_reload_patch(patch, &pax_cartridge::instantiate_hot_reload_cartridge()) {
+                    eprintln!("Failed to hot reload: {}", message);
+                }
+            }
+        }
+    });
+
+    let needs_render = (*engine).needs_render();
+    unsafe {(*engine_container)._engine=  Box::into_raw(engine)};
+    needs_render
+}
+
 /// Perform full tick of engine, including property computation, lifecycle event handling, and rendering side-effects.
 /// Returns a message queue of native rendering actions encoded as a Flexbuffer via FFI to Swift.
 /// The returned message queue requires explicit deallocation: `pax_deallocate_message_queue`
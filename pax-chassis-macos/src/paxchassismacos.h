@@ -25,6 +25,8 @@ void pax_dealloc_engine(struct PaxEngineContainer * container);
 
 void pax_interrupt(struct PaxEngineContainer *engine_container, const void * interrupt);
 
+bool pax_needs_render(struct PaxEngineContainer *engine_container);
+
 struct NativeMessageQueue *pax_tick(struct PaxEngineContainer *engine_container,
                                           void *cgContext,
                                           float width,
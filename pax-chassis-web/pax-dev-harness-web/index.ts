@@ -43,6 +43,15 @@ function main(wasmMod: typeof import('./dist/pax_chassis_web')) {
 
     addEventListenersToNativeLayers(0, layers.native.length, chassis);
 
+    //Apply templates hot-reloaded by `pax run --watch`
+    let hotReloadPort = chassis.hot_reload_port();
+    if (hotReloadPort !== undefined) {
+        let hotReloadSource = new EventSource(`http://localhost:${hotReloadPort}/`);
+        hotReloadSource.onmessage = (event) => {
+            chassis.interrupt(JSON.stringify({"HotReload": JSON.parse(event.data)}), []);
+        };
+    }
+
     //Kick off render loop
     requestAnimationFrame(renderLoop.bind(renderLoop, chassis))
 
@@ -101,14 +110,18 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(clickEvent), []);
+        if (chassis.interrupt(JSON.stringify(clickEvent), [])) {
+            evt.preventDefault();
+        }
         let jabEvent = {
             "Jab": {
                 "x": evt.clientX,
                 "y": evt.clientY,
             }
         };
-        chassis.interrupt(JSON.stringify(jabEvent), []);
+        if (chassis.interrupt(JSON.stringify(jabEvent), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('dblclick', (evt) => {
@@ -120,7 +133,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('mousemove', (evt) => {
@@ -132,7 +147,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('wheel', (evt) => {
@@ -145,14 +162,18 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
         let scrollEvent = {
             "Scroll": {
                 "delta_x": evt.deltaX,
                 "delta_y": evt.deltaY,
             }
         };
-        chassis.interrupt(JSON.stringify(scrollEvent), []);
+        if (chassis.interrupt(JSON.stringify(scrollEvent), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('mousedown', (evt) => {
@@ -164,7 +185,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('mouseup', (evt) => {
@@ -176,7 +199,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('mouseover', (evt) => {
@@ -188,10 +213,16 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('mouseout', (evt) => {
+        //moving between layers, or onto native elements, doesn't leave the app; the engine tracks hover within it
+        if (evt.relatedTarget != null && document.querySelector("#" + MOUNT_ID)?.contains(evt.relatedTarget)) {
+            return;
+        }
         let event = {
             "MouseOut": {
                 "x": evt.clientX,
@@ -200,7 +231,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('contextmenu', (evt) => {
@@ -212,7 +245,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "modifiers": convertModifiers(evt)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('touchstart', (evt) => {
@@ -224,7 +259,9 @@ function setupEventListeners(chassis: any, layer: any) {
         Array.from(evt.changedTouches).forEach(touch => { // @ts-ignore
             lastPositions.set(touch.identifier, { x: touch.clientX, y: touch.clientY });
         });
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
 
         let jabEvent = {
             "Jab": {
@@ -232,7 +269,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "y": evt.touches[0].clientY,
             }
         };
-        chassis.interrupt(JSON.stringify(jabEvent), []);
+        if (chassis.interrupt(JSON.stringify(jabEvent), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('touchmove', (evt) => {
@@ -242,7 +281,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "touches": touches
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
 
         let scrollEvent = {
             "Scroll": {
@@ -250,7 +291,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "delta_y": touches[0].delta_y,
             }
         };
-        chassis.interrupt(JSON.stringify(scrollEvent), []);
+        if (chassis.interrupt(JSON.stringify(scrollEvent), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('touchend', (evt) => {
@@ -259,7 +302,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "touches": getTouchMessages(evt.changedTouches)
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
         Array.from(evt.changedTouches).forEach(touch => { // @ts-ignore
             lastPositions.delete(touch.identifier);
         });
@@ -273,7 +318,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "is_repeat": evt.repeat
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('keyup', (evt) => {
@@ -284,7 +331,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "is_repeat": evt.repeat
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
     // @ts-ignore
     layer.addEventListener('keypress', (evt) => {
@@ -295,7 +344,9 @@ function setupEventListeners(chassis: any, layer: any) {
                 "is_repeat": evt.repeat
             }
         };
-        chassis.interrupt(JSON.stringify(event), []);
+        if (chassis.interrupt(JSON.stringify(event), [])) {
+            evt.preventDefault();
+        }
     }, true);
 }
 
@@ -361,13 +412,16 @@ function clearCanvases(){
 }
 
 function renderLoop (chassis: PaxChassisWeb) {
-     clearCanvases()
-     let messages : string = chassis.tick();
-     messages = JSON.parse(messages);
-
-     // @ts-ignore
-     processMessages(messages, chassis);
-     messages;
+     //skip idle frames entirely, leaving the last render on the canvases
+     if (chassis.needs_render()) {
+         clearCanvases()
+         let messages : string = chassis.tick();
+         messages = JSON.parse(messages);
+
+         // @ts-ignore
+         processMessages(messages, chassis);
+         messages;
+     }
      requestAnimationFrame(renderLoop.bind(renderLoop, chassis))
 }
 
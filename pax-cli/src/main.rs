@@ -27,7 +27,7 @@ fn main() -> Result<(), ()> {
         //platform and run appropriate native harness.  Web is a suitable,
         //sane default for now.
         .default_value("web")
-        .help("Specify the target platform on which to run.  Will run in platform-specific demo harness.")
+        .help("Specify the target platform on which to run: web, macos, linux, or headless.  Will run in platform-specific demo harness.")
         .takes_value(true);
 
     #[allow(non_snake_case)]
@@ -51,6 +51,13 @@ fn main() -> Result<(), ()> {
                 .arg( ARG_TARGET.clone() )
                 .arg( ARG_VERBOSE.clone() )
                 .arg( ARG_LIBDEV.clone() )
+                .arg(
+                    Arg::with_name("watch")
+                        .short("w")
+                        .long("watch")
+                        .takes_value(false)
+                        .help("Watches the project for changes, rebuilding and re-running it on each.  Changes only to Pax are rebuilt incrementally.")
+                )
         )
         .subcommand(
             App::new("build")
@@ -60,11 +67,35 @@ fn main() -> Result<(), ()> {
                 .arg( ARG_VERBOSE.clone() )
                 .arg( ARG_LIBDEV.clone() )
         )
+        .subcommand(
+            App::new("test")
+                .about("Renders the snapshots described in the project's `snapshots` directory without a display, comparing each against its golden image")
+                .arg( ARG_PATH.clone() )
+                .arg( ARG_VERBOSE.clone() )
+                .arg( ARG_LIBDEV.clone() )
+                .arg(
+                    Arg::with_name("update-snapshots")
+                        .long("update-snapshots")
+                        .takes_value(false)
+                        .help("Rewrites each golden image from the frame rendered for it, rather than comparing against it, creating any that are missing")
+                )
+        )
         .subcommand(
             App::new("clean")
                 .arg( ARG_PATH.clone() )
                 .about("Cleans the temporary files associated with the Pax project in the current working directory — notably, the temporary files generated into the .pax directory")
         )
+        .subcommand(
+            App::new("fmt")
+                .arg( ARG_PATH.clone() )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .takes_value(false)
+                        .help("Lists the files that would be reformatted, without rewriting them.  Exits with an error if there are any.")
+                )
+                .about("Formats the .pax files and `#[inlined(...)]` Pax in the project at the specified `path`, preserving comments")
+        )
         .subcommand(
             App::new("libdev")
                 .subcommand(
@@ -89,14 +120,21 @@ fn main() -> Result<(), ()> {
             let path = args.value_of("path").unwrap().to_string(); //default value "."
             let verbose = args.is_present("verbose");
             let libdevmode = args.is_present("libdev");
+            let should_watch = args.is_present("watch");
 
-            pax_compiler::perform_build(&RunContext{
+            let ctx = RunContext{
                 target: RunTarget::from(target.as_str()),
                 path,
                 verbose,
                 should_also_run: true,
                 libdevmode,
-            })
+            };
+            if should_watch {
+                pax_compiler::perform_watch(&ctx);
+                Ok(())
+            } else {
+                pax_compiler::perform_build(&ctx).map_err(|_error| std::process::exit(1))
+            }
 
         },
         ("build", Some(args)) => {
@@ -111,13 +149,27 @@ fn main() -> Result<(), ()> {
                 should_also_run: false,
                 verbose,
                 libdevmode,
-            })
+            }).map_err(|_error| std::process::exit(1))
+        },
+        ("test", Some(args)) => {
+            let path = args.value_of("path").unwrap().to_string(); //default value "."
+            let verbose = args.is_present("verbose");
+            let libdevmode = args.is_present("libdev");
+            let should_update_snapshots = args.is_present("update-snapshots");
+
+            pax_compiler::perform_test(&path, verbose, libdevmode, should_update_snapshots).map_err(|_| std::process::exit(1))
         },
         ("clean", Some(args)) => {
             let path = args.value_of("path").unwrap().to_string(); //default value "."
 
             pax_compiler::perform_clean(&path)
         },
+        ("fmt", Some(args)) => {
+            let path = args.value_of("path").unwrap().to_string(); //default value "."
+            let should_check = args.is_present("check");
+
+            pax_compiler::perform_format(&path, should_check).map_err(|_| std::process::exit(1))
+        },
         ("libdev", Some(args)) => {
             match args.subcommand() {
                 ("parse", Some(args)) => {
@@ -137,7 +189,7 @@ fn main() -> Result<(), ()> {
                     let working_path = Path::new(&path).join(".pax");
                     let pax_dir = fs::canonicalize(working_path).unwrap();
 
-                    let output = pax_compiler::build_chassis_with_cartridge(&pax_dir, &RunTarget::from(target.as_str()));
+                    let output = pax_compiler::build_chassis_with_cartridge(&pax_dir, &RunTarget::from(target.as_str()), None);
 
                     // Forward both stdout and stderr
                     std::io::stderr().write_all(output.stderr.as_slice()).unwrap();
@@ -0,0 +1,232 @@
+use std::fmt;
+
+use colored::Colorize;
+use serde_derive::{Serialize, Deserialize};
+
+use crate::parsing::Rule;
+
+/// A region of Pax source, such as a template tag, a settings value, or a symbol inside an expression.
+/// Lines and columns are 1-based, following pest (and rustc.)
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct SourceSpan {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceSpan {
+    pub fn from_pest(span: pest::Span) -> Self {
+        let (start_line, start_column) = span.start_pos().line_col();
+        let (end_line, end_column) = span.end_pos().line_col();
+        Self {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// For a span whose source begins with `text` (for example, the span of an expression),
+    /// returns the span of the byte range `start..end` within `text`, e.g. a single symbol
+    /// inside of that expression
+    pub fn narrow(&self, text: &str, start: usize, end: usize) -> Self {
+        let (start_line, start_column) = self.advance(&text[..start]);
+        let (end_line, end_column) = self.advance(&text[..end]);
+        Self {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    fn advance(&self, consumed: &str) -> (usize, usize) {
+        let newline_count = consumed.matches('\n').count();
+        if newline_count == 0 {
+            (self.start_line, self.start_column + consumed.chars().count())
+        } else {
+            (self.start_line + newline_count, consumed.rsplit('\n').next().unwrap().chars().count() + 1)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticKind {
+    /// Pax that doesn't conform to the grammar, e.g. an unclosed tag
+    Syntax,
+    /// A symbol in an expression that isn't found in scope, e.g. `self.not_defined`
+    UnresolvedSymbol,
+    /// A setting or nested symbol naming a property that doesn't exist, e.g. `<Rectangle not_a_property=5 />`
+    UnknownProperty,
+    /// A template tag naming a component that hasn't been defined or imported
+    UnknownComponent,
+    /// A value whose type doesn't match the type expected of it, e.g. `for i in self.some_string`
+    TypeMismatch,
+}
+
+/// Why a build failed.  Either way, the errors have already been reported to stderr.
+#[derive(Debug)]
+pub enum BuildError {
+    /// Errors in the provided Pax
+    Diagnostics(Vec<Diagnostic>),
+    /// A failure of cargo, e.g. a Rust compilation error in the host crate, as reported by cargo
+    Cargo,
+}
+
+impl From<Vec<Diagnostic>> for BuildError {
+    fn from(diagnostics: Vec<Diagnostic>) -> Self {
+        BuildError::Diagnostics(diagnostics)
+    }
+}
+
+/// A compiler error, reported against the Pax source that caused it.
+/// Rendered (via `Display`) in the style of rustc, e.g.:
+/// ```text
+/// error: cannot find value `not_defined` in this scope
+///   --> src/hello.pax:4:24
+///    |
+///  4 |     <Rectangle width={not_defined + 6} />
+///    |                       ^^^^^^^^^^^ not found in this scope
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Headline, e.g. "cannot find value `not_defined` in this scope"
+    pub message: String,
+    /// Annotation printed alongside the caret underline, e.g. "not found in this scope"
+    pub label: String,
+    pub span: Option<SourceSpan>,
+    /// Path of the file containing the offending Pax, e.g. `src/hello.pax`
+    pub source_path: Option<String>,
+    /// The lines of source covered by `span`, captured by `with_source` for rendering
+    pub source_lines: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, message: String, label: String) -> Self {
+        Self {
+            kind,
+            message,
+            label,
+            span: None,
+            source_path: None,
+            source_lines: vec![],
+        }
+    }
+
+    pub fn unresolved_symbol(symbol: &str) -> Self {
+        Diagnostic::new(
+            DiagnosticKind::UnresolvedSymbol,
+            format!("cannot find value `{}` in this scope", symbol),
+            "not found in this scope".to_string(),
+        )
+    }
+
+    /// For a built-in such as `$container`, which expressions can't yet refer to
+    pub fn unsupported_builtin(symbol: &str) -> Self {
+        Diagnostic::new(
+            DiagnosticKind::UnresolvedSymbol,
+            format!("built-in `{}` is not yet supported in expressions", symbol),
+            "not yet supported".to_string(),
+        )
+    }
+
+    pub fn unknown_property(property_name: &str, type_name: &str) -> Self {
+        Diagnostic::new(
+            DiagnosticKind::UnknownProperty,
+            format!("no property `{}` on `{}`", property_name, type_name),
+            "unknown property".to_string(),
+        )
+    }
+
+    pub fn type_mismatch(expected: &str, found: &str) -> Self {
+        Diagnostic::new(
+            DiagnosticKind::TypeMismatch,
+            "mismatched types".to_string(),
+            format!("expected `{}`, found `{}`", expected, found),
+        )
+    }
+
+    /// For an operator applied to types that don't support it, e.g. `operation` "`+` to `integer` and `String`"
+    /// and `signature` "integer + String"
+    pub fn unsupported_operation(operation: &str, signature: &str) -> Self {
+        Diagnostic::new(
+            DiagnosticKind::TypeMismatch,
+            format!("cannot apply {}", operation),
+            format!("no implementation for `{}`", signature),
+        )
+    }
+
+    pub fn from_pest_error(error: pest::error::Error<Rule>) -> Self {
+        let span = match error.line_col {
+            pest::error::LineColLocation::Pos((line, column)) => SourceSpan {
+                start_line: line,
+                start_column: column,
+                end_line: line,
+                end_column: column + 1,
+            },
+            pest::error::LineColLocation::Span((start_line, start_column), (end_line, end_column)) => SourceSpan {
+                start_line,
+                start_column,
+                end_line,
+                end_column,
+            },
+        };
+        Diagnostic::new(DiagnosticKind::Syntax, "unable to parse Pax".to_string(), error.variant.message().to_string())
+            .with_span(Some(span))
+    }
+
+    pub fn with_span(mut self, span: Option<SourceSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Attaches the file and source text in which this diagnostic's `span` occurs
+    pub fn with_source(mut self, source_path: Option<String>, source: Option<&str>) -> Self {
+        self.source_path = source_path;
+        if let (Some(span), Some(source)) = (&self.span, source) {
+            self.source_lines = source.lines()
+                .skip(span.start_line - 1)
+                .take(span.end_line - span.start_line + 1)
+                .map(|line| line.to_string())
+                .collect();
+        }
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}{}", "error".red().bold(), format!(": {}", self.message).bold())?;
+
+        let source_path = self.source_path.clone().unwrap_or("<unknown>".to_string());
+        let span = match &self.span {
+            Some(span) => span,
+            None => {
+                return writeln!(f, "  {} {}", "-->".blue().bold(), source_path);
+            }
+        };
+
+        let gutter_width = span.start_line.to_string().len();
+        let gutter = " ".repeat(gutter_width + 2) + &"|".blue().bold().to_string();
+        writeln!(f, "{}{} {}:{}:{}", " ".repeat(gutter_width + 1), "-->".blue().bold(), source_path, span.start_line, span.start_column)?;
+
+        if let Some(first_line) = self.source_lines.first() {
+            writeln!(f, "{}", gutter)?;
+            writeln!(f, " {} {} {}", span.start_line.to_string().blue().bold(), "|".blue().bold(), first_line)?;
+
+            //underline through the end of the span, or through the end of the first line for multi-line spans
+            let end_column = if span.end_line == span.start_line {
+                span.end_column
+            } else {
+                first_line.chars().count() + 1
+            };
+            let underline_width = end_column.saturating_sub(span.start_column).max(1);
+            writeln!(f, "{} {}{} {}", gutter, " ".repeat(span.start_column - 1), "^".repeat(underline_width).red().bold(), self.label.red().bold())?;
+        } else {
+            writeln!(f, "{} {}", gutter, self.label)?;
+        }
+        Ok(())
+    }
+}
@@ -8,11 +8,17 @@ use itertools::Itertools;
 use lazy_static::lazy_static;
 use crate::manifest::{LiteralBlockDefinition, PropertyDefinitionFlags, TypeDefinition, TypeTable};
 use crate::parsing::escape_identifier;
+use crate::diagnostics::{Diagnostic, DiagnosticKind, SourceSpan};
+use crate::source_map::SourceLocation;
+use crate::typing::{NumericKind, PaxelType};
 
-pub fn compile_all_expressions<'a>(manifest: &'a mut PaxManifest) {
+/// Compiles every expression in every component's template, writing the resulting `ExpressionSpec`s
+/// to `manifest.expression_specs`.  Returns every error found across all components, if any.
+pub fn compile_all_expressions<'a>(manifest: &'a mut PaxManifest) -> Result<(), Vec<Diagnostic>> {
 
     let mut swap_expression_specs: HashMap<usize, ExpressionSpec> = HashMap::new();
     let mut all_expression_specs : HashMap<usize, ExpressionSpec> = HashMap::new();
+    let mut all_diagnostics : Vec<Diagnostic> = vec![];
 
     let mut new_components = manifest.components.clone();
     let mut uid_track  = 0;
@@ -36,11 +42,13 @@ pub fn compile_all_expressions<'a>(manifest: &'a mut PaxManifest) {
                 expression_specs: &mut swap_expression_specs,
                 component_def: &read_only_component_def,
                 type_table: &manifest.type_table,
+                diagnostics: vec![],
             };
 
             ctx = recurse_compile_expressions(ctx);
             uid_track = ctx.uid_gen.next().unwrap();
             all_expression_specs.extend(ctx.expression_specs.to_owned());
+            all_diagnostics.extend(std::mem::take(&mut ctx.diagnostics));
             std::mem::swap(&mut ctx.active_node_def, template.index_mut(0));
         }
 
@@ -48,6 +56,12 @@ pub fn compile_all_expressions<'a>(manifest: &'a mut PaxManifest) {
     });
     manifest.components = new_components;
     manifest.expression_specs = Some(swap_expression_specs);
+
+    if all_diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(all_diagnostics)
+    }
 }
 
 fn pull_matched_identifiers_from_inline(inline_settings: &Option<Vec<(String, ValueDefinition)>>, s: String) -> Vec<String>{
@@ -55,7 +69,7 @@ fn pull_matched_identifiers_from_inline(inline_settings: &Option<Vec<(String, Va
     if let Some(val) = inline_settings {
         for (_,matched) in val.iter().filter(|avd| avd.0 == s.as_str()){
             match matched {
-                ValueDefinition::Identifier(s, _) => {
+                ValueDefinition::Identifier(s, _, _) => {
                     ret.push(s.clone())
                 }
                 _ => {}
@@ -120,7 +134,7 @@ fn merge_inline_settings_with_settings_block(inline_settings: &Option<Vec<(Strin
     if merged.len() > 0 {Some(merged)} else{None}
 }
 
-fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefinition)>, mut ctx: &mut ExpressionCompilationContext, current_property_definitions: Vec<PropertyDefinition>, type_id: String){
+fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefinition)>, ctx: &mut ExpressionCompilationContext, current_property_definitions: Vec<PropertyDefinition>, type_id: String){
     settings_pairs.for_each(|pair| {
         match &mut pair.1 {
             ValueDefinition::LiteralValue(_) => {
@@ -130,19 +144,23 @@ fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefin
                 //event bindings are handled on a separate compiler pass; no-op here
             },
             ValueDefinition::Block(block) => {
-                let type_def = (current_property_definitions.iter().find(|property_def| {
+                let type_def = match current_property_definitions.iter().find(|property_def| {
                     property_def.name == pair.0
-                })).expect(
-                    &format!("Property `{}` not found on `{}`", &pair.0, type_id)
-                ).get_type_definition(ctx.type_table);
+                }) {
+                    Some(property_def) => property_def.get_type_definition(ctx.type_table),
+                    None => {
+                        //Blocks don't track their own spans; point at the tag being configured
+                        let span = ctx.active_node_def.span.clone();
+                        ctx.report(Diagnostic::unknown_property(&pair.0, &type_id).with_span(span));
+                        return;
+                    }
+                };
                 recurse_compile_literal_block(block.settings_key_value_pairs.iter_mut(), ctx, type_def.property_definitions.clone(), type_def.type_id_escaped.clone());
             },
-            ValueDefinition::Expression(input, manifest_id) => {
+            ValueDefinition::Expression(input, manifest_id, span) => {
                 // e.g. the `self.num_clicks + 5` in `<SomeNode some_property={self.num_clicks + 5} />`
                 let id = ctx.uid_gen.next().unwrap();
 
-                let (output_statement, invocations) = compile_paxel_to_ril(&input, &ctx);
-
                 let builtin_types = HashMap::from([
                     ("transform","Transform2D".to_string()),
                     ("size","Size2D".to_string()),
@@ -153,16 +171,22 @@ fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefin
 
                 ]);
 
-                let pascalized_return_type = if let Some(type_string) = builtin_types.get(&*pair.0) {
-                    type_string.to_string()
+                let (pascalized_return_type, expected_type) = if let Some(type_string) = builtin_types.get(&*pair.0) {
+                    (type_string.to_string(), PaxelType::from_type_id(type_string))
                 } else {
-                        (current_property_definitions.iter().find(|property_def| {
-                            property_def.name == pair.0
-                        }).expect(
-                            &format!("Property `{}` not found on component `{}`", &pair.0, type_id)
-                        ).get_type_definition(ctx.type_table).type_id_escaped).clone()
+                    match current_property_definitions.iter().find(|property_def| {
+                        property_def.name == pair.0
+                    }) {
+                        Some(property_def) => (property_def.get_type_definition(ctx.type_table).type_id_escaped.clone(), PaxelType::from_type_id(&property_def.type_id)),
+                        None => {
+                            ctx.report(Diagnostic::unknown_property(&pair.0, &type_id).with_span(span.clone()));
+                            return;
+                        }
+                    }
                 };
 
+                let (output_statement, invocations) = compile_paxel_to_ril(&input, span, Some(expected_type), ctx);
+
                 let mut whitespace_removed_input = input.clone();
                 whitespace_removed_input.retain(|c| !c.is_whitespace());
 
@@ -174,19 +198,30 @@ fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefin
                     input_statement: whitespace_removed_input,
                     is_repeat_source_iterable_expression: false,
                     repeat_source_iterable_type_id_escaped: "".to_string(),
+                    source_location: ctx.source_location(span),
                 });
 
                 //Write this id back to the manifest, for downstream use by RIL component tree generator
                 let mut manifest_id_insert = Some(id);
                 std::mem::swap(manifest_id, &mut manifest_id_insert);
             },
-            ValueDefinition::Identifier(identifier, manifest_id) => {
+            ValueDefinition::Identifier(identifier, manifest_id, span) => {
                 // e.g. the self.active_color in `bg_color=self.active_color`
 
                 if pair.0 == "id" || pair.0 == "class" {
                     //No-op -- special-case `id=some_identifier` and `class=some_identifier` — we DON'T want to compile an expression {some_identifier},
                     //so we skip the case where `id` is the key
                 } else {
+                    let (pascalized_return_type, expected_type) = match current_property_definitions.iter().find(|property_def| {
+                        property_def.name == pair.0
+                    }) {
+                        Some(property_def) => (property_def.get_type_definition(ctx.type_table).type_id_escaped.clone(), PaxelType::from_type_id(&property_def.type_id)),
+                        None => {
+                            ctx.report(Diagnostic::unknown_property(&pair.0, &type_id).with_span(span.clone()));
+                            return;
+                        }
+                    };
+
                     let id = ctx.uid_gen.next().unwrap();
 
                     //Write this id back to the manifest, for downstream use by RIL component tree generator
@@ -195,13 +230,7 @@ fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefin
 
                     //a single identifier binding is the same as an expression returning that identifier, `{self.some_identifier}`
                     //thus, we can compile it as PAXEL and make use of any shared logic, e.g. `self`/`this` handling
-                    let (output_statement, invocations) = compile_paxel_to_ril(&identifier, &ctx);
-
-                    let pascalized_return_type = (&ctx.component_def.get_property_definitions(ctx.type_table).iter().find(
-                        |property_def| {
-                            property_def.name == pair.0
-                        }
-                    ).unwrap().get_type_definition(ctx.type_table).type_id_escaped).clone();
+                    let (output_statement, invocations) = compile_paxel_to_ril(&identifier, span, Some(expected_type), ctx);
 
                     ctx.expression_specs.insert(id, ExpressionSpec {
                         id,
@@ -211,6 +240,7 @@ fn recurse_compile_literal_block<'a>(settings_pairs: IterMut<(String, ValueDefin
                         input_statement: identifier.clone(),
                         is_repeat_source_iterable_expression: false,
                         repeat_source_iterable_type_id_escaped: "".to_string(),
+                        source_location: ctx.source_location(span),
                     });
                 }
             },
@@ -235,14 +265,28 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
 
         // Scope created to limit the borrow of ctx
         {
-            let active_node_component = ctx.all_components.get(&type_id)
-                .expect(&format!("No known component with identifier {}.  Try importing or defining a component named {}", &type_id, &type_id));
-
-            pascal_identifier = active_node_component.pascal_identifier.clone();
-            property_def = active_node_component.get_property_definitions(&mut ctx.type_table);
+            match ctx.all_components.get(&type_id) {
+                Some(active_node_component) => {
+                    pascal_identifier = active_node_component.pascal_identifier.clone();
+                    property_def = Some(active_node_component.get_property_definitions(&mut ctx.type_table));
+                },
+                None => {
+                    pascal_identifier = ctx.active_node_def.pascal_identifier.clone();
+                    property_def = None;
+                }
+            }
         }
 
-        recurse_compile_literal_block(inline_settings.iter_mut(), &mut ctx, property_def.clone(), pascal_identifier);
+        if let Some(property_def) = property_def {
+            recurse_compile_literal_block(inline_settings.iter_mut(), &mut ctx, property_def.clone(), pascal_identifier);
+        } else {
+            let span = ctx.active_node_def.span.clone();
+            ctx.report(Diagnostic::new(
+                DiagnosticKind::UnknownComponent,
+                format!("cannot find component `{}` in this scope", pascal_identifier),
+                "not found in this scope; try importing or defining a component with this name".to_string(),
+            ).with_span(span));
+        }
     } else if let Some(ref mut cfa) = cloned_control_flow_settings {
         //Handle attributes for control flow
         //Our purpose here is broadly twofold:
@@ -257,7 +301,11 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
             //  - must be a symbolic identifier, such as `elements` or `self.elements`
             // for i in 0..max_elems
             //  - may use an integer literal or symbolic identifier in either position
-            //  - must use an exclusive (..) range operator (inclusive could be supported; effort required)
+            //  - may use an exclusive (..) or inclusive (..=) range operator; empty if the first operand is greater (e.g. `10..0`)
+            // for i in (0..max_elems).step_by(2)
+            //  - the step may be any expression
+            // for i in (0..max_elems).rev()
+            //  - descends, from the last element of the range to the first
 
             let id = ctx.uid_gen.next().unwrap();
             repeat_source_definition.vtable_id = Some(id);
@@ -271,11 +319,39 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
 
 
 
-            let (paxel, return_type) = if let Some(range_expression_paxel) = &repeat_source_definition.range_expression_paxel {
-                (range_expression_paxel.to_string(), TypeDefinition::builtin_range_isize())
+            // The type of `elem`: isize for ranges, or the `T` in `self.some_data_source: Vec<T>`
+            let iterable_type_id = if let Some(_) = &repeat_source_definition.range_expression_paxel {
+                "isize".to_string()
             } else if let Some(symbolic_binding) = &repeat_source_definition.symbolic_binding {
-                let inner_iterable_type_id = ctx.resolve_symbol_as_prop_def(symbolic_binding).unwrap().last().unwrap().get_inner_iterable_type_definition(ctx.type_table).unwrap().type_id.clone();
-                (symbolic_binding.to_string(), TypeDefinition::builtin_vec_rc_properties_coproduct(inner_iterable_type_id))
+                let span = repeat_source_definition.span.clone();
+                match ctx.resolve_symbol_as_prop_def(symbolic_binding) {
+                    Ok(prop_def_chain) => {
+                        let source_prop_def = prop_def_chain.last().unwrap();
+                        match source_prop_def.get_inner_iterable_type_definition(ctx.type_table) {
+                            Some(inner_iterable_type_definition) => inner_iterable_type_definition.type_id.clone(),
+                            None => {
+                                ctx.report(Diagnostic::new(
+                                    DiagnosticKind::TypeMismatch,
+                                    format!("`{}` is not iterable", symbolic_binding),
+                                    format!("expected an iterable type like `Vec<T>`, found `{}`", source_prop_def.type_id),
+                                ).with_span(span));
+                                //Without a type for `elem`, this Repeat's descendants can't be compiled
+                                return ctx;
+                            }
+                        }
+                    },
+                    Err(diagnostic) => {
+                        ctx.report(diagnostic.with_span(span));
+                        return ctx;
+                    }
+                }
+            } else {unreachable!()};
+
+            let (paxel, return_type, repeat_source_expected_type) = if let Some(range_expression_paxel) = &repeat_source_definition.range_expression_paxel {
+                (range_expression_paxel.to_string(), TypeDefinition::builtin_vec_rc_properties_coproduct(iterable_type_id.clone()), Some(PaxelType::Range))
+            } else if let Some(symbolic_binding) = &repeat_source_definition.symbolic_binding {
+                //iterability of symbolic sources was checked above
+                (symbolic_binding.to_string(), TypeDefinition::builtin_vec_rc_properties_coproduct(iterable_type_id.clone()), None)
             } else {unreachable!()};
 
 
@@ -289,7 +365,15 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
             //with the parser that we are only binding to a simple symbolic id, like `self.foo`.
             //This is because we are inferring the return type of this expression based on the declared-and-known
             //type of property `self.foo`
-            let (output_statement, invocations) = compile_paxel_to_ril(&paxel, &ctx);
+            let (output_statement, invocations) = compile_paxel_to_ril(&paxel, &repeat_source_definition.span, repeat_source_expected_type, &mut ctx);
+
+            //Ranges compile to a `NumericRange`, which we normalize here into the same
+            //Vec<Rc<PropertiesCoproduct>> that Repeat receives for symbolic sources
+            let output_statement = if is_repeat_source_range {
+                format!("({}).into_iter().map(|i|{{Rc::new(PropertiesCoproduct::isize(i))}}).collect::<Vec<Rc<PropertiesCoproduct>>>()", output_statement)
+            } else {
+                output_statement
+            };
 
             // Attach shadowed property symbols to the scope_stack, so e.g. `elem` can be
             // referred to with the symbol `elem` in PAXEL
@@ -313,7 +397,7 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
                             is_repeat_source_iterable,
                             is_property_wrapped: true,
                         },
-                        type_id: "isize".to_string(),
+                        type_id: iterable_type_id.clone(),
                     };
 
                     let scope = HashMap::from([
@@ -326,18 +410,9 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
                 },
                 ControlFlowRepeatPredicateDefinition::ElemIdIndexId(elem_id, index_id) => {
 
-                    //if repeat_source is a range, this is simply isize
-                    //if repeat_source is a symbolic binding, then we resolve that symbolic binding and use that resolved type here
-                    let iterable_type = if let Some(_) = &repeat_source_definition.range_expression_paxel {
-                        TypeDefinition::primitive("isize")
-                    } else if let Some(symbolic_binding) = &repeat_source_definition.symbolic_binding {
-                        let pd = ctx.resolve_symbol_as_prop_def(symbolic_binding).expect(&format!("Property not found: {}", symbolic_binding)).last().unwrap().clone();
-                        pd.get_inner_iterable_type_definition(ctx.type_table).unwrap().clone()
-                    } else {unreachable!()};
-
                     let elem_property_definition = PropertyDefinition {
                         name: format!("{}", elem_id),
-                        type_id: iterable_type.type_id,
+                        type_id: iterable_type_id.clone(),
                         flags: PropertyDefinitionFlags {
                             is_binding_repeat_elem: true,
                             is_binding_repeat_i: false,
@@ -385,12 +460,38 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
                 output_statement,
                 input_statement: whitespace_removed_input,
                 is_repeat_source_iterable_expression: is_repeat_source_iterable,
-                repeat_source_iterable_type_id_escaped
+                repeat_source_iterable_type_id_escaped,
+                source_location: ctx.source_location(&repeat_source_definition.span),
             });
 
+            // Handle the optional `key` expression, e.g. the `elem.id` in `for elem in self.some_data_source key elem.id`.
+            // This is compiled after the predicate's symbols are pushed to the scope_stack, so `elem` and `i` are
+            // resolvable, and is evaluated by Repeat once per datum.  Keys are normalized to their `Debug` representation,
+            // so any `Debug` type (numbers, strings, tuples, enums) may serve as a key.
+            if let Some(repeat_key_expression_paxel) = &cfa.repeat_key_expression_paxel {
+                let (key_output_statement, invocations) = compile_paxel_to_ril(&repeat_key_expression_paxel, &cfa.repeat_key_expression_span, None, &mut ctx);
+                let id = ctx.uid_gen.next().unwrap();
+
+                cfa.repeat_key_expression_vtable_id = Some(id);
+
+                let mut whitespace_removed_input = repeat_key_expression_paxel.clone();
+                whitespace_removed_input.retain(|c| !c.is_whitespace());
+
+                ctx.expression_specs.insert(id, ExpressionSpec {
+                    id,
+                    pascalized_return_type: "String".to_string(),
+                    invocations,
+                    output_statement: format!("format!(\"{{:?}}\", {})", key_output_statement),
+                    input_statement: whitespace_removed_input,
+                    is_repeat_source_iterable_expression: false,
+                    repeat_source_iterable_type_id_escaped: "".to_string(),
+                    source_location: ctx.source_location(&cfa.repeat_key_expression_span),
+                });
+            }
+
         } else if let Some(condition_expression_paxel) = &cfa.condition_expression_paxel {
             //Handle `if` boolean expression, e.g. the `num_clicks > 5` in `if num_clicks > 5 { ... }`
-            let (output_statement, invocations) = compile_paxel_to_ril(&condition_expression_paxel, &ctx);
+            let (output_statement, invocations) = compile_paxel_to_ril(&condition_expression_paxel, &cfa.condition_expression_span, Some(PaxelType::Bool), &mut ctx);
             let id = ctx.uid_gen.next().unwrap();
 
             cfa.condition_expression_vtable_id = Some(id);
@@ -406,10 +507,11 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
                 input_statement: whitespace_removed_input,
                 is_repeat_source_iterable_expression: false,
                 repeat_source_iterable_type_id_escaped: "".to_string(),
+                source_location: ctx.source_location(&cfa.condition_expression_span),
             });
         } else if let Some(slot_index_expression_paxel) = &cfa.slot_index_expression_paxel {
             //Handle `if` boolean expression, e.g. the `num_clicks > 5` in `if num_clicks > 5 { ... }`
-            let (output_statement, invocations) = compile_paxel_to_ril(&slot_index_expression_paxel, &ctx);
+            let (output_statement, invocations) = compile_paxel_to_ril(&slot_index_expression_paxel, &cfa.slot_index_expression_span, Some(PaxelType::Numeric(NumericKind::Any)), &mut ctx);
             let id = ctx.uid_gen.next().unwrap();
 
             cfa.slot_index_expression_vtable_id = Some(id);
@@ -425,6 +527,34 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
                 input_statement: whitespace_removed_input,
                 is_repeat_source_iterable_expression: false,
                 repeat_source_iterable_type_id_escaped: "".to_string(),
+                source_location: ctx.source_location(&cfa.slot_index_expression_span),
+            });
+        } else if let Some(match_expression_paxel) = &cfa.match_expression_paxel {
+            //Handle `match` subject expression, e.g. the `self.direction` in `match self.direction { ... }`
+            //The compiled expression evaluates to the index of the first matching arm, e.g.:
+            //`match (direction) { StackerDirection::Vertical => 0, _ => 1 }`
+            let (subject_output_statement, invocations) = compile_paxel_to_ril(&match_expression_paxel, &cfa.match_expression_span, None, &mut ctx);
+            let id = ctx.uid_gen.next().unwrap();
+
+            cfa.match_expression_vtable_id = Some(id);
+
+            let arms_output : Vec<String> = cfa.match_arm_definitions.as_ref().unwrap().iter().enumerate().map(|(i, arm)| {
+                format!("{} => {}", arm.patterns.join(" | "), i)
+            }).collect();
+            let output_statement = format!("match ({}) {{ {} }}", subject_output_statement, arms_output.join(", "));
+
+            let mut whitespace_removed_input = match_expression_paxel.clone();
+            whitespace_removed_input.retain(|c| !c.is_whitespace());
+
+            ctx.expression_specs.insert(id, ExpressionSpec {
+                id,
+                pascalized_return_type: "usize".to_string(),
+                invocations,
+                output_statement,
+                input_statement: whitespace_removed_input,
+                is_repeat_source_iterable_expression: false,
+                repeat_source_iterable_type_id_escaped: "".to_string(),
+                source_location: ctx.source_location(&cfa.match_expression_span),
             });
         } else {
             unreachable!("encountered invalid control flow definition")
@@ -437,8 +567,18 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
 
     std::mem::swap(&mut merged_settings, &mut ctx.active_node_def.settings);
 
-    // Traverse descendent nodes and continue compiling expressions recursively
-    for id in ctx.active_node_def.child_ids.clone().iter() {
+    // Traverse descendent nodes and continue compiling expressions recursively,
+    // including the `else` branch of an `if` and the arms of a `match`, if present
+    let mut descendent_ids = ctx.active_node_def.child_ids.clone();
+    if let Some(cfa) = &ctx.active_node_def.control_flow_settings {
+        if let Some(else_child_ids) = &cfa.else_child_ids {
+            descendent_ids.extend(else_child_ids.iter());
+        }
+        if let Some(match_arm_definitions) = &cfa.match_arm_definitions {
+            match_arm_definitions.iter().for_each(|arm| descendent_ids.extend(arm.child_ids.iter()));
+        }
+    }
+    for id in descendent_ids.iter() {
         //Create two blanks
         let mut active_node_def = TemplateNodeDefinition::default();
         let mut old_active_node_def = TemplateNodeDefinition::default();
@@ -469,15 +609,16 @@ fn recurse_compile_expressions<'a>(mut ctx: ExpressionCompilationContext<'a>) ->
     ctx
 }
 
-/// From a symbol like `num_clicks` or `self.num_clicks`, populate an ExpressionSpecInvocation
-fn resolve_symbol_as_invocation(sym: &str, ctx: &ExpressionCompilationContext) -> ExpressionSpecInvocation {
+/// From a symbol like `num_clicks` or `self.num_clicks`, populate an ExpressionSpecInvocation.
+/// Returns a diagnostic located at `span` if the symbol can't be resolved.
+fn resolve_symbol_as_invocation(sym: &str, span: Option<SourceSpan>, ctx: &ExpressionCompilationContext) -> Result<ExpressionSpecInvocation, Diagnostic> {
 
     //Handle built-ins, like $container
     if BUILTIN_MAP.contains_key(sym) {
-        unimplemented!("Built-ins like $bounds are not yet supported")
+        Err(Diagnostic::unsupported_builtin(sym).with_span(span))
     } else {
 
-        let prop_def_chain = ctx.resolve_symbol_as_prop_def(&sym).expect(&format!("symbol not found: {}", &sym));
+        let prop_def_chain = ctx.resolve_symbol_as_prop_def(&sym).map_err(|diagnostic| diagnostic.with_span(span))?;
 
         let nested_prop_def = prop_def_chain.last().unwrap();
         let is_nested_numeric = ExpressionSpecInvocation::is_numeric(&nested_prop_def.type_id);
@@ -533,7 +674,7 @@ fn resolve_symbol_as_invocation(sym: &str, ctx: &ExpressionCompilationContext) -
         if nested_symbol_tail_literal != "" {nested_symbol_tail_literal += ".clone()"}
 
 
-        ExpressionSpecInvocation {
+        Ok(ExpressionSpecInvocation {
             root_identifier,
             is_numeric: ExpressionSpecInvocation::is_numeric(&property_properties_coproduct_type),
             is_primitive_nonnumeric: ExpressionSpecInvocation::is_primitive_nonnumeric(&property_properties_coproduct_type),
@@ -544,25 +685,48 @@ fn resolve_symbol_as_invocation(sym: &str, ctx: &ExpressionCompilationContext) -
             property_flags,
             nested_symbol_tail_literal,
             is_nested_numeric,
-        }
+        })
     }
 }
 
-/// Returns (RIL string, list of invocation specs for any symbols used)
-fn compile_paxel_to_ril<'a>(paxel: &str, ctx: &ExpressionCompilationContext<'a>) -> (String, Vec<ExpressionSpecInvocation>) {
+/// Returns (RIL string, list of invocation specs for any symbols used).
+/// Symbols that can't be resolved and type errors, including a result not assignable to `expected_type`,
+/// are reported to `ctx.diagnostics`, located within `span`, the location of `paxel` in the component's source.
+fn compile_paxel_to_ril<'a>(paxel: &str, span: &Option<SourceSpan>, expected_type: Option<PaxelType>, ctx: &mut ExpressionCompilationContext<'a>) -> (String, Vec<ExpressionSpecInvocation>) {
 
     //1. run Pratt parser; generate output RIL and collected symbolic_ids
     let (output_string,  symbolic_ids) = crate::parsing::run_pratt_parser(paxel);
 
     //2. for each symbolic id discovered during parsing, resolve that id through scope_stack and populate an ExpressionSpecInvocation
-    let invocations = symbolic_ids.iter().map(|sym| {
-        resolve_symbol_as_invocation(&sym.trim(), ctx)
-    })
+    let mut invocations = vec![];
+    for (sym, range) in symbolic_ids.iter() {
+        let symbol_span = span.as_ref().map(|span| span.narrow(paxel, range.start, range.end));
+        match resolve_symbol_as_invocation(&sym.trim(), symbol_span, ctx) {
+            Ok(invocation) => invocations.push(invocation),
+            Err(diagnostic) => ctx.report(diagnostic),
+        }
+    }
+
+    let invocations = invocations.into_iter()
         .unique_by(|esi|{esi.escaped_identifier.clone()})
         .sorted_by(|esi0, esi1|{esi0.escaped_identifier.cmp(&esi1.escaped_identifier)})
         .collect();
 
-    //3. return tuple of (RIL string,ExpressionSpecInvocations)
+    //3. infer the expression's type, so that mismatches are reported here rather than by rustc against generated RIL
+    let (paxel_type, type_diagnostics) = crate::typing::infer_paxel_type(paxel, &|sym| {
+        ctx.resolve_symbol_as_prop_def(sym).ok().map(|prop_def_chain| prop_def_chain.last().unwrap().type_id.clone())
+    });
+    for (diagnostic, range) in type_diagnostics {
+        let diagnostic_span = span.as_ref().map(|span| span.narrow(paxel, range.start, range.end));
+        ctx.report(diagnostic.with_span(diagnostic_span));
+    }
+    if let Some(expected_type) = expected_type {
+        if !paxel_type.is_assignable_to(&expected_type) {
+            ctx.report(Diagnostic::type_mismatch(&expected_type.to_string(), &paxel_type.to_string()).with_span(span.clone()));
+        }
+    }
+
+    //4. return tuple of (RIL string,ExpressionSpecInvocations)
     (output_string, invocations)
 
 }
@@ -596,6 +760,9 @@ pub struct ExpressionCompilationContext<'a> {
 
     /// Type table, used for looking up property types by string type_ids
     pub type_table: &'a TypeTable,
+
+    /// Errors encountered while compiling this component's expressions
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 lazy_static! {
@@ -620,11 +787,27 @@ pub fn clean_and_split_symbols(possibly_nested_symbols: &str) -> Vec<String> {
 
 impl<'a> ExpressionCompilationContext<'a> {
 
+    /// Locates `span` within the active node of the current component, for the source map
+    pub fn source_location(&self, span: &Option<SourceSpan>) -> SourceLocation {
+        SourceLocation {
+            component_type_id: self.component_def.type_id.clone(),
+            pax_source_path: self.component_def.pax_source_path.clone(),
+            template_node_id: self.active_node_def.id,
+            span: span.clone(),
+        }
+    }
+
+    /// Records a diagnostic, attaching the current component's Pax source so that it can be rendered
+    pub fn report(&mut self, diagnostic: Diagnostic) {
+        let diagnostic = diagnostic.with_source(self.component_def.pax_source_path.clone(), self.component_def.pax_source.as_deref());
+        self.diagnostics.push(diagnostic);
+    }
+
     /// for an input symbol like `i` or `self.num_clicks`
     /// traverse the self-attached `scope_stack`
-    /// and return a copy of the related `PropertyDefinition`, if found.
-    /// For
-    pub fn resolve_symbol_as_prop_def(&self, symbol: &str) -> Option<Vec<PropertyDefinition>> {
+    /// and return a copy of the related `PropertyDefinition`, along with the `PropertyDefinition`s
+    /// of any nested symbols (e.g. `bar` in `foo.bar`.)  Returns an (unlocated) diagnostic if not found.
+    pub fn resolve_symbol_as_prop_def(&self, symbol: &str) -> Result<Vec<PropertyDefinition>, Diagnostic> {
 
 
 
@@ -666,13 +849,13 @@ impl<'a> ExpressionCompilationContext<'a> {
                     let td = ret.last().unwrap().get_type_definition(self.type_table);
                     ret.push(
                         td.property_definitions.iter().find(|pd|{pd.name == *atomic_symbol})
-                        .expect(&format!("Unable to resolve nested symbol `{}` while evaluating `{}`.", atomic_symbol, symbol))
+                        .ok_or_else(|| Diagnostic::unknown_property(atomic_symbol, &td.type_id))?
                         .clone()
                     );
                 }
-                Some(ret)
+                Ok(ret)
             }
-            None => None,
+            None => Err(Diagnostic::unresolved_symbol(symbol)),
         }
     }
 }
@@ -0,0 +1,920 @@
+use std::ops::Range;
+
+use itertools::Itertools;
+use pest::Parser;
+use pest::iterators::Pair;
+
+use crate::diagnostics::Diagnostic;
+use crate::parsing::{PaxParser, Rule};
+
+const INDENT: &str = "    ";
+
+/// Lines longer than this are broken up, e.g. by placing each of a tag's attributes on its own line
+const MAX_LINE_WIDTH: usize = 100;
+
+/// Returns the canonical formatting of a component definition (the contents of a .pax file or `#[inlined(...)]` block),
+/// preserving comments.  Returns a diagnostic (sans source) if `pax` can't be parsed.
+pub fn format_pax(pax: &str) -> Result<String, Diagnostic> {
+    let pax_component_definition = PaxParser::parse(Rule::pax_component_definition, pax)
+        .map_err(Diagnostic::from_pest_error)?
+        .next()
+        .unwrap();
+
+    let mut formatter = PaxFormatter {
+        source: pax,
+        output: String::new(),
+        indent: 0,
+    };
+    formatter.write_component_definition(pax_component_definition);
+    Ok(formatter.output)
+}
+
+/// Formats the contents of each `#[inlined(...)]` attribute in `rust_source`, leaving the surrounding Rust untouched.
+/// Returns a diagnostic if the contents of any attribute can't be parsed, with its source set to those contents.
+pub fn format_inlined_pax(rust_source: &str) -> Result<String, Diagnostic> {
+    let mut output = String::new();
+    let mut cursor = 0;
+    for contents_range in find_inlined_pax(rust_source) {
+        let contents = &rust_source[contents_range.clone()];
+        let formatted = format_pax(contents).map_err(|diagnostic| diagnostic.with_source(None, Some(contents)))?;
+
+        //indent contents one level deeper than the attribute itself, leaving the contents of multi-line strings as written
+        let line_start = rust_source[..contents_range.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let base_indent : String = rust_source[line_start..].chars().take_while(|c| c.is_whitespace()).collect();
+
+        output += &rust_source[cursor..contents_range.start];
+        output += "\n";
+        for (line, is_continuation) in formatted.lines().zip(string_continuations(&formatted)) {
+            if !line.is_empty() && !is_continuation {
+                output += &base_indent;
+                output += INDENT;
+            }
+            output += line;
+            output += "\n";
+        }
+        output += &base_indent;
+        cursor = contents_range.end;
+    }
+    output += &rust_source[cursor..];
+    Ok(output)
+}
+
+/// Returns the byte range of the contents of each `#[inlined(...)]` attribute in `rust_source`, i.e. its Pax
+pub fn find_inlined_pax(rust_source: &str) -> Vec<Range<usize>> {
+    const INLINED_OPEN: &str = "#[inlined(";
+
+    let mut ret = vec![];
+    let mut search_start = 0;
+    while let Some(offset) = rust_source[search_start..].find(INLINED_OPEN) {
+        let start = search_start + offset;
+        let contents_start = start + INLINED_OPEN.len();
+        search_start = contents_start;
+
+        //only attributes, rather than e.g. mentions in strings or comments
+        let line_start = rust_source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        if !rust_source[line_start..start].chars().all(|c| c.is_whitespace()) {
+            continue;
+        }
+
+        if let Some(length) = find_closing_paren(&rust_source[contents_start..]) {
+            ret.push(contents_start..contents_start + length);
+            search_start = contents_start + length;
+        }
+    }
+    ret
+}
+
+/// For `text` following an opening parenthesis, returns the byte offset of its matching closing parenthesis,
+/// skipping over string literals and comments
+fn find_closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                if depth == 0 {
+                    return if c == ')' { Some(i) } else { None };
+                }
+                depth -= 1;
+            },
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => { chars.next(); },
+                        '"' => break,
+                        _ => {},
+                    }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                while let Some((_, c)) = chars.next() {
+                    if c == '\n' { break; }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut previous = ' ';
+                while let Some((_, c)) = chars.next() {
+                    if previous == '*' && c == '/' { break; }
+                    previous = c;
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+struct PaxFormatter<'a> {
+    source: &'a str,
+    output: String,
+    indent: usize,
+}
+
+impl<'a> PaxFormatter<'a> {
+
+    /// Writes `text` at the current indentation, except for lines continuing a multi-line string literal
+    fn write_line(&mut self, text: &str) {
+        for (line, is_continuation) in text.lines().zip(string_continuations(text)) {
+            if !line.is_empty() && !is_continuation {
+                self.output += &INDENT.repeat(self.indent);
+            }
+            self.output += line;
+            self.output += "\n";
+        }
+    }
+
+    /// Writes a comment at the current indentation, leaving any subsequent lines of a block comment as written
+    fn write_comment(&mut self, comment: &str) {
+        self.output += &INDENT.repeat(self.indent);
+        self.output += comment;
+        self.output += "\n";
+    }
+
+    /// Writes a blank line, unless at the beginning of the output or just after another blank line
+    fn write_blank_line(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output += "\n";
+        }
+    }
+
+    /// Writes any comments found in the source between `start` and `end`, which must fall between pairs
+    /// (where only whitespace, punctuation and comments can occur.)  Blank lines between siblings are preserved,
+    /// as are comments trailing a sibling on the same line, unless `is_leading`, i.e. there's no preceding sibling.
+    fn write_gap(&mut self, start: usize, end: usize, is_leading: bool) {
+        let gap = &self.source[start..end];
+        let mut cursor = 0;
+        for (offset, comment) in find_comments(gap) {
+            let preceding = &gap[cursor..offset];
+            if !is_leading && cursor == 0 && !preceding.contains('\n') && self.output.ends_with('\n') {
+                //comment trailing the previous sibling, e.g. `<Rectangle /> // background`
+                self.output.pop();
+                self.output += " ";
+                self.output += comment;
+                self.output += "\n";
+            } else {
+                if !is_leading && has_blank_line(preceding) {
+                    self.write_blank_line();
+                }
+                self.write_comment(comment);
+            }
+            cursor = offset + comment.len();
+        }
+        if !is_leading && has_blank_line(&gap[cursor..]) {
+            self.write_blank_line();
+        }
+    }
+
+    /// Writes the gap between a region's last child and the end of the region, e.g. comments before a closing tag
+    fn write_trailing_gap(&mut self, start: usize, end: usize, is_leading: bool) {
+        self.write_gap(start, end, is_leading);
+        while self.output.ends_with("\n\n") {
+            self.output.pop();
+        }
+    }
+
+    fn write_component_definition(&mut self, pax_component_definition: Pair<Rule>) {
+        let mut cursor = pax_component_definition.as_span().start();
+        let mut is_leading = true;
+        for pair in pax_component_definition.into_inner() {
+            let span = pair.as_span();
+            match pair.as_rule() {
+                Rule::root_tag_pair => {
+                    self.write_gap(cursor, span.start(), is_leading);
+                    self.write_tag_pair(pair.into_inner().next().unwrap());
+                },
+                Rule::settings_block_declaration | Rule::events_block_declaration => {
+                    self.write_blank_line();
+                    self.write_gap(cursor, span.start(), is_leading);
+                    if pair.as_rule() == Rule::settings_block_declaration {
+                        self.write_settings_block(pair);
+                    } else {
+                        self.write_events_block(pair);
+                    }
+                },
+                _ => {
+                    //EOI
+                    continue;
+                },
+            }
+            cursor = span.end();
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, self.source.len(), is_leading);
+    }
+
+    fn write_tag_pair(&mut self, any_tag_pair: Pair<Rule>) {
+        match any_tag_pair.as_rule() {
+            Rule::matched_tag => {
+                let mut inner = any_tag_pair.into_inner();
+                let open_tag = inner.next().unwrap();
+                let inner_nodes = inner.next().unwrap();
+                let closing_tag = inner.next().unwrap();
+
+                let pascal_identifier = open_tag.clone().into_inner().next().unwrap().as_str().to_string();
+                let open_tag_end = open_tag.as_span().end();
+                let header = self.tag_header(open_tag, false);
+                let closing = format!("</{}>", pascal_identifier);
+
+                let first_inner = inner_nodes.clone().into_inner().next();
+                if let Some(node_inner_content) = first_inner.clone().filter(|pair| pair.as_rule() == Rule::node_inner_content) {
+                    //e.g. `<Text>{self.message}</Text>`
+                    let content = format_node_inner_content(node_inner_content);
+                    if header.len() == 1 && !content.contains('\n') && self.fits(&format!("{}{}{}", header[0], content, closing)) {
+                        self.write_line(&format!("{}{}{}", header[0], content, closing));
+                    } else {
+                        self.write_line(&header.join("\n"));
+                        self.indent += 1;
+                        self.write_line(&content);
+                        self.indent -= 1;
+                        self.write_line(&closing);
+                    }
+                } else if first_inner.is_none() && find_comments(&self.source[open_tag_end..closing_tag.as_span().start()]).is_empty() && header.len() == 1 {
+                    self.write_line(&format!("{}{}", header[0], closing));
+                } else {
+                    self.write_line(&header.join("\n"));
+                    self.write_children(inner_nodes, open_tag_end..closing_tag.as_span().start());
+                    self.write_line(&closing);
+                }
+            },
+            Rule::self_closing_tag => {
+                let header = self.tag_header(any_tag_pair, true);
+                self.write_line(&header.join("\n"));
+            },
+            Rule::statement_control_flow => {
+                let statement = any_tag_pair.into_inner().next().unwrap();
+                match statement.as_rule() {
+                    Rule::statement_if => self.write_statement_if(statement, ""),
+                    Rule::statement_for => self.write_statement_for(statement),
+                    Rule::statement_slot => {
+                        let expression = format_expression(statement.into_inner().next().unwrap());
+                        if expression.starts_with('(') {
+                            self.write_line(&format!("slot{}", expression));
+                        } else {
+                            self.write_line(&format!("slot {}", expression));
+                        }
+                    },
+                    Rule::statement_match => self.write_statement_match(statement),
+                    _ => unreachable!(),
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Writes the nodes inside `inner_nodes`, one level deeper, along with any comments within `region`
+    fn write_children(&mut self, inner_nodes: Pair<Rule>, region: Range<usize>) {
+        self.indent += 1;
+        let mut cursor = region.start;
+        let mut is_leading = true;
+        for child in inner_nodes.into_inner() {
+            self.write_gap(cursor, child.as_span().start(), is_leading);
+            cursor = child.as_span().end();
+            if child.as_rule() == Rule::node_inner_content {
+                let content = format_node_inner_content(child);
+                self.write_line(&content);
+            } else {
+                self.write_tag_pair(child);
+            }
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, region.end, is_leading);
+        self.indent -= 1;
+    }
+
+    /// Writes `header {`, the nodes inside `inner_nodes`, then `}`; or `header {}` if there's nothing inside
+    fn write_block(&mut self, header: &str, inner_nodes: Pair<Rule>, region: Range<usize>) {
+        if inner_nodes.clone().into_inner().next().is_none() && find_comments(&self.source[region.clone()]).is_empty() {
+            self.write_line(&format!("{} {{}}", header));
+        } else {
+            self.write_line(&format!("{} {{", header));
+            self.write_children(inner_nodes, region);
+            self.write_line("}");
+        }
+    }
+
+    /// Returns the lines of an open tag or self-closing tag, relative to the current indentation.  Attributes are
+    /// ordered `id`, `class`, properties, then event handlers, and wrapped one-per-line if they don't fit on a single line.
+    /// Any comments between attributes are written immediately, ahead of the tag.
+    fn tag_header(&mut self, tag: Pair<Rule>, is_self_closing: bool) -> Vec<String> {
+        let tag_end = tag.as_span().end();
+        let mut inner = tag.into_inner();
+        let pascal_identifier = inner.next().unwrap();
+
+        let mut cursor = pascal_identifier.as_span().end();
+        let mut attributes = vec![];
+        let available_width = MAX_LINE_WIDTH.saturating_sub(INDENT.len() * (self.indent + 1));
+        for attribute in inner {
+            self.write_gap(cursor, attribute.as_span().start(), true);
+            cursor = attribute.as_span().end();
+            attributes.push(format_attribute(attribute, available_width));
+        }
+        self.write_gap(cursor, tag_end, true);
+        attributes.sort_by_key(|(order, _)| *order);
+
+        let pascal_identifier = pascal_identifier.as_str();
+        let closing = if is_self_closing { " />" } else { ">" };
+        let single_line = if attributes.is_empty() {
+            format!("<{}{}", pascal_identifier, closing)
+        } else {
+            format!("<{} {}{}", pascal_identifier, attributes.iter().map(|(_, attribute)| attribute.as_str()).join(" "), closing)
+        };
+
+        if self.fits(&single_line) {
+            vec![single_line]
+        } else {
+            let mut lines = vec![format!("<{}", pascal_identifier)];
+            attributes.iter().for_each(|(_, attribute)| {
+                attribute.lines().zip(string_continuations(attribute)).for_each(|(line, is_continuation)| {
+                    lines.push(if is_continuation { line.to_string() } else { format!("{}{}", INDENT, line) });
+                });
+            });
+            lines.push(closing.trim().to_string());
+            lines
+        }
+    }
+
+    fn write_statement_if(&mut self, statement_if: Pair<Rule>, prefix: &str) {
+        let statement_end = statement_if.as_span().end();
+        let mut inner = statement_if.into_inner();
+        let expression_body = inner.next().unwrap();
+        let inner_nodes = inner.next().unwrap();
+        let statement_else = inner.next();
+
+        self.write_line(&format!("{}if {} {{", prefix, format_expression(expression_body.clone())));
+        let children_end = statement_else.as_ref().map(|pair| pair.as_span().start()).unwrap_or(statement_end);
+        self.write_children(inner_nodes, expression_body.as_span().end()..children_end);
+
+        match statement_else {
+            None => self.write_line("}"),
+            Some(statement_else) => {
+                let else_span = statement_else.as_span();
+                let else_branch = statement_else.into_inner().next().unwrap();
+                if else_branch.as_rule() == Rule::statement_if {
+                    self.write_statement_if(else_branch, "} else ");
+                } else {
+                    self.write_line("} else {");
+                    self.write_children(else_branch, else_span.start()..else_span.end());
+                    self.write_line("}");
+                }
+            }
+        }
+    }
+
+    fn write_statement_for(&mut self, statement_for: Pair<Rule>) {
+        let statement_end = statement_for.as_span().end();
+        let mut header = "for ".to_string();
+        let mut header_end = statement_for.as_span().start();
+        let mut inner_nodes = None;
+        for pair in statement_for.into_inner() {
+            match pair.as_rule() {
+                Rule::statement_for_predicate_declaration => {
+                    let identifiers : Vec<&str> = pair.clone().into_inner().map(|identifier| identifier.as_str()).collect();
+                    if identifiers.len() == 1 {
+                        header += identifiers[0];
+                    } else {
+                        header += &format!("({})", identifiers.join(", "));
+                    }
+                },
+                Rule::statement_for_source => {
+                    header += &format!(" in {}", format_primary(pair.clone().into_inner().next().unwrap()));
+                },
+                Rule::statement_for_key => {
+                    header += &format!(" key {}", format_expression(pair.clone().into_inner().next().unwrap()));
+                },
+                Rule::inner_nodes => {
+                    inner_nodes = Some(pair);
+                    continue;
+                },
+                _ => {},
+            }
+            header_end = pair.as_span().end();
+        }
+
+        self.write_block(&header, inner_nodes.unwrap(), header_end..statement_end);
+    }
+
+    fn write_statement_match(&mut self, statement_match: Pair<Rule>) {
+        let statement_end = statement_match.as_span().end();
+        let mut inner = statement_match.into_inner();
+        let expression_body = inner.next().unwrap();
+        self.write_line(&format!("match {} {{", format_expression(expression_body.clone())));
+
+        self.indent += 1;
+        let mut cursor = expression_body.as_span().end();
+        let mut is_leading = true;
+        for arm in inner {
+            let arm_span = arm.as_span();
+            self.write_gap(cursor, arm_span.start(), is_leading);
+
+            let mut arm_inner = arm.into_inner();
+            let patterns = arm_inner.next().unwrap();
+            let patterns_end = patterns.as_span().end();
+            let patterns = patterns.into_inner().map(|pattern| {
+                if pattern.as_rule() == Rule::literal_enum_value {
+                    format_literal(pattern)
+                } else {
+                    pattern.as_str().trim().to_string()
+                }
+            }).join(" | ");
+
+            self.write_block(&format!("{} =>", patterns), arm_inner.next().unwrap(), patterns_end..arm_span.end());
+
+            cursor = arm_span.end();
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, statement_end, is_leading);
+        self.indent -= 1;
+        self.write_line("}");
+    }
+
+    fn write_settings_block(&mut self, settings_block_declaration: Pair<Rule>) {
+        let block_end = settings_block_declaration.as_span().end();
+        self.write_line("@settings {");
+        self.indent += 1;
+
+        let mut cursor = settings_block_declaration.as_span().start();
+        let mut is_leading = true;
+        for selector_block in settings_block_declaration.into_inner() {
+            if !is_leading {
+                self.write_blank_line();
+            }
+            self.write_gap(cursor, selector_block.as_span().start(), is_leading);
+            cursor = selector_block.as_span().end();
+
+            let mut inner = selector_block.into_inner();
+            let selector : String = inner.next().unwrap().as_str().split_whitespace().collect();
+            self.write_literal_object(&format!("{} ", selector), inner.next().unwrap(), "");
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, block_end, is_leading);
+
+        self.indent -= 1;
+        self.write_line("}");
+    }
+
+    /// Writes a `literal_object` across multiple lines, one key-value pair per line, e.g. within `@settings`
+    fn write_literal_object(&mut self, prefix: &str, literal_object: Pair<Rule>, suffix: &str) {
+        let object_span = literal_object.as_span();
+        let mut inner = literal_object.into_inner().peekable();
+        let type_prefix = match inner.peek() {
+            Some(pair) if pair.as_rule() == Rule::pascal_identifier => format!("{} ", inner.next().unwrap().as_str()),
+            _ => "".to_string(),
+        };
+        let key_value_pairs : Vec<Pair<Rule>> = inner.collect();
+
+        if key_value_pairs.is_empty() && find_comments(object_span.as_str()).is_empty() {
+            self.write_line(&format!("{}{}{{}}{}", prefix, type_prefix, suffix));
+            return;
+        }
+
+        self.write_line(&format!("{}{}{{", prefix, type_prefix));
+        self.indent += 1;
+        let mut cursor = object_span.start();
+        let mut is_leading = true;
+        for settings_key_value_pair in key_value_pairs {
+            self.write_gap(cursor, settings_key_value_pair.as_span().start(), is_leading);
+            cursor = settings_key_value_pair.as_span().end();
+
+            let mut kvp_inner = settings_key_value_pair.into_inner();
+            let key = kvp_inner.next().unwrap().into_inner().next().unwrap().as_str();
+            let value = kvp_inner.next().unwrap().into_inner().next().unwrap();
+            match value.as_rule() {
+                Rule::literal_object => {
+                    self.write_literal_object(&format!("{}: ", key), value, ",");
+                },
+                Rule::literal_value => {
+                    self.write_line(&format!("{}: {},", key, format_literal(value)));
+                },
+                _ => {
+                    //expression_body, from `expression_wrapped`
+                    let expression = self.format_wrapped_expression(&format!("{}: ", key), value);
+                    self.write_line(&format!("{}: {},", key, expression));
+                }
+            }
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, object_span.end(), is_leading);
+        self.indent -= 1;
+        self.write_line(&format!("}}{}", suffix));
+    }
+
+    fn write_events_block(&mut self, events_block_declaration: Pair<Rule>) {
+        let block_end = events_block_declaration.as_span().end();
+        self.write_line("@events {");
+        self.indent += 1;
+
+        let mut cursor = events_block_declaration.as_span().start();
+        let mut is_leading = true;
+        for events_key_value_pair in events_block_declaration.into_inner() {
+            self.write_gap(cursor, events_key_value_pair.as_span().start(), is_leading);
+            cursor = events_key_value_pair.as_span().end();
+
+            let mut inner = events_key_value_pair.into_inner();
+            let key = inner.next().unwrap().into_inner().next().unwrap().as_str();
+            let value = inner.next().unwrap().into_inner().next().unwrap();
+            let value = if value.as_rule() == Rule::function_list {
+                format!("[{}]", value.into_inner().map(format_literal_function).join(", "))
+            } else {
+                format_literal_function(value)
+            };
+            self.write_line(&format!("{}: {},", key, value));
+            is_leading = false;
+        }
+        self.write_trailing_gap(cursor, block_end, is_leading);
+
+        self.indent -= 1;
+        self.write_line("}");
+    }
+
+    /// Formats a `{}`-wrapped expression, breaking it across lines at its top-level operators if `prefix` and the
+    /// expression don't fit on a single line
+    fn format_wrapped_expression(&self, prefix: &str, expression_body: Pair<Rule>) -> String {
+        let segments = expression_segments(expression_body);
+        let single_line = format!("{{{}}}", segments.join(" "));
+        if segments.len() == 1 || self.fits(&format!("{}{}", prefix, single_line)) {
+            single_line
+        } else {
+            let mut lines = vec!["{".to_string()];
+            segments.iter().for_each(|segment| lines.push(format!("{}{}", INDENT, segment)));
+            lines.push("}".to_string());
+            lines.join("\n")
+        }
+    }
+
+    fn fits(&self, line: &str) -> bool {
+        !line.contains('\n') && INDENT.len() * self.indent + line.chars().count() <= MAX_LINE_WIDTH
+    }
+}
+
+/// Returns `(order, formatted attribute)`, where `order` sorts `id` and `class` first and event handlers last.
+/// Expressions are broken across lines if the attribute would otherwise be wider than `available_width`.
+fn format_attribute(attribute_key_value_pair: Pair<Rule>, available_width: usize) -> (usize, String) {
+    let mut inner = attribute_key_value_pair.into_inner();
+    let first = inner.next().unwrap();
+    if first.as_rule() == Rule::attribute_event_binding {
+        let mut binding = first.into_inner();
+        let event_id : String = binding.next().unwrap().as_str().split_whitespace().collect();
+        let handler = format_literal_function(binding.next().unwrap());
+        return (3, format!("{}={}", event_id, handler));
+    }
+
+    let key = first.as_str();
+    let value = inner.next().unwrap().into_inner().next().unwrap();
+    let value = match value.as_rule() {
+        Rule::literal_value => format_literal(value),
+        Rule::literal_object => format_inline_literal_object(value),
+        Rule::identifier => value.as_str().to_string(),
+        _ => {
+            //expression_body, from `expression_wrapped`
+            let segments = expression_segments(value);
+            let single_line = format!("{{{}}}", segments.join(" "));
+            if segments.len() == 1 || key.len() + 1 + single_line.chars().count() <= available_width {
+                single_line
+            } else {
+                format!("{{\n{}\n}}", segments.iter().map(|segment| format!("{}{}", INDENT, segment)).join("\n"))
+            }
+        }
+    };
+    let order = match key {
+        "id" => 0,
+        "class" => 1,
+        _ => 2,
+    };
+    (order, format!("{}={}", key, value))
+}
+
+/// Formats a `literal_object` on a single line, e.g. for a tag's attribute.  Objects containing comments are
+/// left as written, so that the comments are preserved.
+fn format_inline_literal_object(literal_object: Pair<Rule>) -> String {
+    if !find_comments(literal_object.as_str()).is_empty() {
+        return normalize_verbatim(literal_object.as_str());
+    }
+    let mut inner = literal_object.into_inner().peekable();
+    let type_prefix = match inner.peek() {
+        Some(pair) if pair.as_rule() == Rule::pascal_identifier => format!("{} ", inner.next().unwrap().as_str()),
+        _ => "".to_string(),
+    };
+    let key_value_pairs : Vec<String> = inner.map(|settings_key_value_pair| {
+        let mut kvp_inner = settings_key_value_pair.into_inner();
+        let key = kvp_inner.next().unwrap().into_inner().next().unwrap().as_str();
+        let value = kvp_inner.next().unwrap().into_inner().next().unwrap();
+        let value = match value.as_rule() {
+            Rule::literal_object => format_inline_literal_object(value),
+            Rule::literal_value => format_literal(value),
+            _ => format!("{{{}}}", format_expression(value)),
+        };
+        format!("{}: {}", key, value)
+    }).collect();
+
+    if key_value_pairs.is_empty() {
+        format!("{}{{}}", type_prefix)
+    } else {
+        format!("{}{{ {} }}", type_prefix, key_value_pairs.join(", "))
+    }
+}
+
+fn format_node_inner_content(node_inner_content: Pair<Rule>) -> String {
+    let content = node_inner_content.into_inner().next().unwrap();
+    if content.as_rule() == Rule::literal_value {
+        format_literal(content)
+    } else {
+        format!("{{{}}}", format_expression(content))
+    }
+}
+
+/// e.g. `self.handle_click` or `handle_click`, sans any trailing comma
+fn format_literal_function(literal_function: Pair<Rule>) -> String {
+    let function : String = literal_function.as_str().split_whitespace().collect();
+    function.trim_end_matches(',').to_string()
+}
+
+/// Formats literals, including `literal_value`s and the literals in `xo_literal`
+fn format_literal(literal: Pair<Rule>) -> String {
+    match literal.as_rule() {
+        Rule::literal_value | Rule::xo_literal => format_literal(literal.into_inner().next().unwrap()),
+        Rule::literal_number_with_unit | Rule::literal_number | Rule::literal_boolean | Rule::literal_tuple_access => {
+            literal.as_str().split_whitespace().collect()
+        },
+        Rule::literal_tuple => {
+            format!("({})", literal.into_inner().map(format_literal).join(", "))
+        },
+        Rule::literal_enum_value => {
+            let mut path = vec![];
+            let mut args = None;
+            for pair in literal.into_inner() {
+                if pair.as_rule() == Rule::literal_enum_args_list {
+                    args = Some(pair.into_inner().map(format_literal).join(", "));
+                } else {
+                    path.push(pair.as_str().to_string());
+                }
+            }
+            match args {
+                Some(args) => format!("{}({})", path.join("::"), args),
+                None => path.join("::"),
+            }
+        },
+        //strings, verbatim
+        _ => literal.as_str().trim().to_string(),
+    }
+}
+
+/// Formats a PAXEL expression on a single line, e.g. `self.num_clicks % 2 == 0`
+fn format_expression(expression_body: Pair<Rule>) -> String {
+    expression_segments(expression_body).join(" ")
+}
+
+/// Splits a formatted expression at its top-level infix operators, e.g. `["a", "* b", "* c"]` for `a*b*c`,
+/// so that long expressions can be broken across lines.  Expressions containing comments are left as written.
+fn expression_segments(expression_body: Pair<Rule>) -> Vec<String> {
+    if !find_comments_in_code(expression_body.as_str()).is_empty() {
+        return vec![normalize_verbatim(expression_body.as_str())];
+    }
+
+    let mut segments : Vec<String> = vec![];
+    let mut current = String::new();
+    for pair in expression_body.into_inner() {
+        match pair.as_rule() {
+            Rule::xo_neg | Rule::xo_bool_not => current += pair.as_str().trim(),
+            Rule::xo_add | Rule::xo_bool_and | Rule::xo_bool_or | Rule::xo_div | Rule::xo_exp | Rule::xo_mod |
+            Rule::xo_mul | Rule::xo_rel_eq | Rule::xo_rel_gt | Rule::xo_rel_gte | Rule::xo_rel_lt | Rule::xo_rel_lte |
+            Rule::xo_rel_neq | Rule::xo_sub | Rule::xo_tern_then | Rule::xo_tern_else => {
+                segments.push(current);
+                current = format!("{} ", pair.as_str().trim());
+            },
+            _ => current += &format_primary(pair),
+        }
+    }
+    segments.push(current);
+    segments
+}
+
+fn format_primary(primary: Pair<Rule>) -> String {
+    match primary.as_rule() {
+        Rule::expression_body => format_expression(primary),
+        Rule::expression_grouped => {
+            let mut inner = primary.into_inner();
+            let expression = format_expression(inner.next().unwrap());
+            let unit = inner.next().map(|unit| unit.as_str().trim().to_string()).unwrap_or_default();
+            format!("({}){}", expression, unit)
+        },
+        Rule::xo_function_call => {
+            let mut path = vec![];
+            let mut args = String::new();
+            for pair in primary.into_inner() {
+                if pair.as_rule() == Rule::xo_function_args_list {
+                    args = pair.into_inner().map(format_expression).join(", ");
+                } else {
+                    path.push(pair.as_str().to_string());
+                }
+            }
+            format!("{}({})", path.join("::"), args)
+        },
+        Rule::xo_object => {
+            let mut type_prefix = String::new();
+            let mut key_value_pairs = vec![];
+            for pair in primary.into_inner() {
+                if pair.as_rule() == Rule::identifier {
+                    type_prefix = format!("{} ", pair.as_str());
+                } else {
+                    let mut kvp_inner = pair.into_inner();
+                    let key = kvp_inner.next().unwrap().into_inner().next().unwrap().as_str();
+                    key_value_pairs.push(format!("{}: {}", key, format_expression(kvp_inner.next().unwrap())));
+                }
+            }
+            if key_value_pairs.is_empty() {
+                format!("{}{{}}", type_prefix)
+            } else {
+                format!("{}{{ {} }}", type_prefix, key_value_pairs.join(", "))
+            }
+        },
+        Rule::xo_range => {
+            primary.into_inner().map(|pair| {
+                match pair.as_rule() {
+                    Rule::xo_range_inclusive | Rule::xo_range_exclusive => pair.as_str().to_string(),
+                    _ => format_primary(pair),
+                }
+            }).join("")
+        },
+        Rule::xo_range_stepped => {
+            let mut inner = primary.into_inner();
+            let range = format_primary(inner.next().unwrap());
+            let step = format_expression(inner.next().unwrap());
+            format!("({}).step_by({})", range, step)
+        },
+        Rule::xo_range_reversed => {
+            let mut inner = primary.into_inner();
+            let range = format_primary(inner.next().unwrap());
+            match inner.next() {
+                Some(step) => format!("({}).step_by({}).rev()", range, format_expression(step)),
+                None => format!("({}).rev()", range),
+            }
+        },
+        Rule::xo_tuple => format!("({})", primary.into_inner().map(format_expression).join(", ")),
+        Rule::xo_list => format!("[{}]", primary.into_inner().map(format_expression).join(", ")),
+        Rule::xo_literal => format_literal(primary),
+        Rule::xo_symbol => {
+            let mut symbol = if primary.as_str().trim_start().starts_with('$') { "$".to_string() } else { String::new() };
+            let mut is_first = true;
+            for pair in primary.into_inner() {
+                if pair.as_rule() == Rule::expression_body {
+                    symbol += &format!("[{}]", format_expression(pair));
+                } else {
+                    if !is_first {
+                        symbol += ".";
+                    }
+                    symbol += pair.as_str();
+                }
+                is_first = false;
+            }
+            symbol
+        },
+        _ => primary.as_str().trim().to_string(),
+    }
+}
+
+/// Returns `(byte offset, comment)` for each comment in `gap`, a region of source between pairs,
+/// which can't contain string literals
+fn find_comments(gap: &str) -> Vec<(usize, &str)> {
+    let mut ret = vec![];
+    let mut i = 0;
+    while i < gap.len() {
+        let rest = &gap[i..];
+        if rest.starts_with("//") {
+            let length = rest.find('\n').unwrap_or(rest.len());
+            ret.push((i, rest[..length].trim_end()));
+            i += length;
+        } else if rest.starts_with("/*") {
+            let length = rest.find("*/").map(|end| end + 2).unwrap_or(rest.len());
+            ret.push((i, &rest[..length]));
+            i += length;
+        } else {
+            i += rest.chars().next().unwrap().len_utf8();
+        }
+    }
+    ret
+}
+
+/// Like `find_comments`, but skips over string literals, e.g. for the text of an expression
+fn find_comments_in_code(code: &str) -> Vec<(usize, &str)> {
+    let mut ret = vec![];
+    let mut cursor = 0;
+    let mut string_delimiter: Option<char> = None;
+    let mut is_escaped = false;
+    for (i, c) in code.char_indices() {
+        if let Some(delimiter) = string_delimiter {
+            if is_escaped {
+                is_escaped = false;
+            } else if c == '\\' {
+                is_escaped = true;
+            } else if c == delimiter {
+                string_delimiter = None;
+                cursor = i + 1;
+            }
+        } else if c == '"' || c == '\'' || c == '`' {
+            ret.extend(find_comments(&code[cursor..i]).into_iter().map(|(offset, comment)| (cursor + offset, comment)));
+            string_delimiter = Some(c);
+        }
+    }
+    if string_delimiter.is_none() {
+        ret.extend(find_comments(&code[cursor..]).into_iter().map(|(offset, comment)| (cursor + offset, comment)));
+    }
+    ret
+}
+
+fn has_blank_line(whitespace: &str) -> bool {
+    whitespace.matches('\n').count() >= 2
+}
+
+/// For each line of `text`, whether it begins inside a string literal, e.g. the second line of `text="a\nb"`
+fn string_continuations(text: &str) -> Vec<bool> {
+    let mut ret = vec![];
+    let mut string_delimiter: Option<char> = None;
+    let mut is_escaped = false;
+    for line in text.lines() {
+        ret.push(string_delimiter.is_some());
+        for c in line.chars() {
+            if let Some(delimiter) = string_delimiter {
+                if is_escaped {
+                    is_escaped = false;
+                } else if c == '\\' {
+                    is_escaped = true;
+                } else if c == delimiter {
+                    string_delimiter = None;
+                }
+            } else if c == '"' || c == '\'' || c == '`' {
+                string_delimiter = Some(c);
+            } else if line.trim_start().starts_with("//") {
+                break;
+            }
+        }
+    }
+    ret
+}
+
+/// Trims source that's kept as written (because it contains comments), indenting any subsequent lines
+/// by one level relative to the first, so that formatting stays stable across runs
+fn normalize_verbatim(text: &str) -> String {
+    text.trim().lines().enumerate().map(|(i, line)| {
+        if i == 0 || line.trim().is_empty() {
+            line.trim().to_string()
+        } else {
+            format!("{}{}", INDENT, line.trim())
+        }
+    }).join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formatting::{format_inlined_pax, format_pax};
+
+    #[test]
+    fn test_format_pax() {
+        let unformatted = "// Header\n<Group   @click=self.handle_click id=root>\n  <Rectangle fill={Color::rgb(1,0, 0)} /> // background\n\n\n  for i in 0 .. 5 {<Text text={i*2} />}\n</Group>\n@settings{#root{width:100%,transform:{Transform2D::align(50%,50%)*Transform2D::anchor(50%,50%)}}}\n";
+        let expected = "// Header\n<Group id=root @click=self.handle_click>\n    <Rectangle fill={Color::rgb(1, 0, 0)} /> // background\n\n    for i in 0..5 {\n        <Text text={i * 2} />\n    }\n</Group>\n\n@settings {\n    #root {\n        width: 100%,\n        transform: {Transform2D::align(50%, 50%) * Transform2D::anchor(50%, 50%)},\n    }\n}\n";
+
+        let formatted = format_pax(unformatted).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_pax(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_reversed_ranges() {
+        let unformatted = "for i in ( 0..self.count ).rev() {<Text text={i} />}\nfor j in (0..=10).step_by( 2 ).rev() {<Text text={j} />}\n";
+        let expected = "for i in (0..self.count).rev() {\n    <Text text={i} />\n}\nfor j in (0..=10).step_by(2).rev() {\n    <Text text={j} />\n}\n";
+
+        assert_eq!(format_pax(unformatted).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_inlined_pax() {
+        let unformatted = "#[derive(Pax)]\n#[inlined(\n<Group><Text text=\"first line\nsecond line\" /></Group>\n)]\npub struct Example {}\n";
+        let expected = "#[derive(Pax)]\n#[inlined(\n    <Group>\n        <Text\n            text=\"first line\nsecond line\"\n        />\n    </Group>\n)]\npub struct Example {}\n";
+
+        let formatted = format_inlined_pax(unformatted).unwrap();
+        assert_eq!(formatted, expected);
+        //continuation lines of the string keep their contents, rather than being indented further on each run
+        assert_eq!(format_inlined_pax(&formatted).unwrap(), formatted);
+    }
+}
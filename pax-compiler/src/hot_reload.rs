@@ -0,0 +1,680 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use pest::Parser;
+use pest::iterators::{Pair, Pairs};
+use pest::pratt_parser::PrattParser;
+
+use pax_message::hot_reload::{BinaryOperator, ComponentTemplatePatch, ExpressionPatch, HotReloadPatch, PaxelExpression, SizeUnit, SymbolBinding, SymbolKind, TemplateNodePatch};
+
+use crate::manifest::{ComponentDefinition, ExpressionSpec, PaxManifest, TemplateNodeDefinition, ValueDefinition};
+use crate::parsing::{build_pratt_parser, symbolic_id_from_pair, PaxParser, Rule};
+
+/// Environment variable through which the chassis learns the port of the `HotReloadServer`, at build time
+pub const HOT_RELOAD_PORT_ENV_VAR: &str = "PAX_HOT_RELOAD_PORT";
+
+/// Types whose values can be represented by the engine's `DynamicValue`, i.e. read and produced by interpreted expressions
+const INTERPRETABLE_TYPES: [&str; 16] = [
+    "u8", "u16", "u32", "u64", "usize",
+    "i8", "i16", "i32", "i64", "isize",
+    "f64", "bool", "String", "Numeric", "Size", "Transform2D",
+];
+
+/// Built-in functions that interpreted expressions may call; see `hot_reload::evaluate_function_call` in pax-core
+const INTERPRETABLE_FUNCTIONS: [&str; 5] = [
+    "Transform2D::rotate", "Transform2D::scale", "Transform2D::translate", "Transform2D::anchor", "Transform2D::align",
+];
+
+/// Whether values of `type_id` (or a fully qualified type path) can be represented by the engine's `DynamicValue`
+pub fn is_interpretable_type(type_id: &str) -> bool {
+    !type_id.contains('<') && INTERPRETABLE_TYPES.contains(&type_id.rsplit("::").next().unwrap())
+}
+
+/// Whether hot-reloaded templates may instantiate nodes of this component, i.e. whether the cartridge can instantiate it dynamically
+pub fn is_hot_reloadable_node_type(cd: &ComponentDefinition) -> bool {
+    !cd.is_main_component && !cd.is_struct_only_component && (!cd.is_primitive || cd.primitive_instance_import_path.is_some())
+}
+
+/// The (containing component type_id, event id, handler method) of each event binding in the manifest's templates, which are
+/// the only event bindings hot-reloaded templates may declare, since their handlers must be compiled into the cartridge
+pub fn collect_event_bindings(manifest: &PaxManifest) -> Vec<(String, String, String)> {
+    let mut bindings : Vec<(String, String, String)> = manifest.components.values().flat_map(|cd| {
+        cd.template.iter().flatten().flat_map(|tnd| tnd.settings.iter().flatten()).filter_map(|(key, value)| {
+            match value {
+                ValueDefinition::EventBindingTarget(method) => Some((cd.type_id.clone(), key.clone(), method.clone())),
+                _ => None,
+            }
+        }).collect::<Vec<_>>()
+    }).collect();
+    bindings.sort();
+    bindings.dedup();
+    bindings
+}
+
+/// Tracks what's compiled into the running app, in order to express changes to templates as `HotReloadPatch`es against it.
+/// A patch can't be made (and the app must be rebuilt) if the changes need anything not compiled into the cartridge, e.g.
+/// control flow, literal blocks, expressions outside of the interpretable subset of PAXEL, or new event bindings.
+pub struct HotReloadSession {
+    /// The manifest the running app was built from, with compiled expressions
+    running_manifest: PaxManifest,
+    /// The Pax of each component's template as the running app currently has it, including patches
+    running_pax_sources: HashMap<String, Option<String>>,
+    event_bindings: HashSet<(String, String, String)>,
+    /// TypesCoproduct variants that the cartridge can coerce interpreted values into
+    return_types: HashSet<String>,
+    next_expression_id: usize,
+}
+
+impl HotReloadSession {
+    pub fn new(running_manifest: PaxManifest) -> Self {
+        let running_pax_sources = running_manifest.components.iter()
+            .map(|(type_id, cd)| (type_id.clone(), cd.pax_source.clone()))
+            .collect();
+        let event_bindings = collect_event_bindings(&running_manifest).into_iter().collect();
+        let mut return_types : HashSet<String> = running_manifest.components.values().flat_map(|cd| {
+            cd.get_property_definitions(&running_manifest.type_table).iter()
+                .map(|pd| pd.get_type_definition(&running_manifest.type_table))
+                .filter(|td| is_interpretable_type(&td.type_id))
+                .map(|td| td.type_id_escaped.clone())
+                .collect::<Vec<_>>()
+        }).collect();
+        //built-in variants of TypesCoproduct
+        return_types.extend(["f64", "bool", "isize", "usize", "String", "Transform2D", "Size", "Numeric"].map(str::to_string));
+        let next_expression_id = running_manifest.expression_specs.as_ref().unwrap().keys().max().map(|id| id + 1).unwrap_or(0);
+        Self {
+            running_manifest,
+            running_pax_sources,
+            event_bindings,
+            return_types,
+            next_expression_id,
+        }
+    }
+
+    /// Expresses the templates of `manifest` (with compiled expressions) that differ from the running app's as a patch,
+    /// or returns `None` if any can't be hot reloaded.  The session assumes that a returned patch is applied.
+    pub fn create_patch(&mut self, manifest: &PaxManifest) -> Option<HotReloadPatch> {
+        let mut changed_type_ids : Vec<&String> = manifest.components.iter()
+            .filter(|(type_id, cd)| self.running_pax_sources.get(*type_id) != Some(&cd.pax_source))
+            .map(|(type_id, _)| type_id)
+            .collect();
+        changed_type_ids.sort();
+
+        let mut builder = PatchBuilder {
+            session: self,
+            manifest,
+            expressions: vec![],
+            next_expression_id: self.next_expression_id,
+        };
+        let templates = changed_type_ids.iter().map(|type_id| builder.build_template(&manifest.components[*type_id])).collect::<Option<Vec<_>>>()?;
+        let expressions = builder.expressions;
+        let next_expression_id = builder.next_expression_id;
+
+        self.next_expression_id = next_expression_id;
+        for type_id in changed_type_ids {
+            self.running_pax_sources.insert(type_id.clone(), manifest.components[type_id].pax_source.clone());
+        }
+        Some(HotReloadPatch {
+            templates,
+            expressions,
+        })
+    }
+}
+
+struct PatchBuilder<'a> {
+    session: &'a HotReloadSession,
+    manifest: &'a PaxManifest,
+    expressions: Vec<ExpressionPatch>,
+    next_expression_id: usize,
+}
+
+impl<'a> PatchBuilder<'a> {
+    fn build_template(&mut self, cd: &ComponentDefinition) -> Option<ComponentTemplatePatch> {
+        //components may only be patched if they were compiled into the running app, e.g. not newly declared
+        let running_cd = self.session.running_manifest.components.get(&cd.type_id)?;
+        if running_cd.is_primitive || running_cd.is_struct_only_component {
+            return None;
+        }
+        let template = cd.template.as_ref()?;
+        let root_nodes = template[0].child_ids.iter()
+            .map(|child_id| self.build_node(cd, template, &template[*child_id]))
+            .collect::<Option<Vec<_>>>()?;
+        Some(ComponentTemplatePatch {
+            component_type_id: cd.type_id.clone(),
+            root_nodes,
+        })
+    }
+
+    fn build_node(&mut self, cd: &ComponentDefinition, template: &[TemplateNodeDefinition], tnd: &TemplateNodeDefinition) -> Option<TemplateNodePatch> {
+        //control flow (`if`, `for`, `slot`, `match`) isn't supported; nor are types the cartridge can't instantiate dynamically
+        let node_cd = self.session.running_manifest.components.get(&tnd.type_id)?;
+        if !is_hot_reloadable_node_type(node_cd) {
+            return None;
+        }
+
+        let mut properties = HashMap::new();
+        let mut events = vec![];
+        for (key, value) in tnd.settings.iter().flatten() {
+            match value {
+                ValueDefinition::EventBindingTarget(method) => {
+                    if !self.session.event_bindings.contains(&(cd.type_id.clone(), key.clone(), method.clone())) {
+                        return None;
+                    }
+                    events.push((key.clone(), method.clone()));
+                },
+                //registered alongside the template id, rather than bound as a property
+                ValueDefinition::LiteralValue(_) if key == "focusable" => {},
+                ValueDefinition::LiteralValue(ril) => {
+                    let return_type = self.get_property_return_type(&tnd.type_id, key)?;
+                    let expression = build_paxel_expression(&paxel_from_literal_ril(ril)?, &[])?;
+                    properties.insert(key.clone(), self.add_expression(return_type, expression)?);
+                },
+                //selectors, already merged into the settings by `compile_all_expressions`
+                ValueDefinition::Identifier(..) if key == "id" || key == "class" => {},
+                ValueDefinition::Expression(_, id, _) |
+                ValueDefinition::Identifier(_, id, _) => {
+                    let spec = self.manifest.expression_specs.as_ref()?.get(&(*id)?)?;
+                    properties.insert(key.clone(), self.bind_expression(spec)?);
+                },
+                _ => return None,
+            }
+        }
+
+        let children = tnd.child_ids.iter()
+            .map(|child_id| self.build_node(cd, template, &template[*child_id]))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(TemplateNodePatch {
+            type_id: tnd.type_id.clone(),
+            properties,
+            events,
+            template_id: crate::get_template_id(tnd),
+            is_focusable: crate::is_focusable(tnd),
+            children,
+        })
+    }
+
+    /// The TypesCoproduct variant of a property of the given component, including the built-ins
+    fn get_property_return_type(&self, type_id: &str, property: &str) -> Option<String> {
+        match property {
+            "width" | "height" => Some("Size".to_string()),
+            "transform" => Some("Transform2D".to_string()),
+            _ => {
+                let type_table = &self.session.running_manifest.type_table;
+                let pd = self.session.running_manifest.components.get(type_id)?
+                    .get_property_definitions(type_table).iter()
+                    .find(|pd| pd.name == property)?;
+                Some(pd.get_type_definition(type_table).type_id_escaped.clone())
+            },
+        }
+    }
+
+    /// Reuses an expression compiled into the running app if there's an identical one, and otherwise interprets it
+    fn bind_expression(&mut self, spec: &ExpressionSpec) -> Option<usize> {
+        let running_id = self.session.running_manifest.expression_specs.as_ref()?.values()
+            .find(|running_spec| is_same_expression(running_spec, spec))
+            .map(|running_spec| running_spec.id);
+        if running_id.is_some() {
+            return running_id;
+        }
+
+        if spec.is_repeat_source_iterable_expression {
+            return None;
+        }
+        let expression = build_paxel_expression(&spec.input_statement, &spec.invocations)?;
+        for invocation in spec.invocations.iter() {
+            //properties are read through the cartridge, which can only read those of interpretable types
+            if !invocation.property_flags.is_binding_repeat_i && !self.is_readable_property(&invocation.properties_coproduct_type, &invocation.root_identifier) {
+                return None;
+            }
+        }
+        self.add_expression(spec.pascalized_return_type.clone(), expression)
+    }
+
+    fn is_readable_property(&self, properties_coproduct_type: &str, property: &str) -> bool {
+        let type_table = &self.session.running_manifest.type_table;
+        self.session.running_manifest.components.values()
+            .find(|cd| cd.type_id_escaped == properties_coproduct_type)
+            .and_then(|cd| cd.get_property_definitions(type_table).iter().find(|pd| pd.name == property))
+            .map(|pd| is_interpretable_type(&pd.type_id))
+            .unwrap_or(false)
+    }
+
+    fn add_expression(&mut self, return_type: String, expression: PaxelExpression) -> Option<usize> {
+        if !self.session.return_types.contains(&return_type) {
+            return None;
+        }
+        let id = self.next_expression_id;
+        self.next_expression_id += 1;
+        self.expressions.push(ExpressionPatch {
+            id,
+            return_type,
+            expression,
+        });
+        Some(id)
+    }
+}
+
+/// Whether two compiled expressions would generate the same vtable entry
+fn is_same_expression(a: &ExpressionSpec, b: &ExpressionSpec) -> bool {
+    a.output_statement == b.output_statement
+        && a.pascalized_return_type == b.pascalized_return_type
+        && a.is_repeat_source_iterable_expression == b.is_repeat_source_iterable_expression
+        && a.repeat_source_iterable_type_id_escaped == b.repeat_source_iterable_type_id_escaped
+        && serde_json::to_string(&a.invocations).ok() == serde_json::to_string(&b.invocations).ok()
+}
+
+/// Recovers the PAXEL of a literal setting from its RIL, as emitted for literals by `recurse_pratt_parse_to_string`
+fn paxel_from_literal_ril(ril: &str) -> Option<String> {
+    if let Some(inner) = ril.strip_prefix("Size::Pixels(").and_then(|rest| rest.strip_suffix(".into()).into()")) {
+        Some(format!("{}px", inner))
+    } else if let Some(inner) = ril.strip_prefix("Size::Percent(").and_then(|rest| rest.strip_suffix(".into()).into()")) {
+        Some(format!("{}%", inner))
+    } else if let Some(inner) = ril.strip_prefix("Numeric::from(").and_then(|rest| rest.strip_suffix(')')) {
+        Some(inner.to_string())
+    } else {
+        ril.strip_suffix(".try_into().unwrap()").map(str::to_string)
+    }
+}
+
+/// Builds the AST of `paxel` for interpretation, resolving its symbols per `invocations`.  `None` if any part of it is
+/// outside of the interpretable subset of PAXEL, e.g. objects, ranges, nested symbols or enum literals.
+pub fn build_paxel_expression(paxel: &str, invocations: &[crate::manifest::ExpressionSpecInvocation]) -> Option<PaxelExpression> {
+    let pairs = PaxParser::parse(Rule::expression_body, paxel).ok()?;
+    let builder = ExpressionBuilder {
+        pratt_parser: build_pratt_parser(),
+        invocations,
+    };
+    match builder.build(pairs)? {
+        PartialExpression::Complete(expression) => Some(expression),
+        PartialExpression::TernaryThen(_, _) => None,
+    }
+}
+
+/// `a ? b : c` is parsed as `(a ? b) : c`, so the left-hand side of `:` is held aside until its `else` branch arrives
+enum PartialExpression {
+    Complete(PaxelExpression),
+    TernaryThen(PaxelExpression, PaxelExpression),
+}
+
+impl PartialExpression {
+    fn complete(self) -> Option<PaxelExpression> {
+        match self {
+            PartialExpression::Complete(expression) => Some(expression),
+            PartialExpression::TernaryThen(_, _) => None,
+        }
+    }
+}
+
+struct ExpressionBuilder<'a> {
+    pratt_parser: PrattParser<Rule>,
+    invocations: &'a [crate::manifest::ExpressionSpecInvocation],
+}
+
+impl<'a> ExpressionBuilder<'a> {
+
+    /// Counterpart to `recurse_pratt_parse_to_string`
+    fn build(&self, expression: Pairs<Rule>) -> Option<PartialExpression> {
+        self.pratt_parser
+            .map_primary(|primary| Some(PartialExpression::Complete(self.build_primary(primary)?)))
+            .map_prefix(|op, rhs| {
+                let rhs = Box::new(rhs?.complete()?);
+                Some(PartialExpression::Complete(match op.as_rule() {
+                    Rule::xo_neg => PaxelExpression::Negate(rhs),
+                    Rule::xo_bool_not => PaxelExpression::Not(rhs),
+                    _ => return None,
+                }))
+            })
+            .map_infix(|lhs, op, rhs| {
+                let rhs = rhs?.complete()?;
+                let operator = match op.as_rule() {
+                    Rule::xo_tern_then => return Some(PartialExpression::TernaryThen(lhs?.complete()?, rhs)),
+                    Rule::xo_tern_else => {
+                        return match lhs? {
+                            PartialExpression::TernaryThen(condition, then_branch) => {
+                                Some(PartialExpression::Complete(PaxelExpression::Conditional(Box::new(condition), Box::new(then_branch), Box::new(rhs))))
+                            },
+                            PartialExpression::Complete(_) => None,
+                        };
+                    },
+                    Rule::xo_add => BinaryOperator::Add,
+                    Rule::xo_sub => BinaryOperator::Sub,
+                    Rule::xo_mul => BinaryOperator::Mul,
+                    Rule::xo_div => BinaryOperator::Div,
+                    Rule::xo_mod => BinaryOperator::Mod,
+                    Rule::xo_exp => BinaryOperator::Exp,
+                    Rule::xo_bool_and => BinaryOperator::And,
+                    Rule::xo_bool_or => BinaryOperator::Or,
+                    Rule::xo_rel_eq => BinaryOperator::Eq,
+                    Rule::xo_rel_neq => BinaryOperator::Neq,
+                    Rule::xo_rel_lt => BinaryOperator::Lt,
+                    Rule::xo_rel_lte => BinaryOperator::Lte,
+                    Rule::xo_rel_gt => BinaryOperator::Gt,
+                    Rule::xo_rel_gte => BinaryOperator::Gte,
+                    _ => return None,
+                };
+                Some(PartialExpression::Complete(PaxelExpression::Binary(Box::new(lhs?.complete()?), operator, Box::new(rhs))))
+            })
+            .parse(expression)
+    }
+
+    fn build_primary(&self, primary: Pair<Rule>) -> Option<PaxelExpression> {
+        match primary.as_rule() {
+            Rule::expression_grouped => {
+                /* expression_grouped = { "(" ~ expression_body ~ ")" ~ literal_number_unit? } */
+                let mut inner = primary.into_inner();
+                let expression = self.build(inner.next().unwrap().into_inner())?.complete()?;
+                match inner.next() {
+                    Some(unit) => Some(PaxelExpression::Size(Box::new(expression), size_unit(unit.as_str().trim()))),
+                    None => Some(expression),
+                }
+            },
+            Rule::xo_function_call => {
+                /* xo_function_call = {identifier ~ (("::") ~ identifier)* ~ ("("~xo_function_args_list~")")} */
+                let mut path = vec![];
+                let mut args = vec![];
+                for pair in primary.into_inner() {
+                    match pair.as_rule() {
+                        Rule::identifier => path.push(pair.as_str().trim()),
+                        Rule::xo_function_args_list => {
+                            for arg in pair.into_inner() {
+                                args.push(self.build(arg.into_inner())?.complete()?);
+                            }
+                        },
+                        _ => return None,
+                    }
+                }
+                let path = path.join("::");
+                if !INTERPRETABLE_FUNCTIONS.contains(&path.as_str()) {
+                    return None;
+                }
+                Some(PaxelExpression::FunctionCall(path, args))
+            },
+            Rule::xo_literal => {
+                let literal_kind = primary.into_inner().next().unwrap();
+                match literal_kind.as_rule() {
+                    Rule::literal_number_with_unit => {
+                        let mut inner = literal_kind.into_inner();
+                        let value = build_number(inner.next().unwrap())?;
+                        let unit = inner.next().unwrap().as_str().trim();
+                        Some(PaxelExpression::Size(Box::new(value), size_unit(unit)))
+                    },
+                    Rule::literal_number => build_number(literal_kind),
+                    Rule::string => {
+                        //only plain strings, sans escapes, are supported; templated strings are left to rustc
+                        let inner = literal_kind.into_inner().next()?;
+                        if inner.as_rule() != Rule::inner || inner.as_str().contains('\\') {
+                            return None;
+                        }
+                        Some(PaxelExpression::String(inner.as_str().to_string()))
+                    },
+                    _ => None,
+                }
+            },
+            Rule::xo_symbol => {
+                let (symbol, _) = symbolic_id_from_pair(&primary);
+                let symbol = symbol.trim();
+                match symbol {
+                    "true" => return Some(PaxelExpression::Bool(true)),
+                    "false" => return Some(PaxelExpression::Bool(false)),
+                    _ => {},
+                }
+                let identifier = symbol.strip_prefix("self.").or_else(|| symbol.strip_prefix("this.")).unwrap_or(symbol);
+                if identifier.contains(|c: char| c == '.' || c == '[' || c == '$') {
+                    return None;
+                }
+                let invocation = self.invocations.iter().find(|invocation| invocation.root_identifier == identifier)?;
+                let kind = if invocation.property_flags.is_binding_repeat_i {
+                    SymbolKind::RepeatIndex
+                } else if invocation.property_flags.is_binding_repeat_elem {
+                    return None;
+                } else {
+                    SymbolKind::Property(identifier.to_string())
+                };
+                Some(PaxelExpression::Symbol(SymbolBinding {
+                    stack_offset: invocation.stack_offset,
+                    kind,
+                }))
+            },
+            Rule::expression_body => self.build(primary.into_inner())?.complete(),
+            _ => None,
+        }
+    }
+}
+
+fn build_number(literal_number: Pair<Rule>) -> Option<PaxelExpression> {
+    let number = literal_number.into_inner().next().unwrap();
+    match number.as_rule() {
+        Rule::literal_number_float => Some(PaxelExpression::Float(number.as_str().trim().parse().ok()?)),
+        _ => Some(PaxelExpression::Integer(number.as_str().trim().parse().ok()?)),
+    }
+}
+
+fn size_unit(unit: &str) -> SizeUnit {
+    if unit == "px" {
+        SizeUnit::Pixels
+    } else {
+        SizeUnit::Percent
+    }
+}
+
+/// Delivers `HotReloadPatch`es to running apps as Server-Sent Events, on a local port that the chassis is built with
+/// (see `HOT_RELOAD_PORT_ENV_VAR`.)  Each event's data is a patch as JSON.
+pub struct HotReloadServer {
+    port: u16,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl HotReloadServer {
+    pub fn start() -> Self {
+        let port = portpicker::pick_unused_port().expect("No ports free for hot reloading");
+        let listener = TcpListener::bind(("127.0.0.1", port)).expect("Failed to listen for hot reloading");
+        let clients = Arc::new(Mutex::new(vec![]));
+
+        let accepted_clients = Arc::clone(&clients);
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+                if let Some(stream) = accept_event_stream(stream) {
+                    accepted_clients.lock().unwrap().push(stream);
+                }
+            }
+        });
+
+        Self {
+            port,
+            clients,
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Sends `patch` to each connected app, returning whether any received it
+    pub fn send(&self, patch: &HotReloadPatch) -> bool {
+        let event = format!("data: {}\n\n", serde_json::to_string(patch).unwrap());
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain_mut(|client| client.write_all(event.as_bytes()).and_then(|_| client.flush()).is_ok());
+        !clients.is_empty()
+    }
+
+    /// Forgets connected apps, e.g. when the app is restarted
+    pub fn disconnect_all(&self) {
+        self.clients.lock().unwrap().clear();
+    }
+}
+
+/// Reads the request headers of a new connection and responds with the headers of an event stream
+fn accept_event_stream(stream: TcpStream) -> Option<TcpStream> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut stream = stream;
+    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n").ok()?;
+    stream.flush().ok()?;
+    Some(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use pax_message::hot_reload::{BinaryOperator, PaxelExpression, SizeUnit, SymbolBinding, SymbolKind};
+
+    use crate::expressions::compile_all_expressions;
+    use crate::hot_reload::{build_paxel_expression, HotReloadSession};
+    use crate::manifest::{get_primitive_type_table, ExpressionSpecInvocation, PaxManifest, PropertyDefinition, PropertyDefinitionFlags, TypeDefinition};
+    use crate::parsing::{assemble_component_definition, assemble_primitive_definition, ParsingContext};
+
+    fn invocation(root_identifier: &str, stack_offset: usize, property_flags: PropertyDefinitionFlags) -> ExpressionSpecInvocation {
+        ExpressionSpecInvocation {
+            root_identifier: root_identifier.to_string(),
+            escaped_identifier: root_identifier.to_string(),
+            stack_offset,
+            properties_coproduct_type: "Example".to_string(),
+            iterable_type_id_escaped: String::new(),
+            is_numeric: true,
+            is_primitive_nonnumeric: false,
+            property_flags,
+            nested_symbol_tail_literal: String::new(),
+            is_nested_numeric: false,
+        }
+    }
+
+    fn int(value: isize) -> Box<PaxelExpression> {
+        Box::new(PaxelExpression::Integer(value))
+    }
+
+    #[test]
+    fn test_build_paxel_expression() {
+        let invocations = vec![
+            invocation("count", 0, PropertyDefinitionFlags::default()),
+            invocation("i", 1, PropertyDefinitionFlags { is_binding_repeat_i: true, ..Default::default() }),
+            invocation("elem", 1, PropertyDefinitionFlags { is_binding_repeat_elem: true, ..Default::default() }),
+        ];
+        let count = Box::new(PaxelExpression::Symbol(SymbolBinding { stack_offset: 0, kind: SymbolKind::Property("count".to_string()) }));
+        let i = Box::new(PaxelExpression::Symbol(SymbolBinding { stack_offset: 1, kind: SymbolKind::RepeatIndex }));
+
+        let cases : Vec<(&str, Option<PaxelExpression>)> = vec![
+            //literals, with and without units
+            ("42", Some(PaxelExpression::Integer(42))),
+            ("2.5", Some(PaxelExpression::Float(2.5))),
+            ("\"hello\"", Some(PaxelExpression::String("hello".to_string()))),
+            ("true", Some(PaxelExpression::Bool(true))),
+            ("10px", Some(PaxelExpression::Size(int(10), SizeUnit::Pixels))),
+            ("50%", Some(PaxelExpression::Size(int(50), SizeUnit::Percent))),
+            ("(count * 2)px", Some(PaxelExpression::Size(Box::new(PaxelExpression::Binary(count.clone(), BinaryOperator::Mul, int(2))), SizeUnit::Pixels))),
+            //operators, by precedence
+            ("1 + 2 * 3", Some(PaxelExpression::Binary(int(1), BinaryOperator::Add, Box::new(PaxelExpression::Binary(int(2), BinaryOperator::Mul, int(3)))))),
+            ("-1", Some(PaxelExpression::Negate(int(1)))),
+            ("!true", Some(PaxelExpression::Not(Box::new(PaxelExpression::Bool(true))))),
+            ("i > 1 ? 1 : 2", Some(PaxelExpression::Conditional(Box::new(PaxelExpression::Binary(i.clone(), BinaryOperator::Gt, int(1))), int(1), int(2)))),
+            ("Transform2D::rotate(45)", Some(PaxelExpression::FunctionCall("Transform2D::rotate".to_string(), vec![PaxelExpression::Integer(45)]))),
+            //symbols, resolved per their invocations
+            ("self.count + i", Some(PaxelExpression::Binary(count.clone(), BinaryOperator::Add, i.clone()))),
+            ("this.count", Some(*count.clone())),
+            //outside of the interpretable subset, so the app is rebuilt instead
+            ("elem", None),
+            ("self.unknown", None),
+            ("self.count.nested", None),
+            ("$bounds", None),
+            ("0..5", None),
+            ("Color::rgb(1, 0, 0)", None),
+            ("\"escaped\\n\"", None),
+            ("{x: 1}", None),
+            ("StackerDirection::Vertical", None),
+        ];
+        for (paxel, expected) in cases {
+            assert_eq!(build_paxel_expression(paxel, &invocations), expected, "{}", paxel);
+        }
+    }
+
+    /// A manifest with compiled expressions, whose main component `Example` declares `pax`.  `Example` has the properties
+    /// `count: usize` and `total: u128`, and its template may use `Text` (a primitive, with `text: String`) and `Card`
+    /// (a component, with `count: usize`), or `Example` itself.
+    fn compile_manifest(pax: &str) -> PaxManifest {
+        let mut type_table = get_primitive_type_table();
+        for (type_id, property_definitions) in [
+            ("crate::Example", vec![PropertyDefinition::primitive_with_name("usize", "count"), PropertyDefinition::primitive_with_name("u128", "total")]),
+            ("crate::Card", vec![PropertyDefinition::primitive_with_name("usize", "count")]),
+            ("pax_std::primitives::Text", vec![PropertyDefinition::primitive_with_name("String", "text")]),
+        ] {
+            type_table.insert(type_id.to_string(), TypeDefinition { property_definitions, ..TypeDefinition::primitive(type_id) });
+        }
+
+        let template_map : HashMap<String, String> = [("Text", "pax_std::primitives::Text"), ("Card", "crate::Card"), ("Example", "crate::Example")].into_iter()
+            .map(|(pascal_identifier, type_id)| (pascal_identifier.to_string(), type_id.to_string()))
+            .collect();
+        let (ctx, example) = assemble_component_definition(ParsingContext::default(), pax, "Example", true, template_map.clone(), "crate", "crate::Example", Some("src/lib.rs (#[inlined])"));
+        assert!(ctx.diagnostics.is_empty(), "{}: {:?}", pax, ctx.diagnostics.iter().map(|diagnostic| &diagnostic.message).collect::<Vec<_>>());
+        let (_, card) = assemble_component_definition(ParsingContext::default(), "<Text text=\"card\" />", "Card", false, template_map, "crate", "crate::Card", Some("src/card.pax"));
+        let text = assemble_primitive_definition("Text", "pax_std::primitives", "pax_std_primitives::text::TextInstance".to_string(), "pax_std::primitives::Text");
+
+        let mut manifest = PaxManifest {
+            components: [example, card, text].into_iter().map(|cd| (cd.type_id.clone(), cd)).collect(),
+            main_component_type_id: "crate::Example".to_string(),
+            expression_specs: None,
+            type_table,
+            import_paths: HashSet::new(),
+        };
+        compile_all_expressions(&mut manifest).unwrap();
+        manifest
+    }
+
+    #[test]
+    fn test_create_patch() {
+        let running_pax = "<Text text=\"hello\" width=10px @click=self.handle_click />\n<Card count={self.count + 1} />";
+        let mut session = HotReloadSession::new(compile_manifest(running_pax));
+
+        //unchanged templates need no patching
+        let patch = session.create_patch(&compile_manifest(running_pax)).unwrap();
+        assert!(patch.templates.is_empty() && patch.expressions.is_empty());
+
+        //literals are interpreted, and expressions compiled into the running app are reused
+        let patch = session.create_patch(&compile_manifest("<Text id=greeting text=\"goodbye\" width=20px @click=self.handle_click />\n<Card count={self.count + 1} />")).unwrap();
+        assert_eq!(patch.templates.len(), 1);
+        assert_eq!(patch.templates[0].component_type_id, "crate::Example");
+        let root_nodes = &patch.templates[0].root_nodes;
+        assert_eq!(root_nodes.len(), 2);
+        assert_eq!(root_nodes[0].template_id.as_deref(), Some("greeting"));
+        assert_eq!(root_nodes[0].events, vec![("click".to_string(), "handle_click".to_string())]);
+        let interpreted : HashMap<usize, (&str, &PaxelExpression)> = patch.expressions.iter()
+            .map(|expression_patch| (expression_patch.id, (expression_patch.return_type.as_str(), &expression_patch.expression)))
+            .collect();
+        assert_eq!(interpreted.len(), 2);
+        assert_eq!(interpreted[&root_nodes[0].properties["text"]], ("String", &PaxelExpression::String("goodbye".to_string())));
+        assert_eq!(interpreted[&root_nodes[0].properties["width"]], ("Size", &PaxelExpression::Size(Box::new(PaxelExpression::Integer(20)), SizeUnit::Pixels)));
+        assert!(!interpreted.contains_key(&root_nodes[1].properties["count"]));
+
+        //new expressions are interpreted, with ids past those of the running app and of previous patches
+        let previous_ids : Vec<usize> = interpreted.keys().copied().collect();
+        let patch = session.create_patch(&compile_manifest("<Card count={self.count * 2} />")).unwrap();
+        assert_eq!(patch.expressions.len(), 1);
+        assert!(previous_ids.iter().all(|id| patch.expressions[0].id > *id));
+        assert_eq!(patch.templates[0].root_nodes[0].properties["count"], patch.expressions[0].id);
+
+        //anything not compiled into the cartridge forces a rebuild, leaving the session as it was
+        for pax in [
+            "if self.count > 0 { <Text /> }",
+            "for i in 0..3 { <Text /> }",
+            "<Card count={self.total} />",
+            "<Text @click=self.other_handler />",
+            "<Example />",
+        ] {
+            assert!(session.create_patch(&compile_manifest(pax)).is_none(), "{}", pax);
+        }
+        let patch = session.create_patch(&compile_manifest("<Card count={self.count * 2} />")).unwrap();
+        assert!(patch.templates.is_empty());
+    }
+}
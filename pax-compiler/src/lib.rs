@@ -4,6 +4,12 @@ pub mod manifest;
 pub mod templating;
 pub mod parsing;
 pub mod expressions;
+pub mod diagnostics;
+pub mod source_map;
+pub mod typing;
+pub mod formatting;
+pub mod watch;
+pub mod hot_reload;
 
 use manifest::PaxManifest;
 use rust_format::{Config, Formatter};
@@ -14,7 +20,7 @@ use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::str::FromStr;
 use std::collections::{HashMap, HashSet};
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
 use itertools::Itertools;
 
 use std::os::unix::fs::PermissionsExt;
@@ -22,13 +28,26 @@ use std::os::unix::fs::PermissionsExt;
 use include_dir::{Dir, DirEntry, include_dir};
 use toml_edit::{Item};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Child, ChildStderr, Command, Output};
+use std::os::unix::process::CommandExt;
 use crate::manifest::{ValueDefinition, ComponentDefinition, EventDefinition, ExpressionSpec, TemplateNodeDefinition, TypeTable, LiteralBlockDefinition, TypeDefinition};
-use crate::templating::{press_template_codegen_cartridge_component_factory, press_template_codegen_cartridge_render_node_literal, TemplateArgsCodegenCartridgeComponentFactory, TemplateArgsCodegenCartridgeRenderNodeLiteral};
+use crate::templating::{press_template_codegen_cartridge_component_factory, press_template_codegen_cartridge_render_node_literal, HotReloadEventBinding, HotReloadNodeType, TemplateArgsCodegenCartridgeComponentFactory, TemplateArgsCodegenCartridgeRenderNodeLiteral};
 
 //relative to pax_dir
 pub const REEXPORTS_PARTIAL_RS_PATH: &str = "reexports.partial.rs";
 
+//relative to pax_dir
+pub const CARTRIDGE_LIB_RS_PATH: &str = "cartridge/src/lib.rs";
+
+//relative to pax_dir; sidecar to CARTRIDGE_LIB_RS_PATH
+pub const CARTRIDGE_SOURCE_MAP_PATH: &str = "cartridge/source-map.json";
+
+//relative to pax_dir
+pub const CHASSIS_HEADLESS_BINARY_PATH: &str = "chassis/Headless/target/debug/pax-chassis-headless";
+
+//relative to the host crate; golden-image snapshot specs and images, run by `pax test`
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
 
 /// Returns a sorted and de-duped list of combined_reexports.
 fn generate_reexports_partial_rs(pax_dir: &PathBuf, manifest: &PaxManifest) {
@@ -274,6 +293,21 @@ fn generate_properties_coproduct(pax_dir: &PathBuf, manifest: &PaxManifest, host
     properties_coproduct_tuples.extend(set.into_iter());
     properties_coproduct_tuples.sort();
 
+    let types_coproduct_tuples = generate_types_coproduct_tuples(manifest, host_crate_info);
+
+    //press template into String
+    let generated_lib_rs = templating::press_template_codegen_properties_coproduct_lib(templating::TemplateArgsCodegenPropertiesCoproductLib {
+        properties_coproduct_tuples,
+        types_coproduct_tuples,
+    });
+
+    //write String to file
+    fs::write(target_dir.join("src/lib.rs"), generated_lib_rs).unwrap();
+
+}
+
+/// (Variant, fully qualified type) of each TypesCoproduct variant, i.e. each possible return type for Expressions
+fn generate_types_coproduct_tuples(manifest: &PaxManifest, host_crate_info: &HostCrateInfo) -> Vec<(String, String)> {
     //build tuples for TypesCoproduct
     // - include all Property types, representing all possible return types for Expressions
     // - include all T such that T is the iterator type for some Property<Vec<T>>
@@ -299,7 +333,6 @@ fn generate_properties_coproduct(pax_dir: &PathBuf, manifest: &PaxManifest, host
         ("String", "String"),
         ("stdCOCOvecCOCOVecLABRstdCOCOrcCOCORcLABRPropertiesCoproductRABRRABR", "std::vec::Vec<std::rc::Rc<PropertiesCoproduct>>"),
         ("Transform2D", "pax_runtime_api::Transform2D"),
-        ("stdCOCOopsCOCORangeLABRisizeRABR", "std::ops::Range<isize>"),
         ("Size2D", "pax_runtime_api::Size2D"),
         ("Size", "pax_runtime_api::Size"),
         ("SizePixels", "pax_runtime_api::SizePixels"),
@@ -310,17 +343,47 @@ fn generate_properties_coproduct(pax_dir: &PathBuf, manifest: &PaxManifest, host
     types_coproduct_tuples.extend(set.into_iter());
     types_coproduct_tuples.sort();
 
-    types_coproduct_tuples = types_coproduct_tuples.into_iter().unique_by(|elem|{elem.0.to_string()}).collect::<Vec<(String, String)>>();
+    types_coproduct_tuples.into_iter().unique_by(|elem|{elem.0.to_string()}).collect::<Vec<(String, String)>>()
+}
 
-    //press template into String
-    let generated_lib_rs = templating::press_template_codegen_properties_coproduct_lib(templating::TemplateArgsCodegenPropertiesCoproductLib {
-        properties_coproduct_tuples,
-        types_coproduct_tuples,
-    });
+fn generate_hot_reload_node_types(manifest: &PaxManifest) -> Vec<HotReloadNodeType> {
+    let mut node_types : Vec<HotReloadNodeType> = manifest.components.values().filter(|cd|{hot_reload::is_hot_reloadable_node_type(cd)}).map(|cd|{
+        HotReloadNodeType {
+            type_id: cd.type_id.clone(),
+            is_primitive: cd.is_primitive,
+            snake_case_type_id: cd.get_snake_case_id(),
+            primitive_instance_import_path: cd.primitive_instance_import_path.clone(),
+            properties_coproduct_variant: cd.type_id_escaped.to_string(),
+            component_properties_struct: cd.pascal_identifier.to_string(),
+            properties: cd.get_property_definitions(&manifest.type_table).iter().map(|pd|{pd.name.clone()}).collect(),
+        }
+    }).collect();
+    node_types.sort_by(|a, b| a.type_id.cmp(&b.type_id));
+    node_types
+}
 
-    //write String to file
-    fs::write(target_dir.join("src/lib.rs"), generated_lib_rs).unwrap();
+fn generate_hot_reload_event_bindings(manifest: &PaxManifest) -> Vec<HotReloadEventBinding> {
+    hot_reload::collect_event_bindings(manifest).into_iter().map(|(component_type_id, event, method)|{
+        let cd = &manifest.components[&component_type_id];
+        HotReloadEventBinding {
+            properties_coproduct_variant: cd.type_id_escaped.to_string(),
+            component_properties_struct: cd.pascal_identifier.to_string(),
+            component_type_id,
+            event,
+            method,
+        }
+    }).collect()
+}
 
+fn generate_hot_reload_property_readers(manifest: &PaxManifest) -> Vec<(String, Vec<String>)> {
+    let mut readers : Vec<(String, Vec<String>)> = manifest.components.values().filter(|cd|{!cd.is_primitive && !cd.is_struct_only_component}).map(|cd|{
+        let properties = cd.get_property_definitions(&manifest.type_table).iter().filter(|pd|{
+            hot_reload::is_interpretable_type(&pd.type_id)
+        }).map(|pd|{pd.name.clone()}).collect();
+        (cd.type_id_escaped.to_string(), properties)
+    }).collect();
+    readers.sort();
+    readers
 }
 
 fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host_crate_info: &HostCrateInfo) {
@@ -342,7 +405,7 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
     fs::write(&target_cargo_full_path, &target_cargo_toml_contents.to_string()).unwrap();
 
 
-    const IMPORTS_BUILTINS : [&str; 27] = [
+    const IMPORTS_BUILTINS : [&str; 35] = [
         "std::cell::RefCell",
         "std::collections::HashMap",
         "std::collections::VecDeque",
@@ -358,6 +421,8 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
         "pax_core::RenderNodePtrList",
         "pax_core::RenderTreeContext",
         "pax_core::ExpressionContext",
+        "pax_core::ExpressionVTable",
+        "pax_core::ExpressionInput",
         "pax_core::PaxEngine",
         "pax_core::RenderNode",
         "pax_core::InstanceRegistry",
@@ -365,10 +430,16 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
         "pax_core::InstantiationArgs",
         "pax_core::ConditionalInstance",
         "pax_core::SlotInstance",
+        "pax_core::MatchInstance",
         "pax_core::StackFrame",
         "pax_core::pax_properties_coproduct::PropertiesCoproduct",
         "pax_core::pax_properties_coproduct::TypesCoproduct",
         "pax_core::repeat::RepeatInstance",
+        "pax_core::HotReloadCartridge",
+        "pax_core::DynamicValue",
+        "pax_core::hot_reload_property",
+        "pax_core::hot_reload_transform",
+        "pax_core::pax_message::hot_reload::TemplateNodePatch",
         "piet_common::RenderContext",
     ];
 
@@ -413,17 +484,8 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
     //   If `const` is bit off for this work, must first populate symbols via pax_const => PaxManifest
     //     -- must also choose scoping rules; probably just component-level scoping for now
     //
-    // Throw errors when symbols in expressions cannot be resolved; ensure path forward to developer-friendly error messages
-    //     For reference, Rust's message is:
-    //  error[E0425]: cannot find value `not_defined` in this scope
-    //         --> pax-compiler/src/main.rs:404:13
-    //          |
-    //      404 |     let y = not_defined + 6;
-    //          |             ^^^^^^^^^^^ not found in this scope
-    //     Python uses:
-    // NameError: name 'z' is not defined
-    //     JavaScript uses:
-    // Uncaught ReferenceError: not_defined is not defined
+    // Errors when symbols in expressions cannot be resolved are reported during `compile_all_expressions`;
+    //     see `diagnostics::Diagnostic` for their rustc-style rendering
 
     let mut expression_specs : Vec<ExpressionSpec> = manifest.expression_specs.as_ref().unwrap().values().map(|es: &ExpressionSpec|{es.clone()}).collect();
     expression_specs = expression_specs.iter().sorted().cloned().collect();
@@ -438,6 +500,12 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
         consts,
         expression_specs,
         component_factories_literal,
+        hot_reload_node_types: generate_hot_reload_node_types(manifest),
+        hot_reload_event_bindings: generate_hot_reload_event_bindings(manifest),
+        hot_reload_property_readers: generate_hot_reload_property_readers(manifest),
+        hot_reload_coercions: generate_types_coproduct_tuples(manifest, host_crate_info).into_iter().filter(|(_, type_path)|{
+            hot_reload::is_interpretable_type(type_path)
+        }).collect(),
     });
 
 
@@ -454,8 +522,37 @@ fn generate_cartridge_definition(pax_dir: &PathBuf, manifest: &PaxManifest, host
         //}
     };
 
+    //write sidecar source map, so that errors in the generated cartridge can be traced back to Pax
+    let source_map = SourceMap::from_generated_code(CARTRIDGE_LIB_RS_PATH, &formatted, &generate_source_locations(manifest));
+    source_map.save(&pax_dir.join(CARTRIDGE_SOURCE_MAP_PATH));
+
     //write String to file
-    fs::write(target_dir.join("src/lib.rs"), formatted).unwrap();
+    fs::write(pax_dir.join(CARTRIDGE_LIB_RS_PATH), formatted).unwrap();
+}
+
+/// Collects the origin of each source-mapped region of the cartridge, keyed as in the
+/// `@pax-source-map` markers emitted by the cartridge templates
+fn generate_source_locations(manifest: &PaxManifest) -> HashMap<String, SourceLocation> {
+    let mut ret = HashMap::new();
+
+    manifest.expression_specs.as_ref().unwrap().values().for_each(|es| {
+        ret.insert(format!("expression {}", es.id), es.source_location.clone());
+    });
+
+    manifest.components.values().for_each(|cd| {
+        if let Some(template) = &cd.template {
+            template.iter().for_each(|tnd| {
+                ret.insert(format!("node {} {}", escape_identifier(cd.type_id.clone()), tnd.id), SourceLocation {
+                    component_type_id: cd.type_id.clone(),
+                    pax_source_path: cd.pax_source_path.clone(),
+                    template_node_id: tnd.id,
+                    span: tnd.span.clone(),
+                });
+            });
+        }
+    });
+
+    ret
 }
 
 
@@ -496,8 +593,8 @@ fn recurse_literal_block(block: LiteralBlockDefinition, type_definition: &TypeDe
     for (key, value_definition) in block.settings_key_value_pairs.iter() {
         let value_string = match value_definition {
             ValueDefinition::LiteralValue(value) => format!("ret.{} = Box::new(PropertyLiteral::new({}));", key, value),
-            ValueDefinition::Expression(_, id) |
-            ValueDefinition::Identifier(_, id) => {
+            ValueDefinition::Expression(_, id, _) |
+            ValueDefinition::Identifier(_, id, _) => {
                 format!("ret.{} = Box::new(PropertyExpression::new({}));", key, id.expect("Tried to use expression but it wasn't compiled"))
             },
             ValueDefinition::Block(inner_block) => format!("ret.{} = Box::new(PropertyLiteral::new({}));", key, recurse_literal_block(inner_block.clone(), type_definition, host_crate_info)),
@@ -531,12 +628,11 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
         let rsd = tnd.control_flow_settings.as_ref().unwrap().repeat_source_definition.as_ref().unwrap();
         let id = rsd.vtable_id.unwrap();
 
-        let rse_vec = if let Some(_) = &rsd.symbolic_binding {
-            format!("Some(Box::new(PropertyExpression::new({})))", id)
-        } else {"None".into()};
+        //Both symbolic and range sources evaluate to a Vec<Rc<PropertiesCoproduct>>
+        let rse_vec = format!("Some(Box::new(PropertyExpression::new({})))", id);
 
-        let rse_range = if let Some(_) = &rsd.range_expression_paxel {
-            format!("Some(Box::new(PropertyExpression::new({})))", id)
+        let rke = if let Some(key_id) = &tnd.control_flow_settings.as_ref().unwrap().repeat_key_expression_vtable_id {
+            format!("Some(Box::new(PropertyExpression::new({})))", key_id)
         } else {"None".into()};
 
         TemplateArgsCodegenCartridgeRenderNodeLiteral {
@@ -551,15 +647,31 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
             children_literal,
             slot_index_literal: "None".to_string(),
             conditional_boolean_expression_literal: "None".to_string(),
+            conditional_false_branch_children_literal: vec![],
+            match_expression_literal: "None".to_string(),
+            match_arms_literal: vec![],
             pascal_identifier: rngc.active_component_definition.pascal_identifier.to_string(),
             type_id_escaped: escape_identifier(rngc.active_component_definition.type_id.to_string()),
             events,
+            template_node_id: tnd.id,
+            template_id: None,
+            is_focusable: false,
             repeat_source_expression_literal_vec: rse_vec,
-            repeat_source_expression_literal_range: rse_range,
+            repeat_key_expression_literal: rke,
         }
     } else if tnd.type_id == parsing::TYPE_ID_IF {
         // If
-        let id = tnd.control_flow_settings.as_ref().unwrap().condition_expression_vtable_id.unwrap();
+        let cfa = tnd.control_flow_settings.as_ref().unwrap();
+        let id = cfa.condition_expression_vtable_id.unwrap();
+
+        //`else` branch, if present.  An `else if` arrives here as a single nested `if` node
+        let false_branch_children_literal : Vec<String> = match &cfa.else_child_ids {
+            Some(else_child_ids) => else_child_ids.iter().map(|child_id|{
+                let active_tnd = &rngc.active_component_definition.template.as_ref().unwrap()[*child_id];
+                recurse_generate_render_nodes_literal(rngc, active_tnd,  host_crate_info)
+            }).collect(),
+            None => vec![],
+        };
 
         TemplateArgsCodegenCartridgeRenderNodeLiteral {
             is_primitive: true,
@@ -573,11 +685,17 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
             children_literal,
             slot_index_literal: "None".to_string(),
             repeat_source_expression_literal_vec:  "None".to_string(),
-            repeat_source_expression_literal_range:  "None".to_string(),
+            repeat_key_expression_literal: "None".to_string(),
             conditional_boolean_expression_literal: format!("Some(Box::new(PropertyExpression::new({})))", id),
+            conditional_false_branch_children_literal: false_branch_children_literal,
+            match_expression_literal: "None".to_string(),
+            match_arms_literal: vec![],
             pascal_identifier: rngc.active_component_definition.pascal_identifier.to_string(),
             type_id_escaped: escape_identifier(rngc.active_component_definition.type_id.to_string()),
             events,
+            template_node_id: tnd.id,
+            template_id: None,
+            is_focusable: false,
         }
     } else if tnd.type_id == parsing::TYPE_ID_SLOT {
         // Slot
@@ -595,11 +713,54 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
             children_literal,
             slot_index_literal: format!("Some(Box::new(PropertyExpression::new({})))", id),
             repeat_source_expression_literal_vec:  "None".to_string(),
-            repeat_source_expression_literal_range:  "None".to_string(),
+            repeat_key_expression_literal: "None".to_string(),
+            conditional_boolean_expression_literal: "None".to_string(),
+            conditional_false_branch_children_literal: vec![],
+            match_expression_literal: "None".to_string(),
+            match_arms_literal: vec![],
+            pascal_identifier: rngc.active_component_definition.pascal_identifier.to_string(),
+            type_id_escaped: escape_identifier(rngc.active_component_definition.type_id.to_string()),
+            events,
+            template_node_id: tnd.id,
+            template_id: None,
+            is_focusable: false,
+        }
+    } else if tnd.type_id == parsing::TYPE_ID_MATCH {
+        // Match
+        let cfa = tnd.control_flow_settings.as_ref().unwrap();
+        let id = cfa.match_expression_vtable_id.unwrap();
+
+        //one list of children literals per arm, in declaration order
+        let match_arms_literal : Vec<Vec<String>> = cfa.match_arm_definitions.as_ref().unwrap().iter().map(|arm| {
+            arm.child_ids.iter().map(|child_id|{
+                let active_tnd = &rngc.active_component_definition.template.as_ref().unwrap()[*child_id];
+                recurse_generate_render_nodes_literal(rngc, active_tnd,  host_crate_info)
+            }).collect()
+        }).collect();
+
+        TemplateArgsCodegenCartridgeRenderNodeLiteral {
+            is_primitive: true,
+            snake_case_type_id: "UNREACHABLE".into(),
+            primitive_instance_import_path: Some("MatchInstance".into()),
+            properties_coproduct_variant: "None".to_string(),
+            component_properties_struct: "None".to_string(),
+            properties: vec![],
+            transform_ril: DEFAULT_PROPERTY_LITERAL.to_string(),
+            size_ril: [DEFAULT_PROPERTY_LITERAL.to_string(), DEFAULT_PROPERTY_LITERAL.to_string()],
+            children_literal,
+            slot_index_literal: "None".to_string(),
+            repeat_source_expression_literal_vec:  "None".to_string(),
+            repeat_key_expression_literal: "None".to_string(),
             conditional_boolean_expression_literal: "None".to_string(),
+            conditional_false_branch_children_literal: vec![],
+            match_expression_literal: format!("Some(Box::new(PropertyExpression::new({})))", id),
+            match_arms_literal,
             pascal_identifier: rngc.active_component_definition.pascal_identifier.to_string(),
             type_id_escaped: escape_identifier(rngc.active_component_definition.type_id.to_string()),
             events,
+            template_node_id: tnd.id,
+            template_id: None,
+            is_focusable: false,
         }
     } else {
         //Handle anything that's not a built-in
@@ -624,8 +785,8 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
                             ValueDefinition::LiteralValue(lv) => {
                                 format!("PropertyLiteral::new({})", lv)
                             },
-                            ValueDefinition::Expression(_, id) |
-                            ValueDefinition::Identifier(_, id) => {
+                            ValueDefinition::Expression(_, id, _) |
+                            ValueDefinition::Identifier(_, id, _) => {
                                 format!("PropertyExpression::new({})", id.expect("Tried to use expression but it wasn't compiled"))
                             },
                             ValueDefinition::Block(block) => {
@@ -656,8 +817,8 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
                         ValueDefinition::LiteralValue(lv) => {
                             format!("PropertyLiteral::new({})", lv)
                         },
-                        ValueDefinition::Expression(_, id) |
-                        ValueDefinition::Identifier(_, id) => {
+                        ValueDefinition::Expression(_, id, _) |
+                        ValueDefinition::Identifier(_, id, _) => {
                             format!("PropertyExpression::new({})", id.expect("Tried to use expression but it wasn't compiled"))
                         },
                         _ => {
@@ -686,17 +847,38 @@ fn recurse_generate_render_nodes_literal(rngc: &RenderNodesGenerationContext, tn
             children_literal,
             slot_index_literal: "None".to_string(),
             repeat_source_expression_literal_vec: "None".to_string(),
-            repeat_source_expression_literal_range:  "None".to_string(),
+            repeat_key_expression_literal: "None".to_string(),
             conditional_boolean_expression_literal: "None".to_string(),
+            conditional_false_branch_children_literal: vec![],
+            match_expression_literal: "None".to_string(),
+            match_arms_literal: vec![],
             pascal_identifier: rngc.active_component_definition.pascal_identifier.to_string(),
             type_id_escaped: escape_identifier(rngc.active_component_definition.type_id.to_string()),
             events,
+            template_node_id: tnd.id,
+            template_id: get_template_id(tnd),
+            is_focusable: is_focusable(tnd),
         }
     };
 
     press_template_codegen_cartridge_render_node_literal(args)
 }
 
+/// The `id` given to a template node in Pax, e.g. `sidebar` for `<Rectangle id=sidebar />`
+fn get_template_id(tnd: &TemplateNodeDefinition) -> Option<String> {
+    tnd.settings.as_ref()?.iter().find_map(|(key, value)| match value {
+        ValueDefinition::Identifier(identifier, _, _) if key == "id" => Some(identifier.clone()),
+        _ => None,
+    })
+}
+
+/// Whether a template node is given `focusable=true` in Pax, i.e. is visited by Tab and focused by clicks
+fn is_focusable(tnd: &TemplateNodeDefinition) -> bool {
+    tnd.settings.as_ref().map_or(false, |settings| settings.iter().any(|(key, value)| {
+        key == "focusable" && matches!(value, ValueDefinition::LiteralValue(literal) if literal == "true")
+    }))
+}
+
 struct RenderNodesGenerationContext<'a> {
     components: &'a std::collections::HashMap<String, ComponentDefinition>,
     active_component_definition: &'a ComponentDefinition,
@@ -727,6 +909,7 @@ fn generate_cartridge_component_factory_literal(manifest: &PaxManifest, cd: &Com
 
     let args = TemplateArgsCodegenCartridgeComponentFactory {
         is_main_component: cd.is_main_component,
+        type_id: cd.type_id.clone(),
         snake_case_type_id: cd.get_snake_case_id(),
         component_properties_struct: cd.pascal_identifier.to_string(),
         properties: cd.get_property_definitions(&manifest.type_table).iter().map(|pd|{
@@ -857,6 +1040,10 @@ static CHASSIS_MACOS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../pax-chassis
 //      improved with a "production build" of `pax-chassis-web` that gets included into the compiler
 static CHASSIS_WEB_LIBDEV: &str = "../pax-chassis-web";
 static CHASSIS_WEB_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../pax-chassis-web");
+static CHASSIS_HEADLESS_LIBDEV: &str = "../pax-chassis-headless";
+static CHASSIS_HEADLESS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../pax-chassis-headless");
+static CHASSIS_LINUX_LIBDEV: &str = "../pax-chassis-linux";
+static CHASSIS_LINUX_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../pax-chassis-linux");
 /// Clone the relevant chassis (and dev harness) to the local .pax directory
 /// The chassis is the final compiled Rust library (thus the point where `patch`es must occur)
 /// and the encapsulated dev harness is the actual dev executable
@@ -905,6 +1092,24 @@ fn clone_target_chassis_to_dot_pax(relative_chassis_specific_target_dir: &PathBu
             //write +x permission to copied run-debuggable-mac-app
             fs::set_permissions(chassis_specific_dir.join("pax-dev-harness-web").join("run-web.sh"), fs::Permissions::from_mode(0o777)).unwrap();
         }
+        RunTarget::Headless => {
+            if libdevmode {
+                libdev_chassis_copy(&fs::canonicalize(CHASSIS_HEADLESS_LIBDEV).expect("cannot pass --libdev outside of pax monorepo environment."), &chassis_specific_dir);
+            } else {
+                persistent_extract(&CHASSIS_HEADLESS_DIR, &chassis_specific_dir).unwrap();
+            }
+
+            fs::set_permissions(chassis_specific_dir.join("pax-dev-harness-headless").join("run-headless.sh"), fs::Permissions::from_mode(0o777)).unwrap();
+        }
+        RunTarget::Linux => {
+            if libdevmode {
+                libdev_chassis_copy(&fs::canonicalize(CHASSIS_LINUX_LIBDEV).expect("cannot pass --libdev outside of pax monorepo environment."), &chassis_specific_dir);
+            } else {
+                persistent_extract(&CHASSIS_LINUX_DIR, &chassis_specific_dir).unwrap();
+            }
+
+            fs::set_permissions(chassis_specific_dir.join("pax-dev-harness-linux").join("run-linux.sh"), fs::Permissions::from_mode(0o777)).unwrap();
+        }
     }
     Ok(())
 
@@ -987,6 +1192,91 @@ pub fn perform_clean(path: &str) -> Result<(), ()> {
     Ok(())
 }
 
+/// Formats each .pax file, and the contents of each `#[inlined(...)]` attribute in each .rs file, under `path`.
+/// If `should_check`, lists the files that would be reformatted instead of rewriting them, returning Err if there are any.
+pub fn perform_format(path: &str, should_check: bool) -> Result<(), ()> {
+    let mut diagnostics = vec![];
+    let mut unformatted_paths = vec![];
+
+    let walker = WalkDir::new(path).into_iter().filter_entry(|entry| {
+        let file_name = entry.file_name().to_str().unwrap_or("");
+        file_name != ".pax" && file_name != "target"
+    });
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        let file_path = entry.path();
+        let extension = file_path.extension().and_then(|extension| extension.to_str());
+        let source = match extension {
+            Some("pax") | Some("rs") => fs::read_to_string(file_path).unwrap(),
+            _ => continue,
+        };
+        let display_path = file_path.strip_prefix(path).unwrap_or(file_path).to_str().unwrap().to_string();
+
+        let formatted = if extension == Some("pax") {
+            formatting::format_pax(&source).map_err(|diagnostic| diagnostic.with_source(Some(display_path.clone()), Some(&source)))
+        } else if source.contains("#[inlined(") {
+            formatting::format_inlined_pax(&source).map_err(|mut diagnostic| {
+                diagnostic.source_path = Some(format!("{}{}", display_path, manifest::INLINED_SOURCE_SUFFIX));
+                diagnostic
+            })
+        } else {
+            continue;
+        };
+
+        match formatted {
+            Ok(formatted) if formatted != source => {
+                if !should_check {
+                    fs::write(file_path, formatted).unwrap();
+                }
+                unformatted_paths.push(display_path);
+            },
+            Ok(_) => {},
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        report_diagnostics(diagnostics, "format");
+        return Err(());
+    }
+
+    if should_check {
+        for unformatted_path in unformatted_paths.iter() {
+            println!("Would reformat: {}", unformatted_path);
+        }
+        if !unformatted_paths.is_empty() {
+            return Err(());
+        }
+    } else {
+        for unformatted_path in unformatted_paths.iter() {
+            println!("Formatted: {}", unformatted_path);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the feature-flagged parser for the project at `path`, writing cargo's output to `stderr`, and returns the
+/// resulting manifest (sans compiled expressions.)  Errors in Pax are returned as diagnostics, while a failure of the
+/// host crate itself, e.g. a Rust compilation error, is reported to `stderr` and returned as `BuildError::Cargo`.
+pub fn run_parser(path: &str, stderr: &mut dyn Write) -> Result<PaxManifest, BuildError> {
+    let output = run_parser_binary(path);
+    stderr.write_all(output.stderr.as_slice()).unwrap();
+
+    let out = String::from_utf8(output.stdout).unwrap();
+    if output.status.code() != Some(0) {
+        //The parser reports errors in Pax as serialized diagnostics on stdout; anything else is a failure of the host crate itself
+        return Err(match serde_json::from_str::<Vec<Diagnostic>>(&out) {
+            Ok(diagnostics) if !diagnostics.is_empty() => BuildError::Diagnostics(diagnostics),
+            _ => BuildError::Cargo,
+        });
+    }
+
+    let mut manifest : PaxManifest = serde_json::from_str(&out).expect(&format!("Malformed JSON from parser: {}", &out));
+    let host_cargo_toml_path = Path::new(path).join("Cargo.toml");
+    let host_crate_info = get_host_crate_info(&host_cargo_toml_path);
+    update_property_prefixes_in_place(&mut manifest, &host_crate_info);
+    Ok(manifest)
+}
+
 /// Executes a shell command to run the feature-flagged parser at the specified path
 /// Returns an output object containing bytestreams of stdout/stderr as well as an exit code
 pub fn run_parser_binary(path: &str) -> Output {
@@ -1008,67 +1298,269 @@ pub fn run_parser_binary(path: &str) -> Output {
 
 use colored::Colorize;
 use crate::parsing::escape_identifier;
+use crate::diagnostics::{BuildError, Diagnostic};
+use crate::source_map::{SourceLocation, SourceMap};
+use walkdir::WalkDir;
+use crate::watch::{fingerprint_rust_sources, ManifestCache, SourceWatcher, TemplateUpdate};
+use crate::hot_reload::{HotReloadServer, HotReloadSession};
 
 
 /// For the specified file path or current working directory, first compile Pax project,
 /// then run it with a patched build of the `chassis` appropriate for the specified platform
 /// See: pax-compiler-sequence-diagram.png
-pub fn perform_build(ctx: &RunContext) -> Result<(), ()> {
+/// Errors in the provided Pax are printed to stderr and returned together, rather than aborting on the first, as are
+/// failures of cargo.
+pub fn perform_build(ctx: &RunContext) -> Result<(), BuildError> {
 
     #[allow(non_snake_case)]
     let PAX_BADGE = "[Pax]".bold().on_black().white();
 
-    println!("{} 🛠 Running `cargo build`...", &PAX_BADGE);
     let pax_dir = get_or_create_pax_directory(&ctx.path);
 
-    // Run parser bin from host project with `--features parser`
-    let output = run_parser_binary(&ctx.path);
+    let manifest = parse_and_cache_manifest(ctx, &pax_dir).inspect_err(|error| {
+        if let BuildError::Cargo = error {
+            eprintln!("{}", "error: parsing failed — there is likely an error in the host crate".red().bold());
+        }
+    })?;
+    build_chassis_from_manifest(ctx, &pax_dir, manifest, false, None).inspect_err(|error| {
+        if let BuildError::Cargo = error {
+            eprintln!("{}", "error: building the chassis failed".red().bold());
+        }
+    })?;
 
-    // Forward stderr only
-    std::io::stderr().write_all(output.stderr.as_slice()).unwrap();
-    assert_eq!(output.status.code().unwrap(), 0, "Parsing failed — there is likely a syntax error in the provided pax");
+    if ctx.should_also_run {
+        //8a::run: compile and run dev harness, with freshly built chassis plugged in
+        println!("{} 🏃‍ Running fully compiled {} app...", &PAX_BADGE, <&RunTarget as Into<&str>>::into(&ctx.target));
 
-    let out = String::from_utf8(output.stdout).unwrap();
-    let mut manifest : PaxManifest = serde_json::from_str(&out).expect(&format!("Malformed JSON from parser: {}", &out));
-    let host_cargo_toml_path = Path::new(&ctx.path).join("Cargo.toml");
-    let host_crate_info = get_host_crate_info(&host_cargo_toml_path);
-    update_property_prefixes_in_place(&mut manifest, &host_crate_info);
+    } else {
+        //8b::compile: compile and write executable binary / package to disk at specified or implicit path
+        println!("{} 🛠 Building fully compiled {} app...", &PAX_BADGE, <&RunTarget as Into<&str>>::into(&ctx.target));
+    }
+    build_harness_with_chassis(&pax_dir, &ctx, &Harness::Development);
+
+    Ok(())
+}
+
+/// Builds the Pax project for the headless target, then runs the golden-image snapshot tests in its `snapshots`
+/// directory with the built chassis (see `paxchassisheadless::snapshot`.)  If `should_update_snapshots`, each golden
+/// image is rewritten (or created) from the frame rendered for it; otherwise, a missing golden image fails its snapshot.
+/// Returns Err if the build or any snapshot fails.
+pub fn perform_test(path: &str, verbose: bool, libdevmode: bool, should_update_snapshots: bool) -> Result<(), ()> {
+
+    #[allow(non_snake_case)]
+    let PAX_BADGE = "[Pax]".bold().on_black().white();
+
+    let snapshots_dir = Path::new(path).join(SNAPSHOTS_DIR);
+    if !snapshots_dir.is_dir() {
+        println!("{} No snapshots to test; add `<name>.json` snapshot specs to {}", &PAX_BADGE, snapshots_dir.display());
+        return Ok(());
+    }
+
+    let ctx = RunContext {
+        target: RunTarget::Headless,
+        path: path.to_string(),
+        verbose,
+        should_also_run: false,
+        libdevmode,
+    };
+    let pax_dir = get_or_create_pax_directory(&ctx.path);
+    let manifest = parse_and_cache_manifest(&ctx, &pax_dir).map_err(|_| ())?;
+    build_chassis_from_manifest(&ctx, &pax_dir, manifest, false, None).map_err(|_| ())?;
+
+    println!("{} 📸 Testing snapshots in {}...", &PAX_BADGE, snapshots_dir.display());
+    let mut command = Command::new(pax_dir.join(CHASSIS_HEADLESS_BINARY_PATH));
+    //Run from the project root, so that image paths resolve as they do when running the app
+    command.current_dir(path)
+        .arg("--snapshots")
+        .arg(SNAPSHOTS_DIR)
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::piped());
+    if should_update_snapshots {
+        command.arg("--update-snapshots");
+    }
+    let mut test_process = command.spawn().expect("failed to run snapshot tests");
+    forward_stderr_with_source_map(test_process.stderr.take().unwrap(), &pax_dir, true);
+
+    if test_process.wait().expect("failed to run snapshot tests").success() { Ok(()) } else { Err(()) }
+}
+
+/// Builds and runs the Pax project as `perform_build` does, then watches the host crate for changes, rebuilding and
+/// re-running it on each.  When only Pax has changed (i.e. .pax files and `#[inlined(...)]` templates), the templates are
+/// re-parsed into the cached manifest and hot-reloaded into the running app if possible (see `hot_reload::HotReloadSession`),
+/// and otherwise only the cartridge is regenerated, skipping the parser binary.
+/// Errors are reported as they occur; watching continues until the process is interrupted.
+pub fn perform_watch(ctx: &RunContext) {
+
+    #[allow(non_snake_case)]
+    let PAX_BADGE = "[Pax]".bold().on_black().white();
+
+    let pax_dir = get_or_create_pax_directory(&ctx.path);
+    let mut watcher = SourceWatcher::new(&ctx.path);
+    let hot_reload_server = HotReloadServer::start();
+    let hot_reload_port = Some(hot_reload_server.port());
+
+    let mut hot_reload_session = None;
+    let mut harness_process = None;
+    if let Ok(manifest) = parse_and_cache_manifest(ctx, &pax_dir)
+        .and_then(|manifest| build_chassis_from_manifest(ctx, &pax_dir, manifest, false, hot_reload_port)) {
+        hot_reload_session = Some(HotReloadSession::new(manifest));
+        harness_process = Some(spawn_harness_with_chassis(&pax_dir, ctx, &Harness::Development));
+    }
+
+    //changes since the last successful build, so that a fix to one file doesn't skip an earlier, broken edit to another
+    let mut changed_paths : Vec<PathBuf> = vec![];
+    loop {
+        println!("{} 👀 Watching for changes...", &PAX_BADGE);
+        changed_paths.extend(watcher.wait_for_changes());
+        changed_paths.sort();
+        changed_paths.dedup();
+
+        let cache = ManifestCache::load(&pax_dir)
+            .filter(|cache| cache.rust_fingerprint == fingerprint_rust_sources(&ctx.path));
+        let result = match cache {
+            Some(mut cache) => {
+                match watch::update_templates_in_place(&mut cache.manifest, &ctx.path, &changed_paths) {
+                    TemplateUpdate::Unaffected => {
+                        changed_paths.clear();
+                        continue;
+                    },
+                    TemplateUpdate::Updated(diagnostics) if !diagnostics.is_empty() => Err(BuildError::Diagnostics(report_diagnostics(diagnostics, "compile"))),
+                    TemplateUpdate::Updated(_) => {
+                        cache.save(&pax_dir);
+                        match compile_manifest_expressions(cache.manifest) {
+                            Ok(manifest) => {
+                                let patch = hot_reload_session.as_mut().and_then(|session| session.create_patch(&manifest));
+                                if patch.map(|patch| hot_reload_server.send(&patch)).unwrap_or(false) {
+                                    println!("{} 🔥 Pax changed; hot reloaded", &PAX_BADGE);
+                                    changed_paths.clear();
+                                    continue;
+                                }
+                                //the session no longer reflects the running app; it's replaced once the rebuilt app runs
+                                hot_reload_session = None;
+                                println!("{} ♻️ Pax changed; rebuilding cartridge", &PAX_BADGE);
+                                build_chassis_from_compiled_manifest(ctx, &pax_dir, &manifest, true, hot_reload_port).map(|_| manifest)
+                            },
+                            Err(diagnostics) => Err(BuildError::Diagnostics(diagnostics)),
+                        }
+                    },
+                    TemplateUpdate::RequiresFullBuild => {
+                        parse_and_cache_manifest(ctx, &pax_dir)
+                            .and_then(|manifest| build_chassis_from_manifest(ctx, &pax_dir, manifest, false, hot_reload_port))
+                    },
+                }
+            },
+            None => {
+                parse_and_cache_manifest(ctx, &pax_dir)
+                    .and_then(|manifest| build_chassis_from_manifest(ctx, &pax_dir, manifest, false, hot_reload_port))
+            },
+        };
+
+        //the running app is only replaced once its successor has built, so that errors can be fixed against it
+        if let Ok(manifest) = result {
+            changed_paths.clear();
+            if let Some(previous_process) = harness_process.take() {
+                stop_harness(previous_process);
+            }
+            hot_reload_server.disconnect_all();
+            hot_reload_session = Some(HotReloadSession::new(manifest));
+            println!("{} 🏃‍ Running fully compiled {} app...", &PAX_BADGE, <&RunTarget as Into<&str>>::into(&ctx.target));
+            harness_process = Some(spawn_harness_with_chassis(&pax_dir, ctx, &Harness::Development));
+        }
+    }
+}
+
+/// Runs the parser binary from the host project with `--features parser`, forwarding stderr only, and caches the resulting
+/// manifest (sans compiled expressions) for incremental rebuilds.  Errors in Pax are reported to stderr; as with
+/// `run_parser`, cargo reports failures of the host crate itself.
+fn parse_and_cache_manifest(ctx: &RunContext, pax_dir: &PathBuf) -> Result<PaxManifest, BuildError> {
+
+    #[allow(non_snake_case)]
+    let PAX_BADGE = "[Pax]".bold().on_black().white();
+
+    println!("{} 🛠 Running `cargo build`...", &PAX_BADGE);
+    let rust_fingerprint = fingerprint_rust_sources(&ctx.path);
+    let manifest = run_parser(&ctx.path, &mut std::io::stderr()).map_err(|error| match error {
+        BuildError::Diagnostics(diagnostics) => BuildError::Diagnostics(report_diagnostics(diagnostics, "compile")),
+        BuildError::Cargo => BuildError::Cargo,
+    })?;
+
+    ManifestCache { rust_fingerprint, manifest: manifest.clone() }.save(pax_dir);
+    Ok(manifest)
+}
+
+/// Compiles the manifest's expressions, then generates and builds the chassis as `build_chassis_from_compiled_manifest` does.
+/// Returns the manifest with compiled expressions, i.e. as built into the cartridge.
+fn build_chassis_from_manifest(ctx: &RunContext, pax_dir: &PathBuf, manifest: PaxManifest, is_cartridge_only: bool, hot_reload_port: Option<u16>) -> Result<PaxManifest, BuildError> {
+    let manifest = compile_manifest_expressions(manifest)?;
+    build_chassis_from_compiled_manifest(ctx, pax_dir, &manifest, is_cartridge_only, hot_reload_port)?;
+    Ok(manifest)
+}
+
+fn compile_manifest_expressions(mut manifest: PaxManifest) -> Result<PaxManifest, Vec<Diagnostic>> {
+
+    #[allow(non_snake_case)]
+    let PAX_BADGE = "[Pax]".bold().on_black().white();
 
     println!("{} 🧮 Compiling expressions", &PAX_BADGE);
-    expressions::compile_all_expressions(&mut manifest);
+    expressions::compile_all_expressions(&mut manifest).map_err(|diagnostics| report_diagnostics(diagnostics, "compile"))?;
+    Ok(manifest)
+}
+
+/// Generates the cartridge (and, unless `is_cartridge_only`, the rest of the generated Rust, which depends only on the
+/// host crate's Rust), then builds the chassis.  A failure of cargo is reported to stderr, and returned as `BuildError::Cargo`.
+fn build_chassis_from_compiled_manifest(ctx: &RunContext, pax_dir: &PathBuf, manifest: &PaxManifest, is_cartridge_only: bool, hot_reload_port: Option<u16>) -> Result<(), BuildError> {
+
+    #[allow(non_snake_case)]
+    let PAX_BADGE = "[Pax]".bold().on_black().white();
+
+    let host_cargo_toml_path = Path::new(&ctx.path).join("Cargo.toml");
+    let host_crate_info = get_host_crate_info(&host_cargo_toml_path);
 
     println!("{} 🦀 Generating Rust", &PAX_BADGE);
-    generate_reexports_partial_rs(&pax_dir, &manifest);
-    generate_properties_coproduct(&pax_dir, &manifest, &host_crate_info);
-    generate_cartridge_definition(&pax_dir, &manifest, &host_crate_info);
-    generate_chassis(&pax_dir, &ctx.target, &host_crate_info, ctx.libdevmode);
+    if !is_cartridge_only {
+        generate_reexports_partial_rs(pax_dir, manifest);
+        generate_properties_coproduct(pax_dir, manifest, &host_crate_info);
+    }
+    generate_cartridge_definition(pax_dir, manifest, &host_crate_info);
+    if !is_cartridge_only {
+        generate_chassis(pax_dir, &ctx.target, &host_crate_info, ctx.libdevmode);
+    }
 
     //7. Build the appropriate `chassis` from source, with the patched `Cargo.toml`, Properties Coproduct, and Cartridge from above
     println!("{} 🧱 Building cartridge with cargo", &PAX_BADGE);
-    let output = build_chassis_with_cartridge(&pax_dir, &ctx.target);
+    let output = build_chassis_with_cartridge(pax_dir, &ctx.target, hot_reload_port);
     //forward stderr only
     std::io::stderr().write_all(output.stderr.as_slice()).unwrap();
-    assert_eq!(output.status.code().unwrap(), 0);
-
-    if ctx.should_also_run {
-        //8a::run: compile and run dev harness, with freshly built chassis plugged in
-        println!("{} 🏃‍ Running fully compiled {} app...", &PAX_BADGE, <&RunTarget as Into<&str>>::into(&ctx.target));
-
-    } else {
-        //8b::compile: compile and write executable binary / package to disk at specified or implicit path
-        println!("{} 🛠 Building fully compiled {} app...", &PAX_BADGE, <&RunTarget as Into<&str>>::into(&ctx.target));
+    if output.status.code() != Some(0) {
+        return Err(BuildError::Cargo);
     }
-    build_harness_with_chassis(&pax_dir, &ctx, &Harness::Development);
-
     Ok(())
 }
 
+/// Prints diagnostics to stderr, followed by a summary line saying that Pax could not `action`, e.g. "compile" or
+/// "format", and passes them through
+fn report_diagnostics(diagnostics: Vec<Diagnostic>, action: &str) -> Vec<Diagnostic> {
+    for diagnostic in diagnostics.iter() {
+        eprintln!("{}", diagnostic);
+    }
+    let plural = if diagnostics.len() == 1 { "" } else { "s" };
+    eprintln!("{}", format!("error: could not {} Pax due to {} previous error{}", action, diagnostics.len(), plural).red().bold());
+    diagnostics
+}
+
 #[derive(Debug)]
 pub enum Harness {
     Development,
 }
 
 fn build_harness_with_chassis(pax_dir: &PathBuf, ctx: &RunContext, harness: &Harness) {
+    let mut harness_process = spawn_harness_with_chassis(pax_dir, ctx, harness);
+    harness_process.wait().expect("failed to run harness");
+}
+
+/// Starts the harness script for the target, in its own process group so that it can be stopped along with the
+/// processes it starts (see `stop_harness`.)  Its stderr is forwarded through the source map on a separate thread.
+fn spawn_harness_with_chassis(pax_dir: &PathBuf, ctx: &RunContext, harness: &Harness) -> Child {
 
     let target_str : &str = ctx.target.borrow().into();
     let target_str_lower: &str = &target_str.to_lowercase();
@@ -1089,11 +1581,15 @@ fn build_harness_with_chassis(pax_dir: &PathBuf, ctx: &RunContext, harness: &Har
             match ctx.target {
                 RunTarget::Web => "./run-web.sh",
                 RunTarget::MacOS => "./run-debuggable-mac-app.sh",
+                RunTarget::Headless => "./run-headless.sh",
+                RunTarget::Linux => "./run-linux.sh",
             }
         }
     };
 
     let is_web = if let RunTarget::Web = ctx.target { true } else { false };
+    let is_headless = if let RunTarget::Headless = ctx.target { true } else { false };
+    let is_linux = if let RunTarget::Linux = ctx.target { true } else { false };
     let target_folder : &str = ctx.target.borrow().into();
     let path = fs::canonicalize(std::path::Path::new(&ctx.path)).unwrap();
     let output_path = path.join("build").join(target_folder);
@@ -1106,17 +1602,17 @@ fn build_harness_with_chassis(pax_dir: &PathBuf, ctx: &RunContext, harness: &Har
         "arm64"
     };
     let should_also_run = &format!("{}",ctx.should_also_run);
-    if is_web {
+    //stderr is piped through the source map in both cases, so that panics in the cartridge point back to Pax
+    let mut harness_process = if is_web || is_headless || is_linux {
         Command::new(script)
             .current_dir(&harness_path)
             .arg(should_also_run)
             .arg(output_path_val)
+            .process_group(0)
             .stdout(std::process::Stdio::inherit())
-            .stderr(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::piped())
             .spawn()
             .expect("failed to run harness")
-            .wait()
-            .expect("failed to run harness");
     } else {
         Command::new(script)
             .current_dir(&harness_path)
@@ -1124,33 +1620,71 @@ fn build_harness_with_chassis(pax_dir: &PathBuf, ctx: &RunContext, harness: &Har
             .arg(exclude_arch_val)
             .arg(should_also_run)
             .arg(output_path_val)
+            .process_group(0)
             .stdout(std::process::Stdio::inherit())
-            .stderr(if ctx.verbose { std::process::Stdio::inherit() } else {std::process::Stdio::piped()})
+            .stderr(std::process::Stdio::piped())
             .spawn()
             .expect("failed to run harness")
-            .wait()
-            .expect("failed to run harness");
+    };
+
+    let stderr = harness_process.stderr.take().unwrap();
+    let pax_dir = pax_dir.clone();
+    let should_forward_all = is_web || is_headless || is_linux || ctx.verbose;
+    std::thread::spawn(move || forward_stderr_with_source_map(stderr, &pax_dir, should_forward_all));
+    harness_process
+}
+
+/// Stops a harness started by `spawn_harness_with_chassis`, along with anything it started, e.g. a dev server or the app itself
+fn stop_harness(mut harness_process: Child) {
+    let _ = Command::new("kill")
+        .arg("-TERM")
+        .arg(format!("-{}", harness_process.id()))
+        .status();
+    let _ = harness_process.wait();
+}
+
+/// Forwards the stderr of a child process, line by line, noting the originating Pax of any line that points into
+/// the generated cartridge, e.g. rustc errors and runtime panics.  Unless `should_forward_all`, only those lines
+/// (and panic messages) are forwarded.
+fn forward_stderr_with_source_map(stderr: ChildStderr, pax_dir: &PathBuf, should_forward_all: bool) {
+    let source_map = SourceMap::load(&pax_dir.join(CARTRIDGE_SOURCE_MAP_PATH));
+
+    for line in BufReader::new(stderr).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let note = source_map.as_ref().and_then(|source_map| source_map.annotate(&line));
+        if should_forward_all || note.is_some() || line.contains("panicked") {
+            eprintln!("{}", line);
+        }
+        if let Some(note) = note {
+            eprintln!("{}", note.bold());
+        }
     }
 }
 
 /// Runs `cargo build` (or `wasm-pack build`) with appropriate env in the directory
 /// of the generated chassis project inside the specified .pax dir
 /// Returns an output object containing bytestreams of stdout/stderr as well as an exit code
-pub fn build_chassis_with_cartridge(pax_dir: &PathBuf, target: &RunTarget) -> Output {
+/// With a `hot_reload_port`, the chassis listens on it for templates hot-reloaded by `pax run --watch`
+pub fn build_chassis_with_cartridge(pax_dir: &PathBuf, target: &RunTarget, hot_reload_port: Option<u16>) -> Output {
 
     let pax_dir = PathBuf::from(pax_dir.to_str().unwrap());
     let chassis_path = pax_dir.join("chassis").join({let s: & str = target.into(); s});
+    let hot_reload_port = hot_reload_port.map(|port| port.to_string()).unwrap_or_default();
     //string together a shell call like the following:
-    let cargo_run_chassis_build = match target {
-        RunTarget::MacOS => {
+    let mut cargo_run_chassis_build = match target {
+        RunTarget::MacOS | RunTarget::Headless | RunTarget::Linux => {
             Command::new("cargo")
                 .current_dir(&chassis_path)
                 .arg("build")
                 .arg("--color")
                 .arg("always")
                 .env("PAX_DIR", &pax_dir)
+                .env(hot_reload::HOT_RELOAD_PORT_ENV_VAR, &hot_reload_port)
                 .stdout(std::process::Stdio::inherit())
-                .stderr(std::process::Stdio::inherit())
+                .stderr(std::process::Stdio::piped())
                 .spawn()
                 .expect("failed to build chassis")
         },
@@ -1162,13 +1696,17 @@ pub fn build_chassis_with_cartridge(pax_dir: &PathBuf, target: &RunTarget) -> Ou
                 .arg("-d")
                 .arg(pax_dir.join("chassis").join("Web").join("pax-dev-harness-web").join("dist").to_str().unwrap()) //--release -d pax-dev-harness-web/dist
                 .env("PAX_DIR", &pax_dir)
+                .env(hot_reload::HOT_RELOAD_PORT_ENV_VAR, &hot_reload_port)
                 .stdout(std::process::Stdio::inherit())
-                .stderr(std::process::Stdio::inherit())
+                .stderr(std::process::Stdio::piped())
                 .spawn()
                 .expect("failed to build chassis")
         }
     };
 
+    //rustc errors in the generated cartridge are annotated with their originating Pax
+    forward_stderr_with_source_map(cargo_run_chassis_build.stderr.take().unwrap(), &pax_dir, true);
+
     cargo_run_chassis_build.wait_with_output().unwrap()
 }
 
@@ -1183,6 +1721,10 @@ pub struct RunContext {
 pub enum RunTarget {
     MacOS,
     Web,
+    /// Renders frames to PNG without a display, e.g. for tests and CI; see `pax-chassis-headless`
+    Headless,
+    /// Native Linux desktop app, on X11 or Wayland; see `pax-chassis-linux`
+    Linux,
 }
 
 impl From<&str> for RunTarget {
@@ -1194,6 +1736,12 @@ impl From<&str> for RunTarget {
             "web" => {
                 RunTarget::Web
             }
+            "headless" => {
+                RunTarget::Headless
+            }
+            "linux" => {
+                RunTarget::Linux
+            }
             _ => {unreachable!()}
         }
     }
@@ -1208,6 +1756,12 @@ impl<'a> Into<&'a str> for &'a RunTarget {
             RunTarget::MacOS => {
                 "MacOS"
             },
+            RunTarget::Headless => {
+                "Headless"
+            },
+            RunTarget::Linux => {
+                "Linux"
+            },
         }
     }
 }
\ No newline at end of file
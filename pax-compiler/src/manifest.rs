@@ -7,9 +7,11 @@ use serde_derive::{Serialize, Deserialize};
 #[allow(unused_imports)]
 use serde_json;
 use crate::parsing::escape_identifier;
+use crate::diagnostics::SourceSpan;
+use crate::source_map::SourceLocation;
 
 /// Definition container for an entire Pax cartridge
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct PaxManifest {
     pub components: HashMap<String, ComponentDefinition>,
     pub main_component_type_id: String,
@@ -63,6 +65,9 @@ pub struct ExpressionSpec {
     /// type `T` for some iterable repeat source type, e.g. `Vec<T>`
     pub repeat_source_iterable_type_id_escaped: String,
 
+    /// Where this expression was declared, for mapping generated RIL back to Pax
+    pub source_location: SourceLocation,
+
 }
 
 /// The spec of an expression `invocation`, the necessary configuration
@@ -140,6 +145,10 @@ impl ExpressionSpecInvocation {
     }
 }
 
+/// Suffix of the `pax_source_path` of a component whose Pax is declared by an `#[inlined(...)]` attribute, following the path
+/// of its Rust file, e.g. `src/lib.rs (#[inlined])`.  Must match the suffix written by pax-macro's `derive_pax` template.
+pub const INLINED_SOURCE_SUFFIX: &str = " (#[inlined])";
+
 /// Container for an entire component definition — includes template, settings,
 /// event bindings, property definitions, and compiler + reflection metadata
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -164,6 +173,13 @@ pub struct ComponentDefinition {
     pub template: Option<Vec<TemplateNodeDefinition>>,
     pub settings: Option<Vec<SettingsSelectorBlockDefinition>>,
     pub events: Option<Vec<EventDefinition>>,
+
+    /// Path to the file declaring this component's Pax, e.g. `src/hello.pax`, for diagnostics
+    pub pax_source_path: Option<String>,
+    /// This component's raw Pax, against which `SourceSpan`s are resolved for diagnostics
+    pub pax_source: Option<String>,
+    /// Path of the file declaring this component's struct, per `file!()`, e.g. for go-to-definition in editors
+    pub rust_source_path: Option<String>,
 }
 
 impl ComponentDefinition {
@@ -200,6 +216,8 @@ pub struct TemplateNodeDefinition {
     pub settings: Option<Vec<(String, ValueDefinition)>>,
     /// e.g. the `SomeName` in `<SomeName some_key="some_value" />`
     pub pascal_identifier: String,
+    /// Location in the component's Pax of this node's tag name, or of its control-flow statement
+    pub span: Option<SourceSpan>,
 }
 
 pub type TypeTable = HashMap<String, TypeDefinition>;
@@ -341,17 +359,6 @@ impl TypeDefinition {
         }
     }
 
-    pub fn builtin_range_isize() -> Self {
-        let type_id = "std::ops::Range<isize>";
-        Self {
-            type_id: type_id.to_string(),
-            type_id_escaped: escape_identifier(type_id.to_string()),
-            property_definitions: vec![],
-            inner_iterable_type_id: Some("isize".to_string()),
-            import_path: "std::ops::Range".to_string(),
-        }
-    }
-
     pub fn builtin_rc_properties_coproduct() -> Self {
         let type_id = "std::rc::Rc<PropertiesCoproduct>";
         Self {
@@ -372,10 +379,10 @@ pub enum ValueDefinition {
     Undefined, //Used for `Default`
     LiteralValue(String),
     Block(LiteralBlockDefinition),
-    /// (Expression contents, vtable id binding)
-    Expression(String, Option<usize>),
-    /// (Expression contents, vtable id binding)
-    Identifier(String, Option<usize>),
+    /// (Expression contents, vtable id binding, location in source)
+    Expression(String, Option<usize>, Option<SourceSpan>),
+    /// (Expression contents, vtable id binding, location in source)
+    Identifier(String, Option<usize>, Option<SourceSpan>),
     EventBindingTarget(String),
 }
 
@@ -396,10 +403,35 @@ pub enum ControlFlowRepeatPredicateDefinition {
 pub struct ControlFlowSettingsDefinition {
     pub condition_expression_paxel: Option<String>,
     pub condition_expression_vtable_id: Option<usize>,
+    pub condition_expression_span: Option<SourceSpan>,
     pub slot_index_expression_paxel: Option<String>,
     pub slot_index_expression_vtable_id: Option<usize>,
+    pub slot_index_expression_span: Option<SourceSpan>,
     pub repeat_predicate_definition: Option<ControlFlowRepeatPredicateDefinition>,
-    pub repeat_source_definition: Option<ControlFlowRepeatSourceDefinition>
+    pub repeat_source_definition: Option<ControlFlowRepeatSourceDefinition>,
+    /// Iff this is an `if` with an `else` branch: the ids of the TemplateNodeDefinitions in that branch.
+    /// `else if` is represented as a single nested `if` node.  The `true` branch is the TND's `child_ids`.
+    pub else_child_ids: Option<Vec<usize>>,
+    /// Iff this is a `match`: the subject expression, e.g. the `self.direction` in `match self.direction { ... }`
+    pub match_expression_paxel: Option<String>,
+    pub match_expression_vtable_id: Option<usize>,
+    pub match_expression_span: Option<SourceSpan>,
+    /// Iff this is a `match`: its arms, in declaration order.  The TND's `child_ids` are unused for `match`.
+    pub match_arm_definitions: Option<Vec<ControlFlowMatchArmDefinition>>,
+    /// Iff this is a keyed `for`: the key expression, e.g. the `elem.id` in `for elem in self.list key elem.id { ... }`
+    pub repeat_key_expression_paxel: Option<String>,
+    pub repeat_key_expression_vtable_id: Option<usize>,
+    pub repeat_key_expression_span: Option<SourceSpan>,
+}
+
+/// Describes a single arm of a `match` statement, e.g. the `Mode::A | Mode::B => { ... }`
+/// in `match self.mode { Mode::A | Mode::B => { ... }, _ => { ... } }`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ControlFlowMatchArmDefinition {
+    /// Pattern alternatives, as RIL-ready strings, e.g. `["Mode::A", "Mode::B"]` or `["_"]`
+    pub patterns: Vec<String>,
+    /// ids of the TemplateNodeDefinitions rendered when this arm matches
+    pub child_ids: Vec<usize>,
 }
 
 /// Container describing the possible variants of a Repeat source
@@ -409,6 +441,7 @@ pub struct ControlFlowRepeatSourceDefinition {
     pub range_expression_paxel: Option<String>,
     pub vtable_id: Option<usize>,
     pub symbolic_binding: Option<String>,
+    pub span: Option<SourceSpan>,
 }
 
 /// Container for parsed Settings blocks (inside `@settings`)
@@ -2,10 +2,11 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::{HashSet, HashMap};
 use std::fs;
-use std::ops::{RangeFrom};
+use std::ops::{Range, RangeFrom};
 use itertools::{Itertools, MultiPeek};
 
-use crate::manifest::{PropertyDefinition, ComponentDefinition, TemplateNodeDefinition, ControlFlowSettingsDefinition, ControlFlowRepeatPredicateDefinition, ValueDefinition, SettingsSelectorBlockDefinition, LiteralBlockDefinition, ControlFlowRepeatSourceDefinition, EventDefinition, TypeDefinition, TypeTable, get_primitive_type_table};
+use crate::diagnostics::{Diagnostic, DiagnosticKind, SourceSpan};
+use crate::manifest::{PropertyDefinition, ComponentDefinition, TemplateNodeDefinition, ControlFlowSettingsDefinition, ControlFlowRepeatPredicateDefinition, ValueDefinition, SettingsSelectorBlockDefinition, LiteralBlockDefinition, ControlFlowRepeatSourceDefinition, ControlFlowMatchArmDefinition, EventDefinition, TypeDefinition, TypeTable, get_primitive_type_table};
 
 use uuid::Uuid;
 
@@ -25,11 +26,24 @@ pub struct PaxParser;
 
 
 /// Returns (RIL output string, `symbolic id`s found during parse)
-/// where a `symbolic id` may be something like `self.num_clicks` or `i`
-pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
+/// where a `symbolic id` may be something like `self.num_clicks` or `i`, paired
+/// with its byte range within `input_paxel` (for reporting diagnostics)
+pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<(String, Range<usize>)>) {
 
+    let pratt = build_pratt_parser();
+
+    let pairs = PaxParser::parse(Rule::expression_body, input_paxel).expect(&format!("unsuccessful pratt parse {}", &input_paxel));
+
+    let symbolic_ids = Rc::new(RefCell::new(vec![]));
+    let output = recurse_pratt_parse_to_string(pairs, &pratt, Rc::clone(&symbolic_ids));
+    (output, symbolic_ids.take())
+}
+
+
+/// Builds the PAXEL operator-precedence parser, shared by RIL generation and type checking
+pub fn build_pratt_parser() -> PrattParser<Rule> {
     // Operator precedence is declared via the ordering here:
-    let pratt = PrattParser::new()
+    PrattParser::new()
         .op(Op::infix(Rule::xo_tern_then, Assoc::Left) | Op::infix(Rule::xo_tern_else, Assoc::Right))
         .op(Op::infix(Rule::xo_bool_and, Assoc::Left) | Op::infix(Rule::xo_bool_or, Assoc::Left))
         .op(Op::infix(Rule::xo_add, Assoc::Left) | Op::infix(Rule::xo_sub, Assoc::Left))
@@ -45,15 +59,15 @@ pub fn run_pratt_parser(input_paxel: &str) -> (String, Vec<String>) {
             Op::infix(Rule::xo_rel_gt, Assoc::Left) |
             Op::infix(Rule::xo_rel_gte, Assoc::Left)
         )
-        .op(Op::prefix(Rule::xo_bool_not));
-
-    let pairs = PaxParser::parse(Rule::expression_body, input_paxel).expect(&format!("unsuccessful pratt parse {}", &input_paxel));
-
-    let symbolic_ids = Rc::new(RefCell::new(vec![]));
-    let output = recurse_pratt_parse_to_string(pairs, &pratt, Rc::clone(&symbolic_ids));
-    (output, symbolic_ids.take())
+        .op(Op::prefix(Rule::xo_bool_not))
 }
 
+/// Captures a symbolic id for `run_pratt_parser`'s output, along with its byte range (sans any trailing whitespace)
+pub fn symbolic_id_from_pair(xo_symbol: &Pair<Rule>) -> (String, Range<usize>) {
+    let symbolic_id = xo_symbol.as_str().trim_end();
+    let start = xo_symbol.as_span().start();
+    (symbolic_id.to_string(), start..(start + symbolic_id.len()))
+}
 
 /// Removes leading `self.` or `this.`, escapes remaining symbol to be a suitable atomic identifier
 fn convert_symbolic_binding_from_paxel_to_ril(xo_symbol: Pair<Rule>) -> String {
@@ -79,7 +93,7 @@ fn convert_symbolic_binding_from_paxel_to_ril(xo_symbol: Pair<Rule>) -> String {
 }
 
 /// Workhorse method for compiling Expressions into Rust Intermediate Language (RIL, a string of Rust)
-fn recurse_pratt_parse_to_string<'a>(expression: Pairs<Rule>, pratt_parser: &PrattParser<Rule>, symbolic_ids: Rc<RefCell<Vec<String>>>) -> String {
+fn recurse_pratt_parse_to_string<'a>(expression: Pairs<Rule>, pratt_parser: &PrattParser<Rule>, symbolic_ids: Rc<RefCell<Vec<(String, Range<usize>)>>>) -> String {
     pratt_parser
         .map_primary(move |primary| match primary.as_rule() {
             /* expression_grouped | xo_function_call | xo_range     */
@@ -129,41 +143,47 @@ fn recurse_pratt_parse_to_string<'a>(expression: Pairs<Rule>, pratt_parser: &Pra
 
                 output
             },
+            Rule::xo_range_stepped => {
+                /* { "(" ~ xo_range ~ ")" ~ "." ~ "step_by" ~ "(" ~ expression_body ~ ")" } */
+                let mut pairs = primary.into_inner();
+                let range = recurse_pratt_parse_to_string(Pairs::single(pairs.next().unwrap()), pratt_parser, Rc::clone(&symbolic_ids));
+                let step = recurse_pratt_parse_to_string(pairs.next().unwrap().into_inner(), pratt_parser, Rc::clone(&symbolic_ids));
+                format!("{}.step_by({})", range, step)
+            },
+            Rule::xo_range_reversed => {
+                /* { "(" ~ xo_range ~ ")" ~ ("." ~ "step_by" ~ "(" ~ expression_body ~ ")")? ~ "." ~ "rev" ~ "(" ~ ")" } */
+                let mut pairs = primary.into_inner();
+                let range = recurse_pratt_parse_to_string(Pairs::single(pairs.next().unwrap()), pratt_parser, Rc::clone(&symbolic_ids));
+                match pairs.next() {
+                    Some(step) => format!("{}.step_by({}).rev()", range, recurse_pratt_parse_to_string(step.into_inner(), pratt_parser, Rc::clone(&symbolic_ids))),
+                    None => format!("{}.rev()", range),
+                }
+            },
             Rule::xo_range => {
                 /* { op0: (xo_literal | xo_symbol) ~ op1: (xo_range_inclusive | xo_range_exclusive) ~ op2: (xo_literal | xo_symbol)} */
                 let mut pairs = primary.into_inner();
 
-                let op0 = pairs.next().unwrap();
-
-                let op0_out = match op0.as_rule() {
-                    Rule::xo_literal => {
-                        //return the literal exactly as it is
-                        op0.as_str().to_string()
-                    },
-                    Rule::xo_symbol => {
-                        //for symbolic identifiers, remove any "this" or "self", then return string
-                        convert_symbolic_binding_from_paxel_to_ril(op0)
-                    },
-                    _ => unimplemented!("")
+                let compile_operand = |op: Pair<Rule>| {
+                    match op.as_rule() {
+                        Rule::xo_literal => {
+                            //return the literal exactly as it is
+                            op.as_str().to_string()
+                        },
+                        Rule::xo_symbol => {
+                            //for symbolic identifiers, remove any "this" or "self", then return string
+                            symbolic_ids.borrow_mut().push(symbolic_id_from_pair(&op));
+                            convert_symbolic_binding_from_paxel_to_ril(op)
+                        },
+                        _ => unimplemented!("")
+                    }
                 };
 
-                let op1 = pairs.next().unwrap();
-                let op1_out = op1.as_str().to_string();
+                let op0_out = compile_operand(pairs.next().unwrap());
+                let is_inclusive = pairs.next().unwrap().as_rule() == Rule::xo_range_inclusive;
+                let op2_out = compile_operand(pairs.next().unwrap());
 
-                let op2 = pairs.next().unwrap();
-                let op2_out = match op2.as_rule() {
-                    Rule::xo_literal => {
-                        //return the literal exactly as it is
-                        op2.as_str().to_string()
-                    },
-                    Rule::xo_symbol => {
-                        //for symbolic identifiers, remove any "this" or "self", then return string
-                        convert_symbolic_binding_from_paxel_to_ril(op2)
-                    },
-                    _ => unimplemented!("")
-                };
-
-                format!("{}", op0_out + &op1_out + &op2_out)
+                //NumericRange handles `Numeric` operands, which `std::ops::Range` does not support
+                format!("pax_runtime_api::numeric::NumericRange::new({}, {}, {})", op0_out, op2_out, is_inclusive)
             },
             Rule::xo_literal => {
                 let literal_kind = primary.into_inner().next().unwrap();
@@ -207,7 +227,7 @@ fn recurse_pratt_parse_to_string<'a>(expression: Pairs<Rule>, pratt_parser: &Pra
 
                 //for parsing xo_object_settings_key_value_pair
                 //iterate over key-value pairs; recurse into expressions
-                fn handle_xoskvp<'a>(xoskvp: Pair<Rule>, pratt_parser: &PrattParser<Rule>, symbolic_ids: Rc<RefCell<Vec<String>>>) -> String {
+                fn handle_xoskvp<'a>(xoskvp: Pair<Rule>, pratt_parser: &PrattParser<Rule>, symbolic_ids: Rc<RefCell<Vec<(String, Range<usize>)>>>) -> String {
                     let mut inner_kvp = xoskvp.into_inner();
                     let settings_key = inner_kvp.next().unwrap().as_str().to_string();
                     let expression_body = inner_kvp.next().unwrap().into_inner();
@@ -236,7 +256,7 @@ fn recurse_pratt_parse_to_string<'a>(expression: Pairs<Rule>, pratt_parser: &Pra
                 output
             },
             Rule::xo_symbol => {
-                symbolic_ids.borrow_mut().push(primary.as_str().to_string());
+                symbolic_ids.borrow_mut().push(symbolic_id_from_pair(&primary));
                 format!("{}",convert_symbolic_binding_from_paxel_to_ril(primary))
             },
             Rule::xo_tuple => {
@@ -332,7 +352,8 @@ fn parse_template_from_component_definition_string(ctx: &mut TemplateNodeParseCo
             type_id: "IMPLICIT_ROOT".to_string(),
             control_flow_settings: None,
             settings: None,
-            pascal_identifier: "<UNREACHABLE>".to_string()
+            pascal_identifier: "<UNREACHABLE>".to_string(),
+            span: None,
         }
     );
 
@@ -348,11 +369,31 @@ struct TemplateNodeParseContext {
     //but not when iterating over siblings
     pub child_id_tracking_stack: Vec<Vec<usize>>,
     pub uid_gen: MultiPeek<RangeFrom<usize>>,
+    /// Errors encountered while visiting the template, e.g. unknown components
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Looks up the type_id for the component named by a template tag, like the `Rectangle` in `<Rectangle />`.
+/// Unknown components are reported to `ctx.diagnostics`, and fall back to `pascal_identifier` as a type_id
+/// so that the rest of the template can still be visited.
+fn resolve_pascal_identifier_to_type_id(ctx: &mut TemplateNodeParseContext, pascal_identifier: &str, span: &SourceSpan) -> String {
+    match ctx.pascal_identifier_to_type_id_map.get(pascal_identifier) {
+        Some(type_id) => type_id.to_string(),
+        None => {
+            ctx.diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnknownComponent,
+                format!("cannot find component `{}` in this scope", pascal_identifier),
+                "not found in this scope".to_string(),
+            ).with_span(Some(span.clone())));
+            pascal_identifier.to_string()
+        }
+    }
 }
 
 pub static TYPE_ID_IF : &str = "IF";
 pub static TYPE_ID_REPEAT : &str = "REPEAT";
 pub static TYPE_ID_SLOT : &str = "SLOT";
+pub static TYPE_ID_MATCH : &str = "MATCH";
 
 fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_tag_pair: Pair<Rule>)  {
     let new_id = ctx.uid_gen.next().unwrap();
@@ -369,7 +410,9 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
             //matched_tag => open_tag > pascal_identifier
             let matched_tag = any_tag_pair;
             let mut open_tag = matched_tag.clone().into_inner().next().unwrap().into_inner();
-            let pascal_identifier = open_tag.next().unwrap().as_str();
+            let pascal_identifier_pair = open_tag.next().unwrap();
+            let pascal_identifier = pascal_identifier_pair.as_str();
+            let span = SourceSpan::from_pest(pascal_identifier_pair.as_span());
 
             //push the empty frame for this node's children
             ctx.child_id_tracking_stack.push(vec![]);
@@ -390,24 +433,28 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
             let mut template_node = TemplateNodeDefinition {
                 id: new_id,
                 control_flow_settings: None,
-                type_id: ctx.pascal_identifier_to_type_id_map.get(pascal_identifier.clone()).expect(&format!("Template key not found {}", &pascal_identifier)).to_string(),
+                type_id: resolve_pascal_identifier_to_type_id(ctx, pascal_identifier, &span),
                 settings: parse_inline_attribute_from_final_pairs_of_tag(open_tag),
                 child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
                 pascal_identifier: pascal_identifier.to_string(),
+                span: Some(span),
             };
             std::mem::swap(ctx.template_node_definitions.get_mut(new_id).unwrap(),  &mut template_node);
         },
         Rule::self_closing_tag => {
             let mut tag_pairs = any_tag_pair.into_inner();
-            let pascal_identifier = tag_pairs.next().unwrap().as_str();
+            let pascal_identifier_pair = tag_pairs.next().unwrap();
+            let pascal_identifier = pascal_identifier_pair.as_str();
+            let span = SourceSpan::from_pest(pascal_identifier_pair.as_span());
 
             let mut template_node = TemplateNodeDefinition {
                 id: new_id,
                 control_flow_settings: None,
-                type_id: ctx.pascal_identifier_to_type_id_map.get(pascal_identifier).expect(&format!("Template key not found {}", &pascal_identifier)).to_string(),
+                type_id: resolve_pascal_identifier_to_type_id(ctx, pascal_identifier, &span),
                 settings: parse_inline_attribute_from_final_pairs_of_tag(tag_pairs),
                 child_ids: vec![],
                 pascal_identifier: pascal_identifier.to_string(),
+                span: Some(span),
             };
             std::mem::swap(ctx.template_node_definitions.get_mut(new_id).unwrap(),  &mut template_node);
         },
@@ -418,36 +465,10 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
             ctx.child_id_tracking_stack.push(vec![]);
 
             let any_tag_pair = any_tag_pair.into_inner().next().unwrap();
+            let span = SourceSpan::from_pest(any_tag_pair.as_span());
             let mut template_node_definition = match any_tag_pair.as_rule() {
                 Rule::statement_if => {
-
-                    let mut statement_if = any_tag_pair.into_inner();
-                    let expression_body = statement_if.next().unwrap().as_str().to_string();
-                    let prospective_inner_nodes = statement_if.next();
-
-                    if let Some(inner_nodes) = prospective_inner_nodes {
-                        inner_nodes.into_inner()
-                            .for_each(|sub_tag_pair|{
-                                recurse_visit_tag_pairs_for_template(ctx, sub_tag_pair);
-                            })
-                    }
-
-                    //`if` TemplateNodeDefinition
-                    TemplateNodeDefinition {
-                        id: new_id.clone(),
-                        control_flow_settings: Some(ControlFlowSettingsDefinition {
-                            condition_expression_paxel: Some(expression_body),
-                            condition_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
-                            slot_index_expression_paxel: None,
-                            slot_index_expression_vtable_id: None,
-                            repeat_predicate_definition: None,
-                            repeat_source_definition: None
-                        }),
-                        type_id: TYPE_ID_IF.to_string(),
-                        settings: None,
-                        child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
-                        pascal_identifier: "Conditional".to_string(),
-                    }
+                    parse_statement_if(ctx, new_id, any_tag_pair)
                 },
                 Rule::statement_for => {
                     let mut cfavd = ControlFlowSettingsDefinition::default();
@@ -455,7 +476,15 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
                     let mut predicate_declaration = for_statement.next().unwrap().into_inner();
                     let source = for_statement.next().unwrap();
 
-                    let prospective_inner_nodes = for_statement.next();
+                    let mut prospective_inner_nodes = for_statement.next();
+
+                    //optional `key` expression, like the `elem.id` in `for elem in self.some_list key elem.id`
+                    if let Some(Rule::statement_for_key) = prospective_inner_nodes.as_ref().map(|pair| pair.as_rule()) {
+                        let expression_body = prospective_inner_nodes.unwrap().into_inner().next().unwrap();
+                        cfavd.repeat_key_expression_paxel = Some(expression_body.as_str().to_string());
+                        cfavd.repeat_key_expression_span = Some(SourceSpan::from_pest(expression_body.as_span()));
+                        prospective_inner_nodes = for_statement.next();
+                    }
 
                     if predicate_declaration.clone().count() > 1 {
                         //tuple, like the `elem, i` in `for (elem, i) in self.some_list`
@@ -470,13 +499,15 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
                     }
 
                     let inner_source = source.into_inner().next().unwrap();
-                    /* statement_for_source = { xo_range | xo_symbol } */
+                    let source_span = Some(SourceSpan::from_pest(inner_source.as_span()));
+                    /* statement_for_source = { xo_range_reversed | xo_range_stepped | xo_range | xo_symbol } */
                     let repeat_source_definition = match inner_source.as_rule() {
-                        Rule::xo_range => {
+                        Rule::xo_range_reversed | Rule::xo_range_stepped | Rule::xo_range => {
                             ControlFlowRepeatSourceDefinition {
                                 range_expression_paxel: Some(inner_source.as_str().to_string()),
                                 vtable_id: None, //This will be written back to this data structure later, during expression compilation
                                 symbolic_binding: None,
+                                span: source_span,
                             }
                         },
                         Rule::xo_symbol => {
@@ -484,6 +515,7 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
                                 range_expression_paxel: None,
                                 vtable_id: None,
                                 symbolic_binding: Some(convert_symbolic_binding_from_paxel_to_ril(inner_source)),
+                                span: source_span,
                             }
                         },
                         _ => {unreachable!()}
@@ -506,11 +538,14 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
                         settings: None,
                         child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
                         pascal_identifier: "Repeat".to_string(),
+                        span: Some(span),
                     }
                 },
                 Rule::statement_slot => {
                     let mut statement_slot = any_tag_pair.into_inner();
-                    let expression_body = statement_slot.next().unwrap().as_str().to_string();
+                    let expression_body = statement_slot.next().unwrap();
+                    let expression_span = SourceSpan::from_pest(expression_body.as_span());
+                    let expression_body = expression_body.as_str().to_string();
                     let prospective_inner_nodes = statement_slot.next();
 
                     if let Some(inner_nodes) = prospective_inner_nodes {
@@ -525,15 +560,82 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
                         control_flow_settings: Some(ControlFlowSettingsDefinition {
                             condition_expression_paxel: None,
                             condition_expression_vtable_id: None,
+                            condition_expression_span: None,
                             slot_index_expression_paxel: Some(expression_body),
                             slot_index_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
+                            slot_index_expression_span: Some(expression_span),
                             repeat_predicate_definition: None,
-                            repeat_source_definition: None
+                            repeat_source_definition: None,
+                            else_child_ids: None,
+                            match_expression_paxel: None,
+                            match_expression_vtable_id: None,
+                            match_expression_span: None,
+                            match_arm_definitions: None,
+                            repeat_key_expression_paxel: None,
+                            repeat_key_expression_vtable_id: None,
+                            repeat_key_expression_span: None,
                         }),
                         type_id: TYPE_ID_SLOT.to_string(),
                         settings: None,
                         child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
                         pascal_identifier: "Slot".to_string(),
+                        span: Some(span),
+                    }
+                },
+                Rule::statement_match => {
+                    /* statement_match = {"match" ~ expression_body ~ "{" ~ statement_match_arm* ~ "}"} */
+                    let mut statement_match = any_tag_pair.into_inner();
+                    let expression_body = statement_match.next().unwrap();
+                    let expression_span = SourceSpan::from_pest(expression_body.as_span());
+                    let expression_body = expression_body.as_str().to_string();
+
+                    //each arm tracks its own children; the `match` node itself has none
+                    let match_arm_definitions = statement_match.map(|statement_match_arm| {
+                        /* statement_match_arm = {statement_match_pattern ~ "=>" ~ "{" ~ inner_nodes ~ "}" ~ ","?} */
+                        let mut statement_match_arm = statement_match_arm.into_inner();
+                        let patterns = statement_match_arm.next().unwrap().into_inner().map(|pattern| {
+                            pattern.as_str().trim().to_string()
+                        }).collect();
+
+                        ctx.child_id_tracking_stack.push(vec![]);
+                        if let Some(inner_nodes) = statement_match_arm.next() {
+                            inner_nodes.into_inner()
+                                .for_each(|sub_tag_pair|{
+                                    recurse_visit_tag_pairs_for_template(ctx, sub_tag_pair);
+                                })
+                        }
+
+                        ControlFlowMatchArmDefinition {
+                            patterns,
+                            child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
+                        }
+                    }).collect();
+
+                    TemplateNodeDefinition {
+                        id: new_id,
+                        control_flow_settings: Some(ControlFlowSettingsDefinition {
+                            condition_expression_paxel: None,
+                            condition_expression_vtable_id: None,
+                            condition_expression_span: None,
+                            slot_index_expression_paxel: None,
+                            slot_index_expression_vtable_id: None,
+                            slot_index_expression_span: None,
+                            repeat_predicate_definition: None,
+                            repeat_source_definition: None,
+                            else_child_ids: None,
+                            match_expression_paxel: Some(expression_body),
+                            match_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
+                            match_expression_span: Some(expression_span),
+                            match_arm_definitions: Some(match_arm_definitions),
+                            repeat_key_expression_paxel: None,
+                            repeat_key_expression_vtable_id: None,
+                            repeat_key_expression_span: None,
+                        }),
+                        type_id: TYPE_ID_MATCH.to_string(),
+                        settings: None,
+                        child_ids: ctx.child_id_tracking_stack.pop().unwrap(),
+                        pascal_identifier: "Match".to_string(),
+                        span: Some(span),
                     }
                 },
                 _ => {
@@ -543,6 +645,15 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
 
             std::mem::swap(ctx.template_node_definitions.get_mut(new_id).unwrap(),  &mut template_node_definition);
         },
+        Rule::statement_if => {
+            //Only reachable as the nested `if` of an `else if` — see `parse_statement_if`
+
+            //push the empty frame for this node's children
+            ctx.child_id_tracking_stack.push(vec![]);
+
+            let mut template_node_definition = parse_statement_if(ctx, new_id, any_tag_pair);
+            std::mem::swap(ctx.template_node_definitions.get_mut(new_id).unwrap(),  &mut template_node_definition);
+        },
         Rule::node_inner_content => {
             //For example:  `<Text>"I am inner content"</Text>`
             unimplemented!("Inner content not yet supported");
@@ -551,6 +662,78 @@ fn recurse_visit_tag_pairs_for_template(ctx: &mut TemplateNodeParseContext, any_
     }
 }
 
+/// Builds the `if` TemplateNodeDefinition for `statement_if`, visiting both the `true` branch (`child_ids`)
+/// and the optional `else` branch (`else_child_ids`.)  Expects the caller to have pushed a frame onto
+/// `child_id_tracking_stack`, which is popped here.
+/// An `else if` is parsed as a nested `if` node, which becomes the sole member of the `else` branch.
+fn parse_statement_if(ctx: &mut TemplateNodeParseContext, new_id: usize, statement_if: Pair<Rule>) -> TemplateNodeDefinition {
+    /* statement_if = {"if" ~ expression_body ~ "{" ~ inner_nodes ~ "}" ~ statement_else?} */
+    let span = SourceSpan::from_pest(statement_if.as_span());
+    let mut statement_if = statement_if.into_inner();
+    let expression_body = statement_if.next().unwrap();
+    let expression_span = SourceSpan::from_pest(expression_body.as_span());
+    let expression_body = expression_body.as_str().to_string();
+    let prospective_inner_nodes = statement_if.next();
+
+    if let Some(inner_nodes) = prospective_inner_nodes {
+        inner_nodes.into_inner()
+            .for_each(|sub_tag_pair|{
+                recurse_visit_tag_pairs_for_template(ctx, sub_tag_pair);
+            })
+    }
+    let child_ids = ctx.child_id_tracking_stack.pop().unwrap();
+
+    /* statement_else = {"else" ~ (statement_if | ("{" ~ inner_nodes ~ "}"))} */
+    let else_child_ids = if let Some(statement_else) = statement_if.next() {
+        ctx.child_id_tracking_stack.push(vec![]);
+        let else_branch = statement_else.into_inner().next().unwrap();
+        match else_branch.as_rule() {
+            Rule::statement_if => {
+                //`else if`
+                recurse_visit_tag_pairs_for_template(ctx, else_branch);
+            },
+            Rule::inner_nodes => {
+                else_branch.into_inner()
+                    .for_each(|sub_tag_pair|{
+                        recurse_visit_tag_pairs_for_template(ctx, sub_tag_pair);
+                    })
+            },
+            _ => {unreachable!("Parsing error: {:?}", else_branch.as_rule());}
+        }
+        Some(ctx.child_id_tracking_stack.pop().unwrap())
+    } else {
+        None
+    };
+
+    //`if` TemplateNodeDefinition
+    TemplateNodeDefinition {
+        id: new_id,
+        control_flow_settings: Some(ControlFlowSettingsDefinition {
+            condition_expression_paxel: Some(expression_body),
+            condition_expression_vtable_id: None, //This will be written back to this data structure later, during expression compilation
+            condition_expression_span: Some(expression_span),
+            slot_index_expression_paxel: None,
+            slot_index_expression_vtable_id: None,
+            slot_index_expression_span: None,
+            repeat_predicate_definition: None,
+            repeat_source_definition: None,
+            else_child_ids,
+            match_expression_paxel: None,
+            match_expression_vtable_id: None,
+            match_expression_span: None,
+            match_arm_definitions: None,
+            repeat_key_expression_paxel: None,
+            repeat_key_expression_vtable_id: None,
+            repeat_key_expression_span: None,
+        }),
+        type_id: TYPE_ID_IF.to_string(),
+        settings: None,
+        child_ids,
+        pascal_identifier: "Conditional".to_string(),
+        span: Some(span),
+    }
+}
+
 fn parse_inline_attribute_from_final_pairs_of_tag ( final_pairs_of_tag: Pairs<Rule>) -> Option<Vec<(String, ValueDefinition)>> {
     let vec : Vec<(String, ValueDefinition)> = final_pairs_of_tag.map(|attribute_key_value_pair|{
         match attribute_key_value_pair.clone().into_inner().next().unwrap().as_rule() {
@@ -578,8 +761,8 @@ fn parse_inline_attribute_from_final_pairs_of_tag ( final_pairs_of_tag: Pairs<Ru
                             derive_value_definition_from_literal_object_pair(raw_value)
                         )
                     },
-                    Rule::expression_body => { ValueDefinition::Expression(raw_value.as_str().to_string(), None)},
-                    Rule::identifier => { ValueDefinition::Identifier(raw_value.as_str().to_string(), None)},
+                    Rule::expression_body => { ValueDefinition::Expression(raw_value.as_str().to_string(), None, Some(SourceSpan::from_pest(raw_value.as_span())))},
+                    Rule::identifier => { ValueDefinition::Identifier(raw_value.as_str().to_string(), None, Some(SourceSpan::from_pest(raw_value.as_span())))},
                     _ => {unreachable!("Parsing error 3342638857230: {:?}", raw_value.as_rule());}
                 };
                 (key, value)
@@ -623,7 +806,7 @@ fn derive_value_definition_from_literal_object_pair(literal_object: Pair<Rule>)
                 )},
                 // Rule::literal_enum_value => {ValueDefinition::Enum(raw_value.as_str().to_string())},
                 Rule::expression_body => {
-                    ValueDefinition::Expression(raw_value.as_str().to_string(), None)},
+                    ValueDefinition::Expression(raw_value.as_str().to_string(), None, Some(SourceSpan::from_pest(raw_value.as_span())))},
                 _ => {unreachable!("Parsing error 231453468: {:?}", raw_value.as_rule());}
             };
 
@@ -724,6 +907,9 @@ pub struct ParsingContext {
     pub type_table: TypeTable,
 
     pub import_paths: HashSet<String>,
+
+    /// Errors encountered while parsing, across all visited components
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Default for ParsingContext {
@@ -736,6 +922,7 @@ impl Default for ParsingContext {
             type_table: get_primitive_type_table(),
             template_node_definitions: vec![],
             import_paths: HashSet::new(),
+            diagnostics: vec![],
         }
     }
 }
@@ -748,16 +935,41 @@ pub fn assemble_component_definition(
     is_main_component: bool,
     template_map: HashMap<String, String>,
     module_path: &str,
-    self_type_id: &str
+    self_type_id: &str,
+    pax_source_path: Option<&str>,
 ) -> (ParsingContext, ComponentDefinition) {
-    let _ast = PaxParser::parse(Rule::pax_component_definition, pax)
-        .expect(&format!("unsuccessful parse from {}", &pax)) // unwrap the parse result
-        .next().unwrap(); // get and unwrap the `pax_component_definition` rule
 
     if is_main_component {
         ctx.main_component_type_id = self_type_id.to_string();
     }
 
+    let modified_module_path = clean_module_path(module_path);
+    let pax_source_path = pax_source_path.map(|path| path.to_string());
+
+    //Syntax errors are reported as diagnostics; the component is still defined (sans template, settings,
+    //and events) so that parsing may continue and report any errors in other components
+    if let Err(error) = PaxParser::parse(Rule::pax_component_definition, pax) {
+        ctx.diagnostics.push(Diagnostic::from_pest_error(error).with_source(pax_source_path.clone(), Some(pax)));
+
+        let new_def = ComponentDefinition {
+            is_primitive: false,
+            is_struct_only_component: false,
+            is_main_component,
+            primitive_instance_import_path: None,
+            type_id: self_type_id.to_string(),
+            type_id_escaped: escape_identifier(self_type_id.to_string()),
+            pascal_identifier: pascal_identifier.to_string(),
+            template: None,
+            settings: None,
+            events: None,
+            module_path: modified_module_path,
+            pax_source_path,
+            pax_source: Some(pax.to_string()),
+            rust_source_path: None,
+        };
+        return (ctx, new_def);
+    }
+
     let mut tpc = TemplateNodeParseContext {
         pascal_identifier_to_type_id_map: template_map,
         template_node_definitions: vec![],
@@ -767,15 +979,14 @@ pub fn assemble_component_definition(
         //but not when iterating over siblings
         child_id_tracking_stack: vec![],
         uid_gen: (1..).multipeek(),
+        diagnostics: vec![],
     };
 
     parse_template_from_component_definition_string(&mut tpc, pax);
 
-    let modified_module_path = if module_path.starts_with("parser") {
-        module_path.replacen("parser", "crate", 1)
-    } else {
-        module_path.to_string()
-    };
+    ctx.diagnostics.extend(tpc.diagnostics.into_iter().map(|diagnostic| {
+        diagnostic.with_source(pax_source_path.clone(), Some(pax))
+    }));
 
     //populate template_node_definitions vec, needed for traversing node tree at codegen-time
     ctx.template_node_definitions = tpc.template_node_definitions.clone();
@@ -792,6 +1003,9 @@ pub fn assemble_component_definition(
         settings: parse_settings_from_component_definition_string(pax),
         events: parse_events_from_component_definition_string(pax),
         module_path: modified_module_path,
+        pax_source_path,
+        pax_source: Some(pax.to_string()),
+        rust_source_path: None,
     };
 
     (ctx, new_def)
@@ -821,6 +1035,9 @@ pub fn assemble_struct_only_component_definition(ctx: ParsingContext, pascal_ide
         template: None,
         settings: None,
         events: None,
+        pax_source_path: None,
+        pax_source: None,
+        rust_source_path: None,
     };
 
     (ctx, new_def)
@@ -846,6 +1063,9 @@ pub fn assemble_primitive_definition(
         settings: None,
         module_path: modified_module_path,
         events: None,
+        pax_source_path: None,
+        pax_source: None,
+        rust_source_path: None,
     }
 }
 
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_derive::{Serialize, Deserialize};
+
+use crate::diagnostics::SourceSpan;
+
+/// Tag for the comments that delimit regions of generated code, e.g.
+/// `/* @pax-source-map begin expression 12 */` ... `/* @pax-source-map end expression 12 */`
+pub const SOURCE_MAP_MARKER: &str = "@pax-source-map";
+
+/// The origin in Pax of some generated code, such as an expression's vtable entry or a render node literal
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SourceLocation {
+    /// type_id of the component whose template declares this node or expression
+    pub component_type_id: String,
+    /// e.g. `src/hello.pax`
+    pub pax_source_path: Option<String>,
+    pub template_node_id: usize,
+    pub span: Option<SourceSpan>,
+}
+
+impl SourceLocation {
+    /// e.g. "src/hello.pax:4:24 (template node 3 of `crate::Hello`)"
+    pub fn describe(&self) -> String {
+        let path = self.pax_source_path.clone().unwrap_or("<unknown>".to_string());
+        let position = match &self.span {
+            Some(span) => format!("{}:{}:{}", path, span.start_line, span.start_column),
+            None => path,
+        };
+        format!("{} (template node {} of `{}`)", position, self.template_node_id, self.component_type_id)
+    }
+}
+
+/// A region of generated code, by 1-based inclusive line numbers, and the Pax it was generated from
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SourceMapEntry {
+    /// e.g. `expression 12` or `node pax_example_Hello 3`
+    pub key: String,
+    pub generated_start_line: usize,
+    pub generated_end_line: usize,
+    pub location: SourceLocation,
+}
+
+/// Sidecar to a generated file (e.g. the cartridge's `lib.rs`), mapping its contents back to .pax sources
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SourceMap {
+    /// Path of the generated file, relative to the .pax directory, e.g. `cartridge/src/lib.rs`
+    pub generated_path: String,
+    pub entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+
+    /// Builds a source map by scanning `generated` for begin/end markers, looking up each marker's key in `locations`
+    pub fn from_generated_code(generated_path: &str, generated: &str, locations: &HashMap<String, SourceLocation>) -> Self {
+        let begin_prefix = format!("{} begin ", SOURCE_MAP_MARKER);
+        let end_prefix = format!("{} end ", SOURCE_MAP_MARKER);
+
+        let mut open_regions: Vec<(String, usize)> = vec![];
+        let mut entries = vec![];
+        for (i, line) in generated.lines().enumerate() {
+            let line_number = i + 1;
+            if let Some(key) = marker_key(line, &begin_prefix) {
+                open_regions.push((key, line_number));
+            } else if let Some(key) = marker_key(line, &end_prefix) {
+                if let Some(position) = open_regions.iter().rposition(|(open_key, _)| *open_key == key) {
+                    let (key, generated_start_line) = open_regions.remove(position);
+                    if let Some(location) = locations.get(&key) {
+                        entries.push(SourceMapEntry {
+                            key,
+                            generated_start_line,
+                            generated_end_line: line_number,
+                            location: location.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        SourceMap {
+            generated_path: generated_path.to_string(),
+            entries,
+        }
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, path: &Path) {
+        fs::write(path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// Returns the innermost entry containing `generated_line`; e.g. an expression rather than
+    /// the render node literal that encloses it
+    pub fn lookup(&self, generated_line: usize) -> Option<&SourceMapEntry> {
+        self.entries.iter()
+            .filter(|entry| entry.generated_start_line <= generated_line && generated_line <= entry.generated_end_line)
+            .min_by_key(|entry| entry.generated_end_line - entry.generated_start_line)
+    }
+
+    /// For a line of rustc output or a panic message that references the generated file
+    /// (e.g. `  --> .pax/cartridge/src/lib.rs:120:5`), returns a note pointing at the originating Pax
+    pub fn annotate(&self, line: &str) -> Option<String> {
+        let path_index = line.find(&format!("{}:", self.generated_path))?;
+        let line_number : String = line[path_index + self.generated_path.len() + 1..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        let entry = self.lookup(line_number.parse().ok()?)?;
+        Some(format!("   = note: generated from Pax at {}", entry.location.describe()))
+    }
+}
+
+fn marker_key(line: &str, prefix: &str) -> Option<String> {
+    let start = line.find(prefix)? + prefix.len();
+    let rest = &line[start..];
+    let end = rest.find("*/").unwrap_or(rest.len());
+    Some(rest[..end].trim().to_string())
+}
@@ -37,11 +37,44 @@ pub struct TemplateArgsCodegenCartridgeLib {
 
     /// List of component factory definitions, as pre-assembled literal Strings.
     pub component_factories_literal: Vec<String>,
+
+    /// Components and primitives that hot-reloaded templates may instantiate
+    pub hot_reload_node_types: Vec<HotReloadNodeType>,
+
+    /// Event bindings that hot-reloaded templates may declare, i.e. those declared by the templates compiled into the cartridge
+    pub hot_reload_event_bindings: Vec<HotReloadEventBinding>,
+
+    /// Properties that hot-reloaded expressions may read, by the PropertiesCoproduct variant of their component
+    pub hot_reload_property_readers: Vec<(String, Vec<String>)>,
+
+    /// TypesCoproduct variants that hot-reloaded expressions may evaluate to, along with their types
+    pub hot_reload_coercions: Vec<(String, String)>,
+}
+
+#[derive(Serialize)]
+pub struct HotReloadNodeType {
+    pub type_id: String,
+    pub is_primitive: bool,
+    pub snake_case_type_id: String,
+    pub primitive_instance_import_path: Option<String>,
+    pub properties_coproduct_variant: String,
+    pub component_properties_struct: String,
+    pub properties: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct HotReloadEventBinding {
+    pub component_type_id: String,
+    pub properties_coproduct_variant: String,
+    pub component_properties_struct: String,
+    pub event: String,
+    pub method: String,
 }
 
 #[derive(Serialize)]
 pub struct TemplateArgsCodegenCartridgeComponentFactory {
     pub is_main_component: bool,
+    pub type_id: String,
     pub snake_case_type_id: String,
     pub component_properties_struct: String,
     pub properties: Vec<(PropertyDefinition, String)>, //PropertyDefinition, TypeIdPascalized
@@ -63,11 +96,20 @@ pub struct TemplateArgsCodegenCartridgeRenderNodeLiteral {
     pub children_literal: Vec<String>,
     pub slot_index_literal: String,
     pub repeat_source_expression_literal_vec: String,
-    pub repeat_source_expression_literal_range: String,
+    pub repeat_key_expression_literal: String,
     pub conditional_boolean_expression_literal: String,
+    pub conditional_false_branch_children_literal: Vec<String>,
+    pub match_expression_literal: String,
+    pub match_arms_literal: Vec<Vec<String>>,
     pub pascal_identifier: String,
     pub type_id_escaped: String,
     pub events: HashMap<String,String>,
+    /// Id of the originating TemplateNodeDefinition, for the source map
+    pub template_node_id: usize,
+    /// The node's `id` in Pax, if any, under which its instances are registered for tests
+    pub template_id: Option<String>,
+    /// Whether the node is given `focusable=true` in Pax
+    pub is_focusable: bool,
 }
 
 #[allow(unused)]
@@ -0,0 +1,350 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+
+use pest::Parser;
+use pest::iterators::{Pair, Pairs};
+use pest::pratt_parser::PrattParser;
+
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
+use crate::manifest::SUPPORTED_NUMERIC_PRIMITIVES;
+use crate::parsing::{build_pratt_parser, symbolic_id_from_pair, PaxParser, Rule};
+
+/// Whether a numeric value is known to hold an integer or a float, mirroring the variants of `Numeric`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericKind {
+    Integer,
+    Float,
+    /// Either, e.g. a property declared as `Numeric`
+    Any,
+}
+
+impl NumericKind {
+    /// The kind resulting from an arithmetic operation, following `Numeric::widen`:
+    /// two integers yield an integer, while a float on either side yields a float
+    pub fn widen(self, other: NumericKind) -> NumericKind {
+        match (self, other) {
+            (NumericKind::Integer, NumericKind::Integer) => NumericKind::Integer,
+            (NumericKind::Float, _) | (_, NumericKind::Float) => NumericKind::Float,
+            _ => NumericKind::Any,
+        }
+    }
+}
+
+/// The statically inferred type of a PAXEL expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaxelType {
+    /// Any of the `SUPPORTED_NUMERIC_PRIMITIVES`, or `Numeric` itself; all are evaluated as `Numeric` in RIL
+    Numeric(NumericKind),
+    Bool,
+    String,
+    Size,
+    /// A range like `0..10`, usable as the source of a `for` statement
+    Range,
+    /// Any other type from the TypeTable, by type_id
+    Other(String),
+    /// Not statically known, e.g. the return value of a function call.  Compatible with every type.
+    Unknown,
+}
+
+impl PaxelType {
+    pub fn from_type_id(type_id: &str) -> Self {
+        match type_id {
+            "f64" | "f32" => PaxelType::Numeric(NumericKind::Float),
+            "bool" => PaxelType::Bool,
+            "String" => PaxelType::String,
+            _ if SUPPORTED_NUMERIC_PRIMITIVES.contains(&type_id) => PaxelType::Numeric(NumericKind::Integer),
+            _ => match type_id.rsplit("::").next().unwrap() {
+                "Numeric" => PaxelType::Numeric(NumericKind::Any),
+                "Size" => PaxelType::Size,
+                _ => PaxelType::Other(type_id.to_string()),
+            }
+        }
+    }
+
+    /// Whether a value of this type may be bound to a property of type `target`.
+    /// Numeric values widen (e.g. an integer expression may be bound to an `f64` property),
+    /// but floats are never implicitly truncated to integers.
+    pub fn is_assignable_to(&self, target: &PaxelType) -> bool {
+        match (self, target) {
+            (PaxelType::Unknown, _) | (_, PaxelType::Unknown) => true,
+            (PaxelType::Numeric(NumericKind::Float), PaxelType::Numeric(NumericKind::Integer)) => false,
+            (PaxelType::Numeric(_), PaxelType::Numeric(_)) => true,
+            //type_ids may or may not be fully qualified, e.g. `Transform2D` vs. `pax_lang::api::Transform2D`
+            (PaxelType::Other(found), PaxelType::Other(expected)) => {
+                found.rsplit("::").next() == expected.rsplit("::").next()
+            },
+            (found, expected) => found == expected,
+        }
+    }
+}
+
+impl fmt::Display for PaxelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaxelType::Numeric(NumericKind::Integer) => write!(f, "integer"),
+            PaxelType::Numeric(NumericKind::Float) => write!(f, "float"),
+            PaxelType::Numeric(NumericKind::Any) => write!(f, "Numeric"),
+            PaxelType::Bool => write!(f, "bool"),
+            PaxelType::String => write!(f, "String"),
+            PaxelType::Size => write!(f, "Size"),
+            PaxelType::Range => write!(f, "range"),
+            PaxelType::Other(type_id) => write!(f, "{}", type_id),
+            PaxelType::Unknown => write!(f, "_"),
+        }
+    }
+}
+
+/// Infers the type of `paxel`, checking each operator in its Pratt-parsed tree along the way,
+/// e.g. reporting `self.num_clicks + "px"`.  `resolve_symbol` returns the type_id of a symbol
+/// like `self.num_clicks`, or `None` if it can't be resolved (in which case its type is `Unknown`.)
+/// Returns the inferred type, along with any diagnostics paired with the byte range within `paxel` that each describes.
+pub fn infer_paxel_type(paxel: &str, resolve_symbol: &dyn Fn(&str) -> Option<String>) -> (PaxelType, Vec<(Diagnostic, Range<usize>)>) {
+    let pairs = PaxParser::parse(Rule::expression_body, paxel).expect(&format!("unsuccessful pratt parse {}", &paxel));
+
+    let checker = TypeChecker {
+        pratt_parser: build_pratt_parser(),
+        resolve_symbol,
+        diagnostics: RefCell::new(vec![]),
+    };
+    let (paxel_type, _) = checker.infer(pairs);
+    (paxel_type, checker.diagnostics.take())
+}
+
+struct TypeChecker<'a> {
+    pratt_parser: PrattParser<Rule>,
+    resolve_symbol: &'a dyn Fn(&str) -> Option<String>,
+    diagnostics: RefCell<Vec<(Diagnostic, Range<usize>)>>,
+}
+
+impl<'a> TypeChecker<'a> {
+
+    /// Counterpart to `recurse_pratt_parse_to_string`, returning the type of `expression` and its byte range
+    fn infer(&self, expression: Pairs<Rule>) -> (PaxelType, Range<usize>) {
+        self.pratt_parser
+            .map_primary(|primary| self.infer_primary(primary))
+            .map_prefix(|op, (rhs, rhs_range)| {
+                let range = op.as_span().start()..rhs_range.end;
+                let paxel_type = match (op.as_rule(), &rhs) {
+                    (_, PaxelType::Unknown) => PaxelType::Unknown,
+                    //as for infix operators, left to rustc, e.g. `Neg` for some other type
+                    (_, PaxelType::Other(_)) => PaxelType::Unknown,
+                    (Rule::xo_neg, PaxelType::Numeric(kind)) => PaxelType::Numeric(*kind),
+                    (Rule::xo_bool_not, PaxelType::Bool) => PaxelType::Bool,
+                    _ => {
+                        self.report(Diagnostic::unsupported_operation(&format!("unary `{}` to `{}`", op.as_str(), rhs), &format!("{}{}", op.as_str(), rhs)), range.clone());
+                        PaxelType::Unknown
+                    }
+                };
+                (paxel_type, range)
+            })
+            .map_infix(|(lhs, lhs_range), op, (rhs, rhs_range)| {
+                let range = lhs_range.start..rhs_range.end;
+                let paxel_type = self.infer_infix(op, lhs, lhs_range, rhs, range.clone());
+                (paxel_type, range)
+            })
+            .parse(expression)
+    }
+
+    fn infer_infix(&self, op: Pair<Rule>, lhs: PaxelType, lhs_range: Range<usize>, rhs: PaxelType, range: Range<usize>) -> PaxelType {
+        let op_rule = op.as_rule();
+
+        //`a ? b : c` is parsed as `(a ? b) : c`
+        if op_rule == Rule::xo_tern_then {
+            self.expect(&PaxelType::Bool, &lhs, lhs_range);
+            return rhs;
+        }
+
+        let paxel_type = match (op_rule, &lhs, &rhs) {
+            (Rule::xo_tern_else, PaxelType::Numeric(lhs_kind), PaxelType::Numeric(rhs_kind)) => Some(PaxelType::Numeric(lhs_kind.widen(*rhs_kind))),
+            (Rule::xo_tern_else, PaxelType::Unknown, _) => Some(rhs.clone()),
+            (Rule::xo_tern_else, _, PaxelType::Unknown) => Some(lhs.clone()),
+            (Rule::xo_tern_else, _, _) => {
+                if rhs.is_assignable_to(&lhs) {
+                    Some(lhs.clone())
+                } else {
+                    self.report(Diagnostic::new(
+                        DiagnosticKind::TypeMismatch,
+                        "ternary branches have incompatible types".to_string(),
+                        format!("expected `{}`, found `{}`", lhs, rhs),
+                    ), range.clone());
+                    Some(PaxelType::Unknown)
+                }
+            },
+            (_, PaxelType::Unknown, _) | (_, _, PaxelType::Unknown) => Some(PaxelType::Unknown),
+            (Rule::xo_add | Rule::xo_sub | Rule::xo_mul | Rule::xo_div | Rule::xo_mod | Rule::xo_exp, PaxelType::Numeric(lhs_kind), PaxelType::Numeric(rhs_kind)) => {
+                Some(PaxelType::Numeric(lhs_kind.widen(*rhs_kind)))
+            },
+            (Rule::xo_mul, PaxelType::Size, PaxelType::Size) => Some(PaxelType::Size),
+            (Rule::xo_bool_and | Rule::xo_bool_or, PaxelType::Bool, PaxelType::Bool) => Some(PaxelType::Bool),
+            (Rule::xo_rel_eq | Rule::xo_rel_neq | Rule::xo_rel_lt | Rule::xo_rel_lte | Rule::xo_rel_gt | Rule::xo_rel_gte, PaxelType::Numeric(_), PaxelType::Numeric(_)) => Some(PaxelType::Bool),
+            (Rule::xo_rel_eq | Rule::xo_rel_neq, _, _) if lhs.is_assignable_to(&rhs) => Some(PaxelType::Bool),
+            //other types may implement any operator, e.g. `Mul` for `Transform2D`; leave them to rustc
+            (_, PaxelType::Other(_), _) | (_, _, PaxelType::Other(_)) => Some(PaxelType::Unknown),
+            _ => None,
+        };
+
+        paxel_type.unwrap_or_else(|| {
+            let op_str = op.as_str().trim();
+            self.report(Diagnostic::unsupported_operation(&format!("`{}` to `{}` and `{}`", op_str, lhs, rhs), &format!("{} {} {}", lhs, op_str, rhs)), range);
+            PaxelType::Unknown
+        })
+    }
+
+    fn infer_primary(&self, primary: Pair<Rule>) -> (PaxelType, Range<usize>) {
+        let range = trimmed_range(&primary);
+        let paxel_type = match primary.as_rule() {
+            Rule::expression_grouped => {
+                /* expression_grouped = { "(" ~ expression_body ~ ")" ~ literal_number_unit? } */
+                let mut inner = primary.into_inner();
+                let (inner_type, inner_range) = self.infer(inner.next().unwrap().into_inner());
+                if inner.next().is_some() {
+                    //e.g. `(self.width / 2)px`
+                    self.expect(&PaxelType::Numeric(NumericKind::Any), &inner_type, inner_range);
+                    PaxelType::Size
+                } else {
+                    inner_type
+                }
+            },
+            Rule::xo_function_call => {
+                //Return types of functions aren't known statically; check only their arguments
+                primary.into_inner().filter(|pair| pair.as_rule() == Rule::xo_function_args_list).for_each(|args| {
+                    args.into_inner().for_each(|arg| { self.infer(arg.into_inner()); });
+                });
+                PaxelType::Unknown
+            },
+            Rule::xo_range_stepped => {
+                /* xo_range_stepped = { "(" ~ xo_range ~ ")" ~ "." ~ "step_by" ~ "(" ~ expression_body ~ ")" } */
+                let mut inner = primary.into_inner();
+                self.infer_primary(inner.next().unwrap());
+                let (step_type, step_range) = self.infer(inner.next().unwrap().into_inner());
+                self.expect(&PaxelType::Numeric(NumericKind::Any), &step_type, step_range);
+                PaxelType::Range
+            },
+            Rule::xo_range_reversed => {
+                /* xo_range_reversed = { "(" ~ xo_range ~ ")" ~ ("." ~ "step_by" ~ "(" ~ expression_body ~ ")")? ~ "." ~ "rev" ~ "(" ~ ")" } */
+                let mut inner = primary.into_inner();
+                self.infer_primary(inner.next().unwrap());
+                if let Some(step) = inner.next() {
+                    let (step_type, step_range) = self.infer(step.into_inner());
+                    self.expect(&PaxelType::Numeric(NumericKind::Any), &step_type, step_range);
+                }
+                PaxelType::Range
+            },
+            Rule::xo_range => {
+                /* xo_range = { (xo_literal | xo_symbol) ~ (xo_range_inclusive | xo_range_exclusive) ~ (xo_literal | xo_symbol)} */
+                primary.into_inner().filter(|pair| pair.as_rule() == Rule::xo_literal || pair.as_rule() == Rule::xo_symbol).for_each(|operand| {
+                    let (operand_type, operand_range) = self.infer_primary(operand);
+                    self.expect(&PaxelType::Numeric(NumericKind::Any), &operand_type, operand_range);
+                });
+                PaxelType::Range
+            },
+            Rule::xo_literal => {
+                let literal_kind = primary.into_inner().next().unwrap();
+                match literal_kind.as_rule() {
+                    Rule::literal_number_with_unit => PaxelType::Size,
+                    Rule::literal_number => {
+                        if literal_kind.into_inner().next().unwrap().as_rule() == Rule::literal_number_float {
+                            PaxelType::Numeric(NumericKind::Float)
+                        } else {
+                            PaxelType::Numeric(NumericKind::Integer)
+                        }
+                    },
+                    Rule::string => PaxelType::String,
+                    /* {literal_enum_value | literal_tuple_access | literal_tuple } */
+                    _ => PaxelType::Unknown,
+                }
+            },
+            Rule::xo_object | Rule::xo_tuple | Rule::xo_list => {
+                //Check nested expressions; aggregates themselves are left to rustc
+                primary.into_inner().for_each(|pair| {
+                    match pair.as_rule() {
+                        Rule::expression_body => { self.infer(pair.into_inner()); },
+                        Rule::xo_object_settings_key_value_pair => {
+                            pair.into_inner().filter(|kvp| kvp.as_rule() == Rule::expression_body).for_each(|expression_body| {
+                                self.infer(expression_body.into_inner());
+                            });
+                        },
+                        _ => {},
+                    }
+                });
+                PaxelType::Unknown
+            },
+            Rule::xo_symbol => {
+                let (symbol, _) = symbolic_id_from_pair(&primary);
+                match (self.resolve_symbol)(&symbol) {
+                    Some(type_id) => PaxelType::from_type_id(&type_id),
+                    None => PaxelType::Unknown,
+                }
+            },
+            Rule::expression_body => {
+                return self.infer(primary.into_inner());
+            },
+            _ => PaxelType::Unknown,
+        };
+        (paxel_type, range)
+    }
+
+    /// Reports a mismatch if `found` can't be used where `expected` is required
+    fn expect(&self, expected: &PaxelType, found: &PaxelType, range: Range<usize>) {
+        if !found.is_assignable_to(expected) {
+            self.report(Diagnostic::type_mismatch(&expected.to_string(), &found.to_string()), range);
+        }
+    }
+
+    fn report(&self, diagnostic: Diagnostic, range: Range<usize>) {
+        self.diagnostics.borrow_mut().push((diagnostic, range));
+    }
+}
+
+/// The byte range of `pair`, sans any trailing whitespace
+fn trimmed_range(pair: &Pair<Rule>) -> Range<usize> {
+    let start = pair.as_span().start();
+    start..(start + pair.as_str().trim_end().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::typing::{infer_paxel_type, NumericKind, PaxelType};
+
+    fn resolve_symbol(symbol: &str) -> Option<String> {
+        match symbol {
+            "self.num_clicks" => Some("usize".to_string()),
+            "self.is_selected" => Some("bool".to_string()),
+            "self.transform_a" | "self.transform_b" => Some("pax_lang::api::Transform2D".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_infer_paxel_type() {
+        let (paxel_type, diagnostics) = infer_paxel_type("self.num_clicks * 2.5 + 1", &resolve_symbol);
+        assert_eq!(paxel_type, PaxelType::Numeric(NumericKind::Float));
+        assert!(diagnostics.is_empty());
+
+        let (paxel_type, diagnostics) = infer_paxel_type("self.is_selected ? 1 : 2", &resolve_symbol);
+        assert_eq!(paxel_type, PaxelType::Numeric(NumericKind::Integer));
+        assert!(diagnostics.is_empty());
+
+        let (_, diagnostics) = infer_paxel_type("self.num_clicks + \"px\"", &resolve_symbol);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1, 0..22);
+
+        //unresolved symbols are reported elsewhere; their type is simply unknown
+        let (paxel_type, diagnostics) = infer_paxel_type("not_defined + 6", &resolve_symbol);
+        assert_eq!(paxel_type, PaxelType::Unknown);
+        assert!(diagnostics.is_empty());
+
+        //operators on other types are left to rustc, e.g. `Mul` for `Transform2D`
+        let (paxel_type, diagnostics) = infer_paxel_type("self.transform_a * self.transform_b", &resolve_symbol);
+        assert_eq!(paxel_type, PaxelType::Unknown);
+        assert!(diagnostics.is_empty());
+
+        //while operators on known types remain checked
+        let (_, diagnostics) = infer_paxel_type("self.is_selected + 1", &resolve_symbol);
+        assert_eq!(diagnostics.len(), 1);
+
+        assert!(!PaxelType::Numeric(NumericKind::Float).is_assignable_to(&PaxelType::from_type_id("isize")));
+        assert!(PaxelType::Numeric(NumericKind::Integer).is_assignable_to(&PaxelType::from_type_id("f64")));
+    }
+}
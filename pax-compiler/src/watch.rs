@@ -0,0 +1,385 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde_derive::{Serialize, Deserialize};
+use walkdir::WalkDir;
+
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
+use crate::formatting::find_inlined_pax;
+use crate::manifest::{ComponentDefinition, PaxManifest, INLINED_SOURCE_SUFFIX};
+use crate::parsing::{assemble_component_definition, ParsingContext, TYPE_ID_IF, TYPE_ID_MATCH, TYPE_ID_REPEAT, TYPE_ID_SLOT};
+
+/// Path of the manifest cache, relative to the .pax directory
+pub const MANIFEST_CACHE_PATH: &str = "manifest-cache.json";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Directories never watched for changes, e.g. build output
+const IGNORED_DIRECTORIES: [&str; 4] = [".pax", "target", "build", "node_modules"];
+
+/// The manifest from the last run of the parser binary (before expression compilation), along with a fingerprint
+/// of the Rust it was parsed from.  While the fingerprint holds, changes to templates can be applied to the cached
+/// manifest directly, without re-running the parser binary.
+#[derive(Serialize, Deserialize)]
+pub struct ManifestCache {
+    pub rust_fingerprint: u64,
+    pub manifest: PaxManifest,
+}
+
+impl ManifestCache {
+    pub fn load(pax_dir: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(pax_dir.join(MANIFEST_CACHE_PATH)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, pax_dir: &Path) {
+        fs::write(pax_dir.join(MANIFEST_CACHE_PATH), serde_json::to_string(self).unwrap()).unwrap();
+    }
+}
+
+/// Hashes the host crate's `Cargo.toml` and Rust sources, sans the contents of `#[inlined(...)]` attributes, so that
+/// edits to inlined templates don't count as changes to Rust
+pub fn fingerprint_rust_sources(path: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    fs::read_to_string(Path::new(path).join("Cargo.toml")).unwrap_or_default().hash(&mut hasher);
+
+    let mut rust_paths : Vec<PathBuf> = walk_sources(path)
+        .filter(|source_path| source_path.extension().map(|extension| extension == "rs").unwrap_or(false))
+        .collect();
+    rust_paths.sort();
+    for rust_path in rust_paths {
+        let source = fs::read_to_string(&rust_path).unwrap_or_default();
+        let mut cursor = 0;
+        for inlined_range in find_inlined_pax(&source) {
+            source[cursor..inlined_range.start].hash(&mut hasher);
+            cursor = inlined_range.end;
+        }
+        source[cursor..].hash(&mut hasher);
+        rust_path.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The .rs and .pax files of the host crate, plus its `Cargo.toml`
+fn walk_sources(path: &str) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(path).into_iter()
+        .filter_entry(|entry| {
+            let file_name = entry.file_name().to_str().unwrap_or("");
+            entry.depth() == 0 || !(IGNORED_DIRECTORIES.contains(&file_name) || file_name.starts_with('.'))
+        })
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|source_path| {
+            match source_path.extension().and_then(|extension| extension.to_str()) {
+                Some("rs") | Some("pax") => true,
+                _ => source_path.file_name().map(|file_name| file_name == "Cargo.toml").unwrap_or(false),
+            }
+        })
+}
+
+fn snapshot_sources(path: &str) -> HashMap<PathBuf, SystemTime> {
+    walk_sources(path)
+        .filter_map(|source_path| {
+            let modified = fs::metadata(&source_path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((source_path, modified))
+        })
+        .collect()
+}
+
+/// Watches the host crate's sources by polling, e.g. for `pax run --watch`
+pub struct SourceWatcher {
+    path: String,
+    snapshot: HashMap<PathBuf, SystemTime>,
+}
+
+impl SourceWatcher {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            snapshot: snapshot_sources(path),
+        }
+    }
+
+    /// Blocks until sources change, then (once they've settled, e.g. after an editor's save) returns the paths of
+    /// the files that were modified, added or removed
+    pub fn wait_for_changes(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let mut snapshot = snapshot_sources(&self.path);
+            if snapshot == self.snapshot {
+                continue;
+            }
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let settled = snapshot_sources(&self.path);
+                if settled == snapshot {
+                    break;
+                }
+                snapshot = settled;
+            }
+
+            let mut changed_paths : Vec<PathBuf> = snapshot.iter()
+                .filter(|(source_path, modified)| self.snapshot.get(*source_path) != Some(modified))
+                .map(|(source_path, _)| source_path.clone())
+                .chain(self.snapshot.keys().filter(|source_path| !snapshot.contains_key(*source_path)).cloned())
+                .collect();
+            changed_paths.sort();
+            self.snapshot = snapshot;
+            return changed_paths;
+        }
+    }
+}
+
+pub enum TemplateUpdate {
+    /// Templates were re-parsed into the manifest, with any errors found in them
+    Updated(Vec<Diagnostic>),
+    /// None of the changed files declare a template
+    Unaffected,
+    /// The changes can't be applied to the cached manifest, e.g. a template now uses a component it didn't before
+    RequiresFullBuild,
+}
+
+/// Re-parses the templates declared in `changed_paths` (.pax files and `#[inlined(...)]` attributes) into `manifest`,
+/// assuming that the host crate's Rust is otherwise unchanged since the manifest was parsed
+pub fn update_templates_in_place(manifest: &mut PaxManifest, path: &str, changed_paths: &[PathBuf]) -> TemplateUpdate {
+    let project_root = Path::new(path);
+    let mut diagnostics = vec![];
+    let mut is_affected = false;
+
+    for changed_path in changed_paths {
+        let source = match fs::read_to_string(changed_path) {
+            Ok(source) => source,
+            Err(_) => return TemplateUpdate::RequiresFullBuild,
+        };
+
+        //pair each changed template with the type_id of its component
+        let templates : Vec<(String, String)> = match changed_path.extension().and_then(|extension| extension.to_str()) {
+            Some("pax") => manifest.components.values()
+                .filter(|component| component.pax_source_path.as_ref().map(|pax_source_path| is_same_file(project_root, pax_source_path, changed_path)).unwrap_or(false))
+                .map(|component| (component.type_id.clone(), source.clone()))
+                .collect(),
+            Some("rs") => find_inlined_pax(&source).into_iter().filter_map(|inlined_range| {
+                let pascal_identifier = find_struct_name(&source[inlined_range.end..])?;
+                manifest.components.values()
+                    .find(|component| component.pascal_identifier == pascal_identifier && is_inlined_in(project_root, component, changed_path))
+                    .map(|component| (component.type_id.clone(), source[inlined_range].to_string()))
+            }).collect(),
+            _ => return TemplateUpdate::RequiresFullBuild,
+        };
+
+        for (type_id, pax) in templates {
+            let previous = manifest.components.get(&type_id).unwrap();
+            if previous.pax_source.as_deref() == Some(pax.as_str()) {
+                continue;
+            }
+            is_affected = true;
+
+            //Tags may only resolve to the components the template already used, since the parser binary generates
+            //the parsing (and importing) of each component a template depends on
+            let template_map : HashMap<String, String> = previous.template.iter().flatten()
+                .filter(|tnd| ![TYPE_ID_IF, TYPE_ID_REPEAT, TYPE_ID_SLOT, TYPE_ID_MATCH].contains(&tnd.type_id.as_str()))
+                .map(|tnd| (tnd.pascal_identifier.clone(), tnd.type_id.clone()))
+                .collect();
+
+            let (ctx, mut component_definition) = assemble_component_definition(
+                ParsingContext::default(),
+                &pax,
+                &previous.pascal_identifier,
+                previous.is_main_component,
+                template_map,
+                &previous.module_path,
+                &type_id,
+                previous.pax_source_path.as_deref(),
+            );
+            if ctx.diagnostics.iter().any(|diagnostic| diagnostic.kind == DiagnosticKind::UnknownComponent) {
+                return TemplateUpdate::RequiresFullBuild;
+            }
+            component_definition.rust_source_path = previous.rust_source_path.clone();
+            diagnostics.extend(ctx.diagnostics);
+            manifest.components.insert(type_id, component_definition);
+        }
+    }
+
+    if is_affected {
+        TemplateUpdate::Updated(diagnostics)
+    } else {
+        TemplateUpdate::Unaffected
+    }
+}
+
+/// Whether `relative_path` (relative to the project root, as recorded by the parser) refers to `path`
+fn is_same_file(project_root: &Path, relative_path: &str, path: &Path) -> bool {
+    match (fs::canonicalize(project_root.join(relative_path)), fs::canonicalize(path)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn is_inlined_in(project_root: &Path, component: &ComponentDefinition, path: &Path) -> bool {
+    let is_inlined = component.pax_source_path.as_ref().map(|pax_source_path| pax_source_path.ends_with(INLINED_SOURCE_SUFFIX)).unwrap_or(false);
+    let rust_source_path = component.rust_source_path.as_ref();
+    is_inlined && rust_source_path.map(|rust_source_path| is_same_file(project_root, rust_source_path, path)).unwrap_or(false)
+}
+
+/// Returns the name of the first struct declared in `rust_source`, e.g. the struct an `#[inlined(...)]` attribute decorates,
+/// skipping over comments and string literals, e.g. doc comments that mention another struct
+fn find_struct_name(rust_source: &str) -> Option<String> {
+    let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut previous = ' ';
+    let mut chars = rust_source.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => { chars.next(); },
+                        '"' => break,
+                        _ => {},
+                    }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' { break; }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, c) in chars.by_ref() {
+                    if previous == '*' && c == '/' { break; }
+                    previous = c;
+                }
+            },
+            's' if !is_identifier_char(previous) && rust_source[i..].starts_with("struct") => {
+                let rest = &rust_source[i + "struct".len()..];
+                if rest.starts_with(char::is_whitespace) {
+                    let name : String = rest.trim_start().chars().take_while(|c| is_identifier_char(*c)).collect();
+                    return Some(name).filter(|name| !name.is_empty());
+                }
+            },
+            _ => {},
+        }
+        previous = c;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::fs;
+    use std::path::Path;
+    use std::slice;
+
+    use crate::manifest::{get_primitive_type_table, PaxManifest, INLINED_SOURCE_SUFFIX};
+    use crate::parsing::{assemble_component_definition, assemble_primitive_definition, ParsingContext};
+    use crate::watch::{find_struct_name, update_templates_in_place, TemplateUpdate};
+
+    #[test]
+    fn test_find_struct_name() {
+        let cases = [
+            (")]\n#[derive(Pax)]\npub struct Example {}", Some("Example")),
+            ("struct Unit;", Some("Unit")),
+            //mentions of other structs in comments and strings
+            (")]\n/// Wraps a struct Other\n#[derive(Pax)]\npub struct Example {}", Some("Example")),
+            ("// struct Other\n/* struct Other, /* */ struct Example", Some("Example")),
+            ("#[doc = \"a \\\"struct Other\\\"\"]\npub struct Example {}", Some("Example")),
+            //`struct` only as a keyword
+            ("mod destruct {}\nstruct Example", Some("Example")),
+            ("pub struct_like: usize,\npub(crate) struct Example", Some("Example")),
+            ("pub enum Example {}", None),
+            ("struct {", None),
+        ];
+        for (rust_source, expected) in cases {
+            assert_eq!(find_struct_name(rust_source).as_deref(), expected, "{:?}", rust_source);
+        }
+    }
+
+    const LIB_RS : &str = "#[derive(Pax)]\n#[inlined(<Text />)]\n/// Displayed alongside struct Sidebar\npub struct Example {}\n\n#[derive(Pax)]\n#[inlined(<Group />)]\npub struct Sidebar {}\n";
+
+    /// The manifest of the project written by `write_project`, with `Example` and `Sidebar` inlined in `src/lib.rs` and
+    /// `Card` in `src/card.pax`
+    fn parse_project() -> PaxManifest {
+        let template_map : HashMap<String, String> = [("Text", "pax_std::primitives::Text"), ("Group", "pax_std::primitives::Group")].into_iter()
+            .map(|(pascal_identifier, type_id)| (pascal_identifier.to_string(), type_id.to_string()))
+            .collect();
+        let inlined_source_path = format!("src/lib.rs{}", INLINED_SOURCE_SUFFIX);
+        let mut components = HashMap::new();
+        for (pascal_identifier, pax, pax_source_path) in [
+            ("Example", "<Text />", inlined_source_path.as_str()),
+            ("Sidebar", "<Group />", inlined_source_path.as_str()),
+            ("Card", "<Text />\n", "src/card.pax"),
+        ] {
+            let type_id = format!("crate::{}", pascal_identifier);
+            let (_, mut component) = assemble_component_definition(ParsingContext::default(), pax, pascal_identifier, pascal_identifier == "Example", template_map.clone(), "crate", &type_id, Some(pax_source_path));
+            component.rust_source_path = Some("src/lib.rs".to_string());
+            components.insert(type_id, component);
+        }
+        for (pascal_identifier, type_id) in template_map {
+            components.insert(type_id.clone(), assemble_primitive_definition(&pascal_identifier, "pax_std::primitives", format!("{}Instance", pascal_identifier), &type_id));
+        }
+
+        PaxManifest {
+            components,
+            main_component_type_id: "crate::Example".to_string(),
+            expression_specs: None,
+            type_table: get_primitive_type_table(),
+            import_paths: HashSet::new(),
+        }
+    }
+
+    fn write_project(project_root: &Path) {
+        fs::create_dir_all(project_root.join("src")).unwrap();
+        fs::write(project_root.join("src/lib.rs"), LIB_RS).unwrap();
+        fs::write(project_root.join("src/card.pax"), "<Text />\n").unwrap();
+        fs::write(project_root.join("Cargo.toml"), "").unwrap();
+    }
+
+    fn pax_source<'a>(manifest: &'a PaxManifest, pascal_identifier: &str) -> &'a str {
+        manifest.components[&format!("crate::{}", pascal_identifier)].pax_source.as_deref().unwrap()
+    }
+
+    #[test]
+    fn test_update_templates_in_place() {
+        let project_root = std::env::temp_dir().join(format!("pax-watch-{}", std::process::id()));
+        write_project(&project_root);
+        let path = project_root.to_str().unwrap();
+        let lib_rs = project_root.join("src/lib.rs");
+        let card_pax = project_root.join("src/card.pax");
+        let mut manifest = parse_project();
+
+        //sources whose templates are unchanged
+        assert!(matches!(update_templates_in_place(&mut manifest, path, &[lib_rs.clone(), card_pax.clone()]), TemplateUpdate::Unaffected));
+
+        //each inlined template is attributed to the struct it decorates, despite the doc comment naming `Sidebar`
+        fs::write(&lib_rs, LIB_RS.replace("<Text />", "<Text /><Text />")).unwrap();
+        assert!(matches!(update_templates_in_place(&mut manifest, path, slice::from_ref(&lib_rs)), TemplateUpdate::Updated(diagnostics) if diagnostics.is_empty()));
+        assert_eq!(pax_source(&manifest, "Example"), "<Text /><Text />");
+        assert_eq!(pax_source(&manifest, "Sidebar"), "<Group />");
+        let example = &manifest.components["crate::Example"];
+        assert_eq!(example.template.as_ref().unwrap()[0].child_ids.len(), 2);
+        assert_eq!(example.rust_source_path.as_deref(), Some("src/lib.rs"));
+
+        fs::write(&card_pax, "<Text />\n<Text />\n").unwrap();
+        assert!(matches!(update_templates_in_place(&mut manifest, path, slice::from_ref(&card_pax)), TemplateUpdate::Updated(_)));
+        assert_eq!(pax_source(&manifest, "Card"), "<Text />\n<Text />\n");
+
+        //errors are reported, rather than forcing a rebuild
+        fs::write(&card_pax, "<Text>\n").unwrap();
+        assert!(matches!(update_templates_in_place(&mut manifest, path, slice::from_ref(&card_pax)), TemplateUpdate::Updated(diagnostics) if !diagnostics.is_empty()));
+
+        //components a template didn't already use are only imported by a full build, as are changes to Rust and removed files
+        fs::write(&card_pax, "<Group />\n").unwrap();
+        assert!(matches!(update_templates_in_place(&mut manifest, path, slice::from_ref(&card_pax)), TemplateUpdate::RequiresFullBuild));
+        assert!(matches!(update_templates_in_place(&mut manifest, path, &[project_root.join("Cargo.toml")]), TemplateUpdate::RequiresFullBuild));
+        assert!(matches!(update_templates_in_place(&mut manifest, path, &[project_root.join("src/removed.pax")]), TemplateUpdate::RequiresFullBuild));
+
+        fs::remove_dir_all(&project_root).unwrap();
+    }
+}
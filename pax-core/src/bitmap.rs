@@ -0,0 +1,40 @@
+//! Reads bitmaps for the chassis that render with piet's cairo backend (headless and Linux), enabled by the `cairo` feature.
+
+use std::fs::File;
+use std::path::Path;
+
+use cairo::{Format, ImageSurface};
+
+/// Reads a PNG into non-premultiplied RGBA, as expected by `PaxEngine::loadImage`
+pub fn read_png_as_rgba(path: &Path) -> Result<(Vec<u8>, usize, usize), String> {
+    let mut file = File::open(path).map_err(|error| error.to_string())?;
+    let surface = ImageSurface::create_from_png(&mut file).map_err(|error| error.to_string())?;
+    surface_to_rgba(&surface)
+}
+
+/// Converts cairo's native-endian, premultiplied ARGB32 into non-premultiplied RGBA, along with its width and height
+pub fn surface_to_rgba(surface: &ImageSurface) -> Result<(Vec<u8>, usize, usize), String> {
+    let width = surface.width() as usize;
+    let height = surface.height() as usize;
+    let stride = surface.stride() as usize;
+    let has_alpha = surface.format() == Format::ARgb32;
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    surface.with_data(|data| {
+        for row in 0..height {
+            for column in 0..width {
+                let offset = row * stride + column * 4;
+                let pixel = u32::from_ne_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+                let alpha = if has_alpha { (pixel >> 24) & 0xff } else { 0xff };
+                let unpremultiply = |channel: u32| -> u8 {
+                    (channel * 255 + alpha / 2).checked_div(alpha).map_or(0, |channel| channel.min(255) as u8)
+                };
+                rgba.push(unpremultiply((pixel >> 16) & 0xff));
+                rgba.push(unpremultiply((pixel >> 8) & 0xff));
+                rgba.push(unpremultiply(pixel & 0xff));
+                rgba.push(alpha as u8);
+            }
+        }
+    }).map_err(|error| error.to_string())?;
+    Ok((rgba, width, height))
+}
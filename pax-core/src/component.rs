@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -44,6 +45,10 @@ impl<R: 'static + RenderContext> RenderNode<R> for ComponentInstance<R> {
         }
     }
 
+    fn get_properties_any(&self) -> Option<Rc<dyn Any>> {
+        Some(Rc::clone(&self.properties) as Rc<dyn Any>)
+    }
+
     fn handle_did_render(&mut self, rtc: &mut RenderTreeContext<R>, _rcs: &mut Vec<R>) {
         (*rtc.runtime).borrow_mut().pop_stack_frame();
     }
@@ -10,13 +10,17 @@ use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 /// subtree of a component template to be rendered conditionally,
 /// based on the value of the property `boolean_expression`.
 /// The Pax compiler handles ConditionalInstance specially
-/// with the `if` syntax in templates.
+/// with the `if` syntax in templates.  An `else` branch populates
+/// `false_branch_children`; `else if` is a nested ConditionalInstance
+/// as the sole member of `false_branch_children`.  Exactly one branch is mounted at a time.
 pub struct ConditionalInstance<R: 'static + RenderContext> {
     pub instance_id: u64,
 
     pub boolean_expression: Box<dyn PropertyInstance<bool>>,
     pub true_branch_children: RenderNodePtrList<R>,
     pub false_branch_children: RenderNodePtrList<R>,
+    /// Value of `boolean_expression` for the currently mounted branch; `None` until its first evaluation
+    pub active_branch: Option<bool>,
     pub next_frame_expression: Option<bool>,
 
     pub transform: Rc<RefCell<dyn PropertyInstance<Transform2D>>>,
@@ -39,7 +43,11 @@ impl<R: 'static + RenderContext> RenderNode<R> for ConditionalInstance<R> {
             },
             transform: args.transform,
             boolean_expression: args.conditional_boolean_expression.expect("Conditional requires boolean_expression"),
-            false_branch_children: Rc::new(RefCell::new(vec![])),
+            false_branch_children: match args.conditional_false_branch_children {
+                None => {Rc::new(RefCell::new(vec![]))}
+                Some(children) => children
+            },
+            active_branch: None,
             next_frame_expression: None
         }));
 
@@ -49,25 +57,36 @@ impl<R: 'static + RenderContext> RenderNode<R> for ConditionalInstance<R> {
 
     fn compute_properties(&mut self, rtc: &mut RenderTreeContext<R>) {
 
-        if self.next_frame_expression.is_some() {
-            self.boolean_expression.set(self.next_frame_expression.clone().unwrap());
-            self.next_frame_expression = None;
+        if let Some(next_frame_expression) = self.next_frame_expression.take() {
+            self.boolean_expression.set(next_frame_expression);
+            self.active_branch = Some(next_frame_expression);
         }
 
         if let Some(boolean_expression) = rtc.compute_vtable_value(self.boolean_expression._get_vtable_id()) {
-            let old_value = *self.boolean_expression.get();
             let new_value = if let TypesCoproduct::bool(v) = boolean_expression { v } else { unreachable!() };
 
-            let mut instance_registry = (*rtc.engine.instance_registry).borrow_mut();
-            if old_value && !new_value {
-                (*self.true_branch_children).borrow_mut().iter().for_each(|child| {
-                    let instance_id = (*(*child)).borrow_mut().get_instance_id();
-                    instance_registry.deregister(instance_id);
-                    instance_registry.mark_for_unmount(instance_id);
-                });
+            match self.active_branch {
+                //nothing is mounted yet, so the first evaluation applies immediately, rather than mounting the
+                //other branch for a frame
+                None => {
+                    self.boolean_expression.set(new_value);
+                    self.active_branch = Some(new_value);
+                },
+                Some(old_value) if old_value != new_value => {
+                    //unmount the outgoing branch, whichever direction we're switching
+                    let outgoing_branch_children = if old_value { &self.true_branch_children } else { &self.false_branch_children };
+                    let mut instance_registry = (*rtc.engine.instance_registry).borrow_mut();
+                    (*outgoing_branch_children).borrow_mut().iter().for_each(|child| {
+                        let instance_id = (*(*child)).borrow_mut().get_instance_id();
+                        instance_registry.deregister(instance_id);
+                        instance_registry.mark_for_unmount(instance_id);
+                    });
+                    //the incoming branch mounts on the next tick
+                    self.next_frame_expression = Some(new_value);
+                    rtc.engine.request_render();
+                },
+                Some(_) => {},
             }
-
-            self.next_frame_expression = Some(new_value);
         }
     }
 
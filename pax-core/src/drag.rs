@@ -0,0 +1,189 @@
+//! Pointer capture and drag gestures, for pointers pressed with `mouse_down` or `touch_start`.
+//!
+//! A `mouse_down` or `touch_start` handler may capture the pointer (see `PointerCapture`), after which the pointer's moves
+//! and release are dispatched to the capturing element rather than whatever lies beneath the pointer.
+//!
+//! Independently, once a pressed pointer moves `DRAG_THRESHOLD` px from where it was pressed, `drag_start` is dispatched
+//! to the element pressed, followed by `drag` upon each move and `drag_end` upon release, wherever the pointer goes.
+//! A handler calling `prevent_default` on the press prevents the drag, as in the DOM.
+//!
+//! A `drag_start` handler may set a payload on the drag's `DragTransfer`, making it a drag-and-drop: the element beneath
+//! the pointer, passing through the element dragged (see `PaxEngine::get_topmost_element_beneath_ray_excluding`), is
+//! then tracked as the drop target.  As for hover, `drag_leave` and `drag_enter` are dispatched to the elements left and
+//! entered as it changes, while `drag_over` is dispatched to the target upon each move and `drop` upon release,
+//! propagating to ancestors.  Upon release, `drag_leave` is dispatched to the elements still entered, after any `drop`.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+
+use pax_runtime_api::{ArgsDrag, ArgsDragEnd, ArgsDragEnter, ArgsDragLeave, ArgsDragOver, ArgsDragStart, ArgsDrop, DragEventArgs, DragTransfer, EventArgs, EventControl};
+
+use crate::{EventHandler, HandlerRegistry, InstanceRegistry, PointerId, RepeatExpandedNode};
+
+/// The distance in px, in the viewport, that a pressed pointer moves before it begins to drag
+pub const DRAG_THRESHOLD: f64 = 3.0;
+
+struct Press<R: 'static + RenderContext> {
+    /// Where the pointer was pressed, in the viewport
+    origin: (f64, f64),
+    /// The element pressed followed by its ancestors, as in `hover`
+    path: Vec<Rc<RepeatExpandedNode<R>>>,
+    /// The pointer's most recent position, in the viewport
+    position: (f64, f64),
+    is_dragging: bool,
+    /// Shared with every handler of the drag, through which `drag_start` handlers set its payload
+    transfer: DragTransfer,
+    /// The drop target followed by its ancestors, while dragging a payload
+    drop_path: Vec<Rc<RepeatExpandedNode<R>>>,
+}
+
+pub struct DragTracker<R: 'static + RenderContext> {
+    presses: HashMap<PointerId, Press<R>>,
+    /// The id chains of the elements capturing each pointer
+    captures: HashMap<PointerId, Vec<u64>>,
+}
+
+impl<R: 'static + RenderContext> Default for DragTracker<R> {
+    fn default() -> Self {
+        DragTracker {
+            presses: HashMap::new(),
+            captures: HashMap::new(),
+        }
+    }
+}
+
+impl<R: 'static + RenderContext> DragTracker<R> {
+    /// Records that `pointer` was pressed at `origin` on `target`, which it may go on to drag
+    pub fn press(&mut self, pointer: PointerId, origin: (f64, f64), target: &Rc<RepeatExpandedNode<R>>) {
+        self.presses.insert(pointer, Press {
+            origin,
+            path: RepeatExpandedNode::get_path(target),
+            position: origin,
+            is_dragging: false,
+            transfer: DragTransfer::default(),
+            drop_path: vec![],
+        });
+    }
+
+    pub fn set_capture(&mut self, pointer: PointerId, id_chain: Vec<u64>) {
+        self.captures.insert(pointer, id_chain);
+    }
+
+    /// The element capturing `pointer`, as rendered by the most recent tick, if any
+    pub fn get_capture(&self, pointer: PointerId, instance_registry: &InstanceRegistry<R>) -> Option<Rc<RepeatExpandedNode<R>>> {
+        self.captures.get(&pointer).and_then(|id_chain| instance_registry.get_repeat_expanded_node(id_chain))
+    }
+
+    /// The id chain of the element dragged by `pointer`, if it's dragging a payload
+    pub fn get_payload_source(&self, pointer: PointerId) -> Option<Vec<u64>> {
+        self.presses.get(&pointer)
+            .filter(|press| press.is_dragging && press.transfer.has_payload())
+            .map(|press| press.path[0].get_id_chain().clone())
+    }
+
+    /// The id chain of an element dragged with `DragTransfer::show_ghost`, if any, and how far it's been dragged in the viewport
+    pub fn get_ghost(&self) -> Option<(&Vec<u64>, (f64, f64))> {
+        self.presses.values()
+            .find(|press| press.is_dragging && press.transfer.is_ghost_shown())
+            .map(|press| (press.path[0].get_id_chain(), (press.position.0 - press.origin.0, press.position.1 - press.origin.1)))
+    }
+
+    /// Dispatches `drag_start` or `drag` if `pointer` is pressed and has moved far enough to drag
+    pub fn move_pointer(&mut self, pointer: PointerId, position: (f64, f64), instance_registry: &InstanceRegistry<R>) {
+        let press = match self.presses.get_mut(&pointer) {
+            Some(press) => press,
+            None => return,
+        };
+        press.position = position;
+        if !press.is_dragging {
+            let distance = (position.0 - press.origin.0).hypot(position.1 - press.origin.1);
+            if distance < DRAG_THRESHOLD {
+                return;
+            }
+            press.is_dragging = true;
+            let drag = press.get_drag_event_args(position, instance_registry);
+            press.dispatch(instance_registry, ArgsDragStart { drag, transfer: press.transfer.clone(), control: EventControl::default() },
+                |registry| &registry.drag_start_capture_handlers, |registry| &registry.drag_start_handlers);
+        }
+        let drag = press.get_drag_event_args(position, instance_registry);
+        press.dispatch(instance_registry, ArgsDrag { drag, transfer: press.transfer.clone(), control: EventControl::default() },
+            |registry| &registry.drag_capture_handlers, |registry| &registry.drag_handlers);
+    }
+
+    /// Records that `target` is the drop target beneath `pointer` at `position`, while dragging a payload, dispatching
+    /// `drag_leave` and `drag_enter` if it wasn't before, then `drag_over`
+    pub fn set_drop_target(&mut self, pointer: PointerId, position: (f64, f64), target: Option<Rc<RepeatExpandedNode<R>>>, instance_registry: &InstanceRegistry<R>) {
+        let press = match self.presses.get_mut(&pointer) {
+            Some(press) => press,
+            None => return,
+        };
+        let drop_path = target.map(|target| RepeatExpandedNode::get_path(&target)).unwrap_or_default();
+        let drag = press.get_drag_event_args(position, instance_registry);
+        let is_within = |path: &[Rc<RepeatExpandedNode<R>>], node: &RepeatExpandedNode<R>| {
+            path.iter().any(|other| other.get_id_chain() == node.get_id_chain())
+        };
+        for node in press.drop_path.iter().filter(|node| !is_within(&drop_path, node)) {
+            node.dispatch_drag_leave(ArgsDragLeave { drag: drag.clone(), transfer: press.transfer.clone(), control: EventControl::default() });
+        }
+        for node in drop_path.iter().rev().filter(|node| !is_within(&press.drop_path, node)) {
+            node.dispatch_drag_enter(ArgsDragEnter { drag: drag.clone(), transfer: press.transfer.clone(), control: EventControl::default() });
+        }
+        press.drop_path = drop_path;
+        if !press.drop_path.is_empty() {
+            let drop_path : Vec<&RepeatExpandedNode<R>> = press.drop_path.iter().map(|node| node.as_ref()).collect();
+            RepeatExpandedNode::dispatch_along_path(&drop_path, ArgsDragOver { drag, transfer: press.transfer.clone(), control: EventControl::default() },
+                |registry| &registry.drag_over_capture_handlers, |registry| &registry.drag_over_handlers);
+        }
+    }
+
+    /// Ends any capture and press of `pointer`, dispatching `drop` and `drag_leave` to any drop target, then `drag_end`,
+    /// if it was dragging
+    pub fn release_pointer(&mut self, pointer: PointerId, position: (f64, f64), instance_registry: &InstanceRegistry<R>) {
+        self.captures.remove(&pointer);
+        if let Some(press) = self.presses.remove(&pointer) {
+            if press.is_dragging {
+                let drag = press.get_drag_event_args(position, instance_registry);
+                if !press.drop_path.is_empty() {
+                    let drop_path : Vec<&RepeatExpandedNode<R>> = press.drop_path.iter().map(|node| node.as_ref()).collect();
+                    RepeatExpandedNode::dispatch_along_path(&drop_path, ArgsDrop { drag: drag.clone(), transfer: press.transfer.clone(), control: EventControl::default() },
+                        |registry| &registry.drop_capture_handlers, |registry| &registry.drop_handlers);
+                    for node in press.drop_path.iter() {
+                        node.dispatch_drag_leave(ArgsDragLeave { drag: drag.clone(), transfer: press.transfer.clone(), control: EventControl::default() });
+                    }
+                }
+                press.dispatch(instance_registry, ArgsDragEnd { drag, transfer: press.transfer.clone(), control: EventControl::default() },
+                    |registry| &registry.drag_end_capture_handlers, |registry| &registry.drag_end_handlers);
+            }
+        }
+    }
+}
+
+impl<R: 'static + RenderContext> Press<R> {
+    /// The element pressed followed by its ancestors, as rendered by the most recent tick if it still is
+    fn get_current_path(&self, instance_registry: &InstanceRegistry<R>) -> Vec<Rc<RepeatExpandedNode<R>>> {
+        instance_registry.get_repeat_expanded_node(self.path[0].get_id_chain())
+            .map(|node| RepeatExpandedNode::get_path(&node))
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    fn get_drag_event_args(&self, position: (f64, f64), instance_registry: &InstanceRegistry<R>) -> DragEventArgs {
+        let target = &self.get_current_path(instance_registry)[0];
+        let origin = target.get_local_point(self.origin);
+        let local = target.get_local_point(position);
+        DragEventArgs { x: position.0, y: position.1, delta_x: local.0 - origin.0, delta_y: local.1 - origin.1 }
+    }
+
+    fn dispatch<A: EventArgs>(
+        &self,
+        instance_registry: &InstanceRegistry<R>,
+        args: A,
+        get_capture_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+        get_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+    ) {
+        let path = self.get_current_path(instance_registry);
+        let path : Vec<&RepeatExpandedNode<R>> = path.iter().map(|node| node.as_ref()).collect();
+        RepeatExpandedNode::dispatch_along_path(&path, args, get_capture_handlers, get_handlers);
+    }
+}
@@ -1,35 +1,57 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::env::Args;
 use std::rc::{Rc, Weak};
 use std::thread::sleep;
 use std::time::Duration;
-use kurbo::Point;
+use kurbo::{Point, Rect};
 
 use pax_message::{LayerAddPatch, NativeMessage};
+use pax_message::hot_reload::HotReloadPatch;
 
 use piet_common::RenderContext;
 
-use crate::{Affine, ComponentInstance, Color, ComputableTransform, RenderNodePtr, ExpressionContext, RenderNodePtrList, RenderNode, TransformAndBounds, StackFrame, ScrollerArgs};
+use crate::{Affine, ComponentInstance, Color, ComputableTransform, RenderNodePtr, ExpressionContext, ExpressionInput, CachedExpression, ExpressionStats, RenderNodePtrList, RenderNode, TransformAndBounds, StackFrame, ScrollerArgs};
 use crate::runtime::{Runtime};
+use crate::hot_reload::{HotReloadCartridge, instantiate_hot_reload_template, interpret_expression};
+use crate::hover::{HoverTracker, PointerId};
+use crate::focus::{FocusTracker, get_template_order};
+use crate::drag::DragTracker;
+use crate::gesture::{GestureThresholds, GestureTracker};
+use crate::spatial::SpatialIndex;
 use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 use pax_message::NativeMessage::LayerAdd;
 
-use pax_runtime_api::{ArgsClick, ArgsJab, ArgsScroll, ArgsTouchStart, ArgsTouchMove, ArgsTouchEnd, ArgsKeyDown, ArgsKeyUp, ArgsKeyPress, ArgsMouseDown, ArgsMouseUp, ArgsMouseOver, ArgsMouseOut, ArgsDoubleClick, ArgsContextMenu, ArgsWheel, Interpolatable, TransitionManager, Layer, LayerInfo, RuntimeContext, ArgsMouseMove};
+use pax_runtime_api::{mint_property_version, peek_property_version, ArgsClick, ArgsJab, ArgsScroll, ArgsTouchStart, ArgsTouchMove, ArgsTouchEnd, ArgsKeyDown, ArgsKeyUp, ArgsKeyPress, ArgsMouseDown, ArgsMouseUp, ArgsMouseOver, ArgsMouseOut, ArgsDoubleClick, ArgsContextMenu, ArgsWheel, Interpolatable, TransitionManager, Layer, LayerInfo, RuntimeContext, ArgsMouseMove, ArgsMouseEnter, ArgsMouseLeave, ArgsFocus, ArgsBlur, ArgsDragStart, ArgsDrag, ArgsDragEnd, ArgsDragEnter, ArgsDragOver, ArgsDragLeave, ArgsDrop, ArgsPinch, ArgsRotate, ArgsPan, ArgsSwipe, ArgsLongPress, MouseEventArgs, EventArgs, FocusRequest, ModifierKey, MouseButton, PointerCapture, Touch};
 
 pub struct PaxEngine<R: 'static + RenderContext> {
     pub frames_elapsed: usize,
     pub instance_registry: Rc<RefCell<InstanceRegistry<R>>>,
-    pub expression_table: HashMap<usize, Box<dyn Fn(ExpressionContext<R>) -> TypesCoproduct> >,
+    pub expression_table: ExpressionVTable<R>,
+    /// Most recent value of each Expression, keyed by vtable id and the address of the properties of the stack frame it was evaluated within
+    expression_cache: RefCell<HashMap<(usize, usize), CachedExpression>>,
+    expression_stats: Cell<ExpressionStats>,
+    /// Whether the next tick may render differently than the last, for reasons other than property writes; see `needs_render`
+    needs_render: Cell<bool>,
+    /// Value of `peek_property_version` at the end of the last tick
+    last_tick_property_version: u64,
     pub main_component: Rc<RefCell<ComponentInstance<R>>>,
     pub runtime: Rc<RefCell<Runtime<R>>>,
     pub image_map: HashMap<Vec<u64>, (Box<Vec<u8>>, usize, usize)>,
     viewport_tab: TransformAndBounds,
+    /// Component templates replaced by hot reloading, paired with their replacements, to be swapped in at the end of the next tick
+    staged_templates: Vec<(RenderNodePtrList<R>, Vec<RenderNodePtr<R>>)>,
+    hover_tracker: HoverTracker<R>,
+    focus_tracker: FocusTracker<R>,
+    drag_tracker: DragTracker<R>,
+    gesture_tracker: GestureTracker<R>,
 }
 
 pub struct ExpressionVTable<R: 'static + RenderContext> {
-    inner_map: HashMap<usize, Box<dyn Fn(ExpressionContext<R>) -> TypesCoproduct>>,
-    dependency_graph: HashMap<u64, Vec<u64>>,
+    pub inner_map: HashMap<usize, Box<dyn Fn(ExpressionContext<R>) -> TypesCoproduct>>,
+    /// For each Expression, reads the current state of the properties it depends on.  Expressions without an entry here,
+    /// e.g. those interpreted by hot reloading, are evaluated every time they are computed.
+    pub dependency_graph: HashMap<usize, Box<dyn Fn(&ExpressionContext<R>) -> Vec<ExpressionInput>>>,
 }
 
 pub struct RenderTreeContext<'a, R: 'static + RenderContext>
@@ -50,6 +72,8 @@ impl<'a, R: 'static + RenderContext> RenderTreeContext<'a, R> {
         RuntimeContext {
             bounds_parent: self.bounds,
             frames_elapsed: self.engine.frames_elapsed,
+            id_chain: self.get_id_chain(self.node.borrow().get_instance_id()),
+            focus_request: self.engine.focus_tracker.get_focus_request(),
         }
     }
 }
@@ -78,19 +102,19 @@ impl<'a, R: RenderContext> RenderTreeContext<'a, R> {
                     current_transition.global_frame_started = Some(self.engine.frames_elapsed);
                 }
                 let progress = (self.engine.frames_elapsed as f64 - current_transition.global_frame_started.unwrap() as f64) / (current_transition.duration_frames as f64);
+                self.engine.request_render();
                 return if progress >= 1.0 { //NOTE: we may encounter float imprecision here, consider `progress >= 1.0 - EPSILON` for some `EPSILON`
                     let new_value = current_transition.curve.interpolate(&current_transition.starting_value, &current_transition.ending_value, progress);
                     tm.value = Some(new_value.clone());
 
                     tm.queue.pop_front();
-                    self.compute_eased_value(Some(tm))
+                    //settle on the final value, after which this returns `None` until another transition is queued
+                    self.compute_eased_value(Some(tm)).or(Some(new_value))
                 } else {
                     let new_value = current_transition.curve.interpolate(&current_transition.starting_value, &current_transition.ending_value, progress);
                     tm.value = Some(new_value.clone());
                     tm.value.clone()
                 };
-            } else {
-                return tm.value.clone();
             }
         }
         None
@@ -115,14 +139,57 @@ impl<'a, R: RenderContext> RenderTreeContext<'a, R> {
     }
 
     pub fn compute_vtable_value(&self, vtable_id: Option<usize>) -> Option<TypesCoproduct> {
+        self.compute_vtable_value_if_changed(vtable_id, 0).map(|(value, _)| value)
+    }
+
+    /// Like `compute_vtable_value`, but returns `None` when the expression's dependencies are unchanged since it last produced
+    /// the value with version `current_version`, i.e. when the bound property already holds the up-to-date value.
+    /// Otherwise returns the value along with its version, to be written back with `PropertyInstance::_set_computed`.
+    pub fn compute_vtable_value_if_changed(&self, vtable_id: Option<usize>, current_version: u64) -> Option<(TypesCoproduct, u64)> {
 
         if let Some(id) = vtable_id {
-            if let Some(evaluator) = self.engine.expression_table.get(&id) {
+            if let Some(evaluator) = self.engine.expression_table.inner_map.get(&id) {
+                let stack_frame = Rc::clone(&(*self.runtime).borrow_mut().peek_stack_frame().unwrap());
+                let cache_key = (id, Rc::as_ptr(&(*stack_frame).borrow().get_properties()) as *const () as usize);
                 let ec = ExpressionContext {
                     engine: self.engine,
-                    stack_frame: Rc::clone(&(*self.runtime).borrow_mut().peek_stack_frame().unwrap()),
+                    stack_frame,
                 };
-                return Some((**evaluator)(ec));
+
+                let mut stats = self.engine.expression_stats.get();
+                let inputs = self.engine.expression_table.dependency_graph.get(&id).map(|dependencies| (**dependencies)(&ec));
+                if let Some(inputs) = inputs {
+                    let mut cache = self.engine.expression_cache.borrow_mut();
+                    if let Some(cached) = cache.get_mut(&cache_key) {
+                        if cached.inputs == inputs {
+                            cached.frame = self.engine.frames_elapsed;
+                            stats.reused += 1;
+                            self.engine.expression_stats.set(stats);
+                            return if cached.version == current_version {
+                                None
+                            } else {
+                                Some((cached.value.clone(), cached.version))
+                            };
+                        }
+                    }
+                    drop(cache);
+
+                    let value = (**evaluator)(ec);
+                    let version = mint_property_version();
+                    self.engine.expression_cache.borrow_mut().insert(cache_key, CachedExpression {
+                        inputs,
+                        value: value.clone(),
+                        version,
+                        frame: self.engine.frames_elapsed,
+                    });
+                    stats.evaluated += 1;
+                    self.engine.expression_stats.set(stats);
+                    return Some((value, version));
+                }
+
+                stats.evaluated += 1;
+                self.engine.expression_stats.set(stats);
+                return Some(((**evaluator)(ec), mint_property_version()));
             }
         } //FUTURE: for timelines: else if present in timeline vtable...
 
@@ -130,24 +197,73 @@ impl<'a, R: RenderContext> RenderTreeContext<'a, R> {
     }
 }
 
+/// A handler for an input event, e.g. `ArgsClick`, bound with `@click=...` or in an `@events` block
+pub type EventHandler<R, A> = fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, A);
+
+/// Handlers for each input event.  `*_capture_handlers` are bound with the `.capture` suffix, e.g. `@click.capture=...`,
+/// and run before ordinary handlers; see `RepeatExpandedNode::dispatch`.  `mouse_enter`, `mouse_leave`, `drag_enter`, `drag_leave`,
+/// `focus` and `blur` don't propagate, so have no capture handlers.
 pub struct HandlerRegistry<R: 'static + RenderContext> {
-    pub scroll_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsScroll)>,
-    pub jab_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsJab)>,
-    pub touch_start_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsTouchStart)>,
-    pub touch_move_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsTouchMove)>,
-    pub touch_end_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsTouchEnd)>,
-    pub key_down_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsKeyDown)>,
-    pub key_up_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsKeyUp)>,
-    pub key_press_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsKeyPress)>,
-    pub click_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsClick)>,
-    pub mouse_down_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsMouseDown)>,
-    pub mouse_up_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsMouseUp)>,
-    pub mouse_move_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsMouseMove)>,
-    pub mouse_over_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsMouseOver)>,
-    pub mouse_out_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsMouseOut)>,
-    pub double_click_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsDoubleClick)>,
-    pub context_menu_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsContextMenu)>,
-    pub wheel_handlers: Vec<fn(Rc<RefCell<StackFrame<R>>>, RuntimeContext, ArgsWheel)>,
+    pub scroll_handlers: Vec<EventHandler<R, ArgsScroll>>,
+    pub jab_handlers: Vec<EventHandler<R, ArgsJab>>,
+    pub touch_start_handlers: Vec<EventHandler<R, ArgsTouchStart>>,
+    pub touch_move_handlers: Vec<EventHandler<R, ArgsTouchMove>>,
+    pub touch_end_handlers: Vec<EventHandler<R, ArgsTouchEnd>>,
+    pub key_down_handlers: Vec<EventHandler<R, ArgsKeyDown>>,
+    pub key_up_handlers: Vec<EventHandler<R, ArgsKeyUp>>,
+    pub key_press_handlers: Vec<EventHandler<R, ArgsKeyPress>>,
+    pub click_handlers: Vec<EventHandler<R, ArgsClick>>,
+    pub mouse_down_handlers: Vec<EventHandler<R, ArgsMouseDown>>,
+    pub mouse_up_handlers: Vec<EventHandler<R, ArgsMouseUp>>,
+    pub mouse_move_handlers: Vec<EventHandler<R, ArgsMouseMove>>,
+    pub mouse_over_handlers: Vec<EventHandler<R, ArgsMouseOver>>,
+    pub mouse_out_handlers: Vec<EventHandler<R, ArgsMouseOut>>,
+    pub mouse_enter_handlers: Vec<EventHandler<R, ArgsMouseEnter>>,
+    pub mouse_leave_handlers: Vec<EventHandler<R, ArgsMouseLeave>>,
+    pub focus_handlers: Vec<EventHandler<R, ArgsFocus>>,
+    pub blur_handlers: Vec<EventHandler<R, ArgsBlur>>,
+    pub drag_start_handlers: Vec<EventHandler<R, ArgsDragStart>>,
+    pub drag_handlers: Vec<EventHandler<R, ArgsDrag>>,
+    pub drag_end_handlers: Vec<EventHandler<R, ArgsDragEnd>>,
+    pub drag_enter_handlers: Vec<EventHandler<R, ArgsDragEnter>>,
+    pub drag_over_handlers: Vec<EventHandler<R, ArgsDragOver>>,
+    pub drag_leave_handlers: Vec<EventHandler<R, ArgsDragLeave>>,
+    pub drop_handlers: Vec<EventHandler<R, ArgsDrop>>,
+    pub pinch_handlers: Vec<EventHandler<R, ArgsPinch>>,
+    pub rotate_handlers: Vec<EventHandler<R, ArgsRotate>>,
+    pub pan_handlers: Vec<EventHandler<R, ArgsPan>>,
+    pub swipe_handlers: Vec<EventHandler<R, ArgsSwipe>>,
+    pub long_press_handlers: Vec<EventHandler<R, ArgsLongPress>>,
+    pub double_click_handlers: Vec<EventHandler<R, ArgsDoubleClick>>,
+    pub context_menu_handlers: Vec<EventHandler<R, ArgsContextMenu>>,
+    pub wheel_handlers: Vec<EventHandler<R, ArgsWheel>>,
+    pub scroll_capture_handlers: Vec<EventHandler<R, ArgsScroll>>,
+    pub jab_capture_handlers: Vec<EventHandler<R, ArgsJab>>,
+    pub touch_start_capture_handlers: Vec<EventHandler<R, ArgsTouchStart>>,
+    pub touch_move_capture_handlers: Vec<EventHandler<R, ArgsTouchMove>>,
+    pub touch_end_capture_handlers: Vec<EventHandler<R, ArgsTouchEnd>>,
+    pub key_down_capture_handlers: Vec<EventHandler<R, ArgsKeyDown>>,
+    pub key_up_capture_handlers: Vec<EventHandler<R, ArgsKeyUp>>,
+    pub key_press_capture_handlers: Vec<EventHandler<R, ArgsKeyPress>>,
+    pub click_capture_handlers: Vec<EventHandler<R, ArgsClick>>,
+    pub mouse_down_capture_handlers: Vec<EventHandler<R, ArgsMouseDown>>,
+    pub mouse_up_capture_handlers: Vec<EventHandler<R, ArgsMouseUp>>,
+    pub mouse_move_capture_handlers: Vec<EventHandler<R, ArgsMouseMove>>,
+    pub mouse_over_capture_handlers: Vec<EventHandler<R, ArgsMouseOver>>,
+    pub mouse_out_capture_handlers: Vec<EventHandler<R, ArgsMouseOut>>,
+    pub double_click_capture_handlers: Vec<EventHandler<R, ArgsDoubleClick>>,
+    pub context_menu_capture_handlers: Vec<EventHandler<R, ArgsContextMenu>>,
+    pub wheel_capture_handlers: Vec<EventHandler<R, ArgsWheel>>,
+    pub drag_start_capture_handlers: Vec<EventHandler<R, ArgsDragStart>>,
+    pub drag_capture_handlers: Vec<EventHandler<R, ArgsDrag>>,
+    pub drag_end_capture_handlers: Vec<EventHandler<R, ArgsDragEnd>>,
+    pub drag_over_capture_handlers: Vec<EventHandler<R, ArgsDragOver>>,
+    pub drop_capture_handlers: Vec<EventHandler<R, ArgsDrop>>,
+    pub pinch_capture_handlers: Vec<EventHandler<R, ArgsPinch>>,
+    pub rotate_capture_handlers: Vec<EventHandler<R, ArgsRotate>>,
+    pub pan_capture_handlers: Vec<EventHandler<R, ArgsPan>>,
+    pub swipe_capture_handlers: Vec<EventHandler<R, ArgsSwipe>>,
+    pub long_press_capture_handlers: Vec<EventHandler<R, ArgsLongPress>>,
     pub will_render_handlers: Vec<fn(Rc<RefCell<PropertiesCoproduct>>, RuntimeContext)>,
     pub did_mount_handlers: Vec<fn(Rc<RefCell<PropertiesCoproduct>>, RuntimeContext)>,
 }
@@ -170,9 +286,52 @@ impl<R: 'static + RenderContext> Default for HandlerRegistry<R> {
             mouse_move_handlers: Vec::new(),
             mouse_over_handlers: Vec::new(),
             mouse_out_handlers: Vec::new(),
+            mouse_enter_handlers: Vec::new(),
+            mouse_leave_handlers: Vec::new(),
+            focus_handlers: Vec::new(),
+            blur_handlers: Vec::new(),
+            drag_start_handlers: Vec::new(),
+            drag_handlers: Vec::new(),
+            drag_end_handlers: Vec::new(),
+            drag_enter_handlers: Vec::new(),
+            drag_over_handlers: Vec::new(),
+            drag_leave_handlers: Vec::new(),
+            drop_handlers: Vec::new(),
+            pinch_handlers: Vec::new(),
+            rotate_handlers: Vec::new(),
+            pan_handlers: Vec::new(),
+            swipe_handlers: Vec::new(),
+            long_press_handlers: Vec::new(),
             double_click_handlers: Vec::new(),
             context_menu_handlers: Vec::new(),
             wheel_handlers: Vec::new(),
+            scroll_capture_handlers: Vec::new(),
+            jab_capture_handlers: Vec::new(),
+            touch_start_capture_handlers: Vec::new(),
+            touch_move_capture_handlers: Vec::new(),
+            touch_end_capture_handlers: Vec::new(),
+            key_down_capture_handlers: Vec::new(),
+            key_up_capture_handlers: Vec::new(),
+            key_press_capture_handlers: Vec::new(),
+            click_capture_handlers: Vec::new(),
+            mouse_down_capture_handlers: Vec::new(),
+            mouse_up_capture_handlers: Vec::new(),
+            mouse_move_capture_handlers: Vec::new(),
+            mouse_over_capture_handlers: Vec::new(),
+            mouse_out_capture_handlers: Vec::new(),
+            double_click_capture_handlers: Vec::new(),
+            context_menu_capture_handlers: Vec::new(),
+            wheel_capture_handlers: Vec::new(),
+            drag_start_capture_handlers: Vec::new(),
+            drag_capture_handlers: Vec::new(),
+            drag_end_capture_handlers: Vec::new(),
+            drag_over_capture_handlers: Vec::new(),
+            drop_capture_handlers: Vec::new(),
+            pinch_capture_handlers: Vec::new(),
+            rotate_capture_handlers: Vec::new(),
+            pan_capture_handlers: Vec::new(),
+            swipe_capture_handlers: Vec::new(),
+            long_press_capture_handlers: Vec::new(),
             will_render_handlers: Vec::new(),
             did_mount_handlers: Vec::new(),
         }
@@ -192,225 +351,175 @@ pub struct RepeatExpandedNode<R: 'static + RenderContext> {
 }
 
 impl<R: 'static + RenderContext> RepeatExpandedNode<R> {
-    pub fn dispatch_scroll(&self, args_scroll: ArgsScroll) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().scroll_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_scroll.clone());
-            });
-        }
-
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_scroll(args_scroll);
-        }
+    /// See `RenderTreeContext::get_id_chain`; the first element is the instance id
+    pub fn get_id_chain(&self) -> &Vec<u64> {
+        &self.id_chain
     }
 
-    pub fn dispatch_jab(&self, args_jab: ArgsJab) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().jab_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_jab.clone());
-            });
-        }
-
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_jab(args_jab);
+    /// This node's parent, its parent's parent, and so on, up to the root of the tree
+    pub fn get_ancestors(&self) -> Vec<Rc<RepeatExpandedNode<R>>> {
+        let mut ancestors = vec![];
+        let mut parent = self.parent_repeat_expanded_node.as_ref().and_then(Weak::upgrade);
+        while let Some(node) = parent {
+            parent = node.parent_repeat_expanded_node.as_ref().and_then(Weak::upgrade);
+            ancestors.push(node);
         }
+        ancestors
     }
 
-    pub fn dispatch_touch_start(&self, args_touch_start: ArgsTouchStart) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().touch_start_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_touch_start.clone());
-            });
-        }
-
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_touch_start(args_touch_start);
-        }
+    /// `point` in the viewport, in this node's coordinate space, e.g. `(0.0, 0.0)` for its top-left corner
+    pub fn get_local_point(&self, point: (f64, f64)) -> (f64, f64) {
+        let local = self.tab.transform.inverse() * Point { x: point.0, y: point.1 };
+        (local.x, local.y)
     }
 
-    pub fn dispatch_touch_move(&self, args_touch_move: ArgsTouchMove) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().touch_move_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_touch_move.clone());
-            });
-        }
-
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_touch_move(args_touch_move);
-        }
+    /// `node` followed by its ancestors
+    pub fn get_path(node: &Rc<Self>) -> Vec<Rc<Self>> {
+        let mut path = vec![Rc::clone(node)];
+        path.extend(node.get_ancestors());
+        path
     }
 
-    pub fn dispatch_touch_end(&self, args_touch_end: ArgsTouchEnd) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().touch_end_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_touch_end.clone());
-            });
-        }
+    /// Dispatches an input event with this node as its target, as in the DOM: first to capture handlers from the root of
+    /// the tree down to this node, then to ordinary handlers from this node back up to the root, stopping after the
+    /// handlers of any node that calls `stop_propagation`.  Returns whether any handler called `prevent_default`.
+    pub fn dispatch<A: EventArgs>(
+        &self,
+        args: A,
+        get_capture_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+        get_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+    ) -> bool {
+        let ancestors = self.get_ancestors();
+        let path : Vec<&RepeatExpandedNode<R>> = std::iter::once(self).chain(ancestors.iter().map(|node| node.as_ref())).collect();
+        Self::dispatch_along_path(&path, args, get_capture_handlers, get_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_touch_end(args_touch_end);
+    /// As `dispatch`, to the target `path[0]` followed by its ancestors, e.g. when the target's ancestors were rendered
+    /// in an earlier frame and are no longer reachable from it
+    pub fn dispatch_along_path<A: EventArgs>(
+        path: &[&RepeatExpandedNode<R>],
+        args: A,
+        get_capture_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+        get_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+    ) -> bool {
+        let control = args.get_control().clone();
+        let phases = path.iter().rev().map(|node| (*node, get_capture_handlers))
+            .chain(path.iter().map(|node| (*node, get_handlers)));
+        for (node, get_phase_handlers) in phases {
+            node.call_handlers(&args, get_phase_handlers);
+            if control.is_propagation_stopped() {
+                break;
+            }
         }
+        control.is_default_prevented()
     }
 
-    pub fn dispatch_key_down(&self, args_key_down: ArgsKeyDown) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().key_down_handlers;
+    fn call_handlers<A: EventArgs>(&self, args: &A, get_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>) {
+        let registry = (*self.instance_node).borrow().get_handler_registry();
+        if let Some(registry) = registry {
+            let handlers = get_handlers(&(*registry).borrow()).clone();
             handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_key_down.clone());
+                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args.clone());
             });
         }
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_key_down(args_key_down);
-        }
+    pub fn dispatch_scroll(&self, args_scroll: ArgsScroll) -> bool {
+        self.dispatch(args_scroll, |registry| &registry.scroll_capture_handlers, |registry| &registry.scroll_handlers)
     }
 
-    pub fn dispatch_key_up(&self, args_key_up: ArgsKeyUp) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().key_up_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_key_up.clone());
-            });
-        }
+    pub fn dispatch_jab(&self, args_jab: ArgsJab) -> bool {
+        self.dispatch(args_jab, |registry| &registry.jab_capture_handlers, |registry| &registry.jab_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_key_up(args_key_up);
-        }
+    pub fn dispatch_touch_start(&self, args_touch_start: ArgsTouchStart) -> bool {
+        self.dispatch(args_touch_start, |registry| &registry.touch_start_capture_handlers, |registry| &registry.touch_start_handlers)
     }
 
-    pub fn dispatch_key_press(&self, args_key_press: ArgsKeyPress) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().key_press_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_key_press.clone());
-            });
-        }
+    pub fn dispatch_touch_move(&self, args_touch_move: ArgsTouchMove) -> bool {
+        self.dispatch(args_touch_move, |registry| &registry.touch_move_capture_handlers, |registry| &registry.touch_move_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_key_press(args_key_press);
-        }
+    pub fn dispatch_touch_end(&self, args_touch_end: ArgsTouchEnd) -> bool {
+        self.dispatch(args_touch_end, |registry| &registry.touch_end_capture_handlers, |registry| &registry.touch_end_handlers)
     }
 
-    pub fn dispatch_click(&self, args_click: ArgsClick) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().click_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_click.clone());
-            });
-        }
+    pub fn dispatch_key_down(&self, args_key_down: ArgsKeyDown) -> bool {
+        self.dispatch(args_key_down, |registry| &registry.key_down_capture_handlers, |registry| &registry.key_down_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_click(args_click);
-        }
+    pub fn dispatch_key_up(&self, args_key_up: ArgsKeyUp) -> bool {
+        self.dispatch(args_key_up, |registry| &registry.key_up_capture_handlers, |registry| &registry.key_up_handlers)
     }
 
-    pub fn dispatch_mouse_down(&self, args_mouse_down: ArgsMouseDown) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().mouse_down_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_mouse_down.clone());
-            });
-        }
+    pub fn dispatch_key_press(&self, args_key_press: ArgsKeyPress) -> bool {
+        self.dispatch(args_key_press, |registry| &registry.key_press_capture_handlers, |registry| &registry.key_press_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_mouse_down(args_mouse_down);
-        }
+    pub fn dispatch_click(&self, args_click: ArgsClick) -> bool {
+        self.dispatch(args_click, |registry| &registry.click_capture_handlers, |registry| &registry.click_handlers)
     }
 
-    pub fn dispatch_mouse_up(&self, args_mouse_up: ArgsMouseUp) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().mouse_up_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_mouse_up.clone());
-            });
-        }
+    pub fn dispatch_mouse_down(&self, args_mouse_down: ArgsMouseDown) -> bool {
+        self.dispatch(args_mouse_down, |registry| &registry.mouse_down_capture_handlers, |registry| &registry.mouse_down_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_mouse_up(args_mouse_up);
-        }
+    pub fn dispatch_mouse_up(&self, args_mouse_up: ArgsMouseUp) -> bool {
+        self.dispatch(args_mouse_up, |registry| &registry.mouse_up_capture_handlers, |registry| &registry.mouse_up_handlers)
     }
 
-    pub fn dispatch_mouse_move(&self, args_mouse_move: ArgsMouseMove) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().mouse_move_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_mouse_move.clone());
-            });
-        }
+    pub fn dispatch_mouse_move(&self, args_mouse_move: ArgsMouseMove) -> bool {
+        self.dispatch(args_mouse_move, |registry| &registry.mouse_move_capture_handlers, |registry| &registry.mouse_move_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_mouse_move(args_mouse_move);
-        }
+    pub fn dispatch_mouse_over(&self, args_mouse_over: ArgsMouseOver) -> bool {
+        self.dispatch(args_mouse_over, |registry| &registry.mouse_over_capture_handlers, |registry| &registry.mouse_over_handlers)
     }
 
-    pub fn dispatch_mouse_over(&self, args_mouse_over: ArgsMouseOver) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().mouse_over_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_mouse_over.clone());
-            });
-        }
+    pub fn dispatch_mouse_out(&self, args_mouse_out: ArgsMouseOut) -> bool {
+        self.dispatch(args_mouse_out, |registry| &registry.mouse_out_capture_handlers, |registry| &registry.mouse_out_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_mouse_over(args_mouse_over);
-        }
+    pub fn dispatch_mouse_enter(&self, args_mouse_enter: ArgsMouseEnter) -> bool {
+        self.call_handlers(&args_mouse_enter, |registry| &registry.mouse_enter_handlers);
+        args_mouse_enter.control.is_default_prevented()
     }
 
-    pub fn dispatch_mouse_out(&self, args_mouse_out: ArgsMouseOut) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().mouse_out_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_mouse_out.clone());
-            });
-        }
+    pub fn dispatch_mouse_leave(&self, args_mouse_leave: ArgsMouseLeave) -> bool {
+        self.call_handlers(&args_mouse_leave, |registry| &registry.mouse_leave_handlers);
+        args_mouse_leave.control.is_default_prevented()
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_mouse_out(args_mouse_out);
-        }
+    pub fn dispatch_drag_enter(&self, args_drag_enter: ArgsDragEnter) -> bool {
+        self.call_handlers(&args_drag_enter, |registry| &registry.drag_enter_handlers);
+        args_drag_enter.control.is_default_prevented()
     }
 
-    pub fn dispatch_double_click(&self, args_double_click: ArgsDoubleClick) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().double_click_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_double_click.clone());
-            });
-        }
+    pub fn dispatch_drag_leave(&self, args_drag_leave: ArgsDragLeave) -> bool {
+        self.call_handlers(&args_drag_leave, |registry| &registry.drag_leave_handlers);
+        args_drag_leave.control.is_default_prevented()
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_double_click(args_double_click);
-        }
+    pub fn dispatch_focus(&self, args_focus: ArgsFocus) -> bool {
+        self.call_handlers(&args_focus, |registry| &registry.focus_handlers);
+        args_focus.control.is_default_prevented()
     }
 
-    pub fn dispatch_context_menu(&self, args_context_menu: ArgsContextMenu) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().context_menu_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_context_menu.clone());
-            });
-        }
+    pub fn dispatch_blur(&self, args_blur: ArgsBlur) -> bool {
+        self.call_handlers(&args_blur, |registry| &registry.blur_handlers);
+        args_blur.control.is_default_prevented()
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_context_menu(args_context_menu);
-        }
+    pub fn dispatch_double_click(&self, args_double_click: ArgsDoubleClick) -> bool {
+        self.dispatch(args_double_click, |registry| &registry.double_click_capture_handlers, |registry| &registry.double_click_handlers)
     }
 
-    pub fn dispatch_wheel(&self, args_wheel: ArgsWheel) {
-        if let Some(registry) = (*self.instance_node).borrow().get_handler_registry() {
-            let handlers = &(*registry).borrow().wheel_handlers;
-            handlers.iter().for_each(|handler| {
-                handler(Rc::clone(&self.stack_frame), self.node_context.clone(), args_wheel.clone());
-            });
-        }
+    pub fn dispatch_context_menu(&self, args_context_menu: ArgsContextMenu) -> bool {
+        self.dispatch(args_context_menu, |registry| &registry.context_menu_capture_handlers, |registry| &registry.context_menu_handlers)
+    }
 
-        if let Some(parent) = &self.parent_repeat_expanded_node {
-            parent.upgrade().unwrap().dispatch_wheel(args_wheel);
-        }
+    pub fn dispatch_wheel(&self, args_wheel: ArgsWheel) -> bool {
+        self.dispatch(args_wheel, |registry| &registry.wheel_capture_handlers, |registry| &registry.wheel_handlers)
     }
 }
 
@@ -426,11 +535,26 @@ pub struct InstanceRegistry<R: 'static + RenderContext> {
     ///otherwise ephemeral calculations, e.g. the descendants of `Repeat` instances.
     repeat_expanded_node_cache: Vec<Rc<RepeatExpandedNode<R>>>,
 
+    ///the nodes of `repeat_expanded_node_cache` that can be hit, by where they are, for ray-casting and area queries
+    spatial_index: SpatialIndex<Rc<RepeatExpandedNode<R>>>,
+
+    ///the nodes of `repeat_expanded_node_cache` by id_chain, for looking up the targets of focus, drags and gestures
+    repeat_expanded_node_map: HashMap<Vec<u64>, Rc<RepeatExpandedNode<R>>>,
+
     ///track which repeat-expanded elements are currently mounted -- if id is present in set, is mounted
     mounted_set: HashSet<Vec<u64>>,
     ///tracks whichs instance nodes are marked for unmounting, to be done at the correct point in the render tree lifecycle
     marked_for_unmount_set: HashSet<u64>,
 
+    ///the templates of live component instances by component type_id, for hot reloading
+    component_templates: HashMap<String, Vec<Weak<RefCell<Vec<RenderNodePtr<R>>>>>>,
+
+    ///instance ids by the `id` of their template node in Pax, e.g. `sidebar` for `<Rectangle id=sidebar />`, for tests (see `testing`)
+    template_id_map: HashMap<String, Vec<u64>>,
+
+    ///instances given `focusable=true` in Pax, which Tab and clicks move keyboard focus to (see `focus`)
+    focusable_set: HashSet<u64>,
+
     ///register holding the next value to mint as an id
     next_id: u64,
 }
@@ -442,6 +566,11 @@ impl<R: 'static + RenderContext> InstanceRegistry<R> {
             marked_for_unmount_set: HashSet::new(),
             instance_map: HashMap::new(),
             repeat_expanded_node_cache: vec![],
+            spatial_index: SpatialIndex::default(),
+            repeat_expanded_node_map: HashMap::new(),
+            component_templates: HashMap::new(),
+            template_id_map: HashMap::new(),
+            focusable_set: HashSet::new(),
             next_id: 0,
         }
     }
@@ -458,6 +587,51 @@ impl<R: 'static + RenderContext> InstanceRegistry<R> {
 
     pub fn deregister(&mut self, instance_id: u64) {
         self.instance_map.remove(&instance_id);
+        self.focusable_set.remove(&instance_id);
+    }
+
+    pub fn get_instance(&self, instance_id: u64) -> Option<RenderNodePtr<R>> {
+        self.instance_map.get(&instance_id).map(Rc::clone)
+    }
+
+    /// Called by generated templates for each node given an `id` in Pax
+    pub fn register_template_id(&mut self, template_id: &str, instance_id: u64) {
+        let instance_ids = self.template_id_map.entry(template_id.to_string()).or_insert_with(Vec::new);
+        //prune the ids of deregistered instances before growing, as in `register_component_template`
+        if instance_ids.len() == instance_ids.capacity() {
+            let instance_map = &self.instance_map;
+            instance_ids.retain(|instance_id| instance_map.contains_key(instance_id));
+        }
+        instance_ids.push(instance_id);
+    }
+
+    /// Ids of the live instances of template nodes with the `id` `template_id`, in order of instantiation.
+    /// A node may be instantiated more than once, e.g. within each instance of a component used more than once.
+    pub fn get_instance_ids_by_template_id(&self, template_id: &str) -> Vec<u64> {
+        match self.template_id_map.get(template_id) {
+            Some(instance_ids) => instance_ids.iter().filter(|instance_id| self.instance_map.contains_key(instance_id)).cloned().collect(),
+            None => vec![],
+        }
+    }
+
+    /// Whether the most recent tick rendered `instance_id`, i.e. whether it is mounted and not skipped by control flow
+    pub fn was_rendered(&self, instance_id: u64) -> bool {
+        self.repeat_expanded_node_cache.iter().any(|node| node.id_chain[0] == instance_id)
+    }
+
+    /// Called by generated templates for each node given `focusable=true` in Pax
+    pub fn register_focusable(&mut self, instance_id: u64) {
+        self.focusable_set.insert(instance_id);
+    }
+
+    pub fn is_focusable(&self, instance_id: u64) -> bool {
+        self.focusable_set.contains(&instance_id)
+    }
+
+    pub fn get_template_ids(&self) -> Vec<String> {
+        let mut template_ids : Vec<String> = self.template_id_map.keys().cloned().collect();
+        template_ids.sort();
+        template_ids
     }
 
     pub fn mark_mounted(&mut self, id_chain: Vec<u64>) {
@@ -472,12 +646,48 @@ impl<R: 'static + RenderContext> InstanceRegistry<R> {
         self.marked_for_unmount_set.insert(instance_id);
     }
 
+    /// Called by generated component factories, so that hot reloading can find every live instance of a component's template
+    pub fn register_component_template(&mut self, component_type_id: &str, template: &RenderNodePtrList<R>) {
+        let templates = self.component_templates.entry(component_type_id.to_string()).or_insert_with(Vec::new);
+        //prune the templates of dropped instances before growing, keeping this amortized O(1)
+        if templates.len() == templates.capacity() {
+            templates.retain(|template| template.strong_count() > 0);
+        }
+        templates.push(Rc::downgrade(template));
+    }
+
+    pub fn get_component_templates(&mut self, component_type_id: &str) -> Vec<RenderNodePtrList<R>> {
+        match self.component_templates.get_mut(component_type_id) {
+            Some(templates) => {
+                templates.retain(|template| template.strong_count() > 0);
+                templates.iter().filter_map(|template| template.upgrade()).collect()
+            },
+            None => vec![],
+        }
+    }
+
+    /// The repeat-expanded nodes visited by the most recent tick, each before its descendants (see `get_template_order`)
+    pub fn get_repeat_expanded_nodes(&self) -> &Vec<Rc<RepeatExpandedNode<R>>> {
+        &self.repeat_expanded_node_cache
+    }
+
+    pub fn get_repeat_expanded_node(&self, id_chain: &[u64]) -> Option<Rc<RepeatExpandedNode<R>>> {
+        self.repeat_expanded_node_map.get(id_chain).map(Rc::clone)
+    }
+
     pub fn reset_repeat_expanded_node_cache(&mut self) {
         self.repeat_expanded_node_cache = vec![];
+        self.spatial_index.clear();
+        self.repeat_expanded_node_map.clear();
     }
 
     pub fn add_to_repeat_expanded_node_cache(&mut self, repeat_expanded_node: Rc<RepeatExpandedNode<R>>) {
         //Note: ray-casting requires that these nodes are sorted by z-index
+        let hit_bounds = (*repeat_expanded_node.instance_node).borrow().get_hit_bounds(&repeat_expanded_node.tab);
+        if let Some(hit_bounds) = hit_bounds {
+            self.spatial_index.insert(Rc::clone(&repeat_expanded_node), hit_bounds);
+        }
+        self.repeat_expanded_node_map.entry(repeat_expanded_node.id_chain.clone()).or_insert_with(|| Rc::clone(&repeat_expanded_node));
         self.repeat_expanded_node_cache.push(repeat_expanded_node);
     }
 
@@ -486,7 +696,7 @@ impl<R: 'static + RenderContext> InstanceRegistry<R> {
 impl<R: 'static + RenderContext> PaxEngine<R> {
     pub fn new(
         main_component_instance: Rc<RefCell<ComponentInstance<R>>>,
-        expression_table: HashMap<usize, Box<dyn Fn(ExpressionContext<R>)->TypesCoproduct>>,
+        expression_table: ExpressionVTable<R>,
         logger: pax_runtime_api::PlatformSpecificLogger,
         viewport_size: (f64, f64),
         instance_registry: Rc<RefCell<InstanceRegistry<R>>>,
@@ -496,6 +706,10 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
             frames_elapsed: 0,
             instance_registry,
             expression_table,
+            expression_cache: RefCell::new(HashMap::new()),
+            expression_stats: Cell::new(ExpressionStats::default()),
+            needs_render: Cell::new(true),
+            last_tick_property_version: 0,
             runtime: Rc::new(RefCell::new(Runtime::new())),
             main_component: main_component_instance,
             viewport_tab: TransformAndBounds {
@@ -503,6 +717,11 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
                 bounds: viewport_size,
             },
             image_map: HashMap::new(),
+            staged_templates: vec![],
+            hover_tracker: HoverTracker::default(),
+            focus_tracker: FocusTracker::default(),
+            drag_tracker: DragTracker::default(),
+            gesture_tracker: GestureTracker::default(),
         }
     }
 
@@ -526,7 +745,7 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         };
 
         let mut depth = LayerInfo::new();
-        self.recurse_traverse_render_tree(&mut rtc, rcs, Rc::clone(&cast_component_rc), &mut depth, false);
+        self.recurse_traverse_render_tree(&mut rtc, rcs, Rc::clone(&cast_component_rc), &mut depth, false, None);
         //reset the marked_for_unmount set
         self.instance_registry.borrow_mut().marked_for_unmount_set = HashSet::new();
 
@@ -542,7 +761,7 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         native_render_queue.into()
     }
 
-    fn recurse_traverse_render_tree(&self, rtc: &mut RenderTreeContext<R>, rcs: &mut Vec<R>, node: RenderNodePtr<R>, layer_info: &mut LayerInfo, marked_for_unmount: bool)  {
+    fn recurse_traverse_render_tree(&self, rtc: &mut RenderTreeContext<R>, rcs: &mut Vec<R>, node: RenderNodePtr<R>, layer_info: &mut LayerInfo, marked_for_unmount: bool, ghost_offset: Option<(f64, f64)>)  {
         //Recurse:
         //  - compute properties for this node
         //  - fire lifecycle events for this node
@@ -550,6 +769,7 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         //  - we now have the back-most leaf node.  Render it.  Return.
         //  - we're now at the second back-most leaf node.  Render it.  Return ...
         //  - manage unmounting, if marked
+        //  - render a ghost of this node, if it's being dragged with `DragTransfer::show_ghost` or is within such a node
 
         //populate a pointer to this (current) `RenderNode` onto `rtc`
         rtc.node = Rc::clone(&node);
@@ -579,8 +799,10 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
                 let registry = (*node).borrow().get_handler_registry();
                 if let Some(registry) = registry {
                     //grab Rc of properties from stack frame; pass to type-specific handler
-                    //on instance in order to dispatch cartridge method
-                    match rtc.runtime.borrow_mut().peek_stack_frame() {
+                    //on instance in order to dispatch cartridge method.
+                    //(release the runtime before calling handlers, since distilling their context reads it)
+                    let stack_frame = rtc.runtime.borrow_mut().peek_stack_frame();
+                    match stack_frame {
                         Some(stack_frame) => {
                             for handler in (*registry).borrow().did_mount_handlers.iter() {
                                 handler(stack_frame.borrow_mut().get_properties(), rtc.distill_userland_node_context());
@@ -632,8 +854,13 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         if let Some(registry) = registry {
             //grab Rc of properties from stack frame; pass to type-specific handler
             //on instance in order to dispatch cartridge method
-            match rtc.runtime.borrow_mut().peek_stack_frame() {
+            let stack_frame = rtc.runtime.borrow_mut().peek_stack_frame();
+            match stack_frame {
                 Some(stack_frame) => {
+                    //`will_render` handlers may write properties on every tick, so keep ticking while any are mounted
+                    if !(*registry).borrow().will_render_handlers.is_empty() {
+                        self.request_render();
+                    }
                     for handler in (*registry).borrow().will_render_handlers.iter() {
                         handler(stack_frame.borrow_mut().get_properties(), rtc.distill_userland_node_context());
                     }
@@ -654,8 +881,9 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
 
 
         let parent_repeat_expanded_node = rtc.parent_repeat_expanded_node.clone();
+        let stack_frame = rtc.runtime.borrow_mut().peek_stack_frame().unwrap();
         let repeat_expanded_node = Rc::new(RepeatExpandedNode {
-            stack_frame: rtc.runtime.borrow_mut().peek_stack_frame().unwrap(),
+            stack_frame,
             tab: repeat_expanded_node_tab.clone(),
             id_chain: id_chain.clone(),
             instance_node: Rc::clone(&node),
@@ -674,13 +902,18 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         //was marked for deletion, or this instance_node is present in the InstanceRegistry's "marked for unmount" set.
         let marked_for_unmount = marked_for_unmount || self.instance_registry.borrow().marked_for_unmount_set.contains(&instance_id);
 
+        //Likewise for the ghost of a dragged element, which is offset by as far as the pointer has dragged it
+        let ghost_offset = ghost_offset.or_else(|| {
+            self.drag_tracker.get_ghost().filter(|(ghost_id_chain, _)| **ghost_id_chain == id_chain).map(|(_, offset)| offset)
+        });
+
 
         //keep recursing through children
         children.borrow_mut().iter().rev().for_each(|child| {
             //note that we're iterating starting from the last child, for z-index (.rev())
             let mut new_rtc = rtc.clone();
             new_rtc.parent_repeat_expanded_node = Some(Rc::downgrade(&repeat_expanded_node));
-            &self.recurse_traverse_render_tree(&mut new_rtc, rcs, Rc::clone(child), layer_info, marked_for_unmount );
+            &self.recurse_traverse_render_tree(&mut new_rtc, rcs, Rc::clone(child), layer_info, marked_for_unmount, ghost_offset);
             //FUTURE: for dependency management, return computed values from subtree above
         });
 
@@ -688,7 +921,7 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
 
 
 
-        let is_viewport_culled = !repeat_expanded_node_tab.intersects(&self.viewport_tab);
+        let is_viewport_culled = repeat_expanded_node_tab.is_outside(&self.viewport_tab);
 
         let node_type = node.borrow_mut().get_layer_type();
         if !is_viewport_culled {
@@ -714,6 +947,14 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
             }
         }
 
+        //render the ghost now rather than after the traversal, while this node's properties are those of this
+        //repeat-expanded instance
+        if let Some(offset) = ghost_offset {
+            let mut ghost_rtc = rtc.clone();
+            ghost_rtc.transform = Affine::translate(offset) * new_accumulated_transform;
+            node.borrow_mut().handle_render(&mut ghost_rtc, rcs.get_mut(last_layer).unwrap());
+        }
+
 
         //Handle node unmounting
         if marked_for_unmount {
@@ -735,7 +976,35 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
     /// the specified point `ray`.  Areas outside of clipping bounds will
     /// not register a `hit`, nor will elements that suppress input events.
     pub fn get_topmost_element_beneath_ray(&self, ray: (f64, f64)) -> Option<Rc<RepeatExpandedNode<R>>> {
-        //Traverse all elements in render tree sorted by z-index (highest-to-lowest)
+        self.find_topmost_element_beneath_ray(ray, None)
+    }
+
+    /// As `get_topmost_element_beneath_ray`, but passing through the element with the id chain `excluded_id_chain`
+    /// and its descendants, e.g. to find the drop target beneath an element being dragged
+    pub fn get_topmost_element_beneath_ray_excluding(&self, ray: (f64, f64), excluded_id_chain: &[u64]) -> Option<Rc<RepeatExpandedNode<R>>> {
+        self.find_topmost_element_beneath_ray(ray, Some(excluded_id_chain))
+    }
+
+    /// The elements intersecting the axis-aligned rectangle from `top_left` to `bottom_right`, as rendered by the most
+    /// recent tick, topmost first, e.g. for marquee selection.  As with ray-casting, elements that are never hit (e.g.
+    /// `Group`) are excluded, but unlike ray-casting, elements are included even where clipped away
+    pub fn get_elements_within(&self, top_left: (f64, f64), bottom_right: (f64, f64)) -> Vec<Rc<RepeatExpandedNode<R>>> {
+        let area = TransformAndBounds {
+            transform: Affine::translate(top_left),
+            bounds: (bottom_right.0 - top_left.0, bottom_right.1 - top_left.1),
+        };
+        (*self.instance_registry).borrow().spatial_index.get_overlapping(&Rect::from_points(top_left, bottom_right))
+            .into_iter()
+            //bounding boxes overlap for rotated elements near the area's corners that don't themselves intersect it,
+            //so check those with sizes by the separating axis theorem, along both sets of axes
+            .filter(|node| {
+                (*node.instance_node).borrow().get_size().is_none() || (node.tab.intersects(&area) && area.intersects(&node.tab))
+            })
+            .collect()
+    }
+
+    fn find_topmost_element_beneath_ray(&self, ray: (f64, f64), excluded_id_chain: Option<&[u64]>) -> Option<Rc<RepeatExpandedNode<R>>> {
+        //Traverse the elements beneath the ray sorted by z-index (highest-to-lowest)
         //First: check whether events are suppressed
         //Next: check whether ancestral clipping bounds (hit_test) are satisfied
         //Finally: check whether element itself satisfies hit_test(ray)
@@ -750,26 +1019,25 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         //     for finding ancestral clipping containers
         //
 
-        // reverse nodes to get top-most first (rendered in reverse order)
-        let mut nodes_ordered : Vec<Rc<RepeatExpandedNode<R>>> = (*self.instance_registry).borrow()
-            .repeat_expanded_node_cache.iter().rev()
-            .map(|rc|{
-                Rc::clone(rc)
-            }).collect();
-
-        // remove root element that is moved to top during reversal
-        nodes_ordered.remove(0);
+        // only the elements whose hit bounds contain the ray, topmost first; see `SpatialIndex`
+        let nodes_ordered : Vec<Rc<RepeatExpandedNode<R>>> = (*self.instance_registry).borrow()
+            .spatial_index.get_at_point(ray);
 
         // let ray = Point {x: ray.0,y: ray.1};
         let mut ret : Option<Rc<RepeatExpandedNode<R>>> = None;
         for node in nodes_ordered {
             // pax_runtime_api::log(&(**node).borrow().get_instance_id().to_string())
 
+            if let Some(excluded_id_chain) = excluded_id_chain {
+                if RepeatExpandedNode::get_path(&node).iter().any(|node| node.get_id_chain() == excluded_id_chain) {
+                    continue;
+                }
+            }
 
             if (*node.instance_node).borrow().ray_cast_test(&ray, &node.tab) {
 
-                //We only care about the topmost node getting hit, and the element
-                //pool is ordered by z-index so we can just resolve the whole
+                //We only care about the topmost node getting hit, and the candidates
+                //are ordered by z-index so we can just resolve the whole
                 //calculation when we find the first matching node
 
                 let mut ancestral_clipping_bounds_are_satisfied = true;
@@ -797,27 +1065,508 @@ impl<R: 'static + RenderContext> PaxEngine<R> {
         ret
     }
 
+    /// The element with keyboard focus, as rendered by the most recent tick; see `focus`.  While no element has focus,
+    /// this is whatever lies beneath the center of the viewport.
     pub fn get_focused_element(&self) -> Option<Rc<RepeatExpandedNode<R>>> {
+        let focused = self.focus_tracker.get_focused_id_chain()
+            .and_then(|id_chain| (*self.instance_registry).borrow().get_repeat_expanded_node(id_chain));
+        if focused.is_some() {
+            return focused;
+        }
         let (x, y) = self.viewport_tab.bounds;
         self.get_topmost_element_beneath_ray((x/2.0,y/2.0))
     }
 
+    /// The id chain of the element with keyboard focus.  Unlike `get_focused_element`, this is `None` while no element has focus.
+    pub fn get_focused_id_chain(&self) -> Option<Vec<u64>> {
+        self.focus_tracker.get_focused_id_chain().cloned()
+    }
+
+    /// Moves keyboard focus to `element`, or removes it with `None`, dispatching `blur` and `focus`
+    pub fn focus(&mut self, element: Option<Rc<RepeatExpandedNode<R>>>) {
+        self.focus_tracker.set_focused_element(element);
+    }
+
+    /// Moves keyboard focus to the next `focusable` element in template order after the focused one, or the previous
+    /// with `is_backward`, wrapping around at either end, as upon Tab and Shift-Tab.  Returns whether there was one.
+    pub fn focus_next(&mut self, is_backward: bool) -> bool {
+        let next = {
+            let instance_registry = (*self.instance_registry).borrow();
+            let mut nodes = get_template_order(&instance_registry);
+            if is_backward {
+                nodes.reverse();
+            }
+            let start = self.focus_tracker.get_focused_id_chain()
+                .and_then(|id_chain| nodes.iter().position(|node| node.get_id_chain() == id_chain))
+                .map(|position| position + 1)
+                .unwrap_or(0);
+            nodes.iter().cycle().skip(start).take(nodes.len())
+                .find(|node| instance_registry.is_focusable(node.get_id_chain()[0]))
+                .cloned()
+        };
+        if next.is_none() {
+            return false;
+        }
+        self.focus(next);
+        true
+    }
+
+    /// Fulfills the latest change of focus requested by a handler through its `RuntimeContext`, returning whether there was one
+    fn fulfill_focus_request(&mut self) -> bool {
+        let request = match self.focus_tracker.take_focus_request() {
+            Some(request) => request,
+            None => return false,
+        };
+        let element = {
+            let instance_registry = (*self.instance_registry).borrow();
+            match request {
+                FocusRequest::Element(id_chain) => instance_registry.get_repeat_expanded_node(&id_chain),
+                FocusRequest::TemplateId(template_id) => {
+                    let instance_ids = instance_registry.get_instance_ids_by_template_id(&template_id);
+                    get_template_order(&instance_registry).into_iter().find(|node| instance_ids.contains(&node.get_id_chain()[0]))
+                },
+                FocusRequest::Blur => None,
+            }
+        };
+        self.focus(element);
+        true
+    }
+
+    /// Upon `mouse_down` on `target` (or on no element), unless a handler called `prevent_default`: moves keyboard focus
+    /// to the nearest `focusable` element at or above `target`, or removes it if there is none.  A change of focus
+    /// requested by a `mouse_down` handler takes precedence.
+    fn focus_on_mouse_down(&mut self, target: Option<Rc<RepeatExpandedNode<R>>>) {
+        if self.fulfill_focus_request() {
+            return;
+        }
+        let element = target.and_then(|target| {
+            let instance_registry = (*self.instance_registry).borrow();
+            RepeatExpandedNode::get_path(&target).into_iter().find(|node| instance_registry.is_focusable(node.get_id_chain()[0]))
+        });
+        self.focus(element);
+    }
+
+    /// Dispatches `key_down` to the focused element (see `get_focused_element`), propagating to its ancestors, then moves
+    /// focus upon Tab or Shift-Tab unless a handler called `prevent_default` or moved focus itself.  Returns whether the
+    /// chassis should skip its own handling of the key, i.e. whether a handler called `prevent_default` or Tab moved focus.
+    pub fn dispatch_key_down(&mut self, args_key_down: ArgsKeyDown) -> bool {
+        self.fulfill_focus_request();
+        let is_tab = args_key_down.keyboard.key == "Tab";
+        let is_backward = args_key_down.keyboard.modifiers.iter().any(|modifier| matches!(modifier, ModifierKey::Shift));
+        let default_prevented = match self.get_focused_element() {
+            Some(focused) => focused.dispatch_key_down(args_key_down),
+            None => false,
+        };
+        if self.fulfill_focus_request() || default_prevented || !is_tab {
+            return default_prevented;
+        }
+        self.focus_next(is_backward)
+    }
+
+    /// Dispatches `key_up` to the focused element, as `dispatch_key_down`.  Returns whether a handler called `prevent_default`.
+    pub fn dispatch_key_up(&mut self, args_key_up: ArgsKeyUp) -> bool {
+        self.fulfill_focus_request();
+        let default_prevented = match self.get_focused_element() {
+            Some(focused) => focused.dispatch_key_up(args_key_up),
+            None => false,
+        };
+        self.fulfill_focus_request();
+        default_prevented
+    }
+
+    /// Dispatches `key_press` to the focused element, as `dispatch_key_down`.  Returns whether a handler called `prevent_default`.
+    pub fn dispatch_key_press(&mut self, args_key_press: ArgsKeyPress) -> bool {
+        self.fulfill_focus_request();
+        let default_prevented = match self.get_focused_element() {
+            Some(focused) => focused.dispatch_key_press(args_key_press),
+            None => false,
+        };
+        self.fulfill_focus_request();
+        default_prevented
+    }
+
+    /// Called by chassis as `pointer` moves, e.g. upon `MouseMove`, to track the element beneath it and dispatch hover
+    /// events (`mouse_over`, `mouse_enter` et al.) when that changes; see `hover`
+    pub fn update_hover(&mut self, pointer: PointerId, mouse: MouseEventArgs) {
+        let element = self.get_topmost_element_beneath_ray((mouse.x, mouse.y));
+        self.hover_tracker.set_hovered_element(pointer, mouse, element);
+    }
+
+    /// Called by chassis when `pointer` stops hovering, e.g. when the mouse leaves the window or a touch ends
+    pub fn remove_pointer(&mut self, pointer: PointerId) {
+        self.hover_tracker.remove_pointer(pointer);
+    }
+
+    /// The element to which events of `pointer` at `position` are dispatched: the element capturing it, if any (see
+    /// `PointerCapture`), or else the topmost element beneath it
+    pub fn get_pointer_target(&self, pointer: PointerId, position: (f64, f64)) -> Option<Rc<RepeatExpandedNode<R>>> {
+        let capture = self.drag_tracker.get_capture(pointer, &(*self.instance_registry).borrow());
+        capture.or_else(|| self.get_topmost_element_beneath_ray(position))
+    }
+
+    /// Dispatches `mouse_down` to the topmost element beneath the mouse, then, unless a handler called `prevent_default`,
+    /// moves keyboard focus (see `focus`) and, for the left button, begins tracking a drag (see `drag`).  Returns whether
+    /// a handler called `prevent_default`.
+    pub fn dispatch_mouse_down(&mut self, args_mouse_down: ArgsMouseDown) -> bool {
+        let position = (args_mouse_down.mouse.x, args_mouse_down.mouse.y);
+        let is_left_button = matches!(args_mouse_down.mouse.button, MouseButton::Left);
+        let pointer_capture = args_mouse_down.pointer_capture.clone();
+        let target = self.get_topmost_element_beneath_ray(position);
+        let default_prevented = match &target {
+            Some(target) => target.dispatch_mouse_down(args_mouse_down),
+            None => false,
+        };
+        self.begin_press(PointerId::Mouse, position, target.as_ref().filter(|_| is_left_button), pointer_capture, default_prevented);
+        if !default_prevented {
+            self.focus_on_mouse_down(target);
+        }
+        default_prevented
+    }
+
+    /// Dispatches `mouse_move` to the element capturing the mouse or beneath it, as well as any hover and drag events.
+    /// Returns whether a handler called `prevent_default` on the `mouse_move`.
+    pub fn dispatch_mouse_move(&mut self, args_mouse_move: ArgsMouseMove) -> bool {
+        let position = (args_mouse_move.mouse.x, args_mouse_move.mouse.y);
+        self.update_hover(PointerId::Mouse, args_mouse_move.mouse.clone());
+        let default_prevented = match self.get_pointer_target(PointerId::Mouse, position) {
+            Some(target) => target.dispatch_mouse_move(args_mouse_move),
+            None => false,
+        };
+        self.drag_tracker.move_pointer(PointerId::Mouse, position, &(*self.instance_registry).borrow());
+        self.update_drop_target(PointerId::Mouse, position);
+        default_prevented
+    }
+
+    /// Dispatches `mouse_up` to the element capturing the mouse or beneath it, then ends any capture and drag.  Returns
+    /// whether a handler called `prevent_default`.
+    pub fn dispatch_mouse_up(&mut self, args_mouse_up: ArgsMouseUp) -> bool {
+        let position = (args_mouse_up.mouse.x, args_mouse_up.mouse.y);
+        let default_prevented = match self.get_pointer_target(PointerId::Mouse, position) {
+            Some(target) => target.dispatch_mouse_up(args_mouse_up),
+            None => false,
+        };
+        self.update_drop_target(PointerId::Mouse, position);
+        self.drag_tracker.release_pointer(PointerId::Mouse, position, &(*self.instance_registry).borrow());
+        default_prevented
+    }
+
+    /// Dispatches `touch_start` to the topmost element beneath its first touch point, then, unless a handler called
+    /// `prevent_default`, begins tracking a drag by that touch and a gesture (see `gesture`), or adds the touches to the
+    /// gesture in progress.  Returns whether a handler called `prevent_default`.
+    pub fn dispatch_touch_start(&mut self, args_touch_start: ArgsTouchStart) -> bool {
+        let (pointer, position) = Self::get_first_touch(&args_touch_start.touches);
+        let touches = args_touch_start.touches.clone();
+        let pointer_capture = args_touch_start.pointer_capture.clone();
+        let target = self.get_topmost_element_beneath_ray(position);
+        let default_prevented = match &target {
+            Some(target) => target.dispatch_touch_start(args_touch_start),
+            None => false,
+        };
+        self.begin_press(pointer, position, target.as_ref(), pointer_capture, default_prevented);
+        self.gesture_tracker.start_touches(&touches, target.as_ref().filter(|_| !default_prevented), self.frames_elapsed);
+        default_prevented
+    }
+
+    /// As `dispatch_mouse_move`, for the first touch point, as well as any gesture events
+    pub fn dispatch_touch_move(&mut self, args_touch_move: ArgsTouchMove) -> bool {
+        let (pointer, position) = Self::get_first_touch(&args_touch_move.touches);
+        let touches = args_touch_move.touches.clone();
+        let default_prevented = match self.get_pointer_target(pointer, position) {
+            Some(target) => target.dispatch_touch_move(args_touch_move),
+            None => false,
+        };
+        self.drag_tracker.move_pointer(pointer, position, &(*self.instance_registry).borrow());
+        self.update_drop_target(pointer, position);
+        self.gesture_tracker.move_touches(&touches, &(*self.instance_registry).borrow());
+        default_prevented
+    }
+
+    /// As `dispatch_mouse_up`, for the first touch point, also ending any gesture whose last touches were lifted
+    pub fn dispatch_touch_end(&mut self, args_touch_end: ArgsTouchEnd) -> bool {
+        let (pointer, position) = Self::get_first_touch(&args_touch_end.touches);
+        let touches = args_touch_end.touches.clone();
+        let default_prevented = match self.get_pointer_target(pointer, position) {
+            Some(target) => target.dispatch_touch_end(args_touch_end),
+            None => false,
+        };
+        self.update_drop_target(pointer, position);
+        self.drag_tracker.release_pointer(pointer, position, &(*self.instance_registry).borrow());
+        self.gesture_tracker.end_touches(&touches, self.frames_elapsed, &(*self.instance_registry).borrow());
+        default_prevented
+    }
+
+    /// Replaces the thresholds past which gestures are recognized, e.g. to require a longer `long_press`
+    pub fn set_gesture_thresholds(&mut self, thresholds: GestureThresholds) {
+        self.gesture_tracker.set_thresholds(thresholds);
+    }
+
+    /// Records the capture requested by handlers of a press on `target`, and, unless one called `prevent_default`, the
+    /// press itself, from which a drag may begin
+    fn begin_press(&mut self, pointer: PointerId, position: (f64, f64), target: Option<&Rc<RepeatExpandedNode<R>>>, pointer_capture: PointerCapture, default_prevented: bool) {
+        if let Some(id_chain) = pointer_capture.get_captured_id_chain() {
+            self.drag_tracker.set_capture(pointer, id_chain);
+        }
+        if let Some(target) = target.filter(|_| !default_prevented) {
+            self.drag_tracker.press(pointer, position, target);
+        }
+    }
+
+    /// Re-checks the drop target beneath `pointer`, if it's dragging a payload (see `drag`), and keeps rendering while
+    /// a ghost of a dragged element follows the pointer
+    fn update_drop_target(&mut self, pointer: PointerId, position: (f64, f64)) {
+        if self.drag_tracker.get_ghost().is_some() {
+            self.request_render();
+        }
+        if let Some(source_id_chain) = self.drag_tracker.get_payload_source(pointer) {
+            let target = self.get_topmost_element_beneath_ray_excluding(position, &source_id_chain);
+            self.drag_tracker.set_drop_target(pointer, position, target, &(*self.instance_registry).borrow());
+        }
+    }
+
+    fn get_first_touch(touches: &[Touch]) -> (PointerId, (f64, f64)) {
+        let first_touch = touches.first().expect("A touch event needs at least one touch point");
+        (PointerId::Touch(first_touch.identifier), (first_touch.x, first_touch.y))
+    }
+
+    /// The topmost element beneath `pointer`, as of its last move or the last tick
+    pub fn get_hovered_element(&self, pointer: PointerId) -> Option<Rc<RepeatExpandedNode<R>>> {
+        self.hover_tracker.get_hovered_element(pointer)
+    }
+
 
     /// Called by chassis when viewport size changes, e.g. with native window resizes
     pub fn set_viewport_size(&mut self, new_viewport_size: (f64, f64)) {
+        if self.viewport_tab.bounds != new_viewport_size {
+            self.request_render();
+        }
         self.viewport_tab.bounds = new_viewport_size;
     }
 
+    /// Whether the next tick may render anything different than the last: true if any property was created or
+    /// written since, e.g. by an event handler, or if a render was requested with `request_render`.
+    /// Chassis may skip ticks (and clearing their canvases) while this is false.
+    pub fn needs_render(&self) -> bool {
+        self.needs_render.get() || peek_property_version() != self.last_tick_property_version
+    }
+
+    /// Ensure that the next tick renders, e.g. upon an interrupt, or when a node has staged changes for the next tick
+    pub fn request_render(&self) {
+        self.needs_render.set(true);
+    }
+
     /// Workhorse method to advance rendering and property calculation by one discrete tick
     /// Will be executed synchronously up to 240 times/second.
     pub fn tick(&mut self, rcs: &mut Vec<R>) -> Vec<NativeMessage> {
+        //re-check hovers against the last frame, since elements may have moved beneath pointers that haven't
+        for (pointer, mouse) in self.hover_tracker.get_pointers() {
+            self.update_hover(pointer, mouse);
+        }
+        //likewise for focus, which may have been requested by handlers since, or lost by an element no longer rendered
+        self.focus_tracker.refresh(&(*self.instance_registry).borrow());
+        self.fulfill_focus_request();
+        //and for long presses, which are recognized by touches staying still rather than by any input
+        self.gesture_tracker.tick(self.frames_elapsed, &(*self.instance_registry).borrow());
         (*self.instance_registry).borrow_mut().reset_repeat_expanded_node_cache();
+        self.expression_stats.set(ExpressionStats::default());
+        self.needs_render.set(false);
         let native_render_queue = self.traverse_render_tree(rcs);
+        //evict the cached values of expressions that weren't computed this tick, e.g. those of unmounted nodes
+        let frames_elapsed = self.frames_elapsed;
+        self.expression_cache.borrow_mut().retain(|_, cached| cached.frame == frames_elapsed);
+        //the outgoing nodes of hot-reloaded templates were unmounted during this traversal; mount their replacements on the next
+        for (template, nodes) in self.staged_templates.drain(..) {
+            *template.borrow_mut() = nodes;
+            self.needs_render.set(true);
+        }
+        //gestures are timed in frames, so keep ticking while one is in progress, even if its touches are still
+        if self.gesture_tracker.is_active() {
+            self.needs_render.set(true);
+        }
+        //properties written during the tick have already been rendered
+        self.last_tick_property_version = peek_property_version();
         self.frames_elapsed = self.frames_elapsed + 1;
         native_render_queue
     }
 
+    /// Applies a template/expression patch from `pax run --watch` without restarting, e.g. between ticks upon a chassis interrupt.
+    /// Expressions are registered immediately (their ids are new), while each live instance of a patched component has its
+    /// template's nodes marked for unmounting and replaced at the end of the next tick.  Components keep their properties,
+    /// apart from those instantiated by the replaced templates themselves.
+    pub fn apply_hot_reload_patch(&mut self, patch: HotReloadPatch, cartridge: &HotReloadCartridge<R>) -> Result<(), String> {
+        let mut staged : Vec<(RenderNodePtrList<R>, Vec<RenderNodePtr<R>>)> = vec![];
+
+        //Replacement templates may themselves instantiate patched components, whose (compiled) templates need replacing
+        //in turn, so repeat until every live template of a patched component has been visited
+        loop {
+            let mut is_settled = true;
+            for template_patch in patch.templates.iter() {
+                let live_templates = self.instance_registry.borrow_mut().get_component_templates(&template_patch.component_type_id);
+                for template in live_templates {
+                    if staged.iter().any(|(staged_template, _)| Rc::ptr_eq(staged_template, &template)) {
+                        continue;
+                    }
+                    is_settled = false;
+                    let nodes = instantiate_hot_reload_template(cartridge, &self.instance_registry, &template_patch.component_type_id, &template_patch.root_nodes)
+                        .ok_or_else(|| format!("The new template of {} uses a component or event handler unknown to the running app", &template_patch.component_type_id))?;
+                    staged.push((template, nodes));
+                }
+            }
+            if is_settled {
+                break;
+            }
+        }
+
+        for expression_patch in patch.expressions {
+            self.expression_table.dependency_graph.remove(&expression_patch.id);
+            self.expression_table.inner_map.insert(expression_patch.id, interpret_expression(expression_patch, cartridge.read_property, cartridge.coerce));
+        }
+
+        let mut instance_registry = self.instance_registry.borrow_mut();
+        for (template, _) in staged.iter() {
+            (**template).borrow().iter().for_each(|node| {
+                let instance_id = (**node).borrow().get_instance_id();
+                instance_registry.deregister(instance_id);
+                instance_registry.mark_for_unmount(instance_id);
+            });
+        }
+        self.staged_templates.extend(staged);
+        self.request_render();
+        Ok(())
+    }
+
+    /// Counts of expressions evaluated vs. reused from cache during the most recent tick
+    pub fn get_expression_stats(&self) -> ExpressionStats {
+        self.expression_stats.get()
+    }
+
     pub fn loadImage(&mut self, id_chain: Vec<u64>, image_data: Vec<u8>, width: usize, height: usize) {
         self.image_map.insert(id_chain, (Box::new(image_data), width, height));
+        self.request_render();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use piet::NullRenderContext;
+
+    use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
+    use pax_runtime_api::{PlatformSpecificLogger, PropertyInstance, PropertyLiteral, Transform2D};
+
+    use crate::testing::TestDriver;
+    use crate::{ComponentInstance, ConditionalInstance, ExpressionInput, InstanceRegistry, InstantiationArgs, PropertyExpression, RenderNode, RenderNodePtr};
+
+    use super::{ExpressionVTable, PaxEngine};
+
+    type Registry = Rc<RefCell<InstanceRegistry<NullRenderContext>>>;
+
+    fn args(instance_registry: &Registry) -> InstantiationArgs<NullRenderContext> {
+        InstantiationArgs {
+            properties: PropertiesCoproduct::None,
+            handler_registry: None,
+            instance_registry: Rc::clone(instance_registry),
+            transform: Rc::new(RefCell::new(PropertyLiteral::new(Transform2D::default()))),
+            size: None,
+            children: None,
+            component_template: None,
+            scroller_args: None,
+            slot_index: None,
+            repeat_source_expression_vec: None,
+            repeat_key_expression: None,
+            conditional_boolean_expression: None,
+            conditional_false_branch_children: None,
+            match_expression: None,
+            match_arms: None,
+            compute_properties_fn: None,
+        }
+    }
+
+    fn conditional(instance_registry: &Registry, vtable_id: usize, children: Vec<RenderNodePtr<NullRenderContext>>) -> RenderNodePtr<NullRenderContext> {
+        ConditionalInstance::instantiate(InstantiationArgs {
+            conditional_boolean_expression: Some(Box::new(PropertyExpression::new(vtable_id))),
+            children: Some(Rc::new(RefCell::new(children))),
+            ..args(instance_registry)
+        })
+    }
+
+    /// Ticks after writing `property`, returning the (evaluated, reused) counts of that tick
+    fn tick_with(driver: &mut TestDriver<NullRenderContext>, property: &Rc<RefCell<PropertyLiteral<bool>>>, value: bool) -> (usize, usize) {
+        property.borrow_mut().set(value);
+        let stats = driver.tick().engine().get_expression_stats();
+        (stats.evaluated, stats.reused)
+    }
+
+    /// `if self.is_open { if true {} }  if self.is_visible {}`, where `is_open` and `is_visible` are stood in for by
+    /// literals captured by expressions 0 and 1.  Expression 2 has no dependencies.
+    fn new_driver(is_open: &Rc<RefCell<PropertyLiteral<bool>>>, is_visible: &Rc<RefCell<PropertyLiteral<bool>>>) -> TestDriver<NullRenderContext> {
+        let mut expression_table = ExpressionVTable { inner_map: HashMap::new(), dependency_graph: HashMap::new() };
+        for (id, property) in [(0, is_open), (1, is_visible)] {
+            let read = Rc::clone(property);
+            expression_table.inner_map.insert(id, Box::new(move |_| TypesCoproduct::bool(*read.borrow().get())));
+            let read = Rc::clone(property);
+            expression_table.dependency_graph.insert(id, Box::new(move |_| vec![ExpressionInput::Version(read.borrow()._get_version())]));
+        }
+        expression_table.inner_map.insert(2, Box::new(|_| TypesCoproduct::bool(true)));
+        expression_table.dependency_graph.insert(2, Box::new(|_| vec![]));
+
+        let instance_registry : Registry = Rc::new(RefCell::new(InstanceRegistry::new()));
+        let nested = conditional(&instance_registry, 2, vec![]);
+        let template = vec![conditional(&instance_registry, 0, vec![nested]), conditional(&instance_registry, 1, vec![])];
+        let main_component = ComponentInstance::instantiate(InstantiationArgs {
+            component_template: Some(Rc::new(RefCell::new(template))),
+            compute_properties_fn: Some(Box::new(|_, _| {})),
+            ..args(&instance_registry)
+        });
+
+        let engine = PaxEngine::new(main_component, expression_table, PlatformSpecificLogger::Native(|_| {}), (100.0, 100.0), instance_registry);
+        TestDriver::new(engine, vec![NullRenderContext::new()])
+    }
+
+    fn is_cached(driver: &TestDriver<NullRenderContext>, vtable_id: usize) -> bool {
+        driver.engine().expression_cache.borrow().keys().any(|(id, _)| *id == vtable_id)
+    }
+
+    #[test]
+    fn test_expressions_are_reused_until_their_dependencies_change() {
+        let is_open = Rc::new(RefCell::new(PropertyLiteral::new(true)));
+        let is_visible = Rc::new(RefCell::new(PropertyLiteral::new(true)));
+        let mut driver = new_driver(&is_open, &is_visible);
+        let stats = driver.engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (3, 0));
+
+        //nothing was written, so nothing is re-evaluated
+        let stats = driver.tick().engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (0, 3));
+
+        //only the expression reading `is_visible` depends on it, whether or not its value changes
+        assert_eq!(tick_with(&mut driver, &is_visible, false), (1, 2));
+        assert_eq!(tick_with(&mut driver, &is_visible, false), (1, 2));
+        let stats = driver.tick().engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (0, 3));
+    }
+
+    #[test]
+    fn test_expressions_of_unmounted_nodes_are_evicted() {
+        let is_open = Rc::new(RefCell::new(PropertyLiteral::new(true)));
+        let is_visible = Rc::new(RefCell::new(PropertyLiteral::new(true)));
+        let mut driver = new_driver(&is_open, &is_visible);
+        assert!(is_cached(&driver, 2));
+
+        //the true branch unmounts on the tick after `is_open` changes, after which its expressions aren't computed
+        tick_with(&mut driver, &is_open, false);
+        let stats = driver.tick().engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (0, 2));
+        assert!(!is_cached(&driver, 2));
+        assert!(is_cached(&driver, 0) && is_cached(&driver, 1));
+
+        //so remounting it re-evaluates them, despite their (lack of) dependencies being unchanged
+        assert_eq!(tick_with(&mut driver, &is_open, true), (1, 1));
+        let stats = driver.tick().engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (1, 2));
+        let stats = driver.tick().engine().get_expression_stats();
+        assert_eq!((stats.evaluated, stats.reused), (0, 3));
     }
 }
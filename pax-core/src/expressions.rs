@@ -6,9 +6,10 @@ use piet::RenderContext;
 
 use crate::{PaxEngine, RenderNodePtr, RenderTreeContext};
 use crate::runtime::StackFrame;
+use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 
 
-use pax_runtime_api::{EasingCurve, PropertyInstance, PropertyLiteral, TransitionManager, TransitionQueueEntry};
+use pax_runtime_api::{EasingCurve, mint_property_version, PropertyInstance, PropertyLiteral, TransitionManager, TransitionQueueEntry};
 
 
 // The `Expression` form of a property — stores a function
@@ -19,6 +20,8 @@ pub struct PropertyExpression<T: Default>
 {
     pub id: usize,
     pub cached_value: T,
+    /// Version of `cached_value`, as minted by the engine when it was evaluated; see `PropertyInstance::_get_version`
+    pub version: u64,
     pub transition_manager: TransitionManager<T>,
 }
 
@@ -28,6 +31,7 @@ impl<T: Default> PropertyExpression<T> {
         Self {
             id,
             cached_value: Default::default(),
+            version: mint_property_version(),
             transition_manager: TransitionManager::new(),
         }
     }
@@ -52,12 +56,23 @@ impl<T: Default + Clone> PropertyInstance<T> for PropertyExpression<T> {
         Some(self.id)
     }
 
+    fn _get_version(&self) -> u64 {
+        self.version
+    }
+
     fn set(&mut self, value: T) {
         self.cached_value = value;
+        self.version = mint_property_version();
+    }
+
+    fn _set_computed(&mut self, value: T, version: u64) {
+        self.cached_value = value;
+        self.version = version;
     }
 
     //FUTURE: when trait fields land, DRY this implementation vs. other <T: PropertyInstance> implementations
     fn ease_to(&mut self, new_value: T, duration_frames: u64, curve: EasingCurve) {
+        self.version = mint_property_version();
         self.transition_manager.value = Some(self.get().clone());
         &self.transition_manager.queue.clear();
         &self.transition_manager.queue.push_back(TransitionQueueEntry {
@@ -70,6 +85,7 @@ impl<T: Default + Clone> PropertyInstance<T> for PropertyExpression<T> {
     }
 
     fn ease_to_later(&mut self, new_value: T, duration_frames: u64, curve: EasingCurve) {
+        self.version = mint_property_version();
         if let None = self.transition_manager.value {
             //handle case where transition queue is empty -- a None value gets skipped, so populate it with Some
             self.transition_manager.value = Some(self.get().clone());
@@ -102,3 +118,42 @@ pub struct ExpressionContext<'a, R: 'static + RenderContext> {
     //TODO: is the following the right approach to enabling evaluation of built-ins?
     // pub render_node: RenderNodePtr<R>,
 }
+
+/// The state of one input to an Expression, as read by its entry in `ExpressionVTable::dependency_graph`.
+/// An Expression's value is reused for as long as all of its inputs compare equal.
+pub enum ExpressionInput {
+    /// The version of a property (see `PropertyInstance::_get_version`)
+    Version(u64),
+    /// The index `i` of a `RepeatItem`
+    Index(usize),
+    /// The datum `elem` of a `RepeatItem`, compared by identity.  Holding it also keeps its address from being reused.
+    Datum(Rc<PropertiesCoproduct>),
+}
+
+impl PartialEq for ExpressionInput {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ExpressionInput::Version(a), ExpressionInput::Version(b)) => a == b,
+            (ExpressionInput::Index(a), ExpressionInput::Index(b)) => a == b,
+            (ExpressionInput::Datum(a), ExpressionInput::Datum(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// The most recent value of an Expression evaluated within a particular stack frame, along with the inputs it was evaluated from
+pub struct CachedExpression {
+    pub inputs: Vec<ExpressionInput>,
+    pub value: TypesCoproduct,
+    /// Version minted for `value`, written along with it to the bound property
+    pub version: u64,
+    /// The last frame in which this entry was used; unused entries are evicted at the end of each tick
+    pub frame: usize,
+}
+
+/// Counts of Expressions evaluated vs. reused during a single tick
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ExpressionStats {
+    pub evaluated: usize,
+    pub reused: usize,
+}
@@ -0,0 +1,89 @@
+//! Keyboard focus.  Key events are dispatched to the focused element, propagating to its ancestors, while `focus` and
+//! `blur` are dispatched to the elements gaining and losing it, without propagating.
+//!
+//! Focus moves:
+//! - with Tab and Shift-Tab, between the elements marked `focusable=true` in Pax, in template order
+//! - upon `mouse_down`, to the nearest `focusable` element at or above the target, or nowhere if there is none
+//! - upon a handler's request, through its `RuntimeContext` (e.g. `ctx.focus()`), once the handler returns
+//!
+//! Focus is dropped, without `blur`, when the focused element is no longer rendered.
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+
+use pax_runtime_api::{ArgsBlur, ArgsFocus, EventControl, FocusRequest};
+
+use crate::{InstanceRegistry, RepeatExpandedNode};
+
+pub struct FocusTracker<R: 'static + RenderContext> {
+    /// The focused element followed by its ancestors, as in `hover`
+    path: Vec<Rc<RepeatExpandedNode<R>>>,
+    /// Shared with every `RuntimeContext`, through which handlers request changes of focus
+    request: Rc<RefCell<Option<FocusRequest>>>,
+}
+
+impl<R: 'static + RenderContext> Default for FocusTracker<R> {
+    fn default() -> Self {
+        FocusTracker {
+            path: vec![],
+            request: Rc::new(RefCell::new(None)),
+        }
+    }
+}
+
+impl<R: 'static + RenderContext> FocusTracker<R> {
+    pub fn get_focus_request(&self) -> Rc<RefCell<Option<FocusRequest>>> {
+        Rc::clone(&self.request)
+    }
+
+    pub fn take_focus_request(&self) -> Option<FocusRequest> {
+        self.request.borrow_mut().take()
+    }
+
+    /// The id chain of the focused element, if any
+    pub fn get_focused_id_chain(&self) -> Option<&Vec<u64>> {
+        self.path.first().map(|node| node.get_id_chain())
+    }
+
+    /// Moves focus to `element`, dispatching `blur` to the element that had it and `focus` to `element`, if they differ
+    pub fn set_focused_element(&mut self, element: Option<Rc<RepeatExpandedNode<R>>>) {
+        if self.get_focused_id_chain() == element.as_ref().map(|node| node.get_id_chain()) {
+            return;
+        }
+        let previous_path = std::mem::replace(&mut self.path, element.map(|element| RepeatExpandedNode::get_path(&element)).unwrap_or_default());
+        if let Some(previous) = previous_path.first() {
+            previous.dispatch_blur(ArgsBlur { control: EventControl::default() });
+        }
+        if let Some(focused) = self.path.first() {
+            focused.dispatch_focus(ArgsFocus { control: EventControl::default() });
+        }
+    }
+
+    /// Re-reads the focused element from the most recent tick, dropping focus if it wasn't rendered
+    pub fn refresh(&mut self, instance_registry: &InstanceRegistry<R>) {
+        if let Some(id_chain) = self.get_focused_id_chain() {
+            self.path = instance_registry.get_repeat_expanded_node(id_chain).map(|node| RepeatExpandedNode::get_path(&node)).unwrap_or_default();
+        }
+    }
+}
+
+/// The elements rendered by the most recent tick in template order, i.e. each element before its descendants and
+/// after its preceding siblings and their descendants
+pub fn get_template_order<R: 'static + RenderContext>(instance_registry: &InstanceRegistry<R>) -> Vec<Rc<RepeatExpandedNode<R>>> {
+    //the cache holds each element before its descendants, but visits siblings last-first for z-ordering
+    let nodes = instance_registry.get_repeat_expanded_nodes();
+    let indices : HashMap<*const RepeatExpandedNode<R>, usize> = nodes.iter().enumerate()
+        .map(|(index, node)| (Rc::as_ptr(node), index))
+        .collect();
+    let mut ordered = nodes.clone();
+    ordered.sort_by_cached_key(|node| {
+        RepeatExpandedNode::get_path(node).iter().rev()
+            .filter_map(|node| indices.get(&Rc::as_ptr(node)).map(|index| Reverse(*index)))
+            .collect::<Vec<_>>()
+    });
+    ordered
+}
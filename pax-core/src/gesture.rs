@@ -0,0 +1,348 @@
+//! Recognizes multi-touch gestures in touch input, so that components needn't each reimplement the math.
+//!
+//! A gesture begins with the first touch upon an element and ends when its last touch is lifted; touches added along
+//! the way join it.  Its events are dispatched to the element beneath the first touch, propagating to its ancestors:
+//! - `pan`, `pinch` and `rotate` once the touches' centroid, spread or angle pass their thresholds, then upon each move
+//! - `long_press` once the touches are held still for long enough, checked each tick
+//! - `swipe` once the touches are lifted, if they moved far and fast enough
+//!
+//! Thresholds are configurable with `PaxEngine::set_gesture_thresholds`.  Time is measured in frames, as for transitions,
+//! so the engine keeps ticking while a gesture is in progress (see `GestureTracker::is_active`), even if nothing moves.
+//! A handler calling `prevent_default` on the first `touch_start` prevents the gesture.
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+
+use pax_runtime_api::{ArgsLongPress, ArgsPan, ArgsPinch, ArgsRotate, ArgsSwipe, EventArgs, EventControl, SwipeDirection, Touch};
+
+use crate::{EventHandler, HandlerRegistry, InstanceRegistry, RepeatExpandedNode};
+
+#[derive(Clone, Debug)]
+pub struct GestureThresholds {
+    /// The distance in px that the touches' centroid moves before a `pan` begins
+    pub pan_distance: f64,
+    /// The change in the touches' relative spread before a `pinch` begins, e.g. 0.1 for 10%
+    pub pinch_scale: f64,
+    /// The angle in radians through which the touches turn before a `rotate` begins
+    pub rotate_angle: f64,
+    /// The least distance in px that the touches' centroid moves for a `swipe`
+    pub swipe_distance: f64,
+    /// The least average speed in px per frame of the touches' centroid for a `swipe`
+    pub swipe_velocity: f64,
+    /// The number of frames that the touches are held before a `long_press`
+    pub long_press_frames: usize,
+    /// The furthest distance in px that the touches' centroid may wander during a `long_press`
+    pub long_press_distance: f64,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        GestureThresholds {
+            pan_distance: 10.0,
+            pinch_scale: 0.1,
+            rotate_angle: 0.15,
+            swipe_distance: 30.0,
+            swipe_velocity: 5.0,
+            long_press_frames: 30,
+            long_press_distance: 10.0,
+        }
+    }
+}
+
+/// The centroid, spread and angle of a set of touches, between which successive moves are compared
+#[derive(Clone, Copy)]
+struct Shape {
+    centroid: (f64, f64),
+    /// The mean distance of the touches from their centroid
+    spread: f64,
+    /// The angle of the line from the first touch to the second, if there are two
+    angle: Option<f64>,
+}
+
+impl Shape {
+    fn new(touches: &BTreeMap<i64, (f64, f64)>) -> Self {
+        let count = touches.len().max(1) as f64;
+        let centroid = touches.values().fold((0.0, 0.0), |sum, point| (sum.0 + point.0 / count, sum.1 + point.1 / count));
+        let spread = touches.values().map(|point| (point.0 - centroid.0).hypot(point.1 - centroid.1)).sum::<f64>() / count;
+        let mut points = touches.values();
+        let angle = match (points.next(), points.next()) {
+            (Some(first), Some(second)) => Some((second.1 - first.1).atan2(second.0 - first.0)),
+            _ => None,
+        };
+        Shape { centroid, spread, angle }
+    }
+}
+
+struct Gesture<R: 'static + RenderContext> {
+    /// The element beneath the first touch followed by its ancestors, as in `hover`
+    path: Vec<Rc<RepeatExpandedNode<R>>>,
+    /// The position of each touch, by `Touch::identifier`
+    touches: BTreeMap<i64, (f64, f64)>,
+    /// The shape of the touches as of their last move, or the last touch added or lifted
+    shape: Shape,
+    start_frame: usize,
+    /// The centroid of the touches when the gesture began
+    origin: (f64, f64),
+    /// Accumulated over every move since the gesture began, so that touches joining or leaving don't cause jumps
+    pan: (f64, f64),
+    scale: f64,
+    rotation: f64,
+    is_panning: bool,
+    is_pinching: bool,
+    is_rotating: bool,
+    is_long_pressed: bool,
+}
+
+pub struct GestureTracker<R: 'static + RenderContext> {
+    gesture: Option<Gesture<R>>,
+    thresholds: GestureThresholds,
+}
+
+impl<R: 'static + RenderContext> Default for GestureTracker<R> {
+    fn default() -> Self {
+        GestureTracker {
+            gesture: None,
+            thresholds: GestureThresholds::default(),
+        }
+    }
+}
+
+impl<R: 'static + RenderContext> GestureTracker<R> {
+    pub fn set_thresholds(&mut self, thresholds: GestureThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Adds `touches` to the gesture in progress, or begins one upon `target` if there is none
+    pub fn start_touches(&mut self, touches: &[Touch], target: Option<&Rc<RepeatExpandedNode<R>>>, frame: usize) {
+        if self.gesture.is_none() {
+            let target = match target {
+                Some(target) => target,
+                None => return,
+            };
+            self.gesture = Some(Gesture::new(RepeatExpandedNode::get_path(target), touches, frame));
+            return;
+        }
+        let gesture = self.gesture.as_mut().unwrap();
+        gesture.touches.extend(Self::get_touch_points(touches));
+        gesture.shape = Shape::new(&gesture.touches);
+    }
+
+    /// Whether a gesture is in progress, during which the engine must tick every frame to time long presses and swipes
+    pub fn is_active(&self) -> bool {
+        self.gesture.is_some()
+    }
+
+    /// Updates the gesture with the moved `touches`, dispatching whichever of `pan`, `pinch` and `rotate` have begun
+    pub fn move_touches(&mut self, touches: &[Touch], instance_registry: &InstanceRegistry<R>) {
+        let thresholds = &self.thresholds;
+        let gesture = match self.gesture.as_mut() {
+            Some(gesture) => gesture,
+            None => return,
+        };
+        gesture.update(touches);
+        gesture.is_panning |= gesture.pan.0.hypot(gesture.pan.1) >= thresholds.pan_distance;
+        gesture.is_pinching |= (gesture.scale - 1.0).abs() >= thresholds.pinch_scale;
+        gesture.is_rotating |= gesture.rotation.abs() >= thresholds.rotate_angle;
+
+        let center = gesture.shape.centroid;
+        if gesture.is_panning {
+            let target = &gesture.get_current_path(instance_registry)[0];
+            let origin = target.get_local_point(gesture.origin);
+            let local = target.get_local_point((gesture.origin.0 + gesture.pan.0, gesture.origin.1 + gesture.pan.1));
+            gesture.dispatch(instance_registry, ArgsPan { delta_x: local.0 - origin.0, delta_y: local.1 - origin.1, center, control: EventControl::default() },
+                |registry| &registry.pan_capture_handlers, |registry| &registry.pan_handlers);
+        }
+        if gesture.is_pinching {
+            gesture.dispatch(instance_registry, ArgsPinch { scale: gesture.scale, center, control: EventControl::default() },
+                |registry| &registry.pinch_capture_handlers, |registry| &registry.pinch_handlers);
+        }
+        if gesture.is_rotating {
+            gesture.dispatch(instance_registry, ArgsRotate { rotation: gesture.rotation, center, control: EventControl::default() },
+                |registry| &registry.rotate_capture_handlers, |registry| &registry.rotate_handlers);
+        }
+    }
+
+    /// Removes the lifted `touches` from the gesture, ending it with any `swipe` if they were its last
+    pub fn end_touches(&mut self, touches: &[Touch], frame: usize, instance_registry: &InstanceRegistry<R>) {
+        let gesture = match self.gesture.as_mut() {
+            Some(gesture) => gesture,
+            None => return,
+        };
+        gesture.update(touches);
+        for touch in touches {
+            gesture.touches.remove(&touch.identifier);
+        }
+        gesture.shape = Shape::new(&gesture.touches);
+        if !gesture.touches.is_empty() {
+            return;
+        }
+
+        let gesture = self.gesture.take().unwrap();
+        let distance = gesture.pan.0.hypot(gesture.pan.1);
+        let velocity = distance / frame.saturating_sub(gesture.start_frame).max(1) as f64;
+        if gesture.is_long_pressed || distance < self.thresholds.swipe_distance || velocity < self.thresholds.swipe_velocity {
+            return;
+        }
+        let direction = get_swipe_direction(gesture.pan);
+        gesture.dispatch(instance_registry, ArgsSwipe { direction, velocity, control: EventControl::default() },
+            |registry| &registry.swipe_capture_handlers, |registry| &registry.swipe_handlers);
+    }
+
+    /// Dispatches `long_press` if the gesture's touches have been held still for long enough
+    pub fn tick(&mut self, frame: usize, instance_registry: &InstanceRegistry<R>) {
+        let thresholds = &self.thresholds;
+        let gesture = match self.gesture.as_mut() {
+            Some(gesture) => gesture,
+            None => return,
+        };
+        let is_still = !gesture.is_panning && !gesture.is_pinching && !gesture.is_rotating
+            && gesture.pan.0.hypot(gesture.pan.1) <= thresholds.long_press_distance;
+        if gesture.is_long_pressed || !is_still || frame.saturating_sub(gesture.start_frame) < thresholds.long_press_frames {
+            return;
+        }
+        gesture.is_long_pressed = true;
+        let (x, y) = gesture.shape.centroid;
+        gesture.dispatch(instance_registry, ArgsLongPress { x, y, control: EventControl::default() },
+            |registry| &registry.long_press_capture_handlers, |registry| &registry.long_press_handlers);
+    }
+
+    fn get_touch_points(touches: &[Touch]) -> BTreeMap<i64, (f64, f64)> {
+        touches.iter().map(|touch| (touch.identifier, (touch.x, touch.y))).collect()
+    }
+}
+
+/// The direction of the greater component of `pan`, favoring the horizontal
+fn get_swipe_direction(pan: (f64, f64)) -> SwipeDirection {
+    if pan.0.abs() >= pan.1.abs() {
+        if pan.0 > 0.0 { SwipeDirection::Right } else { SwipeDirection::Left }
+    } else {
+        if pan.1 > 0.0 { SwipeDirection::Down } else { SwipeDirection::Up }
+    }
+}
+
+impl<R: 'static + RenderContext> Gesture<R> {
+    fn new(path: Vec<Rc<RepeatExpandedNode<R>>>, touches: &[Touch], frame: usize) -> Self {
+        let touch_points = GestureTracker::<R>::get_touch_points(touches);
+        let shape = Shape::new(&touch_points);
+        Gesture {
+            path,
+            touches: touch_points,
+            shape,
+            start_frame: frame,
+            origin: shape.centroid,
+            pan: (0.0, 0.0),
+            scale: 1.0,
+            rotation: 0.0,
+            is_panning: false,
+            is_pinching: false,
+            is_rotating: false,
+            is_long_pressed: false,
+        }
+    }
+
+    /// Moves the gesture's touches among `touches`, accumulating the change in their shape
+    fn update(&mut self, touches: &[Touch]) {
+        for touch in touches {
+            if let Some(point) = self.touches.get_mut(&touch.identifier) {
+                *point = (touch.x, touch.y);
+            }
+        }
+        let shape = Shape::new(&self.touches);
+        self.pan = (self.pan.0 + shape.centroid.0 - self.shape.centroid.0, self.pan.1 + shape.centroid.1 - self.shape.centroid.1);
+        if shape.spread > 0.0 && self.shape.spread > 0.0 {
+            self.scale *= shape.spread / self.shape.spread;
+        }
+        if let (Some(angle), Some(previous_angle)) = (shape.angle, self.shape.angle) {
+            //the smaller of the two turns between the angles, so that crossing from π to -π isn't a full turn back
+            let turn = (angle - previous_angle + PI).rem_euclid(2.0 * PI) - PI;
+            self.rotation += turn;
+        }
+        self.shape = shape;
+    }
+
+    /// The element beneath the first touch followed by its ancestors, as rendered by the most recent tick if it still is
+    fn get_current_path(&self, instance_registry: &InstanceRegistry<R>) -> Vec<Rc<RepeatExpandedNode<R>>> {
+        instance_registry.get_repeat_expanded_node(self.path[0].get_id_chain())
+            .map(|node| RepeatExpandedNode::get_path(&node))
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    fn dispatch<A: EventArgs>(
+        &self,
+        instance_registry: &InstanceRegistry<R>,
+        args: A,
+        get_capture_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+        get_handlers: fn(&HandlerRegistry<R>) -> &Vec<EventHandler<R, A>>,
+    ) {
+        let path = self.get_current_path(instance_registry);
+        let path : Vec<&RepeatExpandedNode<R>> = path.iter().map(|node| node.as_ref()).collect();
+        RepeatExpandedNode::dispatch_along_path(&path, args, get_capture_handlers, get_handlers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use piet::NullRenderContext;
+
+    use pax_runtime_api::{SwipeDirection, Touch};
+
+    use super::{get_swipe_direction, Gesture};
+
+    fn touch(identifier: i64, x: f64, y: f64) -> Touch {
+        Touch { x, y, identifier, delta_x: 0.0, delta_y: 0.0 }
+    }
+
+    fn new_gesture(touches: &[Touch]) -> Gesture<NullRenderContext> {
+        Gesture::new(vec![], touches, 0)
+    }
+
+    #[test]
+    fn test_scale() {
+        let mut gesture = new_gesture(&[touch(0, 90.0, 100.0), touch(1, 110.0, 100.0)]);
+        gesture.update(&[touch(0, 80.0, 100.0), touch(1, 120.0, 100.0)]);
+        assert!((gesture.scale - 2.0).abs() < 1e-9);
+        assert!((gesture.shape.centroid.0 - 100.0).abs() < 1e-9);
+        assert_eq!(gesture.pan, (0.0, 0.0));
+
+        //scales compound across moves
+        gesture.update(&[touch(0, 60.0, 100.0), touch(1, 140.0, 100.0)]);
+        assert!((gesture.scale - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rotation_wraps_at_pi() {
+        //the angle from the first touch to the second crosses from just under π to just over -π
+        let mut gesture = new_gesture(&[touch(0, 100.0, 100.0), touch(1, 0.0, 101.0)]);
+        assert!((gesture.shape.angle.unwrap() - PI).abs() < 0.02);
+        gesture.update(&[touch(0, 100.0, 100.0), touch(1, 0.0, 99.0)]);
+        assert!((gesture.shape.angle.unwrap() + PI).abs() < 0.02);
+        assert!(gesture.rotation > 0.0 && gesture.rotation < 0.03, "rotation {} should be a small turn, not a full one", gesture.rotation);
+    }
+
+    #[test]
+    fn test_pan_accumulates_as_touches_join() {
+        let mut gesture = new_gesture(&[touch(0, 0.0, 0.0)]);
+        gesture.update(&[touch(0, 10.0, 0.0)]);
+        //a second touch shifts the centroid without moving, so isn't a pan
+        gesture.touches.insert(1, (110.0, 0.0));
+        gesture.shape = super::Shape::new(&gesture.touches);
+        gesture.update(&[touch(0, 20.0, 0.0), touch(1, 120.0, 0.0)]);
+        assert!((gesture.pan.0 - 20.0).abs() < 1e-9);
+        assert!((gesture.scale - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_swipe_direction() {
+        assert!(matches!(get_swipe_direction((50.0, 10.0)), SwipeDirection::Right));
+        assert!(matches!(get_swipe_direction((-50.0, 10.0)), SwipeDirection::Left));
+        assert!(matches!(get_swipe_direction((10.0, 50.0)), SwipeDirection::Down));
+        assert!(matches!(get_swipe_direction((10.0, -50.0)), SwipeDirection::Up));
+        //diagonals favor the horizontal
+        assert!(matches!(get_swipe_direction((-30.0, 30.0)), SwipeDirection::Left));
+    }
+}
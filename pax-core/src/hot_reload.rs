@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+use pax_message::hot_reload::{BinaryOperator, ExpressionPatch, PaxelExpression, SizeUnit, SymbolKind, TemplateNodePatch};
+use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
+use pax_runtime_api::{Numeric, PropertyInstance, PropertyLiteral, Size, Transform2D};
+
+use crate::{ExpressionContext, InstanceRegistry, PropertyExpression, RenderNodePtr, RenderNodePtrList};
+
+/// A value computed while interpreting a hot-reloaded expression
+#[derive(Clone)]
+pub enum DynamicValue {
+    Numeric(Numeric),
+    Bool(bool),
+    String(String),
+    Size(Size),
+    Transform2D(Transform2D),
+}
+
+macro_rules! impl_dynamic_value_for_numeric_primitives {
+    ($($primitive:ty => $getter:ident),*) => {
+        $(
+            impl From<$primitive> for DynamicValue {
+                fn from(value: $primitive) -> Self {
+                    DynamicValue::Numeric(Numeric::from(value))
+                }
+            }
+
+            impl TryFrom<DynamicValue> for $primitive {
+                type Error = ();
+                fn try_from(value: DynamicValue) -> Result<Self, Self::Error> {
+                    match value {
+                        DynamicValue::Numeric(numeric) => Ok(numeric.$getter() as $primitive),
+                        _ => Err(()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_dynamic_value_for_numeric_primitives!(
+    u8 => get_as_int, u16 => get_as_int, u32 => get_as_int, u64 => get_as_int, usize => get_as_int,
+    i8 => get_as_int, i16 => get_as_int, i32 => get_as_int, i64 => get_as_int, isize => get_as_int,
+    f64 => get_as_float
+);
+
+macro_rules! impl_dynamic_value_for_variant {
+    ($($value_type:ty => $variant:ident),*) => {
+        $(
+            impl From<$value_type> for DynamicValue {
+                fn from(value: $value_type) -> Self {
+                    DynamicValue::$variant(value)
+                }
+            }
+
+            impl TryFrom<DynamicValue> for $value_type {
+                type Error = ();
+                fn try_from(value: DynamicValue) -> Result<Self, Self::Error> {
+                    match value {
+                        DynamicValue::$variant(value) => Ok(value),
+                        _ => Err(()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_dynamic_value_for_variant!(
+    Numeric => Numeric, bool => Bool, String => String, Size => Size, Transform2D => Transform2D
+);
+
+/// Reads a property by name from a component's properties, if its type is representable as a `DynamicValue`
+pub type ReadPropertyFn = fn(&PropertiesCoproduct, &str) -> Option<DynamicValue>;
+/// Wraps a `DynamicValue` in the named `TypesCoproduct` variant, converting between numeric types as needed
+pub type CoerceFn = fn(&str, DynamicValue) -> Option<TypesCoproduct>;
+/// Instantiates a single node of a replacement template, declared by the component with the given type_id, along with its
+/// already-instantiated children.  `None` if the node's type or any of its event bindings aren't known to the cartridge.
+pub type InstantiateNodeFn<R> = fn(&Rc<RefCell<InstanceRegistry<R>>>, &str, &TemplateNodePatch, RenderNodePtrList<R>) -> Option<RenderNodePtr<R>>;
+
+/// The cartridge-specific half of hot reloading, generated into each cartridge as `instantiate_hot_reload_cartridge`
+pub struct HotReloadCartridge<R: 'static + RenderContext> {
+    pub instantiate_node: InstantiateNodeFn<R>,
+    pub read_property: ReadPropertyFn,
+    pub coerce: CoerceFn,
+}
+
+/// Used by generated code to bind a property of a hot-reloaded node to its expression, or to its default if unset
+pub fn hot_reload_property<T: Default + Clone + 'static>(node: &TemplateNodePatch, name: &str) -> Box<dyn PropertyInstance<T>> {
+    match node.properties.get(name) {
+        Some(id) => Box::new(PropertyExpression::new(*id)),
+        None => Box::new(PropertyLiteral::new(Default::default())),
+    }
+}
+
+/// As `hot_reload_property`, for the built-in `transform`
+pub fn hot_reload_transform(node: &TemplateNodePatch) -> Rc<RefCell<dyn PropertyInstance<Transform2D>>> {
+    match node.properties.get("transform") {
+        Some(id) => Rc::new(RefCell::new(PropertyExpression::<Transform2D>::new(*id))),
+        None => Transform2D::default_wrapped(),
+    }
+}
+
+/// Recursively instantiates the nodes of a replacement template
+pub fn instantiate_hot_reload_template<R: 'static + RenderContext>(cartridge: &HotReloadCartridge<R>, instance_registry: &Rc<RefCell<InstanceRegistry<R>>>, component_type_id: &str, nodes: &[TemplateNodePatch]) -> Option<Vec<RenderNodePtr<R>>> {
+    nodes.iter().map(|node| {
+        let children = instantiate_hot_reload_template(cartridge, instance_registry, component_type_id, &node.children)?;
+        let instance = (cartridge.instantiate_node)(instance_registry, component_type_id, node, Rc::new(RefCell::new(children)))?;
+        if let Some(template_id) = &node.template_id {
+            let instance_id = (*instance).borrow().get_instance_id();
+            (*instance_registry).borrow_mut().register_template_id(template_id, instance_id);
+        }
+        if node.is_focusable {
+            let instance_id = (*instance).borrow().get_instance_id();
+            (*instance_registry).borrow_mut().register_focusable(instance_id);
+        }
+        Some(instance)
+    }).collect()
+}
+
+/// Builds a vtable entry that interprets `expression_patch`.  The compiler only sends expressions whose symbols and
+/// return type are representable, so a failure to evaluate is a bug, as with the `unreachable!`s of compiled expressions.
+pub fn interpret_expression<R: 'static + RenderContext>(expression_patch: ExpressionPatch, read_property: ReadPropertyFn, coerce: CoerceFn) -> Box<dyn Fn(ExpressionContext<R>) -> TypesCoproduct> {
+    Box::new(move |ec: ExpressionContext<R>| -> TypesCoproduct {
+        evaluate(&expression_patch.expression, &ec, read_property)
+            .and_then(|value| coerce(&expression_patch.return_type, value))
+            .unwrap_or_else(|| panic!("Hot-reloaded expression {} couldn't be evaluated as `{}`", expression_patch.id, expression_patch.return_type))
+    })
+}
+
+fn evaluate<R: 'static + RenderContext>(expression: &PaxelExpression, ec: &ExpressionContext<R>, read_property: ReadPropertyFn) -> Option<DynamicValue> {
+    let evaluate_numeric = |expression: &PaxelExpression| -> Option<Numeric> {
+        Numeric::try_from(evaluate(expression, ec, read_property)?).ok()
+    };
+
+    match expression {
+        PaxelExpression::Integer(value) => Some(DynamicValue::Numeric(Numeric::from(*value))),
+        PaxelExpression::Float(value) => Some(DynamicValue::Numeric(Numeric::from(*value))),
+        PaxelExpression::Bool(value) => Some(DynamicValue::Bool(*value)),
+        PaxelExpression::String(value) => Some(DynamicValue::String(value.clone())),
+        PaxelExpression::Size(inner, unit) => {
+            let value = evaluate_numeric(inner)?;
+            Some(DynamicValue::Size(match unit {
+                SizeUnit::Pixels => Size::Pixels(value),
+                SizeUnit::Percent => Size::Percent(value),
+            }))
+        },
+        PaxelExpression::Symbol(binding) => {
+            //as with compiled expressions, the 0th frame is the current one
+            let stack_frame = (*ec.stack_frame).borrow().peek_nth(binding.stack_offset as isize)
+                .unwrap_or_else(|| Rc::clone(&ec.stack_frame));
+            let properties = (*stack_frame).borrow().get_properties();
+            let properties = &*(*properties).borrow();
+            match &binding.kind {
+                SymbolKind::RepeatIndex => {
+                    if let PropertiesCoproduct::RepeatItem(_, i, _) = properties {
+                        Some(DynamicValue::Numeric(Numeric::from(*i)))
+                    } else {
+                        None
+                    }
+                },
+                SymbolKind::Property(name) => read_property(properties, name),
+            }
+        },
+        PaxelExpression::Negate(inner) => Some(DynamicValue::Numeric(-evaluate_numeric(inner)?)),
+        PaxelExpression::Not(inner) => Some(DynamicValue::Bool(!bool::try_from(evaluate(inner, ec, read_property)?).ok()?)),
+        PaxelExpression::Binary(lhs, operator, rhs) => {
+            //short-circuit boolean operators, as rustc does
+            if let BinaryOperator::And | BinaryOperator::Or = operator {
+                let lhs = bool::try_from(evaluate(lhs, ec, read_property)?).ok()?;
+                if lhs == (*operator == BinaryOperator::Or) {
+                    return Some(DynamicValue::Bool(lhs));
+                }
+                return Some(DynamicValue::Bool(bool::try_from(evaluate(rhs, ec, read_property)?).ok()?));
+            }
+            evaluate_binary(evaluate(lhs, ec, read_property)?, *operator, evaluate(rhs, ec, read_property)?)
+        },
+        PaxelExpression::Conditional(condition, then_branch, else_branch) => {
+            if bool::try_from(evaluate(condition, ec, read_property)?).ok()? {
+                evaluate(then_branch, ec, read_property)
+            } else {
+                evaluate(else_branch, ec, read_property)
+            }
+        },
+        PaxelExpression::FunctionCall(path, args) => {
+            let args = args.iter().map(|arg| evaluate(arg, ec, read_property)).collect::<Option<Vec<DynamicValue>>>()?;
+            evaluate_function_call(path, args)
+        },
+    }
+}
+
+fn evaluate_binary(lhs: DynamicValue, operator: BinaryOperator, rhs: DynamicValue) -> Option<DynamicValue> {
+    Some(match (lhs, rhs) {
+        (DynamicValue::Numeric(lhs), DynamicValue::Numeric(rhs)) => match operator {
+            BinaryOperator::Add => DynamicValue::Numeric(lhs + rhs),
+            BinaryOperator::Sub => DynamicValue::Numeric(lhs - rhs),
+            BinaryOperator::Mul => DynamicValue::Numeric(lhs * rhs),
+            BinaryOperator::Div => DynamicValue::Numeric(lhs / rhs),
+            BinaryOperator::Mod => DynamicValue::Numeric(lhs % rhs),
+            BinaryOperator::Exp => DynamicValue::Numeric(Numeric::pow(lhs, rhs)),
+            BinaryOperator::Eq => DynamicValue::Bool(lhs == rhs),
+            BinaryOperator::Neq => DynamicValue::Bool(lhs != rhs),
+            BinaryOperator::Lt => DynamicValue::Bool(lhs < rhs),
+            BinaryOperator::Lte => DynamicValue::Bool(lhs <= rhs),
+            BinaryOperator::Gt => DynamicValue::Bool(lhs > rhs),
+            BinaryOperator::Gte => DynamicValue::Bool(lhs >= rhs),
+            BinaryOperator::And | BinaryOperator::Or => return None,
+        },
+        (DynamicValue::Size(lhs), DynamicValue::Size(rhs)) if operator == BinaryOperator::Mul => DynamicValue::Size(lhs * rhs),
+        (DynamicValue::Transform2D(lhs), DynamicValue::Transform2D(rhs)) if operator == BinaryOperator::Mul => DynamicValue::Transform2D(lhs * rhs),
+        (DynamicValue::Bool(lhs), DynamicValue::Bool(rhs)) => match operator {
+            BinaryOperator::Eq => DynamicValue::Bool(lhs == rhs),
+            BinaryOperator::Neq => DynamicValue::Bool(lhs != rhs),
+            _ => return None,
+        },
+        (DynamicValue::String(lhs), DynamicValue::String(rhs)) => match operator {
+            BinaryOperator::Eq => DynamicValue::Bool(lhs == rhs),
+            BinaryOperator::Neq => DynamicValue::Bool(lhs != rhs),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// The built-in functions available to hot-reloaded expressions
+fn evaluate_function_call(path: &str, args: Vec<DynamicValue>) -> Option<DynamicValue> {
+    let numeric_arg = |i: usize| args.get(i).cloned().and_then(|arg| Numeric::try_from(arg).ok());
+    let size_arg = |i: usize| args.get(i).cloned().and_then(|arg| Size::try_from(arg).ok());
+
+    let transform = match path {
+        "Transform2D::rotate" => Transform2D::rotate(numeric_arg(0)?),
+        "Transform2D::scale" => Transform2D::scale(numeric_arg(0)?, numeric_arg(1)?),
+        "Transform2D::translate" => Transform2D::translate(numeric_arg(0)?, numeric_arg(1)?),
+        "Transform2D::anchor" => Transform2D::anchor(size_arg(0)?, size_arg(1)?),
+        "Transform2D::align" => Transform2D::align(size_arg(0)?, size_arg(1)?),
+        _ => return None,
+    };
+    Some(DynamicValue::Transform2D(transform))
+}
@@ -0,0 +1,97 @@
+//! Synthesizes hover events for canvas-drawn elements, which unlike DOM elements get no `mouseover` of their own.
+//!
+//! The engine tracks the topmost element beneath each pointer as it moves (see `PaxEngine::update_hover`) and re-checks
+//! it every tick, e.g. for elements that move beneath a still pointer.  When it changes, as in the DOM:
+//! - `mouse_out` is dispatched to the element left and `mouse_over` to the element entered, both propagating to ancestors
+//! - `mouse_leave` is dispatched to each element no longer beneath the pointer, innermost first, and `mouse_enter` to
+//!   each element newly beneath it, outermost first; neither propagates, so moving between two children of a `Group`
+//!   neither leaves nor enters the `Group`
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+
+use pax_runtime_api::{ArgsMouseEnter, ArgsMouseLeave, ArgsMouseOut, ArgsMouseOver, EventControl, MouseEventArgs};
+
+use crate::RepeatExpandedNode;
+
+/// A pointer whose hover, capture and drags are tracked independently of any others.  Only the mouse hovers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerId {
+    Mouse,
+    /// A touch point, by its `Touch::identifier`
+    Touch(i64),
+}
+
+struct Hover<R: 'static + RenderContext> {
+    /// The pointer's most recent position, with which the element beneath it is re-checked each tick
+    mouse: MouseEventArgs,
+    /// The element beneath the pointer followed by its ancestors, which are otherwise dropped with the frame they were rendered in
+    path: Vec<Rc<RepeatExpandedNode<R>>>,
+}
+
+pub struct HoverTracker<R: 'static + RenderContext> {
+    hovers: HashMap<PointerId, Hover<R>>,
+}
+
+impl<R: 'static + RenderContext> Default for HoverTracker<R> {
+    fn default() -> Self {
+        HoverTracker {
+            hovers: HashMap::new(),
+        }
+    }
+}
+
+impl<R: 'static + RenderContext> HoverTracker<R> {
+    /// The element beneath `pointer`, if any, as of its last move or the last tick
+    pub fn get_hovered_element(&self, pointer: PointerId) -> Option<Rc<RepeatExpandedNode<R>>> {
+        self.hovers.get(&pointer).and_then(|hover| hover.path.first().cloned())
+    }
+
+    /// Positions of the pointers being tracked, with which to re-check the elements beneath them
+    pub fn get_pointers(&self) -> Vec<(PointerId, MouseEventArgs)> {
+        self.hovers.iter().map(|(pointer, hover)| (*pointer, hover.mouse.clone())).collect()
+    }
+
+    /// Records that `element` is beneath `pointer`, at the position of `mouse`, dispatching hover events if it wasn't before
+    pub fn set_hovered_element(&mut self, pointer: PointerId, mouse: MouseEventArgs, element: Option<Rc<RepeatExpandedNode<R>>>) {
+        let previous_path = self.hovers.remove(&pointer).map(|hover| hover.path).unwrap_or_default();
+        let path = element.map(|element| RepeatExpandedNode::get_path(&element)).unwrap_or_default();
+        dispatch_hover_change(&previous_path, &path, &mouse);
+        self.hovers.insert(pointer, Hover { mouse, path });
+    }
+
+    /// Stops tracking `pointer`, e.g. when the mouse leaves the window, dispatching `mouse_out` and `mouse_leave` to whatever it hovered
+    pub fn remove_pointer(&mut self, pointer: PointerId) {
+        if let Some(hover) = self.hovers.remove(&pointer) {
+            dispatch_hover_change(&hover.path, &[], &hover.mouse);
+        }
+    }
+}
+
+/// Dispatches hover events for a pointer's move from the first element of `from_path` to the first of `to_path`, each
+/// followed by its ancestors
+fn dispatch_hover_change<R: 'static + RenderContext>(from_path: &[Rc<RepeatExpandedNode<R>>], to_path: &[Rc<RepeatExpandedNode<R>>], mouse: &MouseEventArgs) {
+    if from_path.first().map(|node| node.get_id_chain()) == to_path.first().map(|node| node.get_id_chain()) {
+        return;
+    }
+    let is_within = |path: &[Rc<RepeatExpandedNode<R>>], node: &RepeatExpandedNode<R>| {
+        path.iter().any(|other| other.get_id_chain() == node.get_id_chain())
+    };
+
+    if !from_path.is_empty() {
+        let from_path : Vec<&RepeatExpandedNode<R>> = from_path.iter().map(|node| node.as_ref()).collect();
+        RepeatExpandedNode::dispatch_along_path(&from_path, ArgsMouseOut { mouse: mouse.clone(), control: EventControl::default() },
+            |registry| &registry.mouse_out_capture_handlers, |registry| &registry.mouse_out_handlers);
+    }
+    for node in from_path.iter().filter(|node| !is_within(to_path, node)) {
+        node.dispatch_mouse_leave(ArgsMouseLeave { mouse: mouse.clone(), control: EventControl::default() });
+    }
+    if let Some(to) = to_path.first() {
+        to.dispatch_mouse_over(ArgsMouseOver { mouse: mouse.clone(), control: EventControl::default() });
+    }
+    for node in to_path.iter().rev().filter(|node| !is_within(from_path, node)) {
+        node.dispatch_mouse_enter(ArgsMouseEnter { mouse: mouse.clone(), control: EventControl::default() });
+    }
+}
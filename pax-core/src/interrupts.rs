@@ -0,0 +1,110 @@
+//! Translates the user input that native chassis receive from their platforms, as `NativeInterrupt`s, into the events
+//! dispatched by the engine, so that every chassis handles input identically.
+
+use piet_common::RenderContext;
+
+use pax_message::NativeInterrupt;
+use pax_runtime_api::{ArgsClick, ArgsContextMenu, ArgsDoubleClick, ArgsJab, ArgsKeyDown, ArgsKeyPress, ArgsKeyUp, ArgsMouseDown, ArgsMouseMove, ArgsMouseUp, ArgsScroll, ArgsTouchEnd, ArgsTouchMove, ArgsTouchStart, ArgsWheel, EventControl, KeyboardEventArgs, ModifierKey, MouseButton, MouseEventArgs, PointerCapture, Touch};
+
+use crate::{PaxEngine, PointerId};
+
+impl<R: 'static + RenderContext> PaxEngine<R> {
+    /// Dispatches the event(s) corresponding to an input interrupt, with coordinates in logical pixels.  Returns whether a
+    /// handler called `prevent_default` on the resulting event, in which case a chassis may skip its own handling.
+    /// Interrupts other than input, i.e. `Image`, `AddedLayer` and `HotReload`, depend on the chassis, which handles
+    /// them itself; they are ignored here.
+    pub fn dispatch_interrupt(&mut self, native_interrupt: NativeInterrupt) -> bool {
+        let mut default_prevented = false;
+        match native_interrupt {
+            NativeInterrupt::Click(args) => {
+                if let Some(topmost_node) = self.get_topmost_element_beneath_ray((args.x, args.y)) {
+                    let args_click = ArgsClick {
+                        mouse: MouseEventArgs {
+                            x: args.x,
+                            y: args.y,
+                            button: MouseButton::from(args.button),
+                            modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect(),
+                        },
+                        control: EventControl::default(),
+                    };
+                    default_prevented = topmost_node.dispatch_click(args_click);
+                }
+            },
+            NativeInterrupt::Scroll(args) => {
+                if let Some(topmost_node) = self.get_focused_element() {
+                    let args_scroll = ArgsScroll { delta_x: args.delta_x, delta_y: args.delta_y, control: EventControl::default() };
+                    default_prevented = topmost_node.dispatch_scroll(args_scroll);
+                }
+            },
+            NativeInterrupt::Jab(args) => {
+                if let Some(topmost_node) = self.get_topmost_element_beneath_ray((args.x, args.y)) {
+                    let args_jab = ArgsJab { x: args.x, y: args.y, control: EventControl::default() };
+                    default_prevented = topmost_node.dispatch_jab(args_jab);
+                }
+            },
+            NativeInterrupt::TouchStart(args) => {
+                let touches = args.touches.iter().map(|x|{Touch::from(x)}).collect();
+                default_prevented = self.dispatch_touch_start(ArgsTouchStart { touches, pointer_capture: PointerCapture::default(), control: EventControl::default() });
+            },
+            NativeInterrupt::TouchMove(args) => {
+                let touches = args.touches.iter().map(|x|{Touch::from(x)}).collect();
+                default_prevented = self.dispatch_touch_move(ArgsTouchMove { touches, control: EventControl::default() });
+            },
+            NativeInterrupt::TouchEnd(args) => {
+                let touches = args.touches.iter().map(|x|{Touch::from(x)}).collect();
+                default_prevented = self.dispatch_touch_end(ArgsTouchEnd { touches, control: EventControl::default() });
+            },
+            NativeInterrupt::KeyDown(args) => {
+                let modifiers = args.modifiers.iter().map(|x|{ModifierKey::from(x)}).collect();
+                default_prevented = self.dispatch_key_down(ArgsKeyDown { keyboard: KeyboardEventArgs { key: args.key, modifiers, is_repeat: args.is_repeat }, control: EventControl::default() });
+            },
+            NativeInterrupt::KeyUp(args) => {
+                let modifiers = args.modifiers.iter().map(|x|{ModifierKey::from(x)}).collect();
+                default_prevented = self.dispatch_key_up(ArgsKeyUp { keyboard: KeyboardEventArgs { key: args.key, modifiers, is_repeat: args.is_repeat }, control: EventControl::default() });
+            },
+            NativeInterrupt::KeyPress(args) => {
+                let modifiers = args.modifiers.iter().map(|x|{ModifierKey::from(x)}).collect();
+                default_prevented = self.dispatch_key_press(ArgsKeyPress { keyboard: KeyboardEventArgs { key: args.key, modifiers, is_repeat: args.is_repeat }, control: EventControl::default() });
+            },
+            NativeInterrupt::DoubleClick(args) => {
+                if let Some(topmost_node) = self.get_topmost_element_beneath_ray((args.x, args.y)) {
+                    let mouse = MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() };
+                    default_prevented = topmost_node.dispatch_double_click(ArgsDoubleClick { mouse, control: EventControl::default() });
+                }
+            },
+            NativeInterrupt::MouseMove(args) => {
+                let mouse = MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() };
+                default_prevented = self.dispatch_mouse_move(ArgsMouseMove { mouse, control: EventControl::default() });
+            },
+            NativeInterrupt::Wheel(args) => {
+                if let Some(topmost_node) = self.get_topmost_element_beneath_ray((args.x, args.y)) {
+                    let modifiers = args.modifiers.iter().map(|x|{ModifierKey::from(x)}).collect();
+                    default_prevented = topmost_node.dispatch_wheel(ArgsWheel { x: args.x, y: args.y, delta_x: args.delta_x, delta_y: args.delta_y, modifiers, control: EventControl::default() });
+                }
+            },
+            NativeInterrupt::MouseDown(args) => {
+                let mouse = MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() };
+                default_prevented = self.dispatch_mouse_down(ArgsMouseDown { mouse, pointer_capture: PointerCapture::default(), control: EventControl::default() });
+            },
+            NativeInterrupt::MouseUp(args) => {
+                let mouse = MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() };
+                default_prevented = self.dispatch_mouse_up(ArgsMouseUp { mouse, control: EventControl::default() });
+            },
+            //the engine dispatches `mouse_over`, `mouse_out` et al. as the element beneath the mouse changes
+            NativeInterrupt::MouseOver(args) => {
+                self.update_hover(PointerId::Mouse, MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() });
+            },
+            NativeInterrupt::MouseOut(_) => {
+                self.remove_pointer(PointerId::Mouse);
+            },
+            NativeInterrupt::ContextMenu(args) => {
+                if let Some(topmost_node) = self.get_topmost_element_beneath_ray((args.x, args.y)) {
+                    let mouse = MouseEventArgs { x: args.x, y: args.y, button: MouseButton::from(args.button), modifiers: args.modifiers.iter().map(|x| { ModifierKey::from(x) }).collect() };
+                    default_prevented = topmost_node.dispatch_context_menu(ArgsContextMenu { mouse, control: EventControl::default() });
+                }
+            },
+            NativeInterrupt::Image(_) | NativeInterrupt::AddedLayer(_) | NativeInterrupt::HotReload(_) => {},
+        }
+        default_prevented
+    }
+}
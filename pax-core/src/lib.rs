@@ -5,6 +5,7 @@ pub use kurbo::{Affine};
 pub use piet::{Color, StrokeStyle, Error};
 
 pub use pax_properties_coproduct;
+pub use pax_message;
 pub mod engine;
 pub mod rendering;
 pub mod expressions;
@@ -13,7 +14,18 @@ pub mod repeat;
 pub mod slot;
 pub mod runtime;
 pub mod conditional;
+pub mod matching;
 pub mod declarative_macros;
+pub mod hot_reload;
+pub mod hover;
+pub mod focus;
+pub mod drag;
+pub mod gesture;
+pub mod spatial;
+pub mod interrupts;
+#[cfg(feature = "cairo")]
+pub mod bitmap;
+pub mod testing;
 
 pub use crate::engine::*;
 pub use crate::component::*;
@@ -23,6 +35,15 @@ pub use crate::runtime::*;
 pub use crate::repeat::*;
 pub use crate::slot::*;
 pub use crate::conditional::*;
+pub use crate::matching::*;
+pub use crate::hot_reload::*;
+pub use crate::hover::*;
+pub use crate::focus::*;
+pub use crate::drag::*;
+pub use crate::gesture::*;
+pub use crate::spatial::*;
+#[cfg(feature = "cairo")]
+pub use crate::bitmap::*;
 
 
 
@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use piet_common::RenderContext;
+use crate::{RenderNode, RenderNodePtr, RenderNodePtrList, RenderTreeContext, InstantiationArgs};
+use pax_runtime_api::{Layer, PropertyInstance, Size2D, Transform2D};
+use pax_properties_coproduct::TypesCoproduct;
+
+/// A special "control-flow" primitive, Match (`match`) renders exactly one of several
+/// subtrees ("arms") of a component template, based on the value of `match_expression`.
+/// The Pax compiler handles MatchInstance specially with the `match` syntax in templates,
+/// compiling the subject expression and arm patterns into a single expression that
+/// evaluates to the index of the first matching arm.
+pub struct MatchInstance<R: 'static + RenderContext> {
+    pub instance_id: u64,
+
+    pub match_expression: Box<dyn PropertyInstance<usize>>,
+    pub arms: Vec<RenderNodePtrList<R>>,
+    /// Index of the currently mounted arm; `None` until the first evaluation of `match_expression`
+    pub active_arm_index: Option<usize>,
+    pub next_frame_arm_index: Option<usize>,
+
+    pub transform: Rc<RefCell<dyn PropertyInstance<Transform2D>>>,
+}
+
+impl<R: 'static + RenderContext> RenderNode<R> for MatchInstance<R> {
+
+    fn get_instance_id(&self) -> u64 {
+        self.instance_id
+    }
+
+    fn instantiate(args: InstantiationArgs<R>) -> Rc<RefCell<Self>> where Self: Sized {
+        let mut instance_registry = (*args.instance_registry).borrow_mut();
+        let instance_id = instance_registry.mint_id();
+        let ret = Rc::new(RefCell::new(Self {
+            instance_id,
+            match_expression: args.match_expression.expect("Match requires match_expression"),
+            arms: args.match_arms.unwrap_or(vec![]),
+            active_arm_index: None,
+            next_frame_arm_index: None,
+            transform: args.transform,
+        }));
+
+        instance_registry.register(instance_id, Rc::clone(&ret) as RenderNodePtr<R>);
+        ret
+    }
+
+    fn compute_properties(&mut self, rtc: &mut RenderTreeContext<R>) {
+
+        //As with Conditional, switching arms is deferred by one frame, so that
+        //the outgoing arm can be traversed while marked for unmount
+        if let Some(next_frame_arm_index) = self.next_frame_arm_index.take() {
+            self.match_expression.set(next_frame_arm_index);
+            self.active_arm_index = Some(next_frame_arm_index);
+        }
+
+        if let Some(match_expression) = rtc.compute_vtable_value(self.match_expression._get_vtable_id()) {
+            let new_value = if let TypesCoproduct::usize(v) = match_expression { v } else { unreachable!() };
+
+            if self.active_arm_index != Some(new_value) {
+                if let Some(outgoing_arm) = self.active_arm_index.and_then(|i| self.arms.get(i)) {
+                    let mut instance_registry = (*rtc.engine.instance_registry).borrow_mut();
+                    (*outgoing_arm).borrow_mut().iter().for_each(|child| {
+                        let instance_id = (*(*child)).borrow_mut().get_instance_id();
+                        instance_registry.deregister(instance_id);
+                        instance_registry.mark_for_unmount(instance_id);
+                    });
+                }
+                self.next_frame_arm_index = Some(new_value);
+                rtc.engine.request_render();
+            }
+        }
+    }
+
+    fn should_flatten(&self) -> bool {
+        true
+    }
+    fn get_rendering_children(&self) -> RenderNodePtrList<R> {
+        match self.active_arm_index.and_then(|i| self.arms.get(i)) {
+            Some(arm) => Rc::clone(arm),
+            None => Rc::new(RefCell::new(vec![])),
+        }
+    }
+    fn get_size(&self) -> Option<Size2D> { None }
+    fn compute_size_within_bounds(&self, bounds: (f64, f64)) -> (f64, f64) { bounds }
+    fn get_transform(&mut self) -> Rc<RefCell<dyn PropertyInstance<Transform2D>>> { Rc::clone(&self.transform) }
+
+    fn get_layer_type(&mut self) -> Layer {
+        Layer::DontCare
+    }
+
+}
@@ -1,9 +1,10 @@
+use std::any::Any;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::Mul;
 use std::rc::Rc;
 
-use kurbo::{Affine, Point, Vec2};
+use kurbo::{Affine, BezPath, ParamCurveNearest, PathEl, Point, Rect, Shape, Vec2};
 use piet::{Color, StrokeStyle};
 use piet_common::RenderContext;
 use pax_properties_coproduct::PropertiesCoproduct;
@@ -39,13 +40,20 @@ pub struct InstantiationArgs<R: 'static + RenderContext> {
     /// used by Slot
     pub slot_index: Option<Box<dyn PropertyInstance<pax_runtime_api::Numeric>>>,
 
-    ///used by Repeat — the _vec and _range variants are modal, describing whether the source
-    ///is encoded as a Vec<T> or as a Range<...>
+    ///used by Repeat — both symbolic sources (`self.some_vec`) and ranges (`0..5`)
+    ///are normalized by the compiler into a Vec<Rc<PropertiesCoproduct>>
     pub repeat_source_expression_vec: Option<Box<dyn PropertyInstance<Vec<Rc<PropertiesCoproduct>>>>>,
-    pub repeat_source_expression_range: Option<Box<dyn PropertyInstance<std::ops::Range<isize>>>>,
+    ///used by Repeat — optional `key` expression, evaluated once per datum, for keyed reconciliation
+    pub repeat_key_expression: Option<Box<dyn PropertyInstance<String>>>,
 
     ///used by Conditional
     pub conditional_boolean_expression: Option<Box<dyn PropertyInstance<bool>>>,
+    ///used by Conditional — the `else` branch, if any.  `children` is the `true` branch.
+    pub conditional_false_branch_children: Option<RenderNodePtrList<R>>,
+
+    ///used by Match — `match_expression` evaluates to the index of the matching arm in `match_arms`
+    pub match_expression: Option<Box<dyn PropertyInstance<usize>>>,
+    pub match_arms: Option<Vec<RenderNodePtrList<R>>>,
 
     ///used by Component instances, specifically to unwrap type-specific PropertiesCoproducts
     ///and recurse into descendant property computation
@@ -139,6 +147,30 @@ impl TransformAndBounds {
         [top_left, top_right, bottom_right, bottom_left]
     }
 
+    /// The smallest axis-aligned rectangle containing these bounds once transformed, e.g. for `SpatialIndex`
+    pub fn get_bounding_box(&self) -> Rect {
+        let corners = self.corners();
+        corners[1..].iter().fold(Rect::from_points((corners[0].x, corners[0].y), (corners[0].x, corners[0].y)), |bounding_box, corner| {
+            bounding_box.union_pt(Point { x: corner.x, y: corner.y })
+        })
+    }
+
+    /// Whether these bounds lie entirely outside of `viewport`, so that rendering them can be skipped.  Bounding boxes
+    /// outside or within the viewport settle this cheaply, leaving the separating axis test for those straddling its edges.
+    pub fn is_outside(&self, viewport: &Self) -> bool {
+        let bounding_box = self.get_bounding_box();
+        let viewport_bounding_box = viewport.get_bounding_box();
+        if bounding_box.x1 < viewport_bounding_box.x0 || bounding_box.x0 > viewport_bounding_box.x1
+            || bounding_box.y1 < viewport_bounding_box.y0 || bounding_box.y0 > viewport_bounding_box.y1 {
+            true
+        } else if bounding_box.x0 >= viewport_bounding_box.x0 && bounding_box.x1 <= viewport_bounding_box.x1
+            && bounding_box.y0 >= viewport_bounding_box.y0 && bounding_box.y1 <= viewport_bounding_box.y1 {
+            false
+        } else {
+            !self.intersects(viewport)
+        }
+    }
+
     //Applies the separating axis theorem to determine whether two `TransformAndBounds` intersect.
     pub fn intersects(&self, other: &Self) -> bool {
         let corners_self = self.corners();
@@ -161,6 +193,34 @@ impl TransformAndBounds {
     }
 }
 
+/// Hit testing for primitives that fill and stroke a path, e.g. `Ellipse` and `Path`: whether `ray`, in the viewport,
+/// falls within `path` once transformed by `transform`, or within half of `stroke_width` of its outline.  As when
+/// filling, open subpaths are treated as closed, while strokes are `stroke_width` px wide whatever the transform's scale,
+/// since paths are stroked after they're transformed.
+pub fn ray_cast_test_path(ray: &(f64, f64), path: &BezPath, transform: &Affine, stroke_width: f64) -> bool {
+    let ray = Point { x: ray.0, y: ray.1 };
+    let mut closed_path = BezPath::new();
+    let close_open_subpath = |closed_path: &mut BezPath| {
+        if !matches!(closed_path.elements().last(), None | Some(PathEl::ClosePath)) {
+            closed_path.close_path();
+        }
+    };
+    for element in path.elements() {
+        if let PathEl::MoveTo(_) = element {
+            close_open_subpath(&mut closed_path);
+        }
+        closed_path.push(*element);
+    }
+    close_open_subpath(&mut closed_path);
+    if (*transform * closed_path).contains(ray) {
+        return true;
+    }
+
+    let half_stroke_width = stroke_width / 2.0;
+    stroke_width > f64::EPSILON && (*transform * path.clone()).segments()
+        .any(|segment| segment.nearest(ray, 0.1).distance_sq <= half_stroke_width * half_stroke_width)
+}
+
 /// The base trait for a RenderNode, representing any node that can
 /// be rendered by the engine.
 /// T: a member of PropertiesCoproduct, representing the type of the set of properites
@@ -193,15 +253,30 @@ pub trait RenderNode<R: 'static + RenderContext>
         let inverted_transform = tab.transform.inverse();
         let transformed_ray = inverted_transform * Point {x:ray.0,y:ray.1};
 
-        //Default implementation: rectilinear bounding hull
+        //Default implementation: rectilinear bounding hull.  Primitives with other shapes override this, e.g. with
+        //`ray_cast_test_path`
         transformed_ray.x > 0.0 && transformed_ray.y > 0.0
             && transformed_ray.x < tab.bounds.0 && transformed_ray.y < tab.bounds.1
     }
 
+    /// Returns the axis-aligned rectangle in the viewport beyond which `ray_cast_test` never hits, by which this node
+    /// is found in the `SpatialIndex`, or `None` if it never hits at all.  Nodes overriding `ray_cast_test` to hit
+    /// beyond their bounds, or without a size, must override this too
+    fn get_hit_bounds(&self, tab: &TransformAndBounds) -> Option<Rect> {
+        //as with `ray_cast_test`, size-None elements are never hit
+        self.get_size().map(|_| tab.get_bounding_box())
+    }
+
     fn get_handler_registry(&self) -> Option<Rc<RefCell<HandlerRegistry<R>>>> {
         None //default no-op
     }
 
+    /// Returns this node's properties for inspection, e.g. by `testing::TestDriver`: a `RefCell<PropertiesCoproduct>`
+    /// for components, or a `RefCell` of a primitive's properties struct, e.g. `RefCell<Rectangle>`
+    fn get_properties_any(&self) -> Option<Rc<dyn Any>> {
+        None //default: no inspectable properties
+    }
+
     /// Used at least by ray-casting; only nodes that clip content (and thus should
     /// not allow outside content to respond to ray-casting) should return true
     fn is_clipping(&self) -> bool {
@@ -417,3 +492,102 @@ pub struct StrokeInstance {
     //FUTURE: stroke alignment, inner/outer/center?
 }
 
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use kurbo::{Affine, BezPath, Ellipse, Rect, Shape};
+
+    use super::{ray_cast_test_path, TransformAndBounds};
+
+    fn tab(transform: Affine, width: f64, height: f64) -> TransformAndBounds {
+        TransformAndBounds { transform, bounds: (width, height) }
+    }
+
+    #[test]
+    fn test_get_bounding_box() {
+        assert_eq!(tab(Affine::translate((10.0, 20.0)), 100.0, 50.0).get_bounding_box(), Rect::new(10.0, 20.0, 110.0, 70.0));
+        assert_eq!(tab(Affine::scale(-1.0), 100.0, 50.0).get_bounding_box(), Rect::new(-100.0, -50.0, 0.0, 0.0));
+
+        //a square rotated by 45° about its center is bounded by its diagonals
+        let rotated = Affine::translate((50.0, 50.0)) * Affine::rotate(PI / 4.0) * Affine::translate((-50.0, -50.0));
+        let bounding_box = tab(rotated, 100.0, 100.0).get_bounding_box();
+        let half_diagonal = 50.0 * 2.0_f64.sqrt();
+        for (actual, expected) in [bounding_box.x0, bounding_box.y0, bounding_box.x1, bounding_box.y1].iter().zip([50.0 - half_diagonal, 50.0 - half_diagonal, 50.0 + half_diagonal, 50.0 + half_diagonal]) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_is_outside() {
+        let viewport = tab(Affine::IDENTITY, 100.0, 100.0);
+
+        assert!(tab(Affine::translate((150.0, 0.0)), 20.0, 20.0).is_outside(&viewport));
+        assert!(tab(Affine::translate((0.0, -30.0)), 20.0, 20.0).is_outside(&viewport));
+        assert!(!tab(Affine::translate((10.0, 10.0)), 20.0, 20.0).is_outside(&viewport));
+        assert!(!tab(Affine::translate((90.0, 90.0)), 20.0, 20.0).is_outside(&viewport));
+        //edges touching the viewport's are not culled
+        assert!(!tab(Affine::translate((100.0, 0.0)), 20.0, 20.0).is_outside(&viewport));
+        assert!(!tab(Affine::translate((-50.0, -50.0)), 200.0, 200.0).is_outside(&viewport));
+
+        //a diamond off the viewport's corner overlaps it by bounding box alone, leaving the separating axis test to cull it
+        let diamond = tab(Affine::translate((170.0, 170.0)) * Affine::rotate(PI / 4.0) * Affine::translate((-50.0, -50.0)), 100.0, 100.0);
+        assert!(diamond.get_bounding_box().x0 < 100.0 && diamond.get_bounding_box().y0 < 100.0);
+        assert!(diamond.is_outside(&viewport));
+        //while one reaching into the viewport isn't
+        let diamond = tab(Affine::translate((130.0, 130.0)) * Affine::rotate(PI / 4.0) * Affine::translate((-50.0, -50.0)), 100.0, 100.0);
+        assert!(!diamond.is_outside(&viewport));
+    }
+
+    #[test]
+    fn test_ray_cast_test_path_misses_ellipse_bounds_corner() {
+        let ellipse = Ellipse::new((50.0, 50.0), (50.0, 50.0), 0.0).to_path(0.1);
+        assert!(ray_cast_test_path(&(50.0, 50.0), &ellipse, &Affine::IDENTITY, 0.0));
+        //inside the bounding box, but outside the ellipse
+        assert!(!ray_cast_test_path(&(5.0, 5.0), &ellipse, &Affine::IDENTITY, 0.0));
+        assert!(!ray_cast_test_path(&(5.0, 5.0), &ellipse, &Affine::IDENTITY, 2.0));
+    }
+
+    #[test]
+    fn test_ray_cast_test_path_hits_stroke_outside_shape() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0).to_path(0.1);
+        //half of a 10px stroke lies outside the filled shape
+        assert!(ray_cast_test_path(&(104.0, 50.0), &rect, &Affine::IDENTITY, 10.0));
+        assert!(!ray_cast_test_path(&(106.0, 50.0), &rect, &Affine::IDENTITY, 10.0));
+        //strokes aren't scaled along with the path
+        assert!(ray_cast_test_path(&(204.0, 100.0), &rect, &Affine::scale(2.0), 10.0));
+        assert!(!ray_cast_test_path(&(206.0, 100.0), &rect, &Affine::scale(2.0), 10.0));
+    }
+
+    #[test]
+    fn test_ray_cast_test_path_fills_open_subpaths() {
+        //two open triangles, each filled as if closed
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((100.0, 0.0));
+        path.line_to((100.0, 100.0));
+        path.move_to((200.0, 0.0));
+        path.line_to((300.0, 0.0));
+        path.line_to((300.0, 100.0));
+
+        assert!(ray_cast_test_path(&(80.0, 20.0), &path, &Affine::IDENTITY, 0.0));
+        assert!(ray_cast_test_path(&(280.0, 20.0), &path, &Affine::IDENTITY, 0.0));
+        assert!(!ray_cast_test_path(&(20.0, 80.0), &path, &Affine::IDENTITY, 0.0));
+        //the closing edge isn't stroked, as it isn't part of the path
+        assert!(!ray_cast_test_path(&(48.0, 52.0), &path, &Affine::IDENTITY, 10.0));
+    }
+
+    #[test]
+    fn test_ray_cast_test_path_with_zero_stroke_width() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0).to_path(0.1);
+        assert!(!ray_cast_test_path(&(100.5, 50.0), &rect, &Affine::IDENTITY, 0.0));
+
+        //a lone line has no area to fill, so only a stroke can be hit
+        let mut line = BezPath::new();
+        line.move_to((0.0, 0.0));
+        line.line_to((100.0, 0.0));
+        assert!(!ray_cast_test_path(&(50.0, 0.0), &line, &Affine::IDENTITY, 0.0));
+        assert!(ray_cast_test_path(&(50.0, 0.0), &line, &Affine::IDENTITY, 1.0));
+        assert!(!ray_cast_test_path(&(50.0, 0.0), &BezPath::new(), &Affine::IDENTITY, 1.0));
+    }
+}
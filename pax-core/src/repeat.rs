@@ -1,7 +1,7 @@
 use std::borrow::BorrowMut;
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::ops::Deref;
 
 
@@ -15,18 +15,50 @@ use pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 /// That is: for a `source_expression` of length `n`, `Repeat` will render its
 /// template `n` times, each with an embedded component context (`RepeatItem`)
 /// with an index `i` and a pointer to that relevant datum `source_expression[i]`
+///
+/// Each `RepeatItem` is identified by a key — either the value of `key_expression`
+/// (as in `for elem in self.list key elem.id`) or, absent a key expression, its index.
+/// When the source changes, `RepeatItem`s are reconciled by key: items with unchanged
+/// keys are reused (and moved, if needed) with their `id_chain`s intact, new keys are
+/// inserted, and removed keys are unmounted.
 pub struct RepeatInstance<R: 'static + RenderContext> {
     pub instance_id: u64,
     pub repeated_template: RenderNodePtrList<R>,
     pub transform: Rc<RefCell<dyn PropertyInstance<Transform2D>>>,
     pub source_expression_vec: Option<Box<dyn PropertyInstance<Vec<Rc<PropertiesCoproduct>>>>>,
-    pub source_expression_range: Option<Box<dyn PropertyInstance<std::ops::Range<isize>>>>,
+    pub key_expression: Option<Box<dyn PropertyInstance<String>>>,
     pub active_children: RenderNodePtrList<R>,
     pub next_frame_children: Option<RenderNodePtrList<R>>,
-    /// Used for hacked dirty-checking, in the absence of our centralized dirty-checker
-    cached_old_value_vec: Option<Vec<Rc<PropertiesCoproduct>>>,
-    cached_old_value_range: Option<std::ops::Range<isize>>,
-    cached_old_bounds: (f64, f64),
+    /// Bookkeeping for each `RepeatItem`, in the order of the most recently computed children
+    repeat_items: Vec<RepeatItemRecord<R>>,
+    /// Register holding the next stable identity to mint for a keyed `RepeatItem`
+    next_stable_id: u64,
+}
+
+/// A single `RepeatItem`-wrapped `ComponentInstance` managed by Repeat, along with the
+/// key it was reconciled by and the stable identity used for its `id_chain`
+struct RepeatItemRecord<R: 'static + RenderContext> {
+    key: String,
+    stable_id: u64,
+    render_node: RenderNodePtr<R>,
+    properties: Rc<RefCell<PropertiesCoproduct>>,
+}
+
+impl<R: 'static + RenderContext> RepeatInstance<R> {
+    /// Evaluate the key for the datum at index `i`.  The key expression is evaluated inside
+    /// a temporary `RepeatItem` stack frame, so that it may refer to `elem` and `i`
+    fn compute_key(&self, rtc: &mut RenderTreeContext<R>, i: usize, datum: &Rc<PropertiesCoproduct>) -> String {
+        match &self.key_expression {
+            Some(key_expression) => {
+                let properties = Rc::new(RefCell::new(PropertiesCoproduct::RepeatItem(Rc::clone(datum), i, i as u64)));
+                (*rtc.runtime).borrow_mut().push_stack_frame(Rc::new(RefCell::new(vec![])), properties, None);
+                let key = rtc.compute_vtable_value(key_expression._get_vtable_id());
+                (*rtc.runtime).borrow_mut().pop_stack_frame();
+                if let Some(TypesCoproduct::String(key)) = key { key } else { unreachable!() }
+            },
+            None => i.to_string(),
+        }
+    }
 }
 
 impl<R: 'static + RenderContext> RenderNode<R> for RepeatInstance<R> {
@@ -47,12 +79,11 @@ impl<R: 'static + RenderContext> RenderNode<R> for RepeatInstance<R> {
             },
             transform: args.transform,
             source_expression_vec: args.repeat_source_expression_vec,
-            source_expression_range: args.repeat_source_expression_range,
+            key_expression: args.repeat_key_expression,
             active_children: Rc::new(RefCell::new(vec![])),
             next_frame_children: None,
-            cached_old_value_vec: None,
-            cached_old_value_range: None,
-            cached_old_bounds: (0.0, 0.0),
+            repeat_items: vec![],
+            next_stable_id: 0,
         }));
 
         instance_registry.register(instance_id, Rc::clone(&ret) as RenderNodePtr<R>);
@@ -61,109 +92,113 @@ impl<R: 'static + RenderContext> RenderNode<R> for RepeatInstance<R> {
 
     fn compute_properties(&mut self, rtc: &mut RenderTreeContext<R>) {
 
-
-
         if self.next_frame_children.is_some() {
             self.active_children = self.next_frame_children.take().unwrap();
             self.next_frame_children = None;
         }
 
-        let (is_dirty, normalized_vec_of_props) = if let Some(se) = &self.source_expression_vec {
-            //Handle case where the source expression is a Vec<Property<T>>,
-            // like `for elem in self.data_list`
-            let new_value = if let Some(tc) = rtc.compute_vtable_value(se._get_vtable_id().clone()) {
+        //The source expression is either a Vec<Property<T>>, like `for elem in self.data_list`,
+        //or a range, like `for i in 0..5`, already normalized to PropertiesCoproduct::isize elements
+        let normalized_vec_of_props : Vec<Rc<PropertiesCoproduct>> = if let Some(se) = &self.source_expression_vec {
+            if let Some(tc) = rtc.compute_vtable_value(se._get_vtable_id().clone()) {
                 if let TypesCoproduct::stdCOCOvecCOCOVecLABRstdCOCOrcCOCORcLABRPropertiesCoproductRABRRABR(vec) = tc { vec } else { unreachable!() }
             } else {
                 se.get().clone()
-            };
-
-            //let is_dirty = true;
-            //Major hack: will only consider a new vec dirty if its cardinality changes.
-            let is_dirty = {
-                rtc.bounds != self.cached_old_bounds ||
-                if self.cached_old_value_vec.is_none() {
-                    true
-                } else {
-                    self.cached_old_value_vec.as_ref().unwrap().len() != new_value.len()
-                }
-            };
-            self.cached_old_bounds = rtc.bounds.clone();
-            self.cached_old_value_vec = Some(new_value.clone());
-            (is_dirty, new_value)
-        } else if let Some(se) = &self.source_expression_range {
-            //Handle case where the source expression is a Range,
-            // like `for i in 0..5`
-            let new_value = if let Some(tc) = rtc.compute_vtable_value(se._get_vtable_id().clone()) {
-                if let TypesCoproduct::stdCOCOopsCOCORangeLABRisizeRABR(vec) = tc { vec } else { unreachable!() }
-            } else { unreachable!() };
-
-            //let is_dirty = true;
-            //Major hack: will only consider a new vec dirty if its cardinality changes.
-            let is_dirty = {
-                rtc.bounds != self.cached_old_bounds ||
-                if self.cached_old_value_range.is_none() {
-                    true
+            }
+        } else {unreachable!()};
+
+        let new_keys : Vec<String> = normalized_vec_of_props.iter().enumerate().map(|(i, datum)| {
+            self.compute_key(rtc, i, datum)
+        }).collect();
+
+        //Reconcile by key.  Children only need to be re-computed if any RepeatItems were
+        //added, removed, or moved; data and indices of reused RepeatItems are updated in place.
+        let mut is_dirty = new_keys.len() != self.repeat_items.len();
+        let old_keys : Vec<String> = self.repeat_items.iter().map(|record| record.key.clone()).collect();
+
+        //duplicate keys are reused in order, e.g. the second RepeatItem keyed `a` by the second occurrence of `a`
+        let mut old_records : HashMap<String, VecDeque<RepeatItemRecord<R>>> = HashMap::new();
+        std::mem::take(&mut self.repeat_items).into_iter().for_each(|record| {
+            old_records.entry(record.key.clone()).or_default().push_back(record);
+        });
+
+        //Any stated children (repeat template members) of Repeat should be forwarded to the `RepeatItem`-wrapped `ComponentInstance`s
+        //so that `Slot` works as expected
+        let forwarded_children = match (*rtc.runtime).borrow_mut().peek_stack_frame() {
+            Some(frame) => {Rc::clone(&(*frame.borrow()).get_unflattened_adoptees())},
+            None => {Rc::new(RefCell::new(vec![]))},
+        };
+
+        let mut instance_registry = (*rtc.engine.instance_registry).borrow_mut();
+
+        let new_records : Vec<RepeatItemRecord<R>> = normalized_vec_of_props.iter().zip(new_keys.into_iter()).enumerate().map(|(i, (datum, key))| {
+            if let Some(record) = old_records.get_mut(&key).and_then(|records| records.pop_front()) {
+                //reuse: preserve this RepeatItem's render node and stable identity
+                is_dirty = is_dirty || old_keys.get(i) != Some(&key);
+                *(*record.properties).borrow_mut() = PropertiesCoproduct::RepeatItem(Rc::clone(datum), i, record.stable_id);
+                record
+            } else {
+                //insert: wrap datum into a new `RepeatItem`, which attaches the
+                //necessary data as stack frame context
+                is_dirty = true;
+
+                //Unkeyed items are identified by their index, which keeps their `id_chain`s
+                //stable; keyed items are assigned a fresh identity that follows them as they move
+                let stable_id = if self.key_expression.is_some() {
+                    let stable_id = self.next_stable_id;
+                    self.next_stable_id += 1;
+                    stable_id
                 } else {
-                    self.cached_old_value_range.as_ref().unwrap().len() != new_value.len()
+                    i as u64
+                };
+
+                let instance_id = instance_registry.mint_id();
+                let properties = Rc::new(RefCell::new(PropertiesCoproduct::RepeatItem(Rc::clone(datum), i, stable_id)));
+
+                let render_node : RenderNodePtr<R> = Rc::new(RefCell::new(
+                    ComponentInstance {
+                        instance_id,
+                        children: Rc::clone(&forwarded_children),
+                        template: Rc::clone(&self.repeated_template),
+                        transform: Rc::new(RefCell::new(PropertyLiteral::new(Transform2D::default()))),
+                        properties: Rc::clone(&properties),
+                        timeline: None,
+                        handler_registry: None,
+                        compute_properties_fn: Box::new(|props, rtc|{
+                            //no-op since the Repeat RenderNode handles the necessary calc (see `RepeatInstance::compute_properties`)
+                        }),
+
+                    }
+                ));
+
+                instance_registry.register(instance_id, Rc::clone(&render_node));
+                instance_registry.mark_mounted(rtc.get_id_chain(instance_id));
+
+                RepeatItemRecord {
+                    key,
+                    stable_id,
+                    render_node,
+                    properties,
                 }
-            };
-            self.cached_old_bounds = rtc.bounds.clone();
-            self.cached_old_value_range = Some(new_value.clone());
-            let normalized_vec_of_props = new_value.into_iter().enumerate().map(|(i, elem)|{Rc::new(PropertiesCoproduct::isize(elem))}).collect();
-            (is_dirty, normalized_vec_of_props)
-        } else {unreachable!()};
+            }
+        }).collect();
+
+        //unmount: any RepeatItems whose keys are no longer present, or present fewer times
+        old_records.into_values().flatten().for_each(|record| {
+            is_dirty = true;
+            let instance_id = (*record.render_node).borrow_mut().get_instance_id();
+            instance_registry.deregister(instance_id);
+            instance_registry.mark_for_unmount(instance_id);
+        });
 
         if is_dirty {
-            //Any stated children (repeat template members) of Repeat should be forwarded to the `RepeatItem`-wrapped `ComponentInstance`s
-            //so that `Slot` works as expected
-            let forwarded_children = match (*rtc.runtime).borrow_mut().peek_stack_frame() {
-                Some(frame) => {Rc::clone(&(*frame.borrow()).get_unflattened_adoptees())},
-                None => {Rc::new(RefCell::new(vec![]))},
-            };
-
-            let mut instance_registry = (*rtc.engine.instance_registry).borrow_mut();
-
-            (*self.active_children).borrow_mut().iter().for_each(|child| {
-                let instance_id = (*(*child)).borrow_mut().get_instance_id();
-                instance_registry.deregister(instance_id);
-                instance_registry.mark_for_unmount(instance_id);
-            });
-
-            //reset children:
-            //wrap source_expression into `RepeatItems`, which attach
-            //the necessary data as stack frame context
+            //Outgoing RepeatItems remain in `active_children` for this frame, so that they may be unmounted
+            rtc.engine.request_render();
             self.next_frame_children = Some(Rc::new(RefCell::new(
-                normalized_vec_of_props.iter().enumerate().map(|(i, datum)| {
-                    let instance_id = instance_registry.mint_id();
-
-                    let render_node : RenderNodePtr<R> = Rc::new(RefCell::new(
-                        ComponentInstance {
-                            instance_id,
-                            children: Rc::clone(&forwarded_children),
-                            template: Rc::clone(&self.repeated_template),
-                            transform: Rc::new(RefCell::new(PropertyLiteral::new(Transform2D::default()))),
-                            properties: Rc::new(RefCell::new(PropertiesCoproduct::RepeatItem(Rc::clone(datum), i))),
-                            timeline: None,
-                            handler_registry: None,
-                            compute_properties_fn: Box::new(|props, rtc|{
-                                //no-op since the Repeat RenderNode handles the necessary calc (see `RepeatInstance::compute_properties`)
-                            }),
-
-                        }
-                    ));
-
-                    instance_registry.register(instance_id, Rc::clone(&render_node));
-                    instance_registry.mark_mounted(rtc.get_id_chain(instance_id));
-
-                    // (&*render_node).borrow_mut().mount_recursive(rtc);
-
-                    render_node
-                }).collect()
+                new_records.iter().map(|record| Rc::clone(&record.render_node)).collect()
             )));
-
         }
-
-
+        self.repeat_items = new_records;
 
         // pax_runtime_api::log(&format!("finished computing repeat properties, virt len: {}", (*self.virtual_children).borrow().len()));
     }
@@ -182,11 +217,6 @@ impl<R: 'static + RenderContext> RenderNode<R> for RepeatInstance<R> {
         Layer::DontCare
     }
 
-    fn handle_did_mount(&mut self, _rtc: &mut RenderTreeContext<R>) {
-        self.cached_old_value_range = None;
-        self.cached_old_value_vec = None;
-    }
-
 }
 
 
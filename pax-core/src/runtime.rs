@@ -40,8 +40,10 @@ impl<R: 'static + RenderContext> Runtime<R> {
         let mut indices: Vec<u64> = vec![];
 
         self.stack.iter().for_each(|frame_wrapped|{
-            if let PropertiesCoproduct::RepeatItem(datum, i) = &*(*(*(*frame_wrapped).borrow_mut()).borrow().properties).borrow() {
-                indices.push(*i as u64)
+            //use each RepeatItem's stable identity rather than its index, so that keyed items
+            //retain their `id_chain`s when reordered
+            if let PropertiesCoproduct::RepeatItem(_datum, _i, stable_id) = &*(*(*(*frame_wrapped).borrow_mut()).borrow().properties).borrow() {
+                indices.push(*stable_id)
             }
         });
         indices
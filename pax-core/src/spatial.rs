@@ -0,0 +1,204 @@
+//! Indexes the elements rendered each frame by where they are, so that ray casting and area queries needn't visit every
+//! element, e.g. in data visualizations of many thousands of elements.
+//!
+//! The index is a uniform grid of square cells, rebuilt as the render tree is traversed each tick: each element that can
+//! be hit is listed in every cell overlapped by its bounding box (see `RenderNode::get_hit_bounds`), in the order in which
+//! it was visited, so that later entries are on top of earlier ones (see `InstanceRegistry::add_to_repeat_expanded_node_cache`).
+//! Elements too large to list cell-by-cell are kept apart and checked by every query.
+
+use std::collections::HashMap;
+
+use kurbo::{Point, Rect};
+
+/// The width and height in px of each cell of the grid
+const CELL_SIZE: f64 = 128.0;
+
+/// Elements overlapping more cells than this are kept apart rather than listed in each, e.g. full-viewport backgrounds
+const MAX_CELLS_PER_ENTRY: i64 = 256;
+
+struct Entry<T: Clone> {
+    node: T,
+    bounds: Rect,
+}
+
+/// Indexes `T`s by their bounds; the engine indexes `Rc<RepeatExpandedNode<R>>`s
+pub struct SpatialIndex<T: Clone> {
+    /// Every element indexed, in the order in which they were visited
+    entries: Vec<Entry<T>>,
+    /// Indices into `entries` of the elements overlapping each cell, by the cell's column and row
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    /// Indices into `entries` of the elements overlapping more than `MAX_CELLS_PER_ENTRY` cells
+    oversized: Vec<usize>,
+}
+
+impl<T: Clone> Default for SpatialIndex<T> {
+    fn default() -> Self {
+        SpatialIndex {
+            entries: vec![],
+            cells: HashMap::new(),
+            oversized: vec![],
+        }
+    }
+}
+
+impl<T: Clone> SpatialIndex<T> {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cells.clear();
+        self.oversized.clear();
+    }
+
+    /// Indexes `node` by its bounding box in the viewport, on top of every element already indexed
+    pub fn insert(&mut self, node: T, bounds: Rect) {
+        let index = self.entries.len();
+        self.entries.push(Entry { node, bounds });
+        match Self::get_cell_range(&bounds) {
+            Some((min_cell, max_cell)) => {
+                for column in min_cell.0..=max_cell.0 {
+                    for row in min_cell.1..=max_cell.1 {
+                        self.cells.entry((column, row)).or_default().push(index);
+                    }
+                }
+            },
+            None => self.oversized.push(index),
+        }
+    }
+
+    /// The elements whose bounding boxes contain `point`, topmost first
+    pub fn get_at_point(&self, point: (f64, f64)) -> Vec<T> {
+        let cell = Self::get_cell(point);
+        let indices = self.cells.get(&cell).into_iter().flatten().chain(self.oversized.iter()).copied();
+        let point = Point::new(point.0, point.1);
+        self.get_topmost_first(indices.filter(|index| Self::contains(&self.entries[*index].bounds, point)).collect())
+    }
+
+    /// The elements whose bounding boxes overlap `area`, topmost first
+    pub fn get_overlapping(&self, area: &Rect) -> Vec<T> {
+        let indices : Vec<usize> = match Self::get_cell_range(area) {
+            Some((min_cell, max_cell)) => {
+                let mut indices : Vec<usize> = self.oversized.clone();
+                for column in min_cell.0..=max_cell.0 {
+                    for row in min_cell.1..=max_cell.1 {
+                        indices.extend(self.cells.get(&(column, row)).into_iter().flatten());
+                    }
+                }
+                indices.sort_unstable();
+                indices.dedup();
+                indices
+            },
+            //an area spanning too many cells to visit is cheaper to check against every element
+            None => (0..self.entries.len()).collect(),
+        };
+        self.get_topmost_first(indices.into_iter().filter(|index| Self::overlaps(&self.entries[*index].bounds, area)).collect())
+    }
+
+    fn get_topmost_first(&self, mut indices: Vec<usize>) -> Vec<T> {
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        indices.into_iter().map(|index| self.entries[index].node.clone()).collect()
+    }
+
+    fn get_cell(point: (f64, f64)) -> (i64, i64) {
+        ((point.0 / CELL_SIZE).floor() as i64, (point.1 / CELL_SIZE).floor() as i64)
+    }
+
+    /// The first and last cells overlapped by `bounds`, or `None` if there are too many to list it in each
+    fn get_cell_range(bounds: &Rect) -> Option<((i64, i64), (i64, i64))> {
+        if !(bounds.x0.is_finite() && bounds.y0.is_finite() && bounds.x1.is_finite() && bounds.y1.is_finite()) {
+            return None;
+        }
+        let min_cell = Self::get_cell((bounds.x0, bounds.y0));
+        let max_cell = Self::get_cell((bounds.x1, bounds.y1));
+        let cell_count = max_cell.0.saturating_sub(min_cell.0).saturating_add(1)
+            .saturating_mul(max_cell.1.saturating_sub(min_cell.1).saturating_add(1));
+        if cell_count > MAX_CELLS_PER_ENTRY {
+            return None;
+        }
+        Some((min_cell, max_cell))
+    }
+
+    /// Unlike `Rect::contains`, includes the right and bottom edges, so that zero-sized bounds contain their own corner
+    fn contains(bounds: &Rect, point: Point) -> bool {
+        point.x >= bounds.x0 && point.x <= bounds.x1 && point.y >= bounds.y0 && point.y <= bounds.y1
+    }
+
+    fn overlaps(bounds: &Rect, area: &Rect) -> bool {
+        bounds.x0 <= area.x1 && area.x0 <= bounds.x1 && bounds.y0 <= area.y1 && area.y0 <= bounds.y1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::Rect;
+
+    use super::{SpatialIndex, CELL_SIZE, MAX_CELLS_PER_ENTRY};
+
+    #[test]
+    fn test_get_cell_range() {
+        assert_eq!(SpatialIndex::<usize>::get_cell_range(&Rect::new(10.0, 10.0, 20.0, 20.0)), Some(((0, 0), (0, 0))));
+        assert_eq!(SpatialIndex::<usize>::get_cell_range(&Rect::new(-1.0, 100.0, CELL_SIZE, 300.0)), Some(((-1, 0), (1, 2))));
+        assert_eq!(SpatialIndex::<usize>::get_cell_range(&Rect::new(0.0, 0.0, f64::INFINITY, 10.0)), None);
+        assert_eq!(SpatialIndex::<usize>::get_cell_range(&Rect::new(0.0, 0.0, f64::NAN, 10.0)), None);
+        //counting cells mustn't overflow for huge bounds
+        assert_eq!(SpatialIndex::<usize>::get_cell_range(&Rect::new(-1e300, -1e300, 1e300, 1e300)), None);
+    }
+
+    #[test]
+    fn test_oversized_entries() {
+        let side = CELL_SIZE * (MAX_CELLS_PER_ENTRY as f64).sqrt();
+        let mut index = SpatialIndex::default();
+        //exactly `MAX_CELLS_PER_ENTRY` cells are listed cell-by-cell, while one more row of cells is kept apart
+        index.insert(0, Rect::new(0.0, 0.0, side - 1.0, side - 1.0));
+        index.insert(1, Rect::new(0.0, 0.0, side - 1.0, side + 1.0));
+        assert_eq!(index.oversized, vec![1]);
+
+        assert_eq!(index.get_at_point((side / 2.0, side / 2.0)), vec![1, 0]);
+        assert_eq!(index.get_at_point((side / 2.0, side)), vec![1]);
+        assert_eq!(index.get_at_point((side * 2.0, side * 2.0)), Vec::<usize>::new());
+        assert_eq!(index.get_overlapping(&Rect::new(side / 2.0, side - 1.5, side / 2.0, side - 1.5)), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_get_at_point_is_topmost_first() {
+        let mut index = SpatialIndex::default();
+        index.insert("background", Rect::new(0.0, 0.0, 1000.0, 1000.0));
+        index.insert("card", Rect::new(100.0, 100.0, 300.0, 300.0));
+        index.insert("button", Rect::new(150.0, 150.0, 200.0, 200.0));
+        index.insert("elsewhere", Rect::new(500.0, 500.0, 600.0, 600.0));
+
+        assert_eq!(index.get_at_point((175.0, 175.0)), vec!["button", "card", "background"]);
+        assert_eq!(index.get_at_point((250.0, 250.0)), vec!["card", "background"]);
+        assert_eq!(index.get_at_point((-1.0, 0.0)), Vec::<&str>::new());
+
+        index.clear();
+        assert_eq!(index.get_at_point((175.0, 175.0)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_edges_are_inclusive() {
+        let mut index = SpatialIndex::default();
+        index.insert(0, Rect::new(10.0, 10.0, CELL_SIZE, CELL_SIZE));
+        //zero-sized bounds still contain their own corner
+        index.insert(1, Rect::new(50.0, 50.0, 50.0, 50.0));
+
+        assert_eq!(index.get_at_point((10.0, 10.0)), vec![0]);
+        //the bottom-right corner lies in the next cell, where the entry is listed too
+        assert_eq!(index.get_at_point((CELL_SIZE, CELL_SIZE)), vec![0]);
+        assert_eq!(index.get_at_point((50.0, 50.0)), vec![1, 0]);
+        assert_eq!(index.get_overlapping(&Rect::new(0.0, 0.0, 10.0, 10.0)), vec![0]);
+        assert_eq!(index.get_overlapping(&Rect::new(0.0, 0.0, 9.9, 9.9)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_get_overlapping_lists_each_entry_once() {
+        let mut index = SpatialIndex::default();
+        //spans many cells, each of which lists it
+        index.insert(0, Rect::new(0.0, 0.0, CELL_SIZE * 4.0, CELL_SIZE * 4.0));
+        index.insert(1, Rect::new(CELL_SIZE * 2.5, CELL_SIZE * 2.5, CELL_SIZE * 3.5, CELL_SIZE * 3.5));
+        index.insert(2, Rect::new(0.0, 0.0, f64::INFINITY, f64::INFINITY));
+
+        assert_eq!(index.get_overlapping(&Rect::new(0.0, 0.0, CELL_SIZE * 4.0, CELL_SIZE * 4.0)), vec![2, 1, 0]);
+        assert_eq!(index.get_overlapping(&Rect::new(10.0, 10.0, 20.0, 20.0)), vec![2, 0]);
+        //an area too large to visit cell-by-cell is checked against every entry
+        assert_eq!(index.get_overlapping(&Rect::new(-1e9, -1e9, 1e9, 1e9)), vec![2, 1, 0]);
+    }
+}
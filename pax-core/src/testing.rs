@@ -0,0 +1,307 @@
+//! Drives a `PaxEngine` programmatically, e.g. to test event handlers without a platform chassis.
+//!
+//! `TestDriver` dispatches input as a chassis would: pointer events go to the topmost element beneath their coordinates
+//! (see `PaxEngine::get_topmost_element_beneath_ray`), while key and scroll events go to the focused element (see `focus`).  Instances
+//! are addressed by the `id` of their template node in Pax, e.g. `sidebar` for `<Rectangle id=sidebar />`.
+//!
+//! Handlers mutate properties immediately, while expressions are only re-evaluated by ticking.  For example:
+//!
+//! ```ignore
+//! let mut driver = TestDriver::new(engine, vec![piet::NullRenderContext::new()]);
+//! driver.click(100.0, 40.0).key_down("Escape").tick();
+//! driver.assert_property("sidebar", "is_open", |properties: &PropertiesCoproduct| {
+//!     if let PropertiesCoproduct::Sidebar(sidebar) = properties { *sidebar.is_open.get() } else { unreachable!() }
+//! }, false);
+//! ```
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+
+use piet_common::RenderContext;
+
+use pax_message::NativeMessage;
+use pax_runtime_api::{ArgsClick, ArgsDoubleClick, ArgsJab, ArgsKeyDown, ArgsKeyPress, ArgsKeyUp, ArgsMouseDown, ArgsMouseMove, ArgsMouseUp, ArgsScroll, ArgsTouchEnd, ArgsTouchMove, ArgsTouchStart, ArgsWheel, EventControl, KeyboardEventArgs, ModifierKey, MouseButton, MouseEventArgs, PointerCapture, Touch};
+
+use crate::{PaxEngine, RenderNodePtr};
+
+pub struct TestDriver<R: 'static + RenderContext> {
+    engine: PaxEngine<R>,
+    render_contexts: Vec<R>,
+    /// Native messages emitted by the most recent tick
+    native_messages: Vec<NativeMessage>,
+}
+
+impl<R: 'static + RenderContext> TestDriver<R> {
+    /// Ticks `engine` once, so that its elements can be hit-tested.  Any render context will do if rendering isn't under
+    /// test, e.g. `piet::NullRenderContext`.
+    pub fn new(engine: PaxEngine<R>, render_contexts: Vec<R>) -> Self {
+        let mut driver = TestDriver {
+            engine,
+            render_contexts,
+            native_messages: vec![],
+        };
+        driver.tick();
+        driver
+    }
+
+    pub fn engine(&self) -> &PaxEngine<R> {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut PaxEngine<R> {
+        &mut self.engine
+    }
+
+    /// Native messages emitted by the most recent tick, e.g. text patches
+    pub fn get_native_messages(&self) -> &Vec<NativeMessage> {
+        &self.native_messages
+    }
+
+    pub fn tick(&mut self) -> &mut Self {
+        self.native_messages = self.engine.tick(&mut self.render_contexts);
+        self
+    }
+
+    pub fn advance(&mut self, frame_count: usize) -> &mut Self {
+        for _ in 0..frame_count {
+            self.tick();
+        }
+        self
+    }
+
+    pub fn click(&mut self, x: f64, y: f64) -> &mut Self {
+        if let Some(node) = self.engine.get_topmost_element_beneath_ray((x, y)) {
+            node.dispatch_click(ArgsClick { mouse: Self::mouse_event_args(x, y), control: EventControl::default() });
+        }
+        self
+    }
+
+    pub fn double_click(&mut self, x: f64, y: f64) -> &mut Self {
+        if let Some(node) = self.engine.get_topmost_element_beneath_ray((x, y)) {
+            node.dispatch_double_click(ArgsDoubleClick { mouse: Self::mouse_event_args(x, y), control: EventControl::default() });
+        }
+        self
+    }
+
+    /// Also moves keyboard focus and begins tracking a drag, unless a handler calls `prevent_default`, as chassis do
+    pub fn mouse_down(&mut self, x: f64, y: f64) -> &mut Self {
+        self.engine.dispatch_mouse_down(ArgsMouseDown { mouse: Self::mouse_event_args(x, y), pointer_capture: PointerCapture::default(), control: EventControl::default() });
+        self
+    }
+
+    /// Dispatched to the element capturing the mouse, if any, rather than the one beneath it; also ends any drag
+    pub fn mouse_up(&mut self, x: f64, y: f64) -> &mut Self {
+        self.engine.dispatch_mouse_up(ArgsMouseUp { mouse: Self::mouse_event_args(x, y), control: EventControl::default() });
+        self
+    }
+
+    /// Also dispatches `mouse_over`, `mouse_enter` et al. if the element beneath the mouse changes, and drag events
+    /// while a button is down, as chassis do
+    pub fn mouse_move(&mut self, x: f64, y: f64) -> &mut Self {
+        self.engine.dispatch_mouse_move(ArgsMouseMove { mouse: Self::mouse_event_args(x, y), control: EventControl::default() });
+        self
+    }
+
+    /// Presses the mouse at `from`, moves it to `to` over `steps` mouse moves, ticking after each, then releases it
+    pub fn drag(&mut self, from: (f64, f64), to: (f64, f64), steps: usize) -> &mut Self {
+        self.mouse_down(from.0, from.1);
+        for point in Self::interpolate(from, to, steps) {
+            self.mouse_move(point.0, point.1).tick();
+        }
+        self.mouse_up(to.0, to.1)
+    }
+
+    /// Dispatches a jab, the platform-agnostic tap or click, as chassis do alongside `click`
+    pub fn jab(&mut self, x: f64, y: f64) -> &mut Self {
+        if let Some(node) = self.engine.get_topmost_element_beneath_ray((x, y)) {
+            node.dispatch_jab(ArgsJab { x, y, control: EventControl::default() });
+        }
+        self
+    }
+
+    pub fn wheel(&mut self, x: f64, y: f64, delta_x: f64, delta_y: f64) -> &mut Self {
+        if let Some(node) = self.engine.get_topmost_element_beneath_ray((x, y)) {
+            node.dispatch_wheel(ArgsWheel { x, y, delta_x, delta_y, modifiers: vec![], control: EventControl::default() });
+        }
+        self
+    }
+
+    pub fn scroll(&mut self, delta_x: f64, delta_y: f64) -> &mut Self {
+        if let Some(node) = self.engine.get_focused_element() {
+            node.dispatch_scroll(ArgsScroll { delta_x, delta_y, control: EventControl::default() });
+        }
+        self
+    }
+
+    /// Dispatches a key down and key up, as well as a key press for keys that display a character (e.g. "a", but not "Escape")
+    pub fn press_key(&mut self, key: &str) -> &mut Self {
+        self.key_down(key);
+        if key.chars().count() == 1 {
+            self.engine.dispatch_key_press(ArgsKeyPress { keyboard: Self::keyboard_event_args(key), control: EventControl::default() });
+        }
+        self.key_up(key)
+    }
+
+    /// `key` as in the `key` of DOM keyboard events, e.g. "a", "Enter" or "ArrowUp".  "Tab" moves keyboard focus, as
+    /// with chassis.
+    pub fn key_down(&mut self, key: &str) -> &mut Self {
+        self.engine.dispatch_key_down(ArgsKeyDown { keyboard: Self::keyboard_event_args(key), control: EventControl::default() });
+        self
+    }
+
+    pub fn key_up(&mut self, key: &str) -> &mut Self {
+        self.engine.dispatch_key_up(ArgsKeyUp { keyboard: Self::keyboard_event_args(key), control: EventControl::default() });
+        self
+    }
+
+    /// Presses Shift-Tab, moving keyboard focus to the previous `focusable` element
+    pub fn shift_tab(&mut self) -> &mut Self {
+        let keyboard = KeyboardEventArgs { key: "Tab".to_string(), modifiers: vec![ModifierKey::Shift], is_repeat: false };
+        self.engine.dispatch_key_down(ArgsKeyDown { keyboard: keyboard.clone(), control: EventControl::default() });
+        self.engine.dispatch_key_up(ArgsKeyUp { keyboard, control: EventControl::default() });
+        self
+    }
+
+    /// Each touch event is dispatched to the topmost element beneath its first touch point, or the element capturing
+    /// that touch, as by chassis
+    pub fn touch_start(&mut self, points: &[(f64, f64)]) -> &mut Self {
+        self.engine.dispatch_touch_start(ArgsTouchStart { touches: Self::touches(points), pointer_capture: PointerCapture::default(), control: EventControl::default() });
+        self
+    }
+
+    pub fn touch_move(&mut self, points: &[(f64, f64)]) -> &mut Self {
+        self.engine.dispatch_touch_move(ArgsTouchMove { touches: Self::touches(points), control: EventControl::default() });
+        self
+    }
+
+    pub fn touch_end(&mut self, points: &[(f64, f64)]) -> &mut Self {
+        self.engine.dispatch_touch_end(ArgsTouchEnd { touches: Self::touches(points), control: EventControl::default() });
+        self
+    }
+
+    /// Touches down at `from`, moves to `to` over `steps` touch moves, ticking after each, then lifts
+    pub fn swipe(&mut self, from: (f64, f64), to: (f64, f64), steps: usize) -> &mut Self {
+        self.touch_start(&[from]);
+        for point in Self::interpolate(from, to, steps) {
+            self.touch_move(&[point]).tick();
+        }
+        self.touch_end(&[to])
+    }
+
+    /// Touches down with two points `from_distance` apart either side of `center`, moves them until they're
+    /// `to_distance` apart over `steps` touch moves, ticking after each, then lifts both
+    pub fn pinch(&mut self, center: (f64, f64), from_distance: f64, to_distance: f64, steps: usize) -> &mut Self {
+        let points = |distance: f64| [(center.0 - distance / 2.0, center.1), (center.0 + distance / 2.0, center.1)];
+        self.touch_start(&points(from_distance));
+        for (distance, _) in Self::interpolate((from_distance, 0.0), (to_distance, 0.0), steps) {
+            self.touch_move(&points(distance)).tick();
+        }
+        self.touch_end(&points(to_distance))
+    }
+
+    /// Touches down at (`x`, `y`), holds still for `frame_count` ticks, then lifts
+    pub fn long_press(&mut self, x: f64, y: f64, frame_count: usize) -> &mut Self {
+        self.touch_start(&[(x, y)]).advance(frame_count).touch_end(&[(x, y)])
+    }
+
+    /// Ids of the instances of the template node with the `id` `template_id` rendered by the most recent tick, e.g.
+    /// excluding those within the inactive branch of an `if`
+    pub fn get_instance_ids(&self, template_id: &str) -> Vec<u64> {
+        let instance_registry = (*self.engine.instance_registry).borrow();
+        instance_registry.get_instance_ids_by_template_id(template_id).into_iter()
+            .filter(|instance_id| instance_registry.was_rendered(*instance_id))
+            .collect()
+    }
+
+    /// Calls `read` with the properties of the single rendered instance of the template node with the `id` `template_id`, panicking
+    /// with a description of what was found instead if there isn't exactly one or its properties aren't a `T`.
+    /// `T` is `PropertiesCoproduct` for components, or the properties struct of a primitive, e.g. `Rectangle`.
+    /// Within `Repeat`, an instance's properties are those computed for the last repeated element rendered.
+    pub fn with_properties<T: 'static, U>(&self, template_id: &str, read: impl FnOnce(&T) -> U) -> U {
+        let instance = self.get_single_instance(template_id);
+        let properties = (*instance).borrow().get_properties_any()
+            .unwrap_or_else(|| panic!("`{}` (instance {}) has no inspectable properties", template_id, (*instance).borrow().get_instance_id()));
+        let properties = properties.downcast::<RefCell<T>>()
+            .unwrap_or_else(|_| panic!("The properties of `{}` aren't a `{}`", template_id, std::any::type_name::<T>()));
+        let properties = (*properties).borrow();
+        read(&properties)
+    }
+
+    /// Asserts that the property `property_name` of `template_id`, as read by `read` (see `with_properties`), equals `expected`
+    pub fn assert_property<T: 'static, V: PartialEq + Debug>(&self, template_id: &str, property_name: &str, read: impl FnOnce(&T) -> V, expected: V) -> &Self {
+        let actual = self.with_properties(template_id, read);
+        if actual != expected {
+            panic!("Expected `{}.{}` to be {:?}, but it was {:?} after {} frames", template_id, property_name, expected, actual, self.engine.frames_elapsed);
+        }
+        self
+    }
+
+    /// Asserts that the topmost element beneath `(x, y)`, i.e. the one that would receive a click there, is an instance
+    /// of `template_id`
+    pub fn assert_element_beneath(&self, x: f64, y: f64, template_id: &str) -> &Self {
+        let instance_ids = self.get_instance_ids(template_id);
+        match self.engine.get_topmost_element_beneath_ray((x, y)) {
+            Some(node) if instance_ids.contains(&node.get_id_chain()[0]) => {},
+            Some(node) => panic!("Expected `{}` beneath ({}, {}), but found {}", template_id, x, y, self.describe_instance(node.get_id_chain()[0])),
+            None => panic!("Expected `{}` beneath ({}, {}), but found no element", template_id, x, y),
+        }
+        self
+    }
+
+    /// Asserts that an instance of `template_id` has keyboard focus
+    pub fn assert_focused(&self, template_id: &str) -> &Self {
+        let instance_ids = self.get_instance_ids(template_id);
+        match self.engine.get_focused_id_chain() {
+            Some(id_chain) if instance_ids.contains(&id_chain[0]) => {},
+            Some(id_chain) => panic!("Expected `{}` to be focused, but {} was", template_id, self.describe_instance(id_chain[0])),
+            None => panic!("Expected `{}` to be focused, but no element was", template_id),
+        }
+        self
+    }
+
+    fn get_single_instance(&self, template_id: &str) -> RenderNodePtr<R> {
+        let instance_ids = self.get_instance_ids(template_id);
+        let instance_registry = (*self.engine.instance_registry).borrow();
+        match instance_ids.as_slice() {
+            [instance_id] => instance_registry.get_instance(*instance_id).unwrap(),
+            [] if instance_registry.get_instance_ids_by_template_id(template_id).is_empty() => {
+                panic!("No instance has the template id `{}`; known template ids: {:?}", template_id, instance_registry.get_template_ids())
+            },
+            [] => panic!("No instance with the template id `{}` was rendered by the last tick, after {} frames", template_id, self.engine.frames_elapsed),
+            _ => panic!("Expected a single rendered instance with the template id `{}`, but found {}: {:?}", template_id, instance_ids.len(), instance_ids),
+        }
+    }
+
+    /// e.g. "`sidebar` (instance 12)", or "instance 12" for nodes without an `id`
+    fn describe_instance(&self, instance_id: u64) -> String {
+        let instance_registry = (*self.engine.instance_registry).borrow();
+        let template_id = instance_registry.get_template_ids().into_iter()
+            .find(|template_id| instance_registry.get_instance_ids_by_template_id(template_id).contains(&instance_id));
+        match template_id {
+            Some(template_id) => format!("`{}` (instance {})", template_id, instance_id),
+            None => format!("instance {}", instance_id),
+        }
+    }
+
+    /// `steps` points evenly spaced from `from` (exclusive) to `to` (inclusive)
+    fn interpolate(from: (f64, f64), to: (f64, f64), steps: usize) -> Vec<(f64, f64)> {
+        (1..=steps).map(|step| {
+            let progress = step as f64 / steps as f64;
+            (from.0 + (to.0 - from.0) * progress, from.1 + (to.1 - from.1) * progress)
+        }).collect()
+    }
+
+    fn mouse_event_args(x: f64, y: f64) -> MouseEventArgs {
+        MouseEventArgs { x, y, button: MouseButton::Left, modifiers: vec![] }
+    }
+
+    fn keyboard_event_args(key: &str) -> KeyboardEventArgs {
+        KeyboardEventArgs { key: key.to_string(), modifiers: vec![], is_repeat: false }
+    }
+
+    fn touches(points: &[(f64, f64)]) -> Vec<Touch> {
+        points.iter().enumerate().map(|(identifier, (x, y))| {
+            Touch { x: *x, y: *y, identifier: identifier as i64, delta_x: 0.0, delta_y: 0.0 }
+        }).collect()
+    }
+}
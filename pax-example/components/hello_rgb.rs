@@ -12,6 +12,8 @@ pub struct HelloRGB {
 impl HelloRGB {
     pub fn handle_click(&mut self, ctx: RuntimeContext, args: ArgsClick) {
         log("click-ellipse");
+        //the ellipse handles its own clicks, so `handle_global_click` isn't called for them
+        args.control.stop_propagation();
     }
     pub fn handle_scroll(&mut self, ctx: RuntimeContext, args: ArgsScroll) {
         const ROTATION_COEFFICIENT: f64 = 0.005;
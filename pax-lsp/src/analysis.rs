@@ -0,0 +1,489 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{CompletionItem, CompletionItemKind, DiagnosticSeverity, Hover, HoverContents, Location, MarkupContent, MarkupKind, NumberOrString, Position, Range, Url};
+use pax_compiler::diagnostics::{Diagnostic, DiagnosticKind};
+use pax_compiler::manifest::{ComponentDefinition, PaxManifest, PropertyDefinition, INLINED_SOURCE_SUFFIX};
+use pax_compiler::parsing::{PaxParser, Rule};
+use pest::Parser;
+
+use crate::documents::{Document, TemplateRegion};
+
+/// Settings handled by the engine for every node, rather than declared as properties
+const BUILTIN_SETTINGS: [(&str, &str); 5] = [
+    ("id", "String"),
+    ("class", "String"),
+    ("transform", "Transform2D"),
+    ("width", "Size"),
+    ("height", "Size"),
+];
+
+/// Syntax errors in the current text of `document`, plus `project_diagnostics` (from the last run of the parser and
+/// expression compiler, i.e. as of the last save) that were reported against `document`
+pub fn collect_diagnostics(document: &Document, project_diagnostics: &[Diagnostic]) -> Vec<lsp_types::Diagnostic> {
+    let mut ret = vec![];
+    for region in document.regions.iter() {
+        if let Err(error) = PaxParser::parse(Rule::pax_component_definition, &region.text) {
+            ret.push(to_lsp_diagnostic(region, &Diagnostic::from_pest_error(error)));
+        }
+    }
+
+    //syntax errors are covered above, against the current (rather than last-saved) text
+    for diagnostic in project_diagnostics.iter().filter(|diagnostic| diagnostic.kind != DiagnosticKind::Syntax) {
+        if let Some(region) = region_for_diagnostic(document, diagnostic) {
+            ret.push(to_lsp_diagnostic(region, diagnostic));
+        }
+    }
+    ret
+}
+
+fn to_lsp_diagnostic(region: &TemplateRegion, diagnostic: &Diagnostic) -> lsp_types::Diagnostic {
+    let range = match &diagnostic.span {
+        Some(span) => region.range_of(span),
+        None => Range::new(region.start, region.start),
+    };
+    let message = if diagnostic.label.is_empty() {
+        diagnostic.message.clone()
+    } else {
+        format!("{}\n{}", diagnostic.message, diagnostic.label)
+    };
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(format!("{:?}", diagnostic.kind))),
+        source: Some("pax".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Finds the region of `document` that a diagnostic was reported against, by its `source_path` — plus,
+/// for files with several `#[inlined]` templates, by the source line it captured
+fn region_for_diagnostic<'a>(document: &'a Document, diagnostic: &Diagnostic) -> Option<&'a TemplateRegion> {
+    let source_path = diagnostic.source_path.as_ref()?;
+    let source_path = source_path.strip_suffix(INLINED_SOURCE_SUFFIX).unwrap_or(source_path);
+    if !document.path.ends_with(source_path) {
+        return None;
+    }
+
+    let start_line = diagnostic.span.as_ref().map(|span| span.start_line).unwrap_or(1);
+    document.regions.iter()
+        .find(|region| region.text.lines().nth(start_line - 1) == diagnostic.source_lines.first().map(|line| line.as_str()))
+        .or(document.regions.first())
+}
+
+/// The component whose template is `region`
+fn component_for_region<'a>(manifest: &'a PaxManifest, document: &Document, region: &TemplateRegion) -> Option<&'a ComponentDefinition> {
+    let candidates : Vec<&ComponentDefinition> = manifest.components.values().filter(|component| {
+        match &component.pax_source_path {
+            Some(source_path) => document.path.ends_with(source_path.strip_suffix(INLINED_SOURCE_SUFFIX).unwrap_or(source_path)),
+            None => false,
+        }
+    }).collect();
+
+    candidates.iter()
+        .find(|component| component.pax_source.as_deref() == Some(region.text.as_str()))
+        .or(candidates.first())
+        .copied()
+}
+
+/// Resolves a tag name to a component, preferring the type that `component`'s template already resolved it to
+fn resolve_tag<'a>(manifest: &'a PaxManifest, component: Option<&ComponentDefinition>, pascal_identifier: &str) -> Option<&'a ComponentDefinition> {
+    let template_type_id = component
+        .and_then(|component| component.template.as_ref())
+        .and_then(|template| template.iter().find(|tnd| tnd.pascal_identifier == pascal_identifier))
+        .map(|tnd| tnd.type_id.clone());
+
+    match template_type_id.and_then(|type_id| manifest.components.get(&type_id)) {
+        Some(resolved) => Some(resolved),
+        None => manifest.components.values().find(|candidate| candidate.pascal_identifier == pascal_identifier && !candidate.is_struct_only_component),
+    }
+}
+
+fn get_property_definitions<'a>(manifest: &'a PaxManifest, type_id: &str) -> &'a [PropertyDefinition] {
+    manifest.type_table.get(type_id).map(|type_definition| type_definition.property_definitions.as_slice()).unwrap_or(&[])
+}
+
+/// Resolves a PAXEL symbol like `self.foo.bar` (or `foo.bar`) against `component`, returning the type_id of the
+/// type that declares its last segment, and that segment's PropertyDefinition
+fn resolve_symbol<'a>(manifest: &'a PaxManifest, component: &ComponentDefinition, segments: &[&str]) -> Option<(String, &'a PropertyDefinition)> {
+    let segments = match segments.first() {
+        Some(&"self") => &segments[1..],
+        _ => segments,
+    };
+
+    let (last, init) = segments.split_last()?;
+    let mut owner_type_id = component.type_id.clone();
+    for segment in init {
+        owner_type_id = get_property_definitions(manifest, &owner_type_id).iter().find(|pd| pd.name == *segment)?.type_id.clone();
+    }
+    let property_definition = get_property_definitions(manifest, &owner_type_id).iter().find(|pd| pd.name == *last)?;
+    Some((owner_type_id, property_definition))
+}
+
+/// Where within a template a position falls, as far as completion, hover and go-to-definition are concerned
+struct CursorContext<'a> {
+    /// The identifier at (or, for completion, immediately preceding) the cursor, e.g. `ba` in `self.foo.ba|`
+    word: &'a str,
+    /// Byte range of `word` within the region
+    word_range: (usize, usize),
+    /// Identifiers before `word` in a dotted symbol, e.g. `["self", "foo"]` for `self.foo.ba`
+    qualifiers: Vec<&'a str>,
+    /// Whether `word` names a tag, e.g. `<Rect|` or `</Rect|`
+    is_tag_name: bool,
+    /// Name of the tag whose attributes enclose the cursor, if any
+    enclosing_tag: Option<String>,
+    /// Whether the cursor is inside `{}` within an open tag, e.g. an attribute's expression
+    is_in_tag_expression: bool,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns the start of the identifier ending at byte offset `end` of `text`
+fn identifier_start(text: &str, end: usize) -> usize {
+    text[..end].char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(end)
+}
+
+impl<'a> CursorContext<'a> {
+    /// For hover and go-to-definition, `should_extend_word` includes the rest of the identifier after `offset`
+    fn new(text: &'a str, offset: usize, should_extend_word: bool) -> Self {
+        let word_start = identifier_start(text, offset);
+        let word_end = if should_extend_word {
+            text[offset..].find(|c| !is_identifier_char(c)).map(|i| offset + i).unwrap_or(text.len())
+        } else {
+            offset
+        };
+
+        let mut qualifiers = vec![];
+        let mut cursor = word_start;
+        while cursor > 0 && text[..cursor].ends_with('.') {
+            let qualifier_end = cursor - 1;
+            let qualifier_start = identifier_start(text, qualifier_end);
+            //e.g. the `..` of a range, rather than a dotted symbol
+            if qualifier_start == qualifier_end {
+                break;
+            }
+            qualifiers.insert(0, &text[qualifier_start..qualifier_end]);
+            cursor = qualifier_start;
+        }
+
+        let before_word = &text[..cursor];
+        let is_tag_name = qualifiers.is_empty() && (before_word.ends_with('<') || before_word.ends_with("</"));
+        let (enclosing_tag, is_in_tag_expression) = scan_enclosing_tag(&text[..cursor]);
+
+        Self {
+            word: &text[word_start..word_end],
+            word_range: (word_start, word_end),
+            qualifiers,
+            is_tag_name,
+            enclosing_tag,
+            is_in_tag_expression,
+        }
+    }
+
+    /// Whether `word` is an attribute's key, e.g. `<Rectangle fi|`
+    fn is_attribute_key(&self, text: &str) -> bool {
+        self.enclosing_tag.is_some() && !self.is_in_tag_expression && !self.is_tag_name && self.qualifiers.is_empty()
+            && text[..self.word_range.0].ends_with(char::is_whitespace)
+    }
+}
+
+/// Scans `text` (a template up to some position), returning the name of the open tag that encloses the end of `text`,
+/// if any, and whether the end of `text` falls within `{}` inside that tag
+fn scan_enclosing_tag(text: &str) -> (Option<String>, bool) {
+    let mut enclosing_tag: Option<String> = None;
+    let mut brace_depth = 0;
+    let mut tag_brace_depth = 0;
+    let mut string_delimiter: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if let Some(delimiter) = string_delimiter {
+            if c == '\\' {
+                chars.next();
+            } else if c == delimiter {
+                string_delimiter = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '`' => string_delimiter = Some(c),
+            '/' if text[i..].starts_with("//") => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' { break; }
+                }
+            },
+            '/' if text[i..].starts_with("/*") => {
+                let length = text[i..].find("*/").map(|end| end + 2).unwrap_or(text.len() - i);
+                while chars.peek().map(|(j, _)| *j < i + length).unwrap_or(false) {
+                    chars.next();
+                }
+            },
+            '{' => brace_depth += 1,
+            '}' => brace_depth -= 1,
+            '<' if enclosing_tag.is_none() && text[i + 1..].starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                let name : String = text[i + 1..].chars().take_while(|c| is_identifier_char(*c)).collect();
+                enclosing_tag = Some(name);
+                tag_brace_depth = brace_depth;
+            },
+            '>' if enclosing_tag.is_some() && brace_depth == tag_brace_depth => enclosing_tag = None,
+            _ => {},
+        }
+    }
+    let is_in_tag_expression = enclosing_tag.is_some() && brace_depth > tag_brace_depth;
+    (enclosing_tag, is_in_tag_expression)
+}
+
+fn property_completion_item(property_definition: &PropertyDefinition) -> CompletionItem {
+    CompletionItem {
+        label: property_definition.name.clone(),
+        kind: Some(CompletionItemKind::FIELD),
+        detail: Some(property_definition.type_id.clone()),
+        ..Default::default()
+    }
+}
+
+pub fn complete(manifest: &PaxManifest, document: &Document, position: Position) -> Vec<CompletionItem> {
+    let (region, offset) = match document.region_at(position) {
+        Some(region_at) => region_at,
+        None => return vec![],
+    };
+    let context = CursorContext::new(&region.text, offset, false);
+    let component = component_for_region(manifest, document, region);
+
+    if context.is_tag_name {
+        let mut items : Vec<CompletionItem> = manifest.components.values()
+            .filter(|candidate| !candidate.is_struct_only_component)
+            .map(|candidate| CompletionItem {
+                label: candidate.pascal_identifier.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(candidate.type_id.clone()),
+                ..Default::default()
+            })
+            .collect();
+        items.sort_by(|a, b| a.label.cmp(&b.label));
+        items.dedup_by(|a, b| a.label == b.label);
+        return items;
+    }
+
+    if context.is_attribute_key(&region.text) {
+        let tag_component = resolve_tag(manifest, component, context.enclosing_tag.as_ref().unwrap());
+        let mut items : Vec<CompletionItem> = tag_component
+            .map(|tag_component| get_property_definitions(manifest, &tag_component.type_id).iter().map(property_completion_item).collect())
+            .unwrap_or_default();
+        items.extend(BUILTIN_SETTINGS.iter().map(|(name, type_id)| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            detail: Some(type_id.to_string()),
+            ..Default::default()
+        }));
+        return items;
+    }
+
+    let component = match component {
+        Some(component) => component,
+        None => return vec![],
+    };
+    if !context.qualifiers.is_empty() {
+        let owner_type_id = if context.qualifiers == ["self"] {
+            Some(component.type_id.clone())
+        } else {
+            resolve_symbol(manifest, component, &context.qualifiers).map(|(_, property_definition)| property_definition.type_id.clone())
+        };
+        return owner_type_id
+            .map(|type_id| get_property_definitions(manifest, &type_id).iter().map(property_completion_item).collect())
+            .unwrap_or_default();
+    }
+    if context.is_in_tag_expression {
+        return get_property_definitions(manifest, &component.type_id).iter().map(property_completion_item).collect();
+    }
+    vec![]
+}
+
+pub fn hover(manifest: &PaxManifest, document: &Document, position: Position) -> Option<Hover> {
+    let (region, offset) = document.region_at(position)?;
+    let context = CursorContext::new(&region.text, offset, true);
+    if context.word.is_empty() {
+        return None;
+    }
+    let component = component_for_region(manifest, document, region);
+
+    let value = if context.is_tag_name {
+        let tag_component = resolve_tag(manifest, component, context.word)?;
+        let kind = if tag_component.is_primitive { "primitive" } else { "component" };
+        format!("```rust\nstruct {}\n```\nPax {}", tag_component.type_id, kind)
+    } else if context.is_attribute_key(&region.text) {
+        let tag_component = resolve_tag(manifest, component, context.enclosing_tag.as_ref().unwrap());
+        let property_type_id = tag_component
+            .and_then(|tag_component| get_property_definitions(manifest, &tag_component.type_id).iter().find(|pd| pd.name == context.word))
+            .map(|property_definition| property_definition.type_id.as_str())
+            .or(BUILTIN_SETTINGS.iter().find(|(name, _)| *name == context.word).map(|(_, type_id)| *type_id))?;
+        format!("```rust\n{}: {}\n```", context.word, property_type_id)
+    } else {
+        let mut segments = context.qualifiers.clone();
+        segments.push(context.word);
+        let (_, property_definition) = resolve_symbol(manifest, component?, &segments)?;
+        format!("```rust\n{}: {}\n```", segments.join("."), property_definition.type_id)
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(region.range_of_offsets(context.word_range.0, context.word_range.1)),
+    })
+}
+
+/// Jumps from a tag to its component's Rust struct, or from an attribute or symbol to the field declaring it
+pub fn goto_definition(manifest: &PaxManifest, document: &Document, position: Position, project_root: &Path) -> Option<Location> {
+    let (region, offset) = document.region_at(position)?;
+    let context = CursorContext::new(&region.text, offset, true);
+    if context.word.is_empty() {
+        return None;
+    }
+    let component = component_for_region(manifest, document, region);
+
+    let (owner, field_name) = if context.is_tag_name {
+        (resolve_tag(manifest, component, context.word)?, None)
+    } else if context.is_attribute_key(&region.text) {
+        (resolve_tag(manifest, component, context.enclosing_tag.as_ref().unwrap())?, Some(context.word))
+    } else {
+        let mut segments = context.qualifiers.clone();
+        segments.push(context.word);
+        let (owner_type_id, _) = resolve_symbol(manifest, component?, &segments)?;
+        (manifest.components.get(&owner_type_id)?, Some(context.word))
+    };
+
+    let rust_source_path = resolve_rust_source_path(project_root, owner.rust_source_path.as_ref()?);
+    let rust_source = fs::read_to_string(&rust_source_path).ok()?;
+    let (line, column, length) = find_declaration(&rust_source, &owner.pascal_identifier, field_name)?;
+    Some(Location {
+        uri: Url::from_file_path(&rust_source_path).ok()?,
+        range: Range::new(Position::new(line, column), Position::new(line, column + length)),
+    })
+}
+
+/// `file!()` paths are relative to the root of the crate being built, except for dependencies outside of it
+fn resolve_rust_source_path(project_root: &Path, rust_source_path: &str) -> PathBuf {
+    let path = Path::new(rust_source_path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        project_root.join(path)
+    }
+}
+
+/// Returns the 0-based line, column and length of the name of `struct pascal_identifier` in `rust_source` —
+/// or, given `field_name`, of that field within the struct
+fn find_declaration(rust_source: &str, pascal_identifier: &str, field_name: Option<&str>) -> Option<(u32, u32, u32)> {
+    let struct_declaration = format!("struct {}", pascal_identifier);
+    let mut lines = rust_source.lines().enumerate().skip_while(|(_, line)| {
+        match line.find(&struct_declaration) {
+            Some(i) => line[i + struct_declaration.len()..].starts_with(is_identifier_char),
+            None => true,
+        }
+    });
+
+    let (struct_line, line) = lines.next()?;
+    let (name, line_number, text) = match field_name {
+        None => (pascal_identifier, struct_line, line),
+        Some(field_name) => {
+            let (field_line, text) = lines.take_while(|(_, line)| !line.starts_with('}')).find(|(_, line)| {
+                let declaration = line.trim_start().trim_start_matches("pub ").trim_start();
+                declaration.starts_with(field_name) && declaration[field_name.len()..].trim_start().starts_with(':')
+            })?;
+            (field_name, field_line, text)
+        }
+    };
+    let column = if field_name.is_some() {
+        text.find(&format!("{}:", name)).or(text.find(name))?
+    } else {
+        text.find(&struct_declaration)? + "struct ".len()
+    };
+    Some((line_number as u32, text[..column].chars().count() as u32, name.chars().count() as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{find_declaration, scan_enclosing_tag, CursorContext};
+
+    #[test]
+    fn test_scan_enclosing_tag() {
+        let cases : [(&str, Option<&str>, bool); 14] = [
+            ("", None, false),
+            ("<Rectangle ", Some("Rectangle"), false),
+            ("<Rectangle />", None, false),
+            ("<Group>\n    <Text ", Some("Text"), false),
+            ("</Group", None, false),
+            ("<Rectangle fill={", Some("Rectangle"), true),
+            ("<Rectangle fill={Color::rgb(1, 0, 0)} ", Some("Rectangle"), false),
+            //nested braces, e.g. object literals within expressions
+            ("<Text style={ {size: 10", Some("Text"), true),
+            ("<Text style={ {size: 10} } ", Some("Text"), false),
+            ("for i in 0..5 {\n    <Text ", Some("Text"), false),
+            //delimiters within strings and comments are ignored
+            ("<Text text=\"a > b {\" ", Some("Text"), false),
+            ("<Text text=\"say \\\"<Group>\\\"\" ", Some("Text"), false),
+            ("// <Rectangle \n<Text ", Some("Text"), false),
+            ("/* <Rectangle */ <Group> /* > */ ", None, false),
+        ];
+        for (text, enclosing_tag, is_in_tag_expression) in cases {
+            assert_eq!(scan_enclosing_tag(text), (enclosing_tag.map(str::to_string), is_in_tag_expression), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_cursor_context() {
+        // `|` marks the cursor; each case is (template, should_extend_word, word, qualifiers, is_tag_name, enclosing_tag,
+        // is_in_tag_expression, is_attribute_key)
+        let cases = [
+            ("<Rect|", false, "Rect", vec![], true, None, false, false),
+            ("<Group>\n</Gro|up>", true, "Group", vec![], true, None, false, false),
+            ("<Rectangle fi|", false, "fi", vec![], false, Some("Rectangle"), false, true),
+            ("<Rectangle fi|ll={} />", true, "fill", vec![], false, Some("Rectangle"), false, true),
+            ("<Rectangle fill=|", false, "", vec![], false, Some("Rectangle"), false, false),
+            ("<Text text={self.foo.ba|", false, "ba", vec!["self", "foo"], false, Some("Text"), true, false),
+            ("<Text text={self.foo.ba|r} />", true, "bar", vec!["self", "foo"], false, Some("Text"), true, false),
+            ("<Text text={num_|", false, "num_", vec![], false, Some("Text"), true, false),
+            ("<Group />\nfor i in 0..self.cou|", false, "cou", vec!["self"], false, None, false, false),
+        ];
+        for (marked, should_extend_word, word, qualifiers, is_tag_name, enclosing_tag, is_in_tag_expression, is_attribute_key) in cases {
+            let offset = marked.find('|').unwrap();
+            let text = marked.replacen('|', "", 1);
+            let context = CursorContext::new(&text, offset, should_extend_word);
+            assert_eq!(context.word, word, "{:?}", marked);
+            assert_eq!(&text[context.word_range.0..context.word_range.1], word, "{:?}", marked);
+            assert_eq!(context.qualifiers, qualifiers, "{:?}", marked);
+            assert_eq!(context.is_tag_name, is_tag_name, "{:?}", marked);
+            assert_eq!(context.enclosing_tag.as_deref(), enclosing_tag, "{:?}", marked);
+            assert_eq!(context.is_in_tag_expression, is_in_tag_expression, "{:?}", marked);
+            assert_eq!(context.is_attribute_key(&text), is_attribute_key, "{:?}", marked);
+        }
+    }
+
+    #[test]
+    fn test_find_declaration() {
+        let rust_source = "use pax_lang::*;\n\n#[derive(Pax)]\npub struct ExampleCard {\n    pub title: Property<String>,\n}\n\n#[derive(Pax)]\n#[file(\"example.pax\")]\npub struct Example {\n    pub count_max: Property<usize>,\n    pub count: Property<usize>,\n    label : Property<String>,\n}\n";
+        let cases = [
+            ("ExampleCard", None, Some((3, 11, 11))),
+            //not `ExampleCard`, despite the shared prefix
+            ("Example", None, Some((9, 11, 7))),
+            ("Example", Some("count"), Some((11, 8, 5))),
+            ("Example", Some("label"), Some((12, 4, 5))),
+            //fields are only found within their own struct
+            ("Example", Some("title"), None),
+            ("ExampleCard", Some("count"), None),
+            ("Missing", None, None),
+        ];
+        for (pascal_identifier, field_name, expected) in cases {
+            assert_eq!(find_declaration(rust_source, pascal_identifier, field_name), expected, "{} {:?}", pascal_identifier, field_name);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use lsp_types::{Position, Range};
+use pax_compiler::diagnostics::SourceSpan;
+use pax_compiler::formatting::find_inlined_pax;
+
+/// A stretch of Pax within a document: either the whole of a .pax file, or the contents of an `#[inlined(...)]` attribute
+pub struct TemplateRegion {
+    pub text: String,
+    /// Byte offset of `text` within its document
+    pub offset: usize,
+    /// Position of the start of `text` within its document
+    pub start: Position,
+}
+
+impl TemplateRegion {
+    /// Converts a span within this region (1-based lines and columns, following pest) to a range within the document
+    pub fn range_of(&self, span: &SourceSpan) -> Range {
+        Range::new(
+            self.position_of(span.start_line, span.start_column),
+            self.position_of(span.end_line, span.end_column),
+        )
+    }
+
+    /// Converts a byte range within this region to a range within the document
+    pub fn range_of_offsets(&self, start: usize, end: usize) -> Range {
+        let (start_line, start_column) = line_column_at(&self.text, start);
+        let (end_line, end_column) = line_column_at(&self.text, end);
+        Range::new(self.position_of(start_line, start_column), self.position_of(end_line, end_column))
+    }
+
+    fn position_of(&self, line: usize, column: usize) -> Position {
+        let character = column.saturating_sub(1) as u32;
+        if line <= 1 {
+            Position::new(self.start.line, self.start.character + character)
+        } else {
+            Position::new(self.start.line + line as u32 - 1, character)
+        }
+    }
+}
+
+/// An open .pax or .rs file, as last sent by the editor
+pub struct Document {
+    pub path: PathBuf,
+    pub text: String,
+    pub regions: Vec<TemplateRegion>,
+}
+
+impl Document {
+    pub fn new(path: PathBuf, text: String) -> Self {
+        let regions = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pax") => vec![TemplateRegion {
+                text: text.clone(),
+                offset: 0,
+                start: Position::new(0, 0),
+            }],
+            Some("rs") => find_inlined_pax(&text).into_iter().map(|range| {
+                let (line, column) = line_column_at(&text, range.start);
+                TemplateRegion {
+                    text: text[range.clone()].to_string(),
+                    offset: range.start,
+                    start: Position::new(line as u32 - 1, column as u32 - 1),
+                }
+            }).collect(),
+            _ => vec![],
+        };
+
+        Self {
+            path,
+            text,
+            regions,
+        }
+    }
+
+    /// Returns the region containing `position`, along with the byte offset of `position` within that region
+    pub fn region_at(&self, position: Position) -> Option<(&TemplateRegion, usize)> {
+        let offset = offset_at(&self.text, position)?;
+        self.regions.iter()
+            .find(|region| region.offset <= offset && offset <= region.offset + region.text.len())
+            .map(|region| (region, offset - region.offset))
+    }
+}
+
+/// Returns the 1-based line and column of the byte `offset` within `text`.
+/// Columns count chars rather than the UTF-16 code units of LSP, which agree for the ASCII typical of templates.
+fn line_column_at(text: &str, offset: usize) -> (usize, usize) {
+    let consumed = &text[..offset];
+    let line = consumed.matches('\n').count() + 1;
+    let column = consumed.rsplit('\n').next().unwrap().chars().count() + 1;
+    (line, column)
+}
+
+fn offset_at(text: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += text[line_start..].find('\n')? + 1;
+    }
+    let line = text[line_start..].split('\n').next().unwrap();
+    let column_offset = line.char_indices()
+        .nth(position.character as usize)
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    Some(line_start + column_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lsp_types::{Position, Range};
+    use pax_compiler::diagnostics::SourceSpan;
+
+    use crate::documents::{line_column_at, offset_at, Document};
+
+    #[test]
+    fn test_line_column_at_and_offset_at() {
+        // `é` is two bytes in UTF-8, but a single column
+        let text = "ab\ncdé\n\nf";
+        let cases : [(usize, (usize, usize), Position); 7] = [
+            (0, (1, 1), Position::new(0, 0)),
+            (2, (1, 3), Position::new(0, 2)),
+            (3, (2, 1), Position::new(1, 0)),
+            (5, (2, 3), Position::new(1, 2)),
+            (7, (2, 4), Position::new(1, 3)),
+            (8, (3, 1), Position::new(2, 0)),
+            (9, (4, 1), Position::new(3, 0)),
+        ];
+        for (offset, line_column, position) in cases {
+            assert_eq!(line_column_at(text, offset), line_column, "offset {}", offset);
+            assert_eq!(offset_at(text, position), Some(offset), "{:?}", position);
+        }
+
+        //columns past the end of a line clamp to it; lines past the end of the text don't exist
+        assert_eq!(offset_at(text, Position::new(0, 10)), Some(2));
+        assert_eq!(offset_at(text, Position::new(3, 5)), Some(10));
+        assert_eq!(offset_at(text, Position::new(4, 0)), None);
+    }
+
+    #[test]
+    fn test_inlined_region_positions() {
+        let text = "#[derive(Pax)]\n#[inlined(<Group>\n    <Rectangle />\n</Group>)]\npub struct Example {}\n".to_string();
+        let document = Document::new(PathBuf::from("src/lib.rs"), text);
+        assert_eq!(document.regions.len(), 1);
+        let region = &document.regions[0];
+        assert_eq!(region.text, "<Group>\n    <Rectangle />\n</Group>");
+        assert_eq!(region.start, Position::new(1, 10));
+
+        //positions on the region's first line are offset by the attribute preceding it; later lines aren't
+        let cases : [(usize, usize, Position); 4] = [
+            (1, 1, Position::new(1, 10)),
+            (1, 8, Position::new(1, 17)),
+            (2, 5, Position::new(2, 4)),
+            (3, 9, Position::new(3, 8)),
+        ];
+        for (line, column, position) in cases {
+            assert_eq!(region.position_of(line, column), position, "{}:{}", line, column);
+        }
+
+        let span = SourceSpan { start_line: 2, start_column: 5, end_line: 2, end_column: 18 };
+        assert_eq!(region.range_of(&span), Range::new(Position::new(2, 4), Position::new(2, 17)));
+        assert_eq!(region.range_of_offsets(1, 6), Range::new(Position::new(1, 11), Position::new(1, 16)));
+
+        let (region_at, offset) = document.region_at(Position::new(2, 5)).unwrap();
+        assert_eq!(&region_at.text[offset..offset + 9], "Rectangle");
+        assert!(document.region_at(Position::new(4, 0)).is_none());
+    }
+}
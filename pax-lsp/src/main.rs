@@ -0,0 +1,246 @@
+mod analysis;
+mod documents;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::thread;
+
+use crossbeam_channel::{Receiver, Sender};
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Initialized, Notification as _, PublishDiagnostics};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{CompletionOptions, CompletionResponse, GotoDefinitionResponse, HoverProviderCapability, InitializeParams, OneOf, PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions, Url};
+use pax_compiler::diagnostics::{BuildError, Diagnostic};
+use pax_compiler::manifest::PaxManifest;
+
+use crate::documents::Document;
+
+/// Language server for Pax, offering diagnostics, completion, hover and go-to-definition for the templates in
+/// .pax files and `#[inlined(...)]` attributes.  Speaks LSP over stdio; the workspace root must be a Pax project.
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::FULL),
+            save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+            ..Default::default()
+        })),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["<".to_string(), ".".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    })?;
+    let initialize_params : InitializeParams = serde_json::from_value(connection.initialize(capabilities)?)?;
+
+    #[allow(deprecated)]
+    let project_root = match initialize_params.root_uri.and_then(|uri| uri.to_file_path().ok()) {
+        Some(project_root) => project_root,
+        None => std::env::current_dir()?,
+    };
+
+    let mut server = Server::new(&connection, project_root);
+    server.run()?;
+
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Results of running the parser and expression compiler over the project
+struct ProjectAnalysis {
+    /// `None` if the parser failed, e.g. because of errors in Pax or in the host crate
+    manifest: Option<PaxManifest>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    project_root: PathBuf,
+    documents: HashMap<Url, Document>,
+    /// Manifest from the last successful parse, retained while errors are being fixed
+    manifest: Option<PaxManifest>,
+    project_diagnostics: Vec<Diagnostic>,
+    analysis_sender: Sender<ProjectAnalysis>,
+    analysis_receiver: Receiver<ProjectAnalysis>,
+    is_analyzing: bool,
+    /// Set when a file is saved mid-analysis, so that the project is analyzed again once the current run completes
+    is_analysis_stale: bool,
+}
+
+impl<'a> Server<'a> {
+    fn new(connection: &'a Connection, project_root: PathBuf) -> Self {
+        let (analysis_sender, analysis_receiver) = crossbeam_channel::unbounded();
+        Self {
+            connection,
+            project_root,
+            documents: HashMap::new(),
+            manifest: None,
+            project_diagnostics: vec![],
+            analysis_sender,
+            analysis_receiver,
+            is_analyzing: false,
+            is_analysis_stale: false,
+        }
+    }
+
+    fn run(&mut self) -> Result<(), Box<dyn Error + Sync + Send>> {
+        loop {
+            crossbeam_channel::select! {
+                recv(self.connection.receiver) -> message => {
+                    match message? {
+                        Message::Request(request) => {
+                            if self.connection.handle_shutdown(&request)? {
+                                return Ok(());
+                            }
+                            self.handle_request(request)?;
+                        },
+                        Message::Notification(notification) => self.handle_notification(notification)?,
+                        Message::Response(_) => {},
+                    }
+                },
+                recv(self.analysis_receiver) -> analysis => {
+                    let analysis = analysis?;
+                    if analysis.manifest.is_some() {
+                        self.manifest = analysis.manifest;
+                    }
+                    self.project_diagnostics = analysis.diagnostics;
+                    self.is_analyzing = false;
+                    if self.is_analysis_stale {
+                        self.analyze_project();
+                    }
+
+                    let uris : Vec<Url> = self.documents.keys().cloned().collect();
+                    for uri in uris {
+                        self.publish_diagnostics(uri)?;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Runs the parser and expression compiler in the background, as `pax build` would, reporting to `analysis_receiver`
+    fn analyze_project(&mut self) {
+        if self.is_analyzing {
+            self.is_analysis_stale = true;
+            return;
+        }
+        self.is_analyzing = true;
+        self.is_analysis_stale = false;
+
+        let path = self.project_root.to_str().unwrap().to_string();
+        let sender = self.analysis_sender.clone();
+        thread::spawn(move || {
+            let analysis = panic::catch_unwind(AssertUnwindSafe(|| {
+                //cargo's output is discarded; stdout is reserved for LSP, and Rust errors are the business of rust-analyzer
+                match pax_compiler::run_parser(&path, &mut std::io::sink()) {
+                    Ok(mut manifest) => {
+                        //compiling expressions leaves the components and type table used for analysis intact, even on error
+                        let diagnostics = pax_compiler::expressions::compile_all_expressions(&mut manifest).err().unwrap_or_default();
+                        ProjectAnalysis { manifest: Some(manifest), diagnostics }
+                    },
+                    Err(BuildError::Diagnostics(diagnostics)) => ProjectAnalysis { manifest: None, diagnostics },
+                    Err(BuildError::Cargo) => ProjectAnalysis { manifest: None, diagnostics: vec![] },
+                }
+            })).unwrap_or(ProjectAnalysis { manifest: None, diagnostics: vec![] });
+            sender.send(analysis).unwrap();
+        });
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), Box<dyn Error + Sync + Send>> {
+        match request.method.as_str() {
+            Completion::METHOD => {
+                let (id, params) = extract_request::<Completion>(request)?;
+                let position = params.text_document_position;
+                let items = match (&self.manifest, self.documents.get(&position.text_document.uri)) {
+                    (Some(manifest), Some(document)) => analysis::complete(manifest, document, position.position),
+                    _ => vec![],
+                };
+                self.respond(id, Some(CompletionResponse::Array(items)))
+            },
+            HoverRequest::METHOD => {
+                let (id, params) = extract_request::<HoverRequest>(request)?;
+                let position = params.text_document_position_params;
+                let hover = match (&self.manifest, self.documents.get(&position.text_document.uri)) {
+                    (Some(manifest), Some(document)) => analysis::hover(manifest, document, position.position),
+                    _ => None,
+                };
+                self.respond(id, hover)
+            },
+            GotoDefinition::METHOD => {
+                let (id, params) = extract_request::<GotoDefinition>(request)?;
+                let position = params.text_document_position_params;
+                let location = match (&self.manifest, self.documents.get(&position.text_document.uri)) {
+                    (Some(manifest), Some(document)) => analysis::goto_definition(manifest, document, position.position, &self.project_root),
+                    _ => None,
+                };
+                self.respond(id, location.map(GotoDefinitionResponse::Scalar))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Box<dyn Error + Sync + Send>> {
+        match notification.method.as_str() {
+            Initialized::METHOD => self.analyze_project(),
+            DidOpenTextDocument::METHOD => {
+                let params = extract_notification::<DidOpenTextDocument>(notification)?;
+                self.update_document(params.text_document.uri.clone(), params.text_document.text);
+                self.publish_diagnostics(params.text_document.uri)?;
+            },
+            DidChangeTextDocument::METHOD => {
+                let params = extract_notification::<DidChangeTextDocument>(notification)?;
+                //full sync: the last change holds the entire text
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.update_document(params.text_document.uri.clone(), change.text);
+                    self.publish_diagnostics(params.text_document.uri)?;
+                }
+            },
+            DidSaveTextDocument::METHOD => self.analyze_project(),
+            DidCloseTextDocument::METHOD => {
+                let params = extract_notification::<DidCloseTextDocument>(notification)?;
+                self.documents.remove(&params.text_document.uri);
+                self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams::new(params.text_document.uri, vec![], None))?;
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn update_document(&mut self, uri: Url, text: String) {
+        if let Ok(path) = uri.to_file_path() {
+            self.documents.insert(uri, Document::new(path, text));
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: Url) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let diagnostics = match self.documents.get(&uri) {
+            Some(document) => analysis::collect_diagnostics(document, &self.project_diagnostics),
+            None => return Ok(()),
+        };
+        self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn respond<T: serde::Serialize>(&self, id: RequestId, result: T) -> Result<(), Box<dyn Error + Sync + Send>> {
+        self.connection.sender.send(Message::Response(Response::new_ok(id, result)))?;
+        Ok(())
+    }
+
+    fn send_notification<N: lsp_types::notification::Notification>(&self, params: N::Params) -> Result<(), Box<dyn Error + Sync + Send>> {
+        self.connection.sender.send(Message::Notification(Notification::new(N::METHOD.to_string(), params)))?;
+        Ok(())
+    }
+}
+
+fn extract_request<R: lsp_types::request::Request>(request: Request) -> Result<(RequestId, R::Params), Box<dyn Error + Sync + Send>> {
+    Ok(request.extract(R::METHOD)?)
+}
+
+fn extract_notification<N: lsp_types::notification::Notification>(notification: Notification) -> Result<N::Params, Box<dyn Error + Sync + Send>> {
+    Ok(notification.extract(N::METHOD)?)
+}
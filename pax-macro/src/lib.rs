@@ -289,7 +289,7 @@ fn get_static_property_definitions_from_tokens(data: Data) -> Vec<StaticProperty
     ret
 }
 
-fn pax_full_component(raw_pax: String, input_parsed: DeriveInput, is_main_component: bool, include_fix : Option<TokenStream>, include_imports: bool, is_custom_interpolatable: bool) -> proc_macro2::TokenStream {
+fn pax_full_component(raw_pax: String, source_path: Option<String>, input_parsed: DeriveInput, is_main_component: bool, include_fix : Option<TokenStream>, include_imports: bool, is_custom_interpolatable: bool) -> proc_macro2::TokenStream {
 
     let pascal_identifier = input_parsed.ident.to_string();
 
@@ -313,6 +313,7 @@ fn pax_full_component(raw_pax: String, input_parsed: DeriveInput, is_main_compon
         args_full_component: Some(ArgsFullComponent {
             is_main_component,
             raw_pax,
+            source_path,
             template_dependencies,
             reexports_snippet,
         }),
@@ -631,13 +632,14 @@ pub fn pax_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         let mut file = File::open(path);
         let mut content = String::new();
         let _ = file.unwrap().read_to_string(&mut content);
-        let stream: proc_macro::TokenStream = content.parse().unwrap();
-        pax_full_component(stream.to_string(), input.clone(), is_main_component, Some(include_fix),include_imports, is_custom_interpolatable)
+        //Pass the file's contents verbatim, so that line & column numbers in diagnostics match the file on disk
+        let source_path = Path::new("src").join(Path::new(&filename)).to_str().unwrap().to_string();
+        pax_full_component(content, Some(source_path), input.clone(), is_main_component, Some(include_fix),include_imports, is_custom_interpolatable)
 
     } else if is_pax_inlined {
         let contents = if let Some(p) = inlined_contents {p} else {unreachable!()};
 
-        pax_full_component(contents, input.clone(), is_main_component, None,include_imports, is_custom_interpolatable)
+        pax_full_component(contents, None, input.clone(), is_main_component, None,include_imports, is_custom_interpolatable)
     } else if is_primitive {
         pax_primitive(input.clone(), primitive_instance_import_path.unwrap(), include_imports, is_custom_interpolatable)
     } else {
@@ -76,8 +76,22 @@ fn recurse_visit_tag_pairs_for_pascal_identifiers(any_tag_pair: Pair<Rule>, pasc
             let matched_tag = any_tag_pair.into_inner().next().unwrap();
 
             let n = match matched_tag.as_rule() {
+                Rule::statement_match => {
+                    //each arm is a pattern followed by its inner nodes
+                    matched_tag.into_inner()
+                        .filter(|pair| pair.as_rule() == Rule::statement_match_arm)
+                        .for_each(|arm| {
+                            let inner_nodes = arm.into_inner().nth(1).expect("WRONG nth");
+                            inner_nodes.into_inner()
+                                .for_each(|sub_tag_pair|{
+                                    recurse_visit_tag_pairs_for_pascal_identifiers(sub_tag_pair, Rc::clone(&pascal_identifiers));
+                                })
+                        });
+                    return;
+                },
                 Rule::statement_if => {
-                    1
+                    recurse_visit_statement_if_for_pascal_identifiers(matched_tag, pascal_identifiers);
+                    return;
                 },
                 Rule::statement_for => {
                     2
@@ -90,7 +104,10 @@ fn recurse_visit_tag_pairs_for_pascal_identifiers(any_tag_pair: Pair<Rule>, pasc
                 }
             };
 
-            let prospective_inner_nodes = matched_tag.into_inner().nth(n).expect("WRONG nth");
+            //skip the optional `key` of a `for`, which would otherwise take the place of its inner nodes
+            let prospective_inner_nodes = matched_tag.into_inner()
+                .filter(|pair| pair.as_rule() != Rule::statement_for_key)
+                .nth(n).expect("WRONG nth");
             match prospective_inner_nodes.as_rule() {
                 Rule::inner_nodes => {
                     let inner_nodes = prospective_inner_nodes;
@@ -109,4 +126,69 @@ fn recurse_visit_tag_pairs_for_pascal_identifiers(any_tag_pair: Pair<Rule>, pasc
         },
         _ => {unreachable!("Parsing error 123123121: {:?}", any_tag_pair.as_rule());}
     }
-}
\ No newline at end of file
+}
+
+/// Visits both branches of an `if`, including each `else if` chained after it
+fn recurse_visit_statement_if_for_pascal_identifiers(statement_if: Pair<Rule>, pascal_identifiers: Rc<RefCell<HashSet<String>>>) {
+    //statement_if => expression_body, inner_nodes, statement_else?
+    let mut pairs = statement_if.into_inner().skip(1);
+    let inner_nodes = pairs.next().expect("WRONG nth");
+    inner_nodes.into_inner()
+        .for_each(|sub_tag_pair|{
+            recurse_visit_tag_pairs_for_pascal_identifiers(sub_tag_pair, Rc::clone(&pascal_identifiers));
+        });
+
+    if let Some(statement_else) = pairs.next() {
+        //statement_else => statement_if | inner_nodes
+        let else_branch = statement_else.into_inner().next().unwrap();
+        match else_branch.as_rule() {
+            Rule::statement_if => {
+                recurse_visit_statement_if_for_pascal_identifiers(else_branch, pascal_identifiers);
+            },
+            Rule::inner_nodes => {
+                else_branch.into_inner()
+                    .for_each(|sub_tag_pair|{
+                        recurse_visit_tag_pairs_for_pascal_identifiers(sub_tag_pair, Rc::clone(&pascal_identifiers));
+                    })
+            },
+            _ => {unreachable!("Parsing error 5530211: {:?}", else_branch.as_rule());}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_pascal_identifiers_from_component_definition_string;
+
+    fn parse_sorted(pax: &str) -> Vec<String> {
+        let mut pascal_identifiers = parse_pascal_identifiers_from_component_definition_string(pax);
+        pascal_identifiers.sort();
+        pascal_identifiers
+    }
+
+    #[test]
+    fn test_match_arms() {
+        let pax = r#"<Group>
+            match self.mode {
+                Mode::Edit | Mode::Create => { <Editor /> },
+                Mode::View => { <Group><Viewer /></Group> },
+                _ => { <Placeholder /> }
+            }
+        </Group>"#;
+        assert_eq!(parse_sorted(pax), vec!["Editor", "Group", "Placeholder", "Viewer"]);
+    }
+
+    #[test]
+    fn test_else_branches() {
+        let pax = r#"<Group>
+            if self.is_loading {
+                <Spinner />
+            } else if self.has_error {
+                <ErrorBanner />
+            } else {
+                <Content />
+            }
+        </Group>"#;
+        assert_eq!(parse_sorted(pax), vec!["Content", "ErrorBanner", "Group", "Spinner"]);
+    }
+}
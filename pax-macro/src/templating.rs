@@ -27,6 +27,8 @@ pub struct ArgsStructOnlyComponent {}
 #[derive(Serialize)]
 pub struct ArgsFullComponent {
     pub raw_pax: String,
+    /// Path of the `.pax` file containing `raw_pax`, relative to the crate root; `None` for `#[inlined]` Pax
+    pub source_path: Option<String>,
     pub is_main_component: bool,
     pub template_dependencies: Vec<String>,
     pub reexports_snippet: String,
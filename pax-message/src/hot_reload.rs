@@ -0,0 +1,101 @@
+//! Patches for hot-reloading templates into a running engine, sent by `pax run --watch` to the chassis.
+//! Expressions bound by patched templates are either already compiled into the running cartridge
+//! (and referred to by their existing vtable ids) or carried here as PAXEL ASTs, to be interpreted.
+
+use std::collections::HashMap;
+
+/// Replacement templates for one or more components, along with the interpreted expressions they bind to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HotReloadPatch {
+    pub templates: Vec<ComponentTemplatePatch>,
+    pub expressions: Vec<ExpressionPatch>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ComponentTemplatePatch {
+    pub component_type_id: String,
+    pub root_nodes: Vec<TemplateNodePatch>,
+}
+
+/// A node of a replacement template.  Only components and primitives are supported, not control flow.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TemplateNodePatch {
+    pub type_id: String,
+    /// Vtable id of the expression bound to each property, including the built-ins `transform`, `width` and `height`.
+    /// Literal settings are sent as (constant) expressions too; unset properties take their defaults.
+    pub properties: HashMap<String, usize>,
+    /// Pairs of event id and handler method on the containing component, e.g. `("click", "increment")`
+    pub events: Vec<(String, String)>,
+    /// The node's `id` in Pax, if any; see `InstanceRegistry::register_template_id`
+    pub template_id: Option<String>,
+    /// Whether the node is given `focusable=true` in Pax; see `InstanceRegistry::register_focusable`
+    pub is_focusable: bool,
+    pub children: Vec<TemplateNodePatch>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpressionPatch {
+    pub id: usize,
+    /// The TypesCoproduct variant the expression's value is wrapped in, as in `ExpressionSpec::pascalized_return_type`
+    pub return_type: String,
+    pub expression: PaxelExpression,
+}
+
+/// The interpretable subset of PAXEL
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PaxelExpression {
+    Integer(isize),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    /// A number with a `px` or `%` unit, e.g. `10px` or `(self.width / 2)px`
+    Size(Box<PaxelExpression>, SizeUnit),
+    Symbol(SymbolBinding),
+    Negate(Box<PaxelExpression>),
+    Not(Box<PaxelExpression>),
+    Binary(Box<PaxelExpression>, BinaryOperator, Box<PaxelExpression>),
+    /// `condition ? then : else`
+    Conditional(Box<PaxelExpression>, Box<PaxelExpression>, Box<PaxelExpression>),
+    /// A call to a built-in function by path, e.g. `Transform2D::rotate`
+    FunctionCall(String, Vec<PaxelExpression>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SizeUnit {
+    Pixels,
+    Percent,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Exp,
+    And,
+    Or,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+/// A symbol resolved by the compiler to a particular stack frame, as in `ExpressionSpecInvocation`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SymbolBinding {
+    /// Statically known stack offset for traversing Repeat-based scopes at runtime
+    pub stack_offset: usize,
+    pub kind: SymbolKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum SymbolKind {
+    /// A property of the component whose properties are on the stack frame
+    Property(String),
+    /// The `i` of `for (elem, i) in ...`
+    RepeatIndex,
+}
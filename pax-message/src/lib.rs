@@ -2,6 +2,7 @@
 extern crate serde;
 
 pub mod reflection;
+pub mod hot_reload;
 
 //FUTURE: feature-flag, only for Web builds
 #[allow(unused_imports)]
@@ -48,6 +49,7 @@ pub enum NativeInterrupt {
     ContextMenu(ContextMenuInterruptArgs),
     Image(ImageLoadInterruptArgs),
     AddedLayer(AddedLayerArgs),
+    HotReload(hot_reload::HotReloadPatch),
 }
 
 #[derive(Deserialize)]
@@ -5,7 +5,7 @@ pub enum PropertiesCoproduct {
     //core
     None,
     RepeatList(Vec<Rc<RefCell<PropertiesCoproduct>>>),
-    RepeatItem(Rc<PropertiesCoproduct>, usize),
+    RepeatItem(Rc<PropertiesCoproduct>, usize, u64), //datum, index `i`, stable identity for `id_chain`s
     usize(usize),//used by Repeat + numeric ranges, e.g. `for i in 0..5`
     isize(isize),//used by Repeat + numeric ranges, e.g. `for i in 0..5`
 
@@ -13,6 +13,7 @@ pub enum PropertiesCoproduct {
 }
 
 //used namely for return types of expressions — may have other purposes
+#[derive(Clone)]
 pub enum TypesCoproduct {
     //core: primitives
     f64(f64),
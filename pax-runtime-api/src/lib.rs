@@ -1,11 +1,13 @@
 pub mod numeric;
 
+use std::any::Any;
 use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::rc::Rc;
 use std::ffi::CString;
 use std::ops::{Deref, Mul};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 
 #[macro_use]
@@ -29,10 +31,18 @@ pub trait PropertyInstance<T: Default + Clone> {
     fn get(&self) -> &T;
     fn _get_vtable_id(&self) -> Option<usize>;
 
+    /// Used by engine to detect writes: changes whenever this property's value may have changed, and is never shared
+    /// by two values of distinct properties.  See `mint_property_version`
+    fn _get_version(&self) -> u64;
+
     fn get_mut(&mut self) -> &mut T;
 
     fn set(&mut self, value: T);
 
+    /// Used by engine to write the value of an Expression, along with the version minted when it was evaluated,
+    /// so that re-writing an unchanged value doesn't register as a change
+    fn _set_computed(&mut self, value: T, version: u64);
+
     /// Used by engine to gain access to this property's transition queue
     fn _get_transition_manager(&mut self) -> Option<&mut TransitionManager<T>>;
 
@@ -64,12 +74,30 @@ impl<T: Default + Clone + 'static> Clone for Box<dyn PropertyInstance<T>> {
 
 pub type Property<T> = Box<dyn PropertyInstance<T>>;
 
+static NEXT_PROPERTY_VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Mint a version for a property value, unique for the lifetime of the program.  Properties are assigned a fresh
+/// version on creation and on each write, which lets the engine skip re-evaluating Expressions whose inputs are unchanged.
+pub fn mint_property_version() -> u64 {
+    NEXT_PROPERTY_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The version that will be minted next.  A change in this value between two points in time means that some property
+/// was created or written in between, which the engine uses to decide whether a tick needs to render.
+pub fn peek_property_version() -> u64 {
+    NEXT_PROPERTY_VERSION.load(Ordering::Relaxed)
+}
+
 #[derive(Clone)]
 pub struct RuntimeContext {
     /// The current global engine tick count
     pub frames_elapsed: usize,
     /// The bounds of this element's immediate container (parent) in px
     pub bounds_parent: (f64, f64),
+    /// The element this context belongs to, as its instance id followed by the indices of any enclosing `Repeat`s
+    pub id_chain: Vec<u64>,
+    /// Shared with the engine, which fulfills the latest request once the current handler returns; see `focus`
+    pub focus_request: Rc<RefCell<Option<FocusRequest>>>,
     // /// Viewport bounds
     // pub bounds_viewport: (f64, f64)
     // /// The number of adoptees passed to the current component (used by Stacker for auto cell-count calc; might be extended/adjusted for other use-cases)
@@ -78,8 +106,112 @@ pub struct RuntimeContext {
     //pub timeline_playhead_position: usize,
 }
 
+impl RuntimeContext {
+    /// Moves keyboard focus to this element, i.e. the one whose handler is running, dispatching `blur` to the element
+    /// that had it and `focus` to this one.  The element needn't be `focusable`, which only governs Tab traversal and clicks.
+    pub fn focus(&self) {
+        *self.focus_request.borrow_mut() = Some(FocusRequest::Element(self.id_chain.clone()));
+    }
+
+    /// Moves keyboard focus to the first rendered element with the `id` `template_id` in Pax, e.g. `name_input` for
+    /// `<Text id=name_input focusable=true />`
+    pub fn focus_template_id(&self, template_id: &str) {
+        *self.focus_request.borrow_mut() = Some(FocusRequest::TemplateId(template_id.to_string()));
+    }
+
+    /// Removes keyboard focus from whichever element has it
+    pub fn blur(&self) {
+        *self.focus_request.borrow_mut() = Some(FocusRequest::Blur);
+    }
+}
+
+/// A change of keyboard focus requested by a handler through its `RuntimeContext`
+#[derive(Clone, Debug, PartialEq)]
+pub enum FocusRequest {
+    Element(Vec<u64>),
+    TemplateId(String),
+    Blur,
+}
+
 // Unified events
 
+/// Controls how far an event propagates, shared by every handler it's dispatched to (each receives a clone of the
+/// same args.)  Events are dispatched as in the DOM: first to `.capture` handlers (e.g. `@click.capture=...`) from the
+/// root of the tree down to the target element, then to ordinary handlers from the target back up to the root.
+#[derive(Clone, Default)]
+pub struct EventControl {
+    propagation_stopped: Rc<Cell<bool>>,
+    default_prevented: Rc<Cell<bool>>,
+}
+
+impl EventControl {
+    /// Stops the event from reaching any further elements once the handlers of the current element have run, e.g.
+    /// so that a click on a button doesn't also fire the handlers of the clickable card containing it
+    pub fn stop_propagation(&self) {
+        self.propagation_stopped.set(true);
+    }
+
+    /// Asks the chassis to skip its own handling of the native event, e.g. the browser's context menu or the scrolling
+    /// of the page by a wheel event
+    pub fn prevent_default(&self) {
+        self.default_prevented.set(true);
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped.get()
+    }
+
+    pub fn is_default_prevented(&self) -> bool {
+        self.default_prevented.get()
+    }
+}
+
+/// Lets a `mouse_down` or `touch_start` handler capture the pointer, so that the pointer's subsequent moves and its
+/// release are dispatched to the capturing element (and its ancestors) wherever the pointer goes, e.g. to keep a
+/// slider's thumb following the pointer after it slides off.  Capture ends when the pointer is released.
+#[derive(Clone, Default)]
+pub struct PointerCapture {
+    captured_id_chain: Rc<RefCell<Option<Vec<u64>>>>,
+}
+
+impl PointerCapture {
+    /// Captures the pointer to the element whose handler was passed `ctx`
+    pub fn capture(&self, ctx: &RuntimeContext) {
+        *self.captured_id_chain.borrow_mut() = Some(ctx.id_chain.clone());
+    }
+
+    /// Undoes `capture`, e.g. by a descendant's handler after an ancestor's `.capture` handler captured the pointer
+    pub fn release(&self) {
+        *self.captured_id_chain.borrow_mut() = None;
+    }
+
+    /// The id chain of the capturing element, if any, read by the engine once handlers have run
+    pub fn get_captured_id_chain(&self) -> Option<Vec<u64>> {
+        (*self.captured_id_chain).borrow().clone()
+    }
+}
+
+/// Implemented by the args of every user input event
+pub trait EventArgs: Clone {
+    fn get_control(&self) -> &EventControl;
+}
+
+macro_rules! impl_event_args {
+    ($($args:ty),*) => {
+        $(
+            impl EventArgs for $args {
+                fn get_control(&self) -> &EventControl {
+                    &self.control
+                }
+            }
+        )*
+    };
+}
+
+impl_event_args!(ArgsJab, ArgsScroll, ArgsTouchStart, ArgsTouchMove, ArgsTouchEnd, ArgsKeyDown, ArgsKeyUp, ArgsKeyPress,
+    ArgsClick, ArgsDoubleClick, ArgsMouseMove, ArgsWheel, ArgsMouseDown, ArgsMouseUp, ArgsMouseOver, ArgsMouseOut, ArgsMouseEnter, ArgsMouseLeave, ArgsFocus, ArgsBlur, ArgsDragStart, ArgsDrag, ArgsDragEnd,
+    ArgsDragEnter, ArgsDragOver, ArgsDragLeave, ArgsDrop, ArgsPinch, ArgsRotate, ArgsPan, ArgsSwipe, ArgsLongPress, ArgsContextMenu);
+
 /// A Jab describes either a "click" (mousedown followed by mouseup), OR a
 /// "tap" with one finger (singular fingerdown event).
 /// Jabs are a useful alternative to most kinds of `Click` or `Tap` events,
@@ -88,6 +220,7 @@ pub struct RuntimeContext {
 pub struct ArgsJab {
     pub x: f64,
     pub y: f64,
+    pub control: EventControl,
 }
 
 /// Scroll occurs when a frame is translated vertically or horizontally
@@ -98,6 +231,7 @@ pub struct ArgsJab {
 pub struct ArgsScroll {
     pub delta_x: f64,
     pub delta_y: f64,
+    pub control: EventControl,
 }
 
 // Touch Events
@@ -129,6 +263,20 @@ impl From<&TouchMessage> for Touch {
 #[derive(Clone)]
 pub struct ArgsTouchStart {
     pub touches: Vec<Touch>,
+    pub pointer_capture: PointerCapture,
+    pub control: EventControl,
+}
+
+impl ArgsTouchStart {
+    /// See `PointerCapture::capture`
+    pub fn capture_pointer(&self, ctx: &RuntimeContext) {
+        self.pointer_capture.capture(ctx);
+    }
+
+    /// See `PointerCapture::release`
+    pub fn release_pointer_capture(&self) {
+        self.pointer_capture.release();
+    }
 }
 
 /// A TouchMove occurs when the user moves while touching an element.
@@ -136,6 +284,7 @@ pub struct ArgsTouchStart {
 #[derive(Clone)]
 pub struct ArgsTouchMove {
     pub touches: Vec<Touch>,
+    pub control: EventControl,
 }
 
 /// A TouchEnd occurs when the user stops touching an element.
@@ -143,6 +292,7 @@ pub struct ArgsTouchMove {
 #[derive(Clone)]
 pub struct ArgsTouchEnd {
     pub touches: Vec<Touch>,
+    pub control: EventControl,
 }
 
 
@@ -160,18 +310,21 @@ pub struct KeyboardEventArgs {
 #[derive(Clone)]
 pub struct ArgsKeyDown {
     pub keyboard: KeyboardEventArgs,
+    pub control: EventControl,
 }
 
 /// User has released a key.
 #[derive(Clone)]
 pub struct ArgsKeyUp {
     pub keyboard: KeyboardEventArgs,
+    pub control: EventControl,
 }
 
 /// User presses a key that displays a character (alphanumeric or symbol).
 #[derive(Clone)]
 pub struct ArgsKeyPress {
     pub keyboard: KeyboardEventArgs,
+    pub control: EventControl,
 }
 
 // Mouse Events
@@ -227,18 +380,21 @@ impl From<&ModifierKeyMessage> for ModifierKey {
 #[derive(Clone)]
 pub struct ArgsClick {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 /// User double-clicks a mouse button over an element.
 #[derive(Clone)]
 pub struct ArgsDoubleClick {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 /// User moves the mouse while it is over an element.
 #[derive(Clone)]
 pub struct ArgsMouseMove {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 /// User scrolls the mouse wheel over an element.
@@ -249,35 +405,255 @@ pub struct ArgsWheel {
     pub delta_x: f64,
     pub delta_y: f64,
     pub modifiers: Vec<ModifierKey>,
+    pub control: EventControl,
 }
 /// User presses a mouse button over an element.
 #[derive(Clone)]
 pub struct ArgsMouseDown {
     pub mouse: MouseEventArgs,
+    pub pointer_capture: PointerCapture,
+    pub control: EventControl,
+}
+
+impl ArgsMouseDown {
+    /// See `PointerCapture::capture`
+    pub fn capture_pointer(&self, ctx: &RuntimeContext) {
+        self.pointer_capture.capture(ctx);
+    }
+
+    /// See `PointerCapture::release`
+    pub fn release_pointer_capture(&self) {
+        self.pointer_capture.release();
+    }
 }
 
 /// User releases a mouse button over an element.
 #[derive(Clone)]
 pub struct ArgsMouseUp {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
+}
+
+/// Common properties in drag events.  A drag begins once a pressed pointer (a mouse button or a touch) moves a few pixels
+/// from where it was pressed, and its events are dispatched to the element pressed, propagating to its ancestors,
+/// wherever the pointer goes.
+#[derive(Clone)]
+pub struct DragEventArgs {
+    /// The pointer's position in the viewport
+    pub x: f64,
+    pub y: f64,
+    /// The pointer's movement since it was pressed, in the coordinate space of the element pressed, i.e. accounting
+    /// for that element's scale and rotation
+    pub delta_x: f64,
+    pub delta_y: f64,
+}
+
+/// Carries data from the element being dragged to the elements it's dragged over and dropped on.  A `drag_start`
+/// handler sets a payload to make the drag a drag-and-drop, e.g. the id of a kanban card, after which `drag_enter`,
+/// `drag_over`, `drag_leave` and `drop` are dispatched to the elements beneath the pointer, with the same `DragTransfer`.
+#[derive(Clone, Default)]
+pub struct DragTransfer {
+    payload: Rc<RefCell<Option<Rc<dyn Any>>>>,
+    is_ghost_shown: Rc<Cell<bool>>,
+}
+
+impl DragTransfer {
+    pub fn set_payload<T: 'static>(&self, payload: T) {
+        *self.payload.borrow_mut() = Some(Rc::new(payload));
+    }
+
+    /// The payload, if one was set and is a `T`
+    pub fn get_payload<T: 'static + Clone>(&self) -> Option<T> {
+        (*self.payload).borrow().as_ref().and_then(|payload| payload.downcast_ref::<T>().cloned())
+    }
+
+    pub fn has_payload(&self) -> bool {
+        (*self.payload).borrow().is_some()
+    }
+
+    /// Renders a ghost of the element being dragged, and its descendants, following the pointer until the drag ends.
+    /// The ghost is drawn over the topmost canvas layer, so excludes native elements like `Text`.
+    pub fn show_ghost(&self) {
+        self.is_ghost_shown.set(true);
+    }
+
+    pub fn is_ghost_shown(&self) -> bool {
+        self.is_ghost_shown.get()
+    }
+}
+
+/// User begins dragging an element.
+#[derive(Clone)]
+pub struct ArgsDragStart {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User moves the pointer while dragging an element.
+#[derive(Clone)]
+pub struct ArgsDrag {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User releases the pointer dragging an element.
+#[derive(Clone)]
+pub struct ArgsDragEnd {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User drags a payload (see `DragTransfer`) onto an element or one of its descendants, from anywhere else.  As with
+/// `ArgsMouseEnter`, this is dispatched to each element entered, without propagating to ancestors.  The element being
+/// dragged and its descendants are never drop targets.
+#[derive(Clone)]
+pub struct ArgsDragEnter {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User moves a payload over an element.  Dispatched to the topmost element beneath the pointer upon each move,
+/// propagating to its ancestors.
+#[derive(Clone)]
+pub struct ArgsDragOver {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User drags a payload off of an element and all of its descendants, or ends the drag while over it.  As with
+/// `ArgsMouseLeave`, this is dispatched to each element left, without propagating to ancestors.
+#[derive(Clone)]
+pub struct ArgsDragLeave {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User releases a payload over an element.  Dispatched to the topmost element beneath the pointer, propagating to its
+/// ancestors, before `drag_leave` and then `drag_end`.
+#[derive(Clone)]
+pub struct ArgsDrop {
+    pub drag: DragEventArgs,
+    pub transfer: DragTransfer,
+    pub control: EventControl,
+}
+
+/// User moves two or more touch points toward or away from each other.  Like the other gesture events, this is
+/// dispatched to the element beneath the first touch of the gesture, propagating to its ancestors, once the gesture
+/// passes its threshold (see `GestureThresholds`), then upon each touch move until the gesture's touches are lifted.
+#[derive(Clone)]
+pub struct ArgsPinch {
+    /// The distance between the touch points relative to their distance when the gesture began, e.g. 2.0 when
+    /// they've moved twice as far apart
+    pub scale: f64,
+    /// The centroid of the touch points, in the viewport
+    pub center: (f64, f64),
+    pub control: EventControl,
+}
+
+/// User twists two or more touch points around each other.
+#[derive(Clone)]
+pub struct ArgsRotate {
+    /// The angle in radians through which the touch points have turned since the gesture began, clockwise
+    pub rotation: f64,
+    /// The centroid of the touch points, in the viewport
+    pub center: (f64, f64),
+    pub control: EventControl,
+}
+
+/// User moves one or more touch points together across the screen.
+#[derive(Clone)]
+pub struct ArgsPan {
+    /// The movement of the centroid of the touch points since the gesture began, in the coordinate space of the
+    /// gesture's element, as with `DragEventArgs`
+    pub delta_x: f64,
+    pub delta_y: f64,
+    /// The centroid of the touch points, in the viewport
+    pub center: (f64, f64),
+    pub control: EventControl,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// User flicks one or more touch points quickly across the screen and lifts them.  Dispatched once the gesture's
+/// touches are lifted, unless a `long_press` was dispatched first.
+#[derive(Clone)]
+pub struct ArgsSwipe {
+    /// The dominant direction of the flick, in the viewport
+    pub direction: SwipeDirection,
+    /// The average speed of the flick, in px per frame
+    pub velocity: f64,
+    pub control: EventControl,
+}
+
+/// User holds one or more touch points still for a while.  Dispatched once per gesture.
+#[derive(Clone)]
+pub struct ArgsLongPress {
+    /// The centroid of the touch points, in the viewport
+    pub x: f64,
+    pub y: f64,
+    pub control: EventControl,
+}
+
+/// User moves the mouse onto an element or one of its descendants, from anywhere else.  Unlike `ArgsMouseOver`, this is
+/// dispatched to each element entered, without propagating to ancestors.
+#[derive(Clone)]
+pub struct ArgsMouseEnter {
+    pub mouse: MouseEventArgs,
+    pub control: EventControl,
+}
+
+/// User moves the mouse off of an element and all of its descendants.  Unlike `ArgsMouseOut`, this is dispatched to each
+/// element left, without propagating to ancestors.
+#[derive(Clone)]
+pub struct ArgsMouseLeave {
+    pub mouse: MouseEventArgs,
+    pub control: EventControl,
+}
+
+/// An element gains keyboard focus, by Tab traversal, a click or a handler's `RuntimeContext::focus`.  Key events are
+/// dispatched to the focused element, propagating to its ancestors; `focus` itself doesn't propagate.
+#[derive(Clone)]
+pub struct ArgsFocus {
+    pub control: EventControl,
+}
+
+/// An element loses keyboard focus.  Like `ArgsFocus`, this doesn't propagate to ancestors.
+#[derive(Clone)]
+pub struct ArgsBlur {
+    pub control: EventControl,
 }
 
 /// User moves the mouse onto an element.
 #[derive(Clone)]
 pub struct ArgsMouseOver {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 /// User moves the mouse away from an element.
 #[derive(Clone)]
 pub struct ArgsMouseOut {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 /// User right-clicks an element to open the context menu.
 #[derive(Clone)]
 pub struct ArgsContextMenu {
     pub mouse: MouseEventArgs,
+    pub control: EventControl,
 }
 
 
@@ -371,6 +747,8 @@ impl PartialEq<SizePixels> for Numeric {
 pub enum PlatformSpecificLogger {
     Web(fn(&str)),
     MacOS(extern "C" fn(*const std::os::raw::c_char)),
+    /// For chassis that run as plain Rust processes, e.g. headless, logging straight to stdout or stderr
+    Native(fn(&str)),
 }
 
 pub struct Logger(PlatformSpecificLogger);
@@ -379,8 +757,13 @@ lazy_static! {
     static ref LOGGER: MutStatic<Logger> = MutStatic::new();
 }
 
+/// Registers the logger for `log`, replacing any registered by a previous engine in this process (e.g. in tests)
 pub fn register_logger(logger: PlatformSpecificLogger) {
-    LOGGER.borrow().set(Logger(logger)).unwrap();
+    if LOGGER.borrow().is_set().unwrap() {
+        *LOGGER.borrow().write().unwrap() = Logger(logger);
+    } else {
+        LOGGER.borrow().set(Logger(logger)).unwrap();
+    }
 }
 
 /// Log to the appropriate native logging mechanism
@@ -394,7 +777,10 @@ pub fn log(msg: &str) {
         PlatformSpecificLogger::MacOS(closure) => {
             let msg = CString::new(msg).unwrap();
             unsafe {(closure)(msg.as_ptr())};
-        }
+        },
+        PlatformSpecificLogger::Native(closure) => {
+            closure(msg)
+        },
     }
 }
 
@@ -537,6 +923,7 @@ impl<T> TransitionManager<T> {
 /// The Literal form of a Property: a bare literal value with support for easing/interpolation
 pub struct PropertyLiteral<T> {
     value: T,
+    version: u64,
     transition_manager: TransitionManager<T>,
 }
 
@@ -553,6 +940,7 @@ impl<T: Clone> PropertyLiteral<T> {
     pub fn new(value: T) -> Self {
         PropertyLiteral {
             value,
+            version: mint_property_version(),
             transition_manager: TransitionManager::new(),
         }
     }
@@ -565,6 +953,8 @@ impl<T: Default + Clone> PropertyInstance<T> for PropertyLiteral<T> {
     }
 
     fn get_mut(&mut self) -> &mut T {
+        //the value may be written through the returned reference
+        self.version = mint_property_version();
         &mut self.value
     }
 
@@ -572,12 +962,23 @@ impl<T: Default + Clone> PropertyInstance<T> for PropertyLiteral<T> {
         None
     }
 
+    fn _get_version(&self) -> u64 {
+        self.version
+    }
+
     fn set(&mut self, value: T) {
         self.value = value;
+        self.version = mint_property_version();
+    }
+
+    fn _set_computed(&mut self, value: T, version: u64) {
+        self.value = value;
+        self.version = version;
     }
 
     //FUTURE: when trait fields land in Rust, DRY this implementation vs. other <T: PropertyInstance> implementations
     fn ease_to(&mut self, new_value: T, duration_frames: u64, curve: EasingCurve) {
+        self.version = mint_property_version();
         self.transition_manager.value = Some(self.get().clone());
         &self.transition_manager.queue.clear();
         &self.transition_manager.queue.push_back(TransitionQueueEntry {
@@ -590,6 +991,7 @@ impl<T: Default + Clone> PropertyInstance<T> for PropertyLiteral<T> {
     }
 
     fn ease_to_later(&mut self, new_value: T, duration_frames: u64, curve: EasingCurve) {
+        self.version = mint_property_version();
         if let None = self.transition_manager.value {
             //handle case where transition queue is empty -- a None value gets skipped, so populate it with Some
             self.transition_manager.value = Some(self.get().clone());
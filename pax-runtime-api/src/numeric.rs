@@ -333,11 +333,68 @@ impl Div<f64> for Numeric {
     }
 }
 
+/// A range of integers, as expressed in PAXEL by `a..b`, `a..=b`, `(a..b).step_by(n)`, or `(a..b).rev()`.
+/// As with `std::ops::Range`, a NumericRange whose `start` is greater than its `end` is empty, e.g. `5..0`;
+/// descending ranges are explicit, e.g. `(0..5).rev()` yields `4, 3, 2, 1, 0`.  Float bounds are truncated to integers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumericRange {
+    pub start: isize,
+    pub end: isize,
+    pub is_inclusive: bool,
+    /// Zero for an empty range, as from a non-positive `step_by`
+    pub step: usize,
+    /// Whether the range yields its elements from last to first, as from `rev`
+    pub is_reversed: bool,
+}
+
+impl NumericRange {
+    pub fn new<S: Into<Numeric>, E: Into<Numeric>>(start: S, end: E, is_inclusive: bool) -> Self {
+        Self {
+            start: start.into().get_as_int(),
+            end: end.into().get_as_int(),
+            is_inclusive,
+            step: 1,
+            is_reversed: false,
+        }
+    }
+
+    /// A non-positive `step` yields an empty range rather than panicking, since it may come from a property at render time
+    pub fn step_by<N: Into<Numeric>>(mut self, step: N) -> Self {
+        self.step = step.into().get_as_int().max(0) as usize;
+        self
+    }
+
+    /// Yields the same elements as this range, including any `step_by`, from last to first
+    pub fn rev(mut self) -> Self {
+        self.is_reversed = !self.is_reversed;
+        self
+    }
+}
+
+impl IntoIterator for NumericRange {
+    type Item = isize;
+    type IntoIter = Box<dyn Iterator<Item = isize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        if self.step == 0 {
+            return Box::new(std::iter::empty());
+        }
+        //an exclusive range, so that stepped ranges can be reversed (`RangeInclusive<isize>` is not an `ExactSizeIterator`)
+        let end = if self.is_inclusive { self.end.saturating_add(1) } else { self.end };
+        let elements = (self.start..end).step_by(self.step);
+        if self.is_reversed {
+            Box::new(elements.rev())
+        } else {
+            Box::new(elements)
+        }
+    }
+}
+
 /// Tests for Numeric
 
 #[cfg(test)]
 mod tests {
-    use crate::numeric::Numeric;
+    use crate::numeric::{Numeric, NumericRange};
 
     #[test]
     fn test_widen() {
@@ -402,4 +459,24 @@ mod tests {
         assert_eq!(Numeric::float_eq(a,b, epsilon_b ), true);
         assert_eq!(Numeric::float_eq(a,b, epsilon_c ), false);
     }
+
+    #[test]
+    fn test_range() {
+        let collect = |range: NumericRange| range.into_iter().collect::<Vec<isize>>();
+
+        assert_eq!(collect(NumericRange::new(0, 4, false)), vec![0, 1, 2, 3]);
+        assert_eq!(collect(NumericRange::new(0, 4, true)), vec![0, 1, 2, 3, 4]);
+        assert_eq!(collect(NumericRange::new(4, 0, false)), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(4, 0, true)), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(0, -3, false)), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(0, 4, false).rev()), vec![3, 2, 1, 0]);
+        assert_eq!(collect(NumericRange::new(0, 4, true).rev()), vec![4, 3, 2, 1, 0]);
+        assert_eq!(collect(NumericRange::new(4, 0, true).rev()), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(0, 10, false).step_by(3)), vec![0, 3, 6, 9]);
+        assert_eq!(collect(NumericRange::new(0, 10, true).step_by(5).rev()), vec![10, 5, 0]);
+        assert_eq!(collect(NumericRange::new(0, 10, false).step_by(3).rev()), vec![9, 6, 3, 0]);
+        assert_eq!(collect(NumericRange::new(Numeric::from(2.7), 2, false)), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(0, 10, false).step_by(0)), Vec::<isize>::new());
+        assert_eq!(collect(NumericRange::new(0, 10, true).step_by(-2)), Vec::<isize>::new());
+    }
 }
@@ -3,11 +3,12 @@ use piet::{RenderContext};
 
 use pax_std::primitives::{Ellipse};
 use pax_std::types::ColorVariant;
-use pax_core::{Color, RenderNode, RenderNodePtrList, RenderTreeContext, ExpressionContext, InstanceRegistry, HandlerRegistry, InstantiationArgs, RenderNodePtr, unsafe_unwrap};
+use pax_core::{Color, RenderNode, RenderNodePtrList, RenderTreeContext, ExpressionContext, InstanceRegistry, HandlerRegistry, InstantiationArgs, RenderNodePtr, TransformAndBounds, ray_cast_test_path, unsafe_unwrap};
 use pax_core::pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 use pax_runtime_api::{PropertyInstance, PropertyLiteral, Size, Transform2D, Size2D};
 
 use std::str::FromStr;
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -48,6 +49,10 @@ impl<R: 'static + RenderContext>  RenderNode<R> for EllipseInstance<R> {
         ret
     }
 
+    fn get_properties_any(&self) -> Option<Rc<dyn Any>> {
+        Some(Rc::clone(&self.properties) as Rc<dyn Any>)
+    }
+
     fn get_handler_registry(&self) -> Option<Rc<RefCell<HandlerRegistry<R>>>> {
         match &self.handler_registry {
             Some(registry) => {
@@ -57,6 +62,18 @@ impl<R: 'static + RenderContext>  RenderNode<R> for EllipseInstance<R> {
         }
     }
     fn get_size(&self) -> Option<Size2D> { Some(Rc::clone(&self.size)) }
+
+    /// Hits within the ellipse or its stroke, rather than the corners of its bounds
+    fn ray_cast_test(&self, ray: &(f64, f64), tab: &TransformAndBounds) -> bool {
+        let properties = (*self.properties).borrow();
+        let ellipse = KurboEllipse::from_rect(Rect::from_points((0.0, 0.0), tab.bounds));
+        ray_cast_test_path(ray, &ellipse.to_path(0.1), &tab.transform, properties.stroke.get().width.get().into())
+    }
+
+    fn get_hit_bounds(&self, tab: &TransformAndBounds) -> Option<Rect> {
+        let half_stroke_width = f64::from(self.properties.borrow().stroke.get().width.get()) / 2.0;
+        Some(tab.get_bounding_box().inflate(half_stroke_width, half_stroke_width))
+    }
     fn get_transform(&mut self) -> Rc<RefCell<dyn PropertyInstance<Transform2D>>> { Rc::clone(&self.transform) }
     fn compute_properties(&mut self, rtc: &mut RenderTreeContext<R>) {
         let mut properties = &mut *self.properties.as_ref().borrow_mut();
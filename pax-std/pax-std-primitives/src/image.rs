@@ -8,6 +8,7 @@ use pax_core::pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 use pax_runtime_api::{PropertyInstance, PropertyLiteral, Size, Transform2D, Size2D, log};
 
 use std::str::FromStr;
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 use pax_message::ImagePatch;
@@ -51,6 +52,10 @@ impl<R: 'static + RenderContext>  RenderNode<R> for ImageInstance<R> {
         ret
     }
 
+    fn get_properties_any(&self) -> Option<Rc<dyn Any>> {
+        Some(Rc::clone(&self.properties) as Rc<dyn Any>)
+    }
+
     fn get_handler_registry(&self) -> Option<Rc<RefCell<HandlerRegistry<R>>>> {
         match &self.handler_registry {
             Some(registry) => {
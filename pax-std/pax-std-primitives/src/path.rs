@@ -3,11 +3,12 @@ use piet::{RenderContext};
 
 use pax_std::primitives::{Path};
 use pax_std::types::{ColorVariant, CurveSegmentData, LineSegmentData, PathSegment};
-use pax_core::{Color, RenderNode, RenderNodePtrList, RenderTreeContext, ExpressionContext, InstanceRegistry, HandlerRegistry, InstantiationArgs, RenderNodePtr, unsafe_unwrap};
+use pax_core::{Color, RenderNode, RenderNodePtrList, RenderTreeContext, ExpressionContext, InstanceRegistry, HandlerRegistry, InstantiationArgs, RenderNodePtr, TransformAndBounds, ray_cast_test_path, unsafe_unwrap};
 use pax_core::pax_properties_coproduct::{PropertiesCoproduct, TypesCoproduct};
 use pax_runtime_api::{PropertyInstance, PropertyLiteral, Size, Transform2D, Size2D};
 
 use std::str::FromStr;
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::Rc;
 use pax_std::types::PathSegment::LineSegment;
@@ -47,6 +48,10 @@ impl<R: 'static + RenderContext>  RenderNode<R> for PathInstance<R> {
         ret
     }
 
+    fn get_properties_any(&self) -> Option<Rc<dyn Any>> {
+        Some(Rc::clone(&self.properties) as Rc<dyn Any>)
+    }
+
     fn get_handler_registry(&self) -> Option<Rc<RefCell<HandlerRegistry<R>>>> {
         match &self.handler_registry {
             Some(registry) => {
@@ -58,6 +63,20 @@ impl<R: 'static + RenderContext>  RenderNode<R> for PathInstance<R> {
     }
 
     fn get_size(&self) -> Option<Size2D> { None }
+
+    /// Hits within the area filled by the path's segments or within its stroke.  Paths have no size, so would otherwise
+    /// never be hit.
+    fn ray_cast_test(&self, ray: &(f64, f64), tab: &TransformAndBounds) -> bool {
+        let properties = (*self.properties).borrow();
+        ray_cast_test_path(ray, &get_bez_path(&properties), &tab.transform, properties.stroke.get().width.get().into())
+    }
+
+    /// The bounds of the segments themselves, since a `Path`'s bounds are those of its container
+    fn get_hit_bounds(&self, tab: &TransformAndBounds) -> Option<Rect> {
+        let properties = (*self.properties).borrow();
+        let half_stroke_width = f64::from(properties.stroke.get().width.get()) / 2.0;
+        Some((tab.transform * get_bez_path(&properties)).bounding_box().inflate(half_stroke_width, half_stroke_width))
+    }
     fn get_transform(&mut self) -> Rc<RefCell<dyn PropertyInstance<Transform2D>>> { Rc::clone(&self.transform) }
 
     fn compute_properties(&mut self, rtc: &mut RenderTreeContext<R>) {
@@ -90,21 +109,7 @@ impl<R: 'static + RenderContext>  RenderNode<R> for PathInstance<R> {
 
         let properties = (*self.properties).borrow();
 
-        let mut bez_path = BezPath::new();
-
-        for segment in properties.segments.get().iter() {
-            match segment{
-                PathSegment::Empty => {/* no-op */},
-                PathSegment::LineSegment(data) => {
-                    bez_path.move_to(data.start);
-                    bez_path.line_to(data.end);
-                } ,
-                PathSegment::CurveSegment(data) => {
-                    bez_path.move_to(data.start);
-                    bez_path.quad_to( data.handle, data.end);
-                },
-            }
-        }
+        let bez_path = get_bez_path(&properties);
 
         let transformed_bez_path = transform * bez_path;
         let duplicate_transformed_bez_path = transformed_bez_path.clone();
@@ -115,3 +120,24 @@ impl<R: 'static + RenderContext>  RenderNode<R> for PathInstance<R> {
 
     }
 }
+
+/// The untransformed path drawn by a `Path`, one subpath per segment
+fn get_bez_path(properties: &Path) -> BezPath {
+    let mut bez_path = BezPath::new();
+
+    for segment in properties.segments.get().iter() {
+        match segment{
+            PathSegment::Empty => {/* no-op */},
+            PathSegment::LineSegment(data) => {
+                bez_path.move_to(data.start);
+                bez_path.line_to(data.end);
+            } ,
+            PathSegment::CurveSegment(data) => {
+                bez_path.move_to(data.start);
+                bez_path.quad_to( data.handle, data.end);
+            },
+        }
+    }
+
+    bez_path
+}
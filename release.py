@@ -18,6 +18,8 @@ NEW_VERSION = sys.argv[1]
 
 PACKAGES = [
     "pax-cartridge",
+    "pax-chassis-headless",
+    "pax-chassis-linux",
     "pax-chassis-macos",
     "pax-chassis-web",
     "pax-cli",
@@ -25,6 +27,7 @@ PACKAGES = [
     "pax-core",
     "pax-example",
     "pax-lang",
+    "pax-lsp",
     "pax-macro",
     "pax-message",
     "pax-properties-coproduct",